@@ -6,7 +6,7 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 #[repr(u8)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum Pane {
     Hierarchy,
     Project,
@@ -463,11 +463,11 @@ fn unity_like_workspace() -> egui_docking::WorkspaceLayout<Pane> {
     let builder = egui::ViewportBuilder::default()
         .with_title("Game View")
         .with_inner_size([720.0, 480.0]);
-    ws.detached
-        .push(egui_docking::DetachedViewportLayout::new(
-            builder,
-            game_view_detached_tree(),
-        ));
+    // The game preview keeps a regular OS title bar even when tool windows are borderless.
+    ws.detached.push(
+        egui_docking::DetachedViewportLayout::new(builder, game_view_detached_tree())
+            .with_options(egui_docking::DetachedViewportOptions::default().with_decorations(true)),
+    );
     ws
 }
 
@@ -519,6 +519,23 @@ impl eframe::App for App {
             {
                 self.docking.set_workspace_layout_in_ctx(ctx, unity_like_workspace());
             }
+
+            // Start recording after the initial layout so it can't be undone into an empty dock.
+            // Each pane kind exists once, so a pane is its own id.
+            self.docking
+                .enable_layout_history(64, |pane: &Pane| *pane, Some);
+        }
+
+        let (undo, redo) = ctx.input_mut(|i| {
+            let redo = i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z);
+            let undo = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z);
+            (undo, redo)
+        });
+        if undo {
+            self.docking.undo();
+        }
+        if redo {
+            self.docking.redo();
         }
 
         egui::Panel::top("top").show(ctx, |ui| {
@@ -527,6 +544,18 @@ impl eframe::App for App {
                     self.docking
                         .set_workspace_layout_in_ctx(ctx, unity_like_workspace());
                 }
+                if ui
+                    .add_enabled(self.docking.can_undo(), egui::Button::new("Undo"))
+                    .clicked()
+                {
+                    self.docking.undo();
+                }
+                if ui
+                    .add_enabled(self.docking.can_redo(), egui::Button::new("Redo"))
+                    .clicked()
+                {
+                    self.docking.redo();
+                }
                 #[cfg(feature = "persistence")]
                 {
                     if ui.button("Save layout").clicked() {
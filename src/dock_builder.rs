@@ -2,6 +2,8 @@ use egui::Id;
 use egui_tiles::{Container, Linear, LinearDir, TileId, Tiles, Tree};
 use std::collections::BTreeMap;
 
+use crate::multi_viewport::DockNodeFlags;
+
 /// Split direction with Dear ImGui `DockBuilder::SplitNode`-like semantics.
 ///
 /// The direction indicates where the *side* node is placed relative to the *main* node.
@@ -120,6 +122,7 @@ pub struct DockBuilder<Pane> {
     id: Id,
     next_node_id: u64,
     nodes: BTreeMap<DockNodeId, Node<Pane>>,
+    node_flags: BTreeMap<DockNodeId, DockNodeFlags>,
 }
 
 impl<Pane> DockBuilder<Pane> {
@@ -128,6 +131,7 @@ impl<Pane> DockBuilder<Pane> {
             id: id.into(),
             next_node_id: 1,
             nodes: BTreeMap::new(),
+            node_flags: BTreeMap::new(),
         }
     }
 
@@ -183,6 +187,34 @@ impl<Pane> DockBuilder<Pane> {
         (side, main)
     }
 
+    /// Set the [`DockNodeFlags`] of a node, replacing the previous ones.
+    ///
+    /// Flags stay with the node id: if the node is split afterwards, they apply to the split
+    /// container (e.g. [`DockNodeFlags::NO_RESIZE`]), not to the returned `main` child.
+    ///
+    /// Use [`Self::finish_with_node_flags`] and [`crate::DockingMultiViewport::apply_node_flags`]
+    /// to carry them over to the built tree.
+    pub fn set_node_flags(&mut self, node: DockNodeId, flags: DockNodeFlags) {
+        if flags.is_empty() {
+            self.node_flags.remove(&node);
+        } else {
+            self.node_flags.insert(node, flags);
+        }
+    }
+
+    /// Mark a leaf node as the central node (see [`DockNodeFlags::CENTRAL_NODE`]).
+    ///
+    /// The node must stay a leaf: a split node is not a `Tabs` container and can't be central.
+    pub fn set_central_node(&mut self, node: DockNodeId) {
+        let flags = self.node_flags(node) | DockNodeFlags::CENTRAL_NODE;
+        self.set_node_flags(node, flags);
+    }
+
+    /// The flags set with [`Self::set_node_flags`].
+    pub fn node_flags(&self, node: DockNodeId) -> DockNodeFlags {
+        self.node_flags.get(&node).copied().unwrap_or_default()
+    }
+
     /// Dock a pane into a leaf `Tabs` node.
     pub fn dock_pane(&mut self, pane: Pane, node: DockNodeId) {
         match self.nodes.get_mut(&node) {
@@ -220,6 +252,17 @@ impl<Pane> DockBuilder<Pane> {
         self.finish_map(root, |pane| Some(pane))
     }
 
+    /// Like [`Self::finish`], also returning the [`DockNodeFlags`] of each built tile.
+    ///
+    /// ```ignore
+    /// let (tree, flags) = builder.finish_with_node_flags(dockspace);
+    /// let mut docking = DockingMultiViewport::new(tree);
+    /// docking.apply_node_flags(PaneHost::RootDock, flags);
+    /// ```
+    pub fn finish_with_node_flags(self, root: DockNodeId) -> (Tree<Pane>, Vec<(TileId, DockNodeFlags)>) {
+        self.finish_map_with_node_flags(root, |pane| Some(pane))
+    }
+
     /// Finish building and produce the `egui_tiles::Tree`, mapping docked items along the way.
     ///
     /// This is useful when your scripted layout is expressed in terms of stable ids (e.g. `PaneId`
@@ -231,15 +274,25 @@ impl<Pane> DockBuilder<Pane> {
     pub fn finish_map<OutPane>(
         self,
         root: DockNodeId,
-        mut map: impl FnMut(Pane) -> Option<OutPane>,
+        map: impl FnMut(Pane) -> Option<OutPane>,
     ) -> Tree<OutPane> {
+        self.finish_map_with_node_flags(root, map).0
+    }
+
+    /// Like [`Self::finish_map`], also returning the [`DockNodeFlags`] of each built tile.
+    pub fn finish_map_with_node_flags<OutPane>(
+        self,
+        root: DockNodeId,
+        mut map: impl FnMut(Pane) -> Option<OutPane>,
+    ) -> (Tree<OutPane>, Vec<(TileId, DockNodeFlags)>) {
         fn build_tile<Pane, OutPane>(
             node_id: DockNodeId,
             nodes: &mut BTreeMap<DockNodeId, Node<Pane>>,
             tiles: &mut Tiles<OutPane>,
+            built: &mut BTreeMap<DockNodeId, TileId>,
             map: &mut impl FnMut(Pane) -> Option<OutPane>,
         ) -> TileId {
-            match nodes.remove(&node_id) {
+            let tile_id = match nodes.remove(&node_id) {
                 Some(Node::Tabs { panes }) => {
                     let children: Vec<TileId> = panes
                         .into_iter()
@@ -254,8 +307,8 @@ impl<Pane> DockBuilder<Pane> {
                     main,
                     side,
                 }) => {
-                    let main_tile = build_tile(main, nodes, tiles, map);
-                    let side_tile = build_tile(side, nodes, tiles, map);
+                    let main_tile = build_tile(main, nodes, tiles, built, map);
+                    let side_tile = build_tile(side, nodes, tiles, built, map);
 
                     let (linear_dir, first, second, first_fraction) = match dir {
                         SplitDirection::Left => (LinearDir::Horizontal, side_tile, main_tile, side_fraction),
@@ -273,13 +326,21 @@ impl<Pane> DockBuilder<Pane> {
                     tiles.insert_container(container)
                 }
                 None => tiles.insert_tab_tile(Vec::new()),
-            }
+            };
+            built.insert(node_id, tile_id);
+            tile_id
         }
 
         let mut nodes = self.nodes;
         let mut tiles: Tiles<OutPane> = Tiles::default();
-        let root_tile = build_tile(root, &mut nodes, &mut tiles, &mut map);
-        Tree::new(self.id, root_tile, tiles)
+        let mut built = BTreeMap::new();
+        let root_tile = build_tile(root, &mut nodes, &mut tiles, &mut built, &mut map);
+        let node_flags = self
+            .node_flags
+            .iter()
+            .filter_map(|(node, &flags)| Some((*built.get(node)?, flags)))
+            .collect();
+        (Tree::new(self.id, root_tile, tiles), node_flags)
     }
 }
 
@@ -327,6 +388,34 @@ mod tests {
         assert_eq!(right_tabs.children.len(), 1);
     }
 
+    #[test]
+    fn node_flags_follow_their_node_into_the_tree() {
+        let mut b = DockBuilder::new("dock_builder_flags_test");
+        let dockspace = b.add_node();
+        let (toolbar, main) = b.split_node(dockspace, SplitDirection::Up, 0.1);
+        b.dock_window(1u8, toolbar);
+        b.dock_window(2u8, main);
+        b.set_node_flags(toolbar, DockNodeFlags::NO_SPLIT | DockNodeFlags::NO_UNDOCKING);
+        b.set_node_flags(dockspace, DockNodeFlags::NO_RESIZE);
+        b.set_node_flags(main, DockNodeFlags::NO_TAB_INSERT);
+        b.set_node_flags(main, DockNodeFlags::NONE);
+
+        let (tree, flags) = b.finish_with_node_flags(dockspace);
+        let root = tree.root.unwrap();
+        let Some(Tile::Container(egui_tiles::Container::Linear(linear))) = tree.tiles.get(root) else {
+            panic!("root should be a Linear container");
+        };
+        // Up split: side (toolbar) is first.
+        let toolbar_tile = linear.children[0];
+
+        assert_eq!(flags.len(), 2);
+        assert!(flags.contains(&(root, DockNodeFlags::NO_RESIZE)));
+        assert!(flags.contains(&(
+            toolbar_tile,
+            DockNodeFlags::NO_SPLIT | DockNodeFlags::NO_UNDOCKING
+        )));
+    }
+
     #[test]
     #[should_panic]
     fn dock_pane_into_non_leaf_panics() {
@@ -4,7 +4,13 @@ pub mod multi_viewport;
 pub mod dock_builder;
 pub mod workspace;
 
-pub use multi_viewport::{DockingMultiViewport, DockingMultiViewportOptions};
+pub use multi_viewport::{
+    DebugLogEntry, DebugLogFileFormat, DebugLogRecord, DetachedViewportOptions, DockEvent,
+    DockNodeFlags, DockingMultiViewport, DockingMultiViewportOptions, DockingPolicy, DropContext,
+    DropVerdict, FloatingId, IntegrityRepairReport, LayoutSnapshot, LoadReport, NewWindowKind,
+    PaneHost, PaneLocation, TearOffContext, TearOffVerdict, WindowMoveCommand, WorkspacePresets,
+    FLOATING_MIN_SIZE, LAYOUT_SNAPSHOT_VERSION,
+};
 pub use dock_builder::{DockBuilder, DockNodeId, DockTreeBuilder, SplitDirection};
 pub use workspace::{DetachedViewportLayout, WorkspaceLayout};
 pub use multi_viewport::{
@@ -15,7 +21,20 @@ pub use multi_viewport::{
 };
 
 #[cfg(feature = "persistence")]
-pub use multi_viewport::{LayoutPersistenceError, LayoutSnapshot, LAYOUT_SNAPSHOT_VERSION};
+pub use multi_viewport::{
+    migrate_layout_snapshot, validate_layout_snapshot, LayoutAutosave, LayoutPersistenceError,
+    LayoutStorage, DEFAULT_LAYOUT_STORAGE_KEY, LAYOUT_SNAPSHOT_MAX_DEPTH,
+    LAYOUT_SNAPSHOT_MAX_NODES,
+};
 
 #[cfg(feature = "persistence")]
-pub use multi_viewport::{PaneRegistry, SimplePaneRegistry};
+pub use multi_viewport::{PaneRegistry, PaneStateRegistry, SimplePaneRegistry};
+
+#[cfg(feature = "persistence")]
+pub use multi_viewport::{
+    DragSessionRecorder, DragSessionRecording, DragSessionRecordingError, RecordedFrame,
+    DRAG_SESSION_RECORDING_VERSION,
+};
+
+#[cfg(feature = "testing")]
+pub use multi_viewport::{DockingSimulation, SimulatedDropTarget};
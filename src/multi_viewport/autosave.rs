@@ -0,0 +1,363 @@
+use std::hash::Hash;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use egui::Context;
+
+use super::persistence::{pretty_ron_config, LayoutPersistenceError};
+use super::snapshot::LayoutSnapshot;
+use super::{DockingMultiViewport, PaneRegistry};
+
+/// Saves the layout to a RON file shortly after the user stops rearranging it.
+///
+/// Call [`Self::update_with_registry`] (or [`Self::update`]) every frame after
+/// [`DockingMultiViewport::ui`]. A snapshot is only taken when
+/// [`DockingMultiViewport::layout_generation`] moved (docking changes, tab switches, window
+/// moves/resizes and split drags all bump it); its [`LayoutSnapshot::layout_hash`] then tells
+/// whether the layout really differs from the saved one. Once it has been stable for
+/// `quiet_period` the snapshot is written with write-then-rename, so a crash mid-write never
+/// leaves a truncated layout file behind.
+///
+/// The layout seen on the first update (typically the one just loaded) is taken as already saved.
+#[derive(Clone, Debug)]
+pub struct LayoutAutosave {
+    path: PathBuf,
+    quiet_period: Duration,
+    saved_hash: Option<u64>,
+    pending: Option<PendingSave>,
+    /// [`DockingMultiViewport::layout_generation`] at the last snapshot.
+    seen_generation: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PendingSave {
+    hash: u64,
+    since: f64,
+}
+
+#[derive(Debug, PartialEq)]
+enum AutosavePoll {
+    Idle,
+    Wait(Duration),
+    Save,
+}
+
+impl LayoutAutosave {
+    pub fn new(path: impl Into<PathBuf>, quiet_period: Duration) -> Self {
+        Self {
+            path: path.into(),
+            quiet_period,
+            saved_hash: None,
+            pending: None,
+            seen_generation: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// `true` if the layout changed since the last save and a write is still due.
+    pub fn has_pending_changes(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Save after `quiet_period` without changes. Returns `Ok(true)` on the frame a file was
+    /// written; a failed write is retried after another quiet period.
+    pub fn update_with_registry<Pane, R>(
+        &mut self,
+        ctx: &Context,
+        docking: &DockingMultiViewport<Pane>,
+        registry: &mut R,
+    ) -> Result<bool, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        self.update_with(ctx, docking, || {
+            docking.snapshot_layout_with_registry(registry)
+        })
+    }
+
+    pub fn update<Pane, PaneId>(
+        &mut self,
+        ctx: &Context,
+        docking: &DockingMultiViewport<Pane>,
+        mut pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> Result<bool, LayoutPersistenceError>
+    where
+        PaneId: Hash + serde::Serialize,
+    {
+        self.update_with(ctx, docking, || {
+            docking.snapshot_layout_impl(&mut pane_to_id)
+        })
+    }
+
+    /// Write a pending change now instead of waiting for the quiet period (e.g. on exit).
+    pub fn flush_with_registry<Pane, R>(
+        &mut self,
+        docking: &DockingMultiViewport<Pane>,
+        registry: &mut R,
+    ) -> Result<bool, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        self.flush_with(docking, || docking.snapshot_layout_with_registry(registry))
+    }
+
+    pub fn flush<Pane, PaneId>(
+        &mut self,
+        docking: &DockingMultiViewport<Pane>,
+        pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> Result<bool, LayoutPersistenceError>
+    where
+        PaneId: Hash + serde::Serialize,
+    {
+        self.flush_with(docking, || docking.snapshot_layout_impl(pane_to_id))
+    }
+
+    fn update_with<Pane, PaneId>(
+        &mut self,
+        ctx: &Context,
+        docking: &DockingMultiViewport<Pane>,
+        mut snapshot: impl FnMut() -> LayoutSnapshot<PaneId>,
+    ) -> Result<bool, LayoutPersistenceError>
+    where
+        PaneId: Hash + serde::Serialize,
+    {
+        if mid_gesture(ctx, docking) {
+            return Ok(false);
+        }
+        let now = ctx.input(|i| i.time);
+        let generation = docking.layout_generation();
+        let hash = if self.seen_generation == Some(generation) {
+            // Nothing changed since the last snapshot; only a pending save can come due.
+            match self.pending {
+                Some(pending) => pending.hash,
+                None => return Ok(false),
+            }
+        } else {
+            self.seen_generation = Some(generation);
+            snapshot().layout_hash()
+        };
+
+        match self.poll(hash, now) {
+            AutosavePoll::Idle => Ok(false),
+            AutosavePoll::Wait(remaining) => {
+                // Make sure a frame runs once the quiet period is over, even if the app is idle.
+                ctx.request_repaint_after(remaining);
+                Ok(false)
+            }
+            AutosavePoll::Save => match self.write(&snapshot()) {
+                Ok(()) => Ok(true),
+                Err(err) => {
+                    if let Some(pending) = &mut self.pending {
+                        pending.since = now;
+                    }
+                    Err(err)
+                }
+            },
+        }
+    }
+
+    fn flush_with<Pane, PaneId>(
+        &mut self,
+        docking: &DockingMultiViewport<Pane>,
+        snapshot: impl FnOnce() -> LayoutSnapshot<PaneId>,
+    ) -> Result<bool, LayoutPersistenceError>
+    where
+        PaneId: Hash + serde::Serialize,
+    {
+        // Also catch changes made since the last update (e.g. on the frame the app exits).
+        let generation = docking.layout_generation();
+        let changed = self.seen_generation.is_some_and(|seen| seen != generation);
+        if self.pending.is_none() && !changed {
+            return Ok(false);
+        }
+        self.seen_generation = Some(generation);
+        let snapshot = snapshot();
+        if self.saved_hash == Some(snapshot.layout_hash()) {
+            self.pending = None;
+            return Ok(false);
+        }
+        self.write(&snapshot)?;
+        Ok(true)
+    }
+
+    fn poll(&mut self, hash: u64, now: f64) -> AutosavePoll {
+        let Some(saved_hash) = self.saved_hash else {
+            self.saved_hash = Some(hash);
+            return AutosavePoll::Idle;
+        };
+        if hash == saved_hash {
+            self.pending = None;
+            return AutosavePoll::Idle;
+        }
+
+        let since = match self.pending {
+            Some(pending) if pending.hash == hash => pending.since,
+            _ => now,
+        };
+        self.pending = Some(PendingSave { hash, since });
+        let elapsed = Duration::from_secs_f64((now - since).max(0.0));
+        match self.quiet_period.checked_sub(elapsed) {
+            Some(remaining) if !remaining.is_zero() => AutosavePoll::Wait(remaining),
+            _ => AutosavePoll::Save,
+        }
+    }
+
+    fn write<PaneId>(
+        &mut self,
+        snapshot: &LayoutSnapshot<PaneId>,
+    ) -> Result<(), LayoutPersistenceError>
+    where
+        PaneId: Hash + serde::Serialize,
+    {
+        let ron = ron::ser::to_string_pretty(snapshot, pretty_ron_config())?;
+        write_atomically(&self.path, ron.as_bytes())?;
+        self.saved_hash = Some(snapshot.layout_hash());
+        self.pending = None;
+        Ok(())
+    }
+}
+
+/// Don't save half-finished gestures (a ghost window or a tab still being dragged).
+fn mid_gesture<Pane>(ctx: &Context, docking: &DockingMultiViewport<Pane>) -> bool {
+    docking.ghost.is_some() || egui::DragAndDrop::has_any_payload(ctx)
+}
+
+/// Write `contents` next to `path` and rename it into place, so readers only ever see a complete
+/// file.
+fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let Some(file_name) = path.file_name() else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "layout path has no file name",
+        ));
+    };
+    let mut tmp_name = file_name.to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let written = std::fs::File::create(&tmp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    let result = written.and_then(|()| std::fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multi_viewport::test_support::docking_with_tabs;
+
+    /// [`LayoutAutosave::update_with`], counting the snapshots taken.
+    fn update_counting(
+        autosave: &mut LayoutAutosave,
+        docking: &DockingMultiViewport<usize>,
+        snapshots: &mut usize,
+    ) {
+        let ctx = Context::default();
+        let saved = autosave.update_with(&ctx, docking, || {
+            *snapshots += 1;
+            docking.snapshot_layout(|&pane| pane)
+        });
+        assert!(!saved.unwrap());
+    }
+
+    #[test]
+    fn layouts_are_only_snapshotted_once_the_generation_moves() {
+        let mut docking = docking_with_tabs(&[1, 2]);
+        let mut autosave = LayoutAutosave::new("layout.ron", Duration::from_secs(2));
+        let mut snapshots = 0;
+
+        for _ in 0..3 {
+            update_counting(&mut autosave, &docking, &mut snapshots);
+        }
+        assert_eq!(snapshots, 1);
+
+        docking.mark_layout_changed();
+        update_counting(&mut autosave, &docking, &mut snapshots);
+        assert_eq!(snapshots, 2);
+        // Same layout as the saved one: nothing to write.
+        assert!(!autosave.has_pending_changes());
+
+        // Recording an undo step doesn't hide the change from autosave.
+        docking.mark_layout_changed();
+        docking.commit_layout_history();
+        update_counting(&mut autosave, &docking, &mut snapshots);
+        assert_eq!(snapshots, 3);
+    }
+
+    #[test]
+    fn saves_only_after_a_quiet_period() {
+        let mut autosave = LayoutAutosave::new("layout.ron", Duration::from_secs(2));
+
+        // The first layout seen counts as saved.
+        assert_eq!(autosave.poll(1, 0.0), AutosavePoll::Idle);
+        assert_eq!(autosave.poll(1, 10.0), AutosavePoll::Idle);
+
+        assert_eq!(
+            autosave.poll(2, 10.0),
+            AutosavePoll::Wait(Duration::from_secs(2))
+        );
+        assert_eq!(
+            autosave.poll(2, 11.0),
+            AutosavePoll::Wait(Duration::from_secs(1))
+        );
+        // Another change restarts the quiet period.
+        assert_eq!(
+            autosave.poll(3, 11.5),
+            AutosavePoll::Wait(Duration::from_secs(2))
+        );
+        assert_eq!(autosave.poll(3, 13.5), AutosavePoll::Save);
+
+        // Changing back to the saved layout cancels the pending save.
+        assert_eq!(autosave.poll(1, 14.0), AutosavePoll::Idle);
+        assert!(!autosave.has_pending_changes());
+    }
+
+    #[test]
+    fn layout_hash_tracks_layout_changes() {
+        use crate::multi_viewport::snapshot::NodeSnapshot;
+
+        let mut tiles = egui_tiles::Tiles::default();
+        let children = vec![tiles.insert_pane(1usize), tiles.insert_pane(2)];
+        let root = tiles.insert_tab_tile(children);
+        let docking =
+            DockingMultiViewport::new(egui_tiles::Tree::new(egui::Id::new("root"), root, tiles));
+
+        let snapshot = docking.snapshot_layout(|&pane| pane);
+        let hash = snapshot.layout_hash();
+        assert_eq!(hash, docking.snapshot_layout(|&pane| pane).layout_hash());
+
+        let mut switched_tab = snapshot.clone();
+        let root = switched_tab.root.root.unwrap();
+        if let NodeSnapshot::Tabs { active, .. } = &mut switched_tab.root.nodes[root] {
+            *active = Some(1);
+        }
+        assert_ne!(hash, switched_tab.layout_hash());
+
+        let renamed = snapshot.filter_map_pane_ids(|pane| Some(pane + 10));
+        assert_ne!(hash, renamed.layout_hash());
+    }
+
+    #[test]
+    fn atomic_write_replaces_the_file() {
+        let dir =
+            std::env::temp_dir().join(format!("egui_docking_autosave_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("layout.ron");
+
+        write_atomically(&path, b"first").unwrap();
+        write_atomically(&path, b"second").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"second");
+        assert!(!dir.join("layout.ron.tmp").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
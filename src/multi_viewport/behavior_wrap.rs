@@ -1,14 +1,76 @@
+use std::collections::BTreeMap;
+
 use egui::{Rect, Response, Stroke, Ui, Visuals, WidgetText};
-use egui_tiles::{Behavior, SimplificationOptions, TabState, TileId, Tiles, UiResponse};
+use egui_tiles::{Behavior, SimplificationOptions, TabState, TileId, Tiles, Tree, UiResponse};
+
+use super::node_flags::{DockNodeFlags, lock_splitters};
 
 pub(super) struct PaneBackgroundBehavior<'a, Pane> {
     inner: &'a mut dyn Behavior<Pane>,
     enabled: bool,
+
+    /// [`DockNodeFlags`] of the tree currently being shown (set before each `tree.ui`).
+    node_flags: BTreeMap<TileId, DockNodeFlags>,
+
+    /// Replaces `inner.simplification_options()` while set (the root tree keeps its central node).
+    simplification_override: Option<SimplificationOptions>,
+
+    /// Set when a tab close was allowed (the layout history must record a step).
+    closed_tab: bool,
+
+    /// Set when egui_tiles reported [`egui_tiles::EditAction::TileDropped`].
+    tile_dropped: bool,
+
+    /// Set when egui_tiles reported [`egui_tiles::EditAction::TileResized`] (a split drag).
+    tile_resized: bool,
 }
 
 impl<'a, Pane> PaneBackgroundBehavior<'a, Pane> {
     pub(super) fn new(inner: &'a mut dyn Behavior<Pane>, enabled: bool) -> Self {
-        Self { inner, enabled }
+        Self {
+            inner,
+            enabled,
+            node_flags: BTreeMap::new(),
+            simplification_override: None,
+            closed_tab: false,
+            tile_dropped: false,
+            tile_resized: false,
+        }
+    }
+
+    pub(super) fn take_closed_tab(&mut self) -> bool {
+        std::mem::take(&mut self.closed_tab)
+    }
+
+    pub(super) fn take_tile_dropped(&mut self) -> bool {
+        std::mem::take(&mut self.tile_dropped)
+    }
+
+    pub(super) fn take_tile_resized(&mut self) -> bool {
+        std::mem::take(&mut self.tile_resized)
+    }
+
+    pub(super) fn set_simplification_override(&mut self, options: Option<SimplificationOptions>) {
+        self.simplification_override = options;
+    }
+
+    pub(super) fn set_node_flags(&mut self, node_flags: BTreeMap<TileId, DockNodeFlags>) {
+        self.node_flags = node_flags;
+    }
+
+    /// See [`lock_splitters`]; uses the flags set for the tree that was just shown.
+    pub(super) fn lock_splitters(&self, ui: &mut Ui, tree: &Tree<Pane>) {
+        lock_splitters(ui, tree, &self.node_flags);
+    }
+
+    fn has_node_flag(&self, tile_id: TileId, flag: DockNodeFlags) -> bool {
+        self.node_flags
+            .get(&tile_id)
+            .is_some_and(|flags| flags.contains(flag))
+    }
+
+    fn hides_window_menu_button(&self, tile_id: TileId) -> bool {
+        self.has_node_flag(tile_id, DockNodeFlags::NO_WINDOW_MENU_BUTTON)
     }
 }
 
@@ -34,7 +96,9 @@ impl<'a, Pane> Behavior<Pane> for PaneBackgroundBehavior<'a, Pane> {
     }
 
     fn on_tab_close(&mut self, tiles: &mut Tiles<Pane>, tile_id: TileId) -> bool {
-        self.inner.on_tab_close(tiles, tile_id)
+        let close = self.inner.on_tab_close(tiles, tile_id);
+        self.closed_tab |= close;
+        close
     }
 
     fn show_tab_close_button(&self, state: &TabState, tab_hovered: bool) -> bool {
@@ -69,7 +133,10 @@ impl<'a, Pane> Behavior<Pane> for PaneBackgroundBehavior<'a, Pane> {
         tile_id: TileId,
         state: &TabState,
     ) -> Response {
-        self.inner.tab_ui(tiles, ui, id, tile_id, state)
+        let response = self.inner.tab_ui(tiles, ui, id, tile_id, state);
+        #[cfg(any(test, feature = "testing"))]
+        super::simulation::record_tab_rect(ui.ctx(), id, response.rect);
+        response
     }
 
     fn drag_ui(&mut self, tiles: &Tiles<Pane>, ui: &mut Ui, tile_id: TileId) {
@@ -97,6 +164,9 @@ impl<'a, Pane> Behavior<Pane> for PaneBackgroundBehavior<'a, Pane> {
         tabs: &egui_tiles::Tabs,
         scroll_offset: &mut f32,
     ) {
+        if self.hides_window_menu_button(tile_id) {
+            return;
+        }
         self.inner
             .top_bar_left_ui(tiles, ui, tile_id, tabs, scroll_offset);
     }
@@ -109,6 +179,9 @@ impl<'a, Pane> Behavior<Pane> for PaneBackgroundBehavior<'a, Pane> {
         tabs: &egui_tiles::Tabs,
         scroll_offset: &mut f32,
     ) {
+        if self.hides_window_menu_button(tile_id) {
+            return;
+        }
         self.inner
             .top_bar_right_ui(tiles, ui, tile_id, tabs, scroll_offset);
     }
@@ -117,6 +190,11 @@ impl<'a, Pane> Behavior<Pane> for PaneBackgroundBehavior<'a, Pane> {
         self.inner.tab_bar_height(style)
     }
 
+    fn show_tab_bar(&self, tiles: &Tiles<Pane>, tile_id: TileId) -> bool {
+        !self.has_node_flag(tile_id, DockNodeFlags::NO_TAB_BAR)
+            && self.inner.show_tab_bar(tiles, tile_id)
+    }
+
     fn gap_width(&self, style: &egui::Style) -> f32 {
         self.inner.gap_width(style)
     }
@@ -134,7 +212,8 @@ impl<'a, Pane> Behavior<Pane> for PaneBackgroundBehavior<'a, Pane> {
     }
 
     fn simplification_options(&self) -> SimplificationOptions {
-        self.inner.simplification_options()
+        self.simplification_override
+            .unwrap_or_else(|| self.inner.simplification_options())
     }
 
     fn auto_hide_tab_bar_when_single_tab(&self) -> bool {
@@ -195,6 +274,8 @@ impl<'a, Pane> Behavior<Pane> for PaneBackgroundBehavior<'a, Pane> {
     }
 
     fn on_edit(&mut self, edit_action: egui_tiles::EditAction) {
+        self.tile_dropped |= matches!(edit_action, egui_tiles::EditAction::TileDropped);
+        self.tile_resized |= matches!(edit_action, egui_tiles::EditAction::TileResized);
         self.inner.on_edit(edit_action)
     }
 }
@@ -0,0 +1,362 @@
+use egui::{Rect, Ui, UiBuilder};
+use egui_tiles::{Container, Linear, LinearDir, SimplificationOptions, Tabs, Tile, TileId, Tree};
+
+use super::DockingMultiViewport;
+use super::node_flags::DockNodeFlags;
+use super::pane_query::PaneHost;
+
+/// Put `new` where `old` is (its parent's slot, or the root).
+fn replace_tile<Pane>(tree: &mut Tree<Pane>, old: TileId, new: TileId) {
+    if tree.root == Some(old) {
+        tree.root = Some(new);
+        return;
+    }
+    let Some(parent) = tree.tiles.parent_of(old) else {
+        return;
+    };
+    match tree.tiles.get_mut(parent) {
+        Some(Tile::Container(Container::Linear(linear))) => {
+            let share = linear.shares[old];
+            if let Some(slot) = linear.children.iter_mut().find(|child| **child == old) {
+                *slot = new;
+            }
+            linear.shares.set_share(new, share);
+        }
+        Some(Tile::Container(Container::Tabs(tabs))) => {
+            if let Some(slot) = tabs.children.iter_mut().find(|child| **child == old) {
+                *slot = new;
+            }
+            if tabs.active == Some(old) {
+                tabs.active = Some(new);
+            }
+        }
+        Some(Tile::Container(container)) => {
+            container.retain(|child| child != old);
+            container.add_child(new);
+        }
+        _ => {}
+    }
+}
+
+fn remove_tile<Pane>(tree: &mut Tree<Pane>, tile_id: TileId) {
+    if tree.root == Some(tile_id) {
+        tree.root = None;
+    } else if let Some(parent) = tree.tiles.parent_of(tile_id)
+        && let Some(Tile::Container(container)) = tree.tiles.get_mut(parent)
+    {
+        container.retain(|child| child != tile_id);
+    }
+    tree.tiles.remove(tile_id);
+}
+
+/// `Tree::simplify`, except that the `Tabs` container `keep` is never pruned.
+///
+/// `egui_tiles` has no per-tile opt-out, so `Tabs` pruning is done here and turned off in the
+/// returned options, which `tree.ui` must use for the rest of the frame.
+pub(super) fn simplify_keeping_tabs<Pane>(
+    tree: &mut Tree<Pane>,
+    mut options: SimplificationOptions,
+    keep: TileId,
+) -> SimplificationOptions {
+    let prune_empty_tabs = options.prune_empty_tabs;
+    let prune_single_child_tabs = options.prune_single_child_tabs;
+    let all_panes_must_have_tabs = options.all_panes_must_have_tabs;
+    options.prune_empty_tabs = false;
+    options.prune_single_child_tabs = false;
+
+    // Pruning a `Tabs` can leave its parent empty or with a single child, and the other way
+    // around: repeat until nothing changes (bounded, trees are shallow).
+    for _ in 0..8 {
+        tree.simplify(&options);
+
+        let candidates: Vec<(TileId, Option<TileId>, usize)> = tree
+            .tiles
+            .tile_ids()
+            .filter(|&tile_id| tile_id != keep)
+            .filter_map(|tile_id| match tree.tiles.get(tile_id) {
+                Some(Tile::Container(Container::Tabs(tabs))) => {
+                    Some((tile_id, tabs.children.first().copied(), tabs.children.len()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut changed = false;
+        for (tabs_id, first_child, len) in candidates {
+            if len == 0 && prune_empty_tabs {
+                remove_tile(tree, tabs_id);
+                changed = true;
+            } else if let (1, Some(child)) = (len, first_child)
+                && prune_single_child_tabs
+            {
+                let child_is_pane = matches!(tree.tiles.get(child), Some(Tile::Pane(_)));
+                if all_panes_must_have_tabs && child_is_pane {
+                    continue;
+                }
+                replace_tile(tree, tabs_id, child);
+                tree.tiles.remove(tabs_id);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    options
+}
+
+/// Storage for the empty-state UI callback (keeps `DockingMultiViewport: Debug`).
+#[derive(Default)]
+pub(super) struct CentralNodeEmptyUiSlot(pub(super) Option<Box<dyn FnMut(&mut Ui)>>);
+
+impl std::fmt::Debug for CentralNodeEmptyUiSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
+impl<Pane> DockingMultiViewport<Pane> {
+    /// The central node of the root dock (see [`DockNodeFlags::CENTRAL_NODE`]), if any.
+    pub fn central_node(&self) -> Option<TileId> {
+        self.node_flags
+            .iter()
+            .find(|&(&(host, _), flags)| {
+                host == PaneHost::RootDock && flags.contains(DockNodeFlags::CENTRAL_NODE)
+            })
+            .map(|(&(_, tile_id), _)| tile_id)
+    }
+
+    /// Make the root-dock `Tabs` container `tile_id` the central node (ImGui's `CentralNode`).
+    ///
+    /// The central node is kept in the layout when its last pane is closed or dragged away,
+    /// shows the UI installed with [`Self::set_central_node_empty_ui`] while empty, and is where
+    /// [`Self::add_pane_to_central_node`] puts new documents. Its panes can be dragged out, but
+    /// the node itself stays in the root dock. It is saved with the layout.
+    ///
+    /// Returns `false` if `tile_id` is not a `Tabs` container of the root dock.
+    pub fn set_central_node(&mut self, tile_id: TileId) -> bool {
+        let flags = self.node_flags(PaneHost::RootDock, tile_id) | DockNodeFlags::CENTRAL_NODE;
+        self.set_node_flags(PaneHost::RootDock, tile_id, flags)
+    }
+
+    /// Turn the central node back into a regular node (it can be pruned again).
+    pub fn clear_central_node(&mut self) {
+        let Some(tile_id) = self.central_node() else {
+            return;
+        };
+        let key = (PaneHost::RootDock, tile_id);
+        if let Some(flags) = self.node_flags.get_mut(&key) {
+            flags.remove(DockNodeFlags::CENTRAL_NODE);
+            if flags.is_empty() {
+                self.node_flags.remove(&key);
+            }
+        }
+    }
+
+    /// UI shown inside the central node while it has no panes (e.g. "Open a file…" hints).
+    pub fn set_central_node_empty_ui(&mut self, ui: impl FnMut(&mut Ui) + 'static) {
+        self.central_node_empty_ui = CentralNodeEmptyUiSlot(Some(Box::new(ui)));
+    }
+
+    pub fn clear_central_node_empty_ui(&mut self) {
+        self.central_node_empty_ui = CentralNodeEmptyUiSlot(None);
+    }
+
+    /// Add `pane` as the active tab of the central node and return its tile.
+    ///
+    /// Gives the pane back if there is no central node.
+    pub fn add_pane_to_central_node(&mut self, pane: Pane) -> Result<TileId, Pane> {
+        self.restore_central_node_if_missing();
+        let Some(central) = self.central_node() else {
+            return Err(pane);
+        };
+        let tile_id = self.tree.tiles.insert_pane(pane);
+        if let Some(Tile::Container(Container::Tabs(tabs))) = self.tree.tiles.get_mut(central) {
+            tabs.add_child(tile_id);
+            tabs.set_active(tile_id);
+        }
+        Ok(tile_id)
+    }
+
+    /// Put an empty central node back if it left the root tree (e.g. the app edited
+    /// [`Self::tree`] directly). It keeps its [`TileId`] and is docked to the right of the root.
+    fn restore_central_node_if_missing(&mut self) {
+        let Some(central) = self.central_node() else {
+            return;
+        };
+        let attached = self.tree.root == Some(central) || self.tree.tiles.parent_of(central).is_some();
+        match self.tree.tiles.get(central) {
+            Some(Tile::Container(Container::Tabs(_))) if attached => return,
+            Some(Tile::Container(Container::Tabs(_))) => {
+                self.tree.tiles.remove(central);
+            }
+            Some(_) => {
+                // The id now belongs to something else (the tree was replaced).
+                self.clear_central_node();
+                return;
+            }
+            None => {}
+        }
+
+        self.tree
+            .tiles
+            .insert(central, Tile::Container(Container::from(Tabs::new(Vec::new()))));
+        self.tree.root = Some(match self.tree.root {
+            Some(root) => self
+                .tree
+                .tiles
+                .insert_container(Linear::new(LinearDir::Horizontal, vec![root, central])),
+            None => central,
+        });
+        if self.options.debug_event_log {
+            self.debug_log_event(format!("central_node RESTORE tile={central:?}"));
+        }
+    }
+
+    /// Simplify the root tree before `tree.ui`, without pruning the central node.
+    ///
+    /// Returns the simplification options `tree.ui` must use this frame, or `None` if there is
+    /// no central node (plain `egui_tiles` behavior).
+    pub(super) fn simplify_root_keeping_central_node(
+        &mut self,
+        options: SimplificationOptions,
+    ) -> Option<SimplificationOptions> {
+        self.restore_central_node_if_missing();
+        let central = self.central_node()?;
+        Some(simplify_keeping_tabs(&mut self.tree, options, central))
+    }
+
+    /// Show the empty-state UI over the central node's content area if it has no panes.
+    pub(super) fn ui_central_node_empty_state(&mut self, ui: &mut Ui, tab_bar_height: f32) {
+        let Some(central) = self.central_node() else {
+            return;
+        };
+        let Some(empty_ui) = self.central_node_empty_ui.0.as_mut() else {
+            return;
+        };
+        let Some(Tile::Container(Container::Tabs(tabs))) = self.tree.tiles.get(central) else {
+            return;
+        };
+        if !tabs.children.is_empty() {
+            return;
+        }
+        let Some(rect) = self.tree.tiles.rect(central) else {
+            return;
+        };
+        let content_rect = Rect::from_min_max(
+            egui::pos2(rect.min.x, (rect.min.y + tab_bar_height).min(rect.max.y)),
+            rect.max,
+        );
+        if !content_rect.is_positive() {
+            return;
+        }
+
+        let mut content_ui = ui.new_child(UiBuilder::new().max_rect(content_rect));
+        content_ui.set_clip_rect(ui.clip_rect().intersect(content_rect));
+        empty_ui(&mut content_ui);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tabs_children(tree: &Tree<u32>, tile_id: TileId) -> Vec<TileId> {
+        match tree.tiles.get(tile_id) {
+            Some(Tile::Container(Container::Tabs(tabs))) => tabs.children.clone(),
+            other => panic!("expected a Tabs container, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn simplification_keeps_only_the_central_tabs() {
+        let mut tiles = egui_tiles::Tiles::default();
+        let central = tiles.insert_tab_tile(Vec::new());
+        let stale = tiles.insert_tab_tile(Vec::new());
+        let console = tiles.insert_pane(1);
+        let console_tabs = tiles.insert_tab_tile(vec![console]);
+        let bottom = tiles.insert_horizontal_tile(vec![stale, console_tabs]);
+        let root = tiles.insert_vertical_tile(vec![central, bottom]);
+        let mut tree = Tree::new(egui::Id::new("central"), root, tiles);
+
+        let options = SimplificationOptions {
+            prune_empty_tabs: true,
+            prune_single_child_tabs: true,
+            all_panes_must_have_tabs: false,
+            ..Default::default()
+        };
+        let relaxed = simplify_keeping_tabs(&mut tree, options, central);
+        assert!(!relaxed.prune_empty_tabs && !relaxed.prune_single_child_tabs);
+
+        // The empty central node stays, the other empty `Tabs` and the single-child `Tabs`
+        // are pruned like `egui_tiles` would.
+        assert!(tabs_children(&tree, central).is_empty());
+        assert!(tree.tiles.get(stale).is_none());
+        assert!(tree.tiles.get(console_tabs).is_none());
+        assert_eq!(tree.tiles.parent_of(console), Some(root));
+        assert_eq!(tree.tiles.parent_of(central), Some(root));
+    }
+
+    #[test]
+    fn central_node_keeps_a_single_pane_in_tabs() {
+        let mut tiles = egui_tiles::Tiles::default();
+        let doc = tiles.insert_pane(1);
+        let central = tiles.insert_tab_tile(vec![doc]);
+        let mut tree = Tree::new(egui::Id::new("central"), central, tiles);
+
+        let options = SimplificationOptions {
+            prune_single_child_tabs: true,
+            all_panes_must_have_tabs: false,
+            ..Default::default()
+        };
+        simplify_keeping_tabs(&mut tree, options, central);
+        assert_eq!(tree.root, Some(central));
+        assert_eq!(tabs_children(&tree, central), vec![doc]);
+    }
+
+    #[test]
+    fn central_node_api() {
+        let mut tiles = egui_tiles::Tiles::default();
+        let doc = tiles.insert_pane(1);
+        let central = tiles.insert_tab_tile(vec![doc]);
+        let other = tiles.insert_pane(2);
+        let other_tabs = tiles.insert_tab_tile(vec![other]);
+        let root = tiles.insert_horizontal_tile(vec![central, other_tabs]);
+        let mut docking = DockingMultiViewport::new(Tree::new(egui::Id::new("root"), root, tiles));
+
+        assert_eq!(docking.add_pane_to_central_node(3), Err(3));
+        assert!(!docking.set_central_node(root));
+        assert!(!docking.set_central_node(doc));
+        // Only one central node at a time.
+        assert!(docking.set_central_node(other_tabs));
+        assert!(docking.set_central_node(central));
+        assert_eq!(docking.central_node(), Some(central));
+        assert!(docking.node_flags(PaneHost::RootDock, other_tabs).is_empty());
+
+        // Closing the last document keeps the node around.
+        if let Some(Tile::Container(container)) = docking.tree.tiles.get_mut(central) {
+            container.retain(|child| child != doc);
+        }
+        docking.tree.tiles.remove(doc);
+        docking.prune_node_flags();
+        assert!(docking
+            .simplify_root_keeping_central_node(SimplificationOptions::default())
+            .is_some());
+        assert_eq!(docking.tree.tiles.parent_of(central), Some(root));
+
+        let opened = docking.add_pane_to_central_node(4).unwrap();
+        assert_eq!(docking.tree.tiles.parent_of(opened), Some(central));
+
+        // Removed behind our back: put back empty, with the same id.
+        docking.tree.tiles.remove(opened);
+        docking.tree.tiles.remove(central);
+        if let Some(Tile::Container(container)) = docking.tree.tiles.get_mut(root) {
+            container.retain(|child| child != central);
+        }
+        docking.prune_node_flags();
+        docking.simplify_root_keeping_central_node(SimplificationOptions::default());
+        assert!(tabs_children(&docking.tree, central).is_empty());
+        assert!(docking.tree.tiles.parent_of(central).is_some());
+    }
+}
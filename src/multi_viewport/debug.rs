@@ -2,7 +2,9 @@ use egui::{Context, ViewportId};
 use egui_tiles::{TileId, Tree};
 
 use super::DockingMultiViewport;
+use super::debug_log::{DebugLogEntry, DebugLogRecord};
 use super::integrity;
+use super::options::DebugLogFileFormat;
 
 use std::fs::{create_dir_all, File, OpenOptions};
 use std::io::{BufWriter, Write as _};
@@ -176,31 +178,58 @@ impl<Pane> DockingMultiViewport<Pane> {
     }
 
     pub(super) fn debug_log_event(&mut self, message: impl Into<String>) {
+        self.debug_log_record(DebugLogRecord::Message(message.into()));
+    }
+
+    pub(super) fn debug_log_record(&mut self, record: DebugLogRecord) {
+        #[cfg(feature = "tracing")]
+        self.trace_record(&record);
         if !self.options.debug_event_log {
             return;
         }
-        self.push_debug_log_line(message.into());
+        self.push_debug_log_record(record);
     }
 
     pub(super) fn debug_integrity_log_event(&mut self, message: impl Into<String>) {
+        self.debug_integrity_log_record(DebugLogRecord::Message(message.into()));
+    }
+
+    pub(super) fn debug_integrity_log_record(&mut self, record: DebugLogRecord) {
+        #[cfg(feature = "tracing")]
+        self.trace_record(&record);
         if !self.options.debug_integrity {
             return;
         }
-        self.push_debug_log_line(message.into());
+        self.push_debug_log_record(record);
     }
 
-    pub(super) fn push_debug_log_line(&mut self, message: String) {
+    pub(super) fn push_debug_log_record(&mut self, record: DebugLogRecord) {
         self.debug_log_file_prepare_if_needed();
 
         let cap = self.options.debug_event_log_capacity.max(1).min(10_000);
         while self.debug_log.len() >= cap {
             self.debug_log.pop_front();
         }
-        let line = format!("[frame {}] {}", self.debug_frame, message);
-        self.debug_log.push_back(line.clone());
+        let entry = DebugLogEntry {
+            frame: self.debug_frame,
+            record,
+        };
         if self.options.debug_log_file_path.is_some() {
+            let line = match self.options.debug_log_file_format {
+                DebugLogFileFormat::Text => entry.to_text_line(),
+                #[cfg(feature = "debug-log-json")]
+                DebugLogFileFormat::JsonLines => entry.to_json_line(),
+            };
             self.debug_log_file_append_line(&line);
         }
+        self.debug_log.push_back(entry);
+    }
+
+    /// The debug event log ring buffer, oldest first.
+    ///
+    /// Filled while `debug_event_log` / `debug_integrity` are enabled.
+    pub fn debug_log_entries(&self) -> impl Iterator<Item = &DebugLogEntry> + '_ {
+        self.debug_log.iter()
     }
 
     pub(super) fn debug_log_clear(&mut self) {
@@ -210,11 +239,21 @@ impl<Pane> DockingMultiViewport<Pane> {
     pub(super) fn debug_log_text(&self) -> String {
         self.debug_log
             .iter()
-            .cloned()
+            .map(DebugLogEntry::to_text_line)
             .collect::<Vec<_>>()
             .join("\n")
     }
 
+    /// Whether to run the integrity checks this frame: with `debug_integrity`, or whenever a
+    /// `tracing` subscriber records `egui_docking` warnings (integrity failures are `warn` events).
+    pub(super) fn integrity_checks_enabled(&self) -> bool {
+        #[cfg(feature = "tracing")]
+        if tracing::enabled!(target: "egui_docking", tracing::Level::WARN) {
+            return true;
+        }
+        self.options.debug_integrity
+    }
+
     pub(super) fn debug_check_integrity_all(&mut self) {
         let mut results: Vec<(ViewportId, egui::Id, Vec<String>, u64)> = Vec::new();
 
@@ -268,14 +307,11 @@ impl<Pane> DockingMultiViewport<Pane> {
             return;
         }
 
-        self.debug_integrity_log_event(format!(
-            "integrity FAIL viewport={viewport_id:?} tree={:04X} issues={}",
-            tree_id.value() as u16,
-            issues.len()
-        ));
-        for issue in &issues {
-            self.debug_integrity_log_event(issue.clone());
-        }
+        self.debug_integrity_log_record(DebugLogRecord::IntegrityFailure {
+            viewport: viewport_id,
+            tree: tree_id,
+            issues: issues.clone(),
+        });
         // Include a short tree summary to make copy-paste debugging self contained.
         let summary = if viewport_id == ViewportId::ROOT && tree_id == self.tree.id() {
             debug_tree_summary(&self.tree, 48)
@@ -295,7 +331,10 @@ impl<Pane> DockingMultiViewport<Pane> {
         };
         self.debug_integrity_log_event(format!("integrity tree_summary:\n{summary}"));
 
-        if self.options.debug_integrity_panic && cfg!(debug_assertions) {
+        if self.options.debug_integrity
+            && self.options.debug_integrity_panic
+            && cfg!(debug_assertions)
+        {
             panic!(
                 "egui_docking integrity failure viewport={viewport_id:?} tree={:04X}\n{}",
                 tree_id.value() as u16,
@@ -0,0 +1,852 @@
+use std::fmt;
+
+use egui::{Pos2, Rect, Vec2, ViewportId};
+use egui_tiles::{InsertionPoint, TileId};
+
+use super::events::DockEvent;
+use super::pane_query::PaneHost;
+use super::types::FloatingId;
+
+/// One line of the debug event log, stamped with the frame it was recorded in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugLogEntry {
+    pub frame: u64,
+    pub record: DebugLogRecord,
+}
+
+/// What a [`DebugLogEntry`] reports.
+///
+/// Rendered as text (`[frame N] ...`) in the debug window and the default log file, or as one
+/// JSON object per line with `DebugLogFileFormat::JsonLines` (feature `debug-log-json`).
+#[derive(Clone, Debug, PartialEq)]
+pub enum DebugLogRecord {
+    /// A drag session began; `source` is what was first seen dragging (payload, ghost, tiles).
+    SessionStart { session: u64, source: &'static str },
+
+    /// A release action (`kind`) was taken for the session.
+    ///
+    /// `session` is `None` if no drag source was observed; `ignored` if another release action
+    /// already ran for the session this frame.
+    SessionRelease {
+        session: Option<u64>,
+        kind: &'static str,
+        source: Option<&'static str>,
+        ignored: bool,
+    },
+
+    /// No drag source was observed anymore.
+    SessionEnd {
+        session: u64,
+        started_frame: u64,
+        end_frame: u64,
+    },
+
+    /// Where a drop goes: `stage` is `local`, `cross_viewport` or `internal`.
+    DropDecision {
+        stage: &'static str,
+        window_move: bool,
+        /// The docking policy (or node flags) refused the drop, so nothing was moved.
+        rejected: bool,
+        source_host: PaneHost,
+        target_host: PaneHost,
+        /// The dragged tile; `None` for a window move.
+        tile_id: Option<TileId>,
+        /// The dock surface (tree or floating window) the pointer was hit-tested against.
+        target_surface: Option<PaneHost>,
+        insertion: Option<InsertionPoint>,
+        pointer_global: Option<Pos2>,
+        /// Pointer position in the target viewport, in points.
+        pointer_local: Option<Pos2>,
+    },
+
+    /// A dock tree failed the integrity checks.
+    IntegrityFailure {
+        viewport: ViewportId,
+        tree: egui::Id,
+        issues: Vec<String>,
+    },
+
+    /// A viewport command sent to move a native window.
+    WindowMove {
+        viewport: ViewportId,
+        command: WindowMoveCommand,
+    },
+
+    /// `tile_id` was torn off `source_host` into a new window (`floating`: a contained one), by a
+    /// drag or through the API (`api`: `detach_tile_to_viewport` / `float_tile`).
+    TearOff {
+        source_host: PaneHost,
+        tile_id: TileId,
+        floating: bool,
+        api: bool,
+        /// `false` if the docking policy (or node flags) refused it.
+        accepted: bool,
+        /// The window the tile went to, if it was created right away.
+        new_host: Option<PaneHost>,
+    },
+
+    /// A [`DockEvent`] was emitted.
+    DockEvent(DockEvent),
+
+    /// Switched to the workspace preset `name`; `parked` panes are now parked.
+    PresetSwitch { name: String, parked: usize },
+
+    /// A contained ghost left its viewport and became the native window `native_viewport`.
+    GhostUpgrade {
+        viewport: ViewportId,
+        floating: FloatingId,
+        native_viewport: ViewportId,
+    },
+
+    /// The backend hints changed (see [`super::backend_mouse_hovered_viewport_id`],
+    /// [`super::backend_pointer_global_points`] and [`super::backend_monitors_outer_rects_points`]).
+    ///
+    /// `monitors` is `None` if the backend doesn't publish them.
+    BackendHints {
+        hovered_viewport: Option<ViewportId>,
+        pointer_global: Option<Pos2>,
+        monitors: Option<Vec<Rect>>,
+    },
+
+    /// Anything else, as free-form text.
+    Message(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowMoveCommand {
+    /// `ViewportCommand::StartDrag`: the OS moves the window from here on.
+    StartDrag,
+
+    /// `ViewportCommand::OuterPosition` sent while a native ghost window follows the pointer.
+    OuterPosition {
+        step: u64,
+        pointer_global: Pos2,
+        grab_in_inner: Vec2,
+        outer_from_inner: Vec2,
+        desired_outer: Pos2,
+    },
+}
+
+impl DebugLogRecord {
+    /// Stable name used as `"kind"` in JSON lines.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::SessionStart { .. } => "session_start",
+            Self::SessionRelease { .. } => "session_release",
+            Self::SessionEnd { .. } => "session_end",
+            Self::DropDecision { .. } => "drop_decision",
+            Self::IntegrityFailure { .. } => "integrity_failure",
+            Self::WindowMove { .. } => "window_move",
+            Self::TearOff { .. } => "tear_off",
+            Self::DockEvent(_) => "dock_event",
+            Self::PresetSwitch { .. } => "preset_switch",
+            Self::GhostUpgrade { .. } => "ghost_upgrade",
+            Self::BackendHints { .. } => "backend_hints",
+            Self::Message(_) => "message",
+        }
+    }
+}
+
+impl fmt::Display for DebugLogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SessionStart { session, source } => {
+                write!(f, "session START id={session} source={source}")
+            }
+            Self::SessionRelease {
+                session: None,
+                kind,
+                ..
+            } => write!(f, "session RELEASE kind={kind} (no active session)"),
+            Self::SessionRelease {
+                session: Some(session),
+                kind,
+                ignored: true,
+                ..
+            } => write!(f, "session RELEASE ignored id={session} kind={kind}"),
+            Self::SessionRelease {
+                session: Some(session),
+                kind,
+                source,
+                ignored: false,
+            } => write!(
+                f,
+                "session RELEASE id={session} kind={kind} source={}",
+                source.unwrap_or("unknown")
+            ),
+            Self::SessionEnd {
+                session,
+                started_frame,
+                end_frame,
+            } => write!(
+                f,
+                "session END id={session} started_frame={started_frame} end_frame={end_frame}"
+            ),
+            Self::DropDecision {
+                stage: "local",
+                rejected: true,
+                source_host,
+                tile_id,
+                target_surface,
+                ..
+            } => write!(
+                f,
+                "apply_local_drop REJECTED (docking policy) target_surface={target_surface:?} source_host={source_host:?} tile_id={tile_id:?}"
+            ),
+            Self::DropDecision {
+                stage: "cross_viewport",
+                rejected: true,
+                source_host,
+                target_host,
+                tile_id,
+                ..
+            } => write!(
+                f,
+                "resolve_cross_viewport_drop REJECTED (docking policy) source_host={source_host:?} payload_tile_id={tile_id:?} target_host={target_host:?}"
+            ),
+            Self::DropDecision {
+                stage: "local",
+                target_surface,
+                insertion,
+                ..
+            } => write!(
+                f,
+                "apply_local_drop OK target_surface={target_surface:?} insertion={insertion:?}"
+            ),
+            Self::DropDecision {
+                stage: "cross_viewport",
+                window_move,
+                source_host,
+                target_host,
+                tile_id,
+                target_surface,
+                insertion,
+                pointer_global,
+                pointer_local,
+                ..
+            } => {
+                write!(
+                    f,
+                    "resolve_cross_viewport_drop window_move={window_move} source_host={source_host:?} payload_tile_id={tile_id:?}"
+                )?;
+                write_point(f, "pointer_global", *pointer_global)?;
+                write!(
+                    f,
+                    " target_host={target_host:?} target_surface={target_surface:?}"
+                )?;
+                write_point(f, "pointer_local", *pointer_local)?;
+                write!(f, " insertion={insertion:?}")
+            }
+            Self::DropDecision {
+                stage: "internal",
+                target_host,
+                tile_id,
+                insertion,
+                ..
+            } => {
+                match target_host {
+                    PaneHost::RootDock => write!(f, "apply_internal_drop BEGIN viewport=ROOT")?,
+                    PaneHost::DetachedViewport { viewport } => {
+                        write!(f, "apply_internal_drop BEGIN viewport={viewport:?}")?;
+                    }
+                    PaneHost::Floating { viewport, floating } => write!(
+                        f,
+                        "apply_internal_drop BEGIN viewport={viewport:?} floating={floating}"
+                    )?,
+                }
+                write!(f, " tile_id={tile_id:?} insertion={insertion:?}")
+            }
+            Self::DropDecision {
+                stage,
+                window_move,
+                rejected,
+                source_host,
+                target_host,
+                insertion,
+                pointer_global,
+                ..
+            } => {
+                write!(
+                    f,
+                    "drop DECISION stage={stage} window_move={window_move} rejected={rejected} source_host={source_host:?} target_host={target_host:?} insertion={insertion:?}"
+                )?;
+                write_point(f, "pointer_global", *pointer_global)
+            }
+            Self::IntegrityFailure {
+                viewport,
+                tree,
+                issues,
+            } => {
+                // Kept on one line (like every other record) so line-based tools can split the log.
+                write!(
+                    f,
+                    "integrity FAIL viewport={viewport:?} tree={:04X} issues={}",
+                    tree.value() as u16,
+                    issues.len()
+                )?;
+                for (i, issue) in issues.iter().enumerate() {
+                    let separator = if i == 0 { ": " } else { "; " };
+                    write!(f, "{separator}{}", issue.replace('\n', " "))?;
+                }
+                Ok(())
+            }
+            Self::WindowMove {
+                viewport,
+                command: WindowMoveCommand::StartDrag,
+            } => write!(f, "detached_window_move START viewport={viewport:?}"),
+            Self::WindowMove {
+                viewport,
+                command:
+                    WindowMoveCommand::OuterPosition {
+                        step,
+                        pointer_global,
+                        grab_in_inner,
+                        outer_from_inner,
+                        desired_outer,
+                    },
+            } => write!(
+                f,
+                "ghost_window_move step={step} viewport={viewport:?} pointer_global=({:.1},{:.1}) grab_in_inner=({:.1},{:.1}) outer_from_inner=({:.1},{:.1}) desired_outer=({:.1},{:.1})",
+                pointer_global.x,
+                pointer_global.y,
+                grab_in_inner.x,
+                grab_in_inner.y,
+                outer_from_inner.x,
+                outer_from_inner.y,
+                desired_outer.x,
+                desired_outer.y
+            ),
+            Self::TearOff {
+                source_host,
+                tile_id,
+                floating,
+                api,
+                accepted,
+                new_host,
+            } => {
+                let name = match (api, floating) {
+                    (true, false) => "api_detach",
+                    (true, true) => "api_float",
+                    (false, _) => "tear_off",
+                };
+                let verdict = if *accepted {
+                    "OK"
+                } else {
+                    "REJECTED by docking policy"
+                };
+                write!(
+                    f,
+                    "{name} {verdict} host={source_host:?} tile={tile_id:?} floating={floating}"
+                )?;
+                match new_host {
+                    Some(new_host) => write!(f, " -> {new_host:?}"),
+                    None => Ok(()),
+                }
+            }
+            Self::DockEvent(event) => write!(f, "dock_event {event:?}"),
+            Self::PresetSwitch { name, parked } => {
+                write!(f, "workspace_preset switch name={name:?} parked={parked}")
+            }
+            Self::GhostUpgrade {
+                viewport,
+                floating,
+                native_viewport,
+            } => write!(
+                f,
+                "ghost UPGRADE viewport={viewport:?} floating={floating} native_viewport={native_viewport:?}"
+            ),
+            Self::BackendHints {
+                hovered_viewport,
+                pointer_global,
+                monitors: Some(monitors),
+            } => write!(
+                f,
+                "backend_hints hovered={hovered_viewport:?} pointer={pointer_global:?} monitors_outer_rects_points={} first={:?}",
+                monitors.len(),
+                monitors.first().map(|r| (r.min, r.max))
+            ),
+            Self::BackendHints {
+                hovered_viewport,
+                pointer_global,
+                monitors: None,
+            } => write!(
+                f,
+                "backend_hints hovered={hovered_viewport:?} pointer={pointer_global:?} monitors_outer_rects_points=<missing>"
+            ),
+            Self::Message(text) => f.write_str(text),
+        }
+    }
+}
+
+impl DebugLogEntry {
+    pub fn to_text_line(&self) -> String {
+        format!("[frame {}] {}", self.frame, self.record)
+    }
+
+    /// One JSON object without a trailing newline, e.g.
+    /// `{"frame":12,"kind":"session_start","session":3,"source":"payload"}`.
+    ///
+    /// Hosts and insertion points are nested objects (`{"kind":"floating","viewport":..}`,
+    /// `{"parent":"#3","container":"tabs","index":1}`), viewport ids are hex strings and points
+    /// are `[x, y]`.
+    #[cfg(feature = "debug-log-json")]
+    pub fn to_json_line(&self) -> String {
+        use serde_json::{Map, Value, json};
+
+        let mut object = Map::new();
+        object.insert("frame".to_owned(), json!(self.frame));
+        object.insert("kind".to_owned(), json!(self.record.kind()));
+        let fields = match &self.record {
+            DebugLogRecord::SessionStart { session, source } => json!({
+                "session": session,
+                "source": source,
+            }),
+            DebugLogRecord::SessionRelease {
+                session,
+                kind,
+                source,
+                ignored,
+            } => json!({
+                "session": session,
+                "release_kind": kind,
+                "source": source,
+                "ignored": ignored,
+            }),
+            DebugLogRecord::SessionEnd {
+                session,
+                started_frame,
+                end_frame,
+            } => json!({
+                "session": session,
+                "started_frame": started_frame,
+                "end_frame": end_frame,
+            }),
+            DebugLogRecord::DropDecision {
+                stage,
+                window_move,
+                rejected,
+                source_host,
+                target_host,
+                tile_id,
+                target_surface,
+                insertion,
+                pointer_global,
+                pointer_local,
+            } => json!({
+                "stage": stage,
+                "window_move": window_move,
+                "rejected": rejected,
+                "source_host": json_host(*source_host),
+                "target_host": json_host(*target_host),
+                "tile_id": tile_id.map(json_tile_id),
+                "target_surface": target_surface.map(json_host),
+                "insertion": insertion.map(json_insertion),
+                "pointer_global": pointer_global.map(|pos| json!([pos.x, pos.y])),
+                "pointer_local": pointer_local.map(|pos| json!([pos.x, pos.y])),
+            }),
+            DebugLogRecord::IntegrityFailure {
+                viewport,
+                tree,
+                issues,
+            } => json!({
+                "viewport": json_viewport(*viewport),
+                "tree": format!("{:04X}", tree.value() as u16),
+                "issues": issues,
+            }),
+            DebugLogRecord::WindowMove {
+                viewport,
+                command: WindowMoveCommand::StartDrag,
+            } => json!({
+                "viewport": json_viewport(*viewport),
+                "command": "start_drag",
+            }),
+            DebugLogRecord::WindowMove {
+                viewport,
+                command:
+                    WindowMoveCommand::OuterPosition {
+                        step,
+                        pointer_global,
+                        grab_in_inner,
+                        outer_from_inner,
+                        desired_outer,
+                    },
+            } => json!({
+                "viewport": json_viewport(*viewport),
+                "command": "outer_position",
+                "step": step,
+                "pointer_global": [pointer_global.x, pointer_global.y],
+                "grab_in_inner": [grab_in_inner.x, grab_in_inner.y],
+                "outer_from_inner": [outer_from_inner.x, outer_from_inner.y],
+                "desired_outer": [desired_outer.x, desired_outer.y],
+            }),
+            DebugLogRecord::TearOff {
+                source_host,
+                tile_id,
+                floating,
+                api,
+                accepted,
+                new_host,
+            } => json!({
+                "source_host": json_host(*source_host),
+                "tile_id": json_tile_id(*tile_id),
+                "floating": floating,
+                "api": api,
+                "accepted": accepted,
+                "new_host": new_host.map(json_host),
+            }),
+            DebugLogRecord::DockEvent(event) => json_dock_event(*event),
+            DebugLogRecord::PresetSwitch { name, parked } => json!({
+                "name": name,
+                "parked": parked,
+            }),
+            DebugLogRecord::GhostUpgrade {
+                viewport,
+                floating,
+                native_viewport,
+            } => json!({
+                "viewport": json_viewport(*viewport),
+                "floating": floating,
+                "native_viewport": json_viewport(*native_viewport),
+            }),
+            DebugLogRecord::BackendHints {
+                hovered_viewport,
+                pointer_global,
+                monitors,
+            } => json!({
+                "hovered_viewport": hovered_viewport.map(json_viewport),
+                "pointer_global": pointer_global.map(|pos| json!([pos.x, pos.y])),
+                "monitors": monitors.as_ref().map(|monitors| {
+                    monitors
+                        .iter()
+                        .map(|r| json!([r.min.x, r.min.y, r.max.x, r.max.y]))
+                        .collect::<Vec<_>>()
+                }),
+            }),
+            DebugLogRecord::Message(text) => json!({ "text": text }),
+        };
+        if let Value::Object(fields) = fields {
+            object.extend(fields);
+        }
+        Value::Object(object).to_string()
+    }
+}
+
+fn write_point(f: &mut fmt::Formatter<'_>, name: &str, pos: Option<Pos2>) -> fmt::Result {
+    match pos {
+        Some(pos) => write!(f, " {name}=({:.1},{:.1})", pos.x, pos.y),
+        None => Ok(()),
+    }
+}
+
+#[cfg(feature = "debug-log-json")]
+fn json_viewport(viewport: ViewportId) -> serde_json::Value {
+    format!("{:016X}", viewport.0.value()).into()
+}
+
+#[cfg(feature = "debug-log-json")]
+fn json_tile_id(tile_id: TileId) -> serde_json::Value {
+    format!("{tile_id:?}").into()
+}
+
+#[cfg(feature = "debug-log-json")]
+fn json_host(host: PaneHost) -> serde_json::Value {
+    match host {
+        PaneHost::RootDock => serde_json::json!({ "kind": "root_dock" }),
+        PaneHost::DetachedViewport { viewport } => serde_json::json!({
+            "kind": "detached_viewport",
+            "viewport": json_viewport(viewport),
+        }),
+        PaneHost::Floating { viewport, floating } => serde_json::json!({
+            "kind": "floating",
+            "viewport": json_viewport(viewport),
+            "floating": floating,
+        }),
+    }
+}
+
+/// `{"event": "drop_applied", "source": {..}, "target": {..}}`; the event name is the variant in
+/// snake case.
+#[cfg(feature = "debug-log-json")]
+fn json_dock_event(event: DockEvent) -> serde_json::Value {
+    use serde_json::json;
+
+    match event {
+        DockEvent::PaneDetached { source, viewport } => json!({
+            "event": "pane_detached",
+            "source": json_host(source),
+            "viewport": json_viewport(viewport),
+        }),
+        DockEvent::PaneRedocked { source, target } => json!({
+            "event": "pane_redocked",
+            "source": json_host(source),
+            "target": json_host(target),
+        }),
+        DockEvent::FloatingCreated { viewport, floating } => json!({
+            "event": "floating_created",
+            "viewport": json_viewport(viewport),
+            "floating": floating,
+        }),
+        DockEvent::FloatingClosed { viewport, floating } => json!({
+            "event": "floating_closed",
+            "viewport": json_viewport(viewport),
+            "floating": floating,
+        }),
+        DockEvent::ViewportClosed { viewport } => json!({
+            "event": "viewport_closed",
+            "viewport": json_viewport(viewport),
+        }),
+        DockEvent::DropApplied { source, target } => json!({
+            "event": "drop_applied",
+            "source": json_host(source),
+            "target": json_host(target),
+        }),
+        DockEvent::TabActivated {
+            host,
+            tabs,
+            tile_id,
+        } => json!({
+            "event": "tab_activated",
+            "host": json_host(host),
+            "tabs": json_tile_id(tabs),
+            "tile_id": json_tile_id(tile_id),
+        }),
+    }
+}
+
+#[cfg(feature = "debug-log-json")]
+fn json_insertion(insertion: InsertionPoint) -> serde_json::Value {
+    use egui_tiles::ContainerInsertion;
+
+    let parent = json_tile_id(insertion.parent_id);
+    match insertion.insertion {
+        ContainerInsertion::Tabs(index) => {
+            serde_json::json!({ "parent": parent, "container": "tabs", "index": index })
+        }
+        ContainerInsertion::Horizontal(index) => {
+            serde_json::json!({ "parent": parent, "container": "horizontal", "index": index })
+        }
+        ContainerInsertion::Vertical(index) => {
+            serde_json::json!({ "parent": parent, "container": "vertical", "index": index })
+        }
+        ContainerInsertion::Grid(loc) => serde_json::json!({
+            "parent": parent,
+            "container": "grid",
+            "col": loc.col,
+            "row": loc.row,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_lines_keep_the_legacy_format() {
+        let entry = DebugLogEntry {
+            frame: 7,
+            record: DebugLogRecord::SessionRelease {
+                session: Some(3),
+                kind: "local_drop",
+                source: None,
+                ignored: false,
+            },
+        };
+        assert_eq!(
+            entry.to_text_line(),
+            "[frame 7] session RELEASE id=3 kind=local_drop source=unknown"
+        );
+    }
+
+    #[test]
+    fn drop_decisions_keep_the_legacy_lines() {
+        let tile_id = TileId::from_u64(4);
+        let host = PaneHost::Floating {
+            viewport: ViewportId::ROOT,
+            floating: 2,
+        };
+        let internal = DebugLogRecord::DropDecision {
+            stage: "internal",
+            window_move: false,
+            rejected: false,
+            source_host: PaneHost::RootDock,
+            target_host: PaneHost::RootDock,
+            tile_id: Some(tile_id),
+            target_surface: Some(PaneHost::RootDock),
+            insertion: None,
+            pointer_global: None,
+            pointer_local: None,
+        };
+        assert_eq!(
+            internal.to_string(),
+            format!("apply_internal_drop BEGIN viewport=ROOT tile_id={tile_id:?} insertion=None")
+        );
+
+        let mut cross = DebugLogRecord::DropDecision {
+            stage: "cross_viewport",
+            window_move: false,
+            rejected: false,
+            source_host: PaneHost::RootDock,
+            target_host: host,
+            tile_id: Some(tile_id),
+            target_surface: Some(host),
+            insertion: None,
+            pointer_global: Some(Pos2::new(1.0, 2.5)),
+            pointer_local: Some(Pos2::new(3.0, 4.0)),
+        };
+        assert_eq!(
+            cross.to_string(),
+            format!(
+                "resolve_cross_viewport_drop window_move=false source_host=RootDock payload_tile_id={:?} pointer_global=(1.0,2.5) target_host={host:?} target_surface={:?} pointer_local=(3.0,4.0) insertion=None",
+                Some(tile_id),
+                Some(host)
+            )
+        );
+
+        let DebugLogRecord::DropDecision { stage, .. } = &mut cross else {
+            unreachable!()
+        };
+        *stage = "local";
+        assert_eq!(
+            cross.to_string(),
+            format!(
+                "apply_local_drop OK target_surface={:?} insertion=None",
+                Some(host)
+            )
+        );
+
+        let DebugLogRecord::DropDecision { rejected, .. } = &mut cross else {
+            unreachable!()
+        };
+        *rejected = true;
+        assert_eq!(
+            cross.to_string(),
+            format!(
+                "apply_local_drop REJECTED (docking policy) target_surface={:?} source_host=RootDock tile_id={:?}",
+                Some(host),
+                Some(tile_id)
+            )
+        );
+    }
+
+    #[test]
+    fn tear_offs_name_their_origin() {
+        let tile_id = TileId::from_u64(4);
+        let mut record = DebugLogRecord::TearOff {
+            source_host: PaneHost::RootDock,
+            tile_id,
+            floating: true,
+            api: true,
+            accepted: false,
+            new_host: None,
+        };
+        assert_eq!(
+            record.to_string(),
+            format!(
+                "api_float REJECTED by docking policy host=RootDock tile={tile_id:?} floating=true"
+            )
+        );
+
+        let DebugLogRecord::TearOff { api, accepted, .. } = &mut record else {
+            unreachable!()
+        };
+        (*api, *accepted) = (false, true);
+        assert_eq!(
+            record.to_string(),
+            format!("tear_off OK host=RootDock tile={tile_id:?} floating=true")
+        );
+    }
+
+    #[test]
+    fn integrity_failures_stay_on_one_line() {
+        let record = DebugLogRecord::IntegrityFailure {
+            viewport: ViewportId::ROOT,
+            tree: egui::Id::new("tree"),
+            issues: vec!["missing child".to_owned(), "two\nlines".to_owned()],
+        };
+        let text = record.to_string();
+        assert!(!text.contains('\n'), "{text}");
+        assert!(
+            text.ends_with("issues=2: missing child; two lines"),
+            "{text}"
+        );
+    }
+
+    #[cfg(feature = "debug-log-json")]
+    #[test]
+    fn json_lines_are_structured_objects() {
+        let entry = DebugLogEntry {
+            frame: 2,
+            record: DebugLogRecord::Message("tree:\n\"root\"".to_owned()),
+        };
+        assert_eq!(
+            entry.to_json_line(),
+            r#"{"frame":2,"kind":"message","text":"tree:\n\"root\""}"#
+        );
+
+        let entry = DebugLogEntry {
+            frame: 3,
+            record: DebugLogRecord::WindowMove {
+                viewport: ViewportId::ROOT,
+                command: WindowMoveCommand::OuterPosition {
+                    step: 1,
+                    pointer_global: Pos2::new(10.5, f32::NAN),
+                    grab_in_inner: Vec2::ZERO,
+                    outer_from_inner: Vec2::ZERO,
+                    desired_outer: Pos2::new(-2.0, 4.0),
+                },
+            },
+        };
+        let json: serde_json::Value = serde_json::from_str(&entry.to_json_line()).unwrap();
+        assert_eq!(json["command"], "outer_position");
+        assert_eq!(json["pointer_global"], serde_json::json!([10.5, null]));
+        assert_eq!(json["desired_outer"], serde_json::json!([-2.0, 4.0]));
+
+        let entry = DebugLogEntry {
+            frame: 4,
+            record: DebugLogRecord::DropDecision {
+                stage: "internal",
+                window_move: false,
+                rejected: false,
+                source_host: PaneHost::RootDock,
+                target_host: PaneHost::Floating {
+                    viewport: ViewportId::ROOT,
+                    floating: 7,
+                },
+                tile_id: Some(TileId::from_u64(5)),
+                target_surface: None,
+                insertion: Some(InsertionPoint::new(
+                    TileId::from_u64(1),
+                    egui_tiles::ContainerInsertion::Tabs(2),
+                )),
+                pointer_global: None,
+                pointer_local: None,
+            },
+        };
+        let line = entry.to_json_line();
+        assert!(!line.contains('\n'));
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(
+            json["source_host"],
+            serde_json::json!({ "kind": "root_dock" })
+        );
+        assert_eq!(json["target_host"]["kind"], "floating");
+        assert_eq!(json["target_host"]["floating"], 7);
+        assert_eq!(json["insertion"]["container"], "tabs");
+        assert_eq!(json["insertion"]["index"], 2);
+        assert!(json["target_surface"].is_null());
+        assert_eq!(json["rejected"], false);
+
+        let entry = DebugLogEntry {
+            frame: 5,
+            record: DebugLogRecord::DockEvent(DockEvent::FloatingClosed {
+                viewport: ViewportId::ROOT,
+                floating: 7,
+            }),
+        };
+        let json: serde_json::Value = serde_json::from_str(&entry.to_json_line()).unwrap();
+        assert_eq!(json["kind"], "dock_event");
+        assert_eq!(json["event"], "floating_closed");
+        assert_eq!(json["floating"], 7);
+    }
+}
@@ -3,7 +3,13 @@ use egui::{Context, Order, Rect, ResizeDirection, ViewportClass, ViewportCommand
 use egui_tiles::Behavior;
 
 use super::DockingMultiViewport;
+use super::behavior_wrap::PaneBackgroundBehavior;
+use super::debug_log::{DebugLogRecord, WindowMoveCommand};
+use super::events::DockEvent;
 use super::geometry::outer_position_for_window_move;
+use super::host::WindowHost;
+use super::options::ResolvedDetachedOptions;
+use super::pane_query::PaneHost;
 use super::title::title_for_detached_tree;
 use super::types::DockPayload;
 use super::types::{GhostDrag, GhostDragMode};
@@ -223,6 +229,10 @@ impl<'a, Pane> Behavior<Pane> for DetachedRootTabsCsdBehavior<'a, Pane> {
         self.inner.tab_bar_height(style)
     }
 
+    fn show_tab_bar(&self, tiles: &egui_tiles::Tiles<Pane>, tile_id: egui_tiles::TileId) -> bool {
+        self.inner.show_tab_bar(tiles, tile_id)
+    }
+
     fn gap_width(&self, style: &egui::Style) -> f32 {
         self.inner.gap_width(style)
     }
@@ -404,11 +414,10 @@ impl<Pane> DockingMultiViewport<Pane> {
         ctx.stop_dragging();
         ctx.request_repaint_of(ViewportId::ROOT);
 
-        if self.options.debug_event_log {
-            self.debug_log_event(format!(
-                "detached_window_move START viewport={viewport_id:?}"
-            ));
-        }
+        self.debug_log_record(DebugLogRecord::WindowMove {
+            viewport: viewport_id,
+            command: WindowMoveCommand::StartDrag,
+        });
     }
 
     fn paint_csd_button_icon(
@@ -451,9 +460,10 @@ impl<Pane> DockingMultiViewport<Pane> {
         ctx: &Context,
         viewport_id: ViewportId,
         bar_rect: Rect,
+        chrome: ResolvedDetachedOptions,
         should_redock_to_root: &mut bool,
     ) {
-        if !self.options.detached_csd_window_controls {
+        if !chrome.csd_window_controls {
             return;
         }
 
@@ -694,6 +704,7 @@ impl<Pane> DockingMultiViewport<Pane> {
         behavior: &mut dyn Behavior<Pane>,
         viewport_id: ViewportId,
         title: &str,
+        chrome: ResolvedDetachedOptions,
         should_redock_to_root: &mut bool,
     ) {
         let style = ctx.global_style();
@@ -710,7 +721,7 @@ impl<Pane> DockingMultiViewport<Pane> {
 
                 // Choose a drag rect that never overlaps window buttons.
                 let mut drag_rect = rect.shrink2(egui::vec2(padding_x, 0.0));
-                if self.options.detached_csd_window_controls {
+                if chrome.csd_window_controls {
                     let controls_rect = self.csd_window_controls_rect(ctx, rect);
                     if controls_rect.center().x <= rect.center().x {
                         drag_rect.min.x = (controls_rect.max.x + padding_x).min(rect.max.x);
@@ -747,8 +758,14 @@ impl<Pane> DockingMultiViewport<Pane> {
                     }
                 }
 
-                if self.options.detached_csd_window_controls {
-                    self.csd_window_controls_ui(ctx, viewport_id, rect, should_redock_to_root);
+                if chrome.csd_window_controls {
+                    self.csd_window_controls_ui(
+                        ctx,
+                        viewport_id,
+                        rect,
+                        chrome,
+                        should_redock_to_root,
+                    );
                 } else {
                     let button_rects =
                         egui::containers::window_chrome::title_bar_button_rects(ui, rect);
@@ -770,7 +787,12 @@ impl<Pane> DockingMultiViewport<Pane> {
             });
     }
 
-    fn ui_borderless_resize_handles(&self, ctx: &Context, viewport_id: ViewportId) {
+    fn ui_borderless_resize_handles(
+        &self,
+        ctx: &Context,
+        viewport_id: ViewportId,
+        chrome: ResolvedDetachedOptions,
+    ) {
         let maximized_or_fullscreen = ctx.input(|i| {
             i.viewport().maximized.unwrap_or(false) || i.viewport().fullscreen.unwrap_or(false)
         });
@@ -783,14 +805,12 @@ impl<Pane> DockingMultiViewport<Pane> {
             return;
         }
 
-        let thickness = self
-            .options
-            .detached_csd_resize_edge_thickness
+        let thickness = chrome
+            .csd_resize_edge_thickness
             .max(1.0)
             .min(viewport_rect.width().min(viewport_rect.height()) * 0.5);
-        let corner = self
-            .options
-            .detached_csd_resize_corner_size
+        let corner = chrome
+            .csd_resize_corner_size
             .max(thickness)
             .min(viewport_rect.width().min(viewport_rect.height()) * 0.5);
 
@@ -889,7 +909,7 @@ impl<Pane> DockingMultiViewport<Pane> {
     pub(super) fn ui_detached_viewports(
         &mut self,
         ctx: &Context,
-        behavior: &mut dyn Behavior<Pane>,
+        behavior: &mut PaneBackgroundBehavior<'_, Pane>,
     ) {
         let viewport_ids: Vec<ViewportId> = self.detached.keys().copied().collect();
         let bridge_id = self.tree.id();
@@ -910,17 +930,14 @@ impl<Pane> DockingMultiViewport<Pane> {
             };
 
             let root_is_tabs = Self::detached_root_is_tabs(&detached);
+            let chrome = detached.options.resolve(&self.options);
 
-            let builder = detached
-                .builder
-                .clone()
-                .with_decorations(self.options.detached_viewport_decorations);
+            let builder = chrome.apply_to_builder(detached.builder.clone());
             let mut should_redock_to_root = false;
 
             ctx.show_viewport_immediate(viewport_id, builder, |ctx, class| {
                 self.update_last_pointer_global_from_active_viewport(ctx);
                 self.update_viewport_outer_from_inner_offset(ctx);
-                #[cfg(feature = "persistence")]
                 self.capture_viewport_runtime(ctx);
                 self.observe_drag_sources_in_ctx(ctx);
 
@@ -971,18 +988,16 @@ impl<Pane> DockingMultiViewport<Pane> {
                                     let log_every_send = self.options.debug_log_window_move_every_send
                                         && self.options.debug_log_file_path.is_some();
                                     if log_every_send || step == 1 || step % 20 == 0 {
-                                        self.debug_log_event(format!(
-                                            "ghost_window_move step={step} viewport={:?} pointer_global=({:.1},{:.1}) grab_in_inner=({:.1},{:.1}) outer_from_inner=({:.1},{:.1}) desired_outer=({:.1},{:.1})",
-                                            viewport_id,
-                                            pointer_global.x,
-                                            pointer_global.y,
-                                            grab_offset.x,
-                                            grab_offset.y,
-                                            outer_from_inner.x,
-                                            outer_from_inner.y,
-                                            desired_outer.x,
-                                            desired_outer.y
-                                        ));
+                                        self.debug_log_record(DebugLogRecord::WindowMove {
+                                            viewport: viewport_id,
+                                            command: WindowMoveCommand::OuterPosition {
+                                                step,
+                                                pointer_global,
+                                                grab_in_inner: grab_offset,
+                                                outer_from_inner,
+                                                desired_outer,
+                                            },
+                                        });
                                     }
                                 }
                             }
@@ -991,6 +1006,11 @@ impl<Pane> DockingMultiViewport<Pane> {
                 }
 
                 let title = title_for_detached_tree(&detached.tree, behavior);
+                behavior.set_node_flags(
+                    self.node_flags_in_host(PaneHost::DetachedViewport {
+                        viewport: viewport_id,
+                    }),
+                );
 
                 match class {
                     ViewportClass::Immediate | ViewportClass::Deferred | ViewportClass::Root => {
@@ -1005,27 +1025,37 @@ impl<Pane> DockingMultiViewport<Pane> {
                                     .inner_size
                                     .unwrap_or(self.options.default_detached_inner_size),
                             )
-                            .show(ctx, |ui| detached.tree.ui(behavior, ui));
+                            .show(ctx, |ui| {
+                                detached.tree.ui(behavior, ui);
+                                behavior.lock_splitters(ui, &detached.tree);
+                            });
+                        self.emit_tiles_drop_event(
+                            behavior,
+                            PaneHost::DetachedViewport {
+                                viewport: viewport_id,
+                            },
+                        );
                         return;
                     }
                 }
 
-                if !self.options.detached_viewport_decorations {
+                if !chrome.decorations {
                     // Important: render borderless resize handles early so later chrome/controls
                     // (tab-bar controls, overlays) can take pointer priority.
-                    self.ui_borderless_resize_handles(ctx, viewport_id);
+                    self.ui_borderless_resize_handles(ctx, viewport_id, chrome);
                 }
 
                 // For borderless detached windows:
                 // - If root is a Tabs container, the tab bar is the only "chrome" (ImGui-like).
                 // - Otherwise, render a small custom title bar above the dock surface.
-                let use_borderless_chrome = !self.options.detached_viewport_decorations && !root_is_tabs;
+                let use_borderless_chrome = !chrome.decorations && !root_is_tabs;
                 if use_borderless_chrome {
                     self.ui_borderless_detached_chrome(
                         ctx,
                         behavior,
                         viewport_id,
                         &title,
+                        chrome,
                         &mut should_redock_to_root,
                     );
                 }
@@ -1073,10 +1103,7 @@ impl<Pane> DockingMultiViewport<Pane> {
                         .unwrap_or((None, None));
                     let root_is_tabs = root_tabs_tile.is_some();
 
-                    if !self.options.detached_viewport_decorations
-                        && root_is_tabs
-                        && self.options.detached_csd_window_controls
-                    {
+                    if !chrome.decorations && root_is_tabs && chrome.csd_window_controls {
                         // Window controls are injected into the root Tabs tab bar via a Behavior wrapper
                         // (so tab scrolling/layout accounts for the reserved width).
                     }
@@ -1105,11 +1132,13 @@ impl<Pane> DockingMultiViewport<Pane> {
                         ctx.request_repaint_of(ViewportId::ROOT);
                     }
 
-                    let took_over_internal_drop = self.process_release_before_detached_tree_ui(
+                    let took_over_internal_drop = self.process_release_before_tree_ui(
                         ctx,
                         behavior,
                         dock_rect,
-                        viewport_id,
+                        WindowHost::DockTree {
+                            viewport: viewport_id,
+                        },
                         &detached.tree,
                         "internal_overlay_drop_detached",
                     );
@@ -1124,7 +1153,9 @@ impl<Pane> DockingMultiViewport<Pane> {
                         ctx,
                         behavior,
                         dock_rect,
-                        viewport_id,
+                        WindowHost::DockTree {
+                            viewport: viewport_id,
+                        },
                         &detached.tree,
                     );
 
@@ -1158,9 +1189,9 @@ impl<Pane> DockingMultiViewport<Pane> {
                     );
 
                     self.set_tiles_debug_visit_enabled(ctx, detached.tree.id(), viewport_id);
-                    let enable_root_tabs_controls = !self.options.detached_viewport_decorations
+                    let enable_root_tabs_controls = !chrome.decorations
                         && root_is_tabs
-                        && self.options.detached_csd_window_controls
+                        && chrome.csd_window_controls
                         && detached.tree.root.is_some();
                     if enable_root_tabs_controls {
                         let root_tabs = detached.tree.root.expect("checked above");
@@ -1169,12 +1200,19 @@ impl<Pane> DockingMultiViewport<Pane> {
                             root_tabs,
                             bridge_id,
                             viewport_id,
-                            self.options.detached_csd_window_controls,
+                            chrome.csd_window_controls,
                         );
                         detached.tree.ui(&mut wrapped, ui);
                     } else {
                         detached.tree.ui(behavior, ui);
                     }
+                    behavior.lock_splitters(ui, &detached.tree);
+                    self.emit_tiles_drop_event(
+                        behavior,
+                        PaneHost::DetachedViewport {
+                            viewport: viewport_id,
+                        },
+                    );
 
                     if ctx.data(|d| {
                         d.get_temp::<bool>(detached_root_tabs_redock_requested_id(
@@ -1193,7 +1231,7 @@ impl<Pane> DockingMultiViewport<Pane> {
                     }
 
                     // ImGui-like: double-click tab-bar background toggles maximize.
-                    if !self.options.detached_viewport_decorations
+                    if !chrome.decorations
                         && root_is_tabs
                         && ctx.dragged_id().is_none()
                     {
@@ -1259,7 +1297,9 @@ impl<Pane> DockingMultiViewport<Pane> {
                         behavior,
                         &detached.tree,
                         dock_rect,
-                        viewport_id,
+                        WindowHost::DockTree {
+                            viewport: viewport_id,
+                        },
                     );
 
                     self.ui_floating_windows_in_viewport(ui, behavior, dock_rect, viewport_id);
@@ -1284,7 +1324,7 @@ impl<Pane> DockingMultiViewport<Pane> {
             });
 
             if should_redock_to_root {
-                self.dock_tree_into_root(detached.tree, None);
+                self.redock_detached_tree_into_root(viewport_id, detached.tree);
                 continue;
             }
 
@@ -1295,15 +1335,19 @@ impl<Pane> DockingMultiViewport<Pane> {
                         "detached_viewport EMPTY -> close viewport={viewport_id:?}"
                     ));
                 }
+                self.push_dock_event(DockEvent::ViewportClosed {
+                    viewport: viewport_id,
+                });
                 continue;
             }
 
             // Keep detached.
-            detached.builder = detached
-                .builder
-                .clone()
-                .with_title(title_for_detached_tree(&detached.tree, behavior))
-                .with_decorations(self.options.detached_viewport_decorations);
+            detached.builder = chrome.apply_to_builder(
+                detached
+                    .builder
+                    .clone()
+                    .with_title(title_for_detached_tree(&detached.tree, behavior)),
+            );
             self.detached.insert(viewport_id, detached);
         }
     }
@@ -0,0 +1,415 @@
+use egui::{Rect, ViewportId};
+use egui_tiles::{ContainerInsertion, InsertionPoint, Tile, TileId, Tree};
+
+use super::DockingMultiViewport;
+use super::host::WindowHost;
+use super::node_flags::{insertion_stays_inside, NodeFlagsView};
+use super::overlay::tile_contains_descendant;
+use super::pane_query::PaneHost;
+
+/// What a [`DockingPolicy`] decides for a candidate drop.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DropVerdict {
+    /// Keep the insertion the overlay/heuristics picked.
+    Accept,
+
+    /// Forbid the drop: no preview is shown and releasing leaves the layout untouched.
+    Reject,
+
+    /// Dock somewhere else instead. The preview shows the rewritten insertion.
+    ///
+    /// The insertion must refer to a tile of [`DropContext::target_tree`]; anything else
+    /// (or a parent inside the dragged subtree) is treated as [`DropVerdict::Reject`].
+    Rewrite(InsertionPoint),
+}
+
+/// Everything a [`DockingPolicy`] gets to look at for one candidate drop.
+pub struct DropContext<'a, Pane> {
+    /// Where the dragged panes come from.
+    pub source: PaneHost,
+
+    /// The panes being moved, in depth-first order.
+    ///
+    /// While previewing, this can be empty for a frame if the source tree is momentarily
+    /// unavailable (e.g. dragging between two floating windows of a detached viewport).
+    /// The final drop always sees the full list.
+    pub dragged_panes: &'a [&'a Pane],
+
+    /// `true` when a whole window host is moved (native viewport title bar / floating header),
+    /// `false` for a tab, pane or subtree.
+    pub is_window_move: bool,
+
+    /// The host that would receive the panes.
+    pub target: PaneHost,
+
+    /// The dock tree of [`Self::target`], before the drop.
+    pub target_tree: &'a Tree<Pane>,
+
+    /// Where in [`Self::target_tree`] the panes would be inserted.
+    pub insertion: InsertionPoint,
+}
+
+impl<'a, Pane> DropContext<'a, Pane> {
+    /// `true` if the drop would add the panes as tabs (rather than splitting).
+    pub fn is_tab_insertion(&self) -> bool {
+        matches!(self.insertion.insertion, ContainerInsertion::Tabs(_))
+    }
+
+    /// Panes under the insertion parent, i.e. the panes the dragged ones would end up next to
+    /// (or tabbed with, see [`Self::is_tab_insertion`]).
+    pub fn target_panes(&self) -> Vec<&'a Pane> {
+        let mut out = Vec::new();
+        collect_panes(self.target_tree, self.insertion.parent_id, &mut out);
+        out
+    }
+}
+
+/// The window a tear-off would create.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NewWindowKind {
+    /// A detached native viewport (OS window).
+    Native,
+
+    /// A contained floating window shown inside `viewport`.
+    Floating { viewport: ViewportId },
+}
+
+/// What a [`DockingPolicy`] decides for a candidate tear-off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TearOffVerdict {
+    /// Create the new window.
+    Accept,
+
+    /// Keep the panes where they are: the drag continues (or ends) as if it never left its dock.
+    Reject,
+}
+
+/// Everything a [`DockingPolicy`] gets to look at when panes would move into a new window.
+pub struct TearOffContext<'a, Pane> {
+    /// Where the panes come from.
+    pub source: PaneHost,
+
+    /// The panes being torn off, in depth-first order.
+    pub panes: &'a [&'a Pane],
+
+    /// The window that would be created.
+    pub new_window: NewWindowKind,
+
+    /// `true` for the programmatic APIs ([`DockingMultiViewport::detach_pane_to_viewport`],
+    /// [`DockingMultiViewport::float_pane`], ...), `false` for mouse drags.
+    pub is_programmatic: bool,
+}
+
+/// Application hook to forbid or redirect drops.
+///
+/// Consulted for every docking decision — overlay previews, cross-viewport and floating drops,
+/// window-move docking and drags within one dock tree — both when painting the preview and when
+/// applying the release, so "preview = outcome" still holds. With
+/// [`super::DockingMultiViewportOptions::show_overlay_for_internal_drags`] disabled, drags within
+/// one tree are asked about the drop `egui_tiles` would apply; rejecting or rewriting it takes the
+/// drop over from `egui_tiles`.
+///
+/// [`Self::check_tear_off`] is consulted whenever panes would leave their host for a new window:
+/// tear-off on release, ghost tear-off, a contained ghost turning into a native window, and the
+/// `detach_*` / `float_*` APIs.
+///
+/// Closures `Fn(&DropContext<'_, Pane>) -> DropVerdict` implement this trait (and accept every
+/// tear-off).
+pub trait DockingPolicy<Pane> {
+    fn check_drop(&self, drop: &DropContext<'_, Pane>) -> DropVerdict;
+
+    /// Defaults to [`TearOffVerdict::Accept`].
+    fn check_tear_off(&self, tear_off: &TearOffContext<'_, Pane>) -> TearOffVerdict {
+        let _ = tear_off;
+        TearOffVerdict::Accept
+    }
+}
+
+impl<Pane, F> DockingPolicy<Pane> for F
+where
+    F: Fn(&DropContext<'_, Pane>) -> DropVerdict,
+{
+    fn check_drop(&self, drop: &DropContext<'_, Pane>) -> DropVerdict {
+        self(drop)
+    }
+}
+
+/// Storage for the installed policy (keeps `DockingMultiViewport: Debug`).
+pub(super) struct DockingPolicySlot<Pane>(pub(super) Option<Box<dyn DockingPolicy<Pane>>>);
+
+impl<Pane> Default for DockingPolicySlot<Pane> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<Pane> std::fmt::Debug for DockingPolicySlot<Pane> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
+/// The installed [`DockingPolicy`] vetoed a drop: the release must leave every tree untouched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct DropRejected;
+
+/// A fully resolved policy question, minus the insertion (filled in by the overlay decision).
+///
+/// Besides the app's [`DockingPolicy`], this carries the [`super::DockNodeFlags`] that apply to
+/// the drop: those of the target host, and the `NO_UNDOCKING` node the dragged tile is pinned to.
+pub(super) struct PolicyQuery<'a, Pane> {
+    pub(super) policy: Option<&'a dyn DockingPolicy<Pane>>,
+    pub(super) target_flags: NodeFlagsView<'a>,
+    pub(super) undocking_anchor: Option<TileId>,
+    pub(super) source: PaneHost,
+    pub(super) dragged_tile: Option<TileId>,
+    pub(super) dragged_panes: Vec<&'a Pane>,
+    pub(super) is_window_move: bool,
+    pub(super) target: PaneHost,
+}
+
+impl<Pane> PolicyQuery<'_, Pane> {
+    /// Ask the policy about `insertion` into `target_tree`.
+    ///
+    /// Rewrites are validated here, so callers only ever see `Accept`, `Reject`, or a rewrite that
+    /// can be applied as-is.
+    pub(super) fn verdict(&self, target_tree: &Tree<Pane>, insertion: InsertionPoint) -> DropVerdict {
+        if !self.node_flags_allow(target_tree, insertion) {
+            return DropVerdict::Reject;
+        }
+        let Some(policy) = self.policy else {
+            return DropVerdict::Accept;
+        };
+        let verdict = policy.check_drop(&DropContext {
+            source: self.source,
+            dragged_panes: &self.dragged_panes,
+            is_window_move: self.is_window_move,
+            target: self.target,
+            target_tree,
+            insertion,
+        });
+        match verdict {
+            DropVerdict::Rewrite(rewritten) => {
+                let parent_exists = target_tree.tiles.get(rewritten.parent_id).is_some();
+                let inside_dragged = self.source == self.target
+                    && self.dragged_tile.is_some_and(|dragged| {
+                        tile_contains_descendant(target_tree, dragged, rewritten.parent_id)
+                    });
+                if parent_exists && !inside_dragged && self.node_flags_allow(target_tree, rewritten) {
+                    verdict
+                } else {
+                    DropVerdict::Reject
+                }
+            }
+            _ => verdict,
+        }
+    }
+
+    /// The [`super::DockNodeFlags`] part of the question, asked before the app's policy.
+    fn node_flags_allow(&self, target_tree: &Tree<Pane>, insertion: InsertionPoint) -> bool {
+        if !self.target_flags.allows_insertion(target_tree, insertion) {
+            return false;
+        }
+        match self.undocking_anchor {
+            Some(anchor) => {
+                self.source == self.target && insertion_stays_inside(target_tree, anchor, insertion)
+            }
+            None => true,
+        }
+    }
+}
+
+fn collect_panes<'a, Pane>(tree: &'a Tree<Pane>, tile_id: TileId, out: &mut Vec<&'a Pane>) {
+    match tree.tiles.get(tile_id) {
+        Some(Tile::Pane(pane)) => out.push(pane),
+        Some(Tile::Container(container)) => {
+            for &child in container.children() {
+                collect_panes(tree, child, out);
+            }
+        }
+        None => {}
+    }
+}
+
+/// Preview rect for an insertion that did not come from a hovered overlay target.
+///
+/// Mirrors the overlay semantics: tabs/grid cover the parent, splits cover the matching half.
+pub(super) fn insertion_preview_rect<Pane>(
+    tree: &Tree<Pane>,
+    insertion: InsertionPoint,
+) -> Option<Rect> {
+    let rect = tree.tiles.rect(insertion.parent_id)?;
+    Some(match insertion.insertion {
+        ContainerInsertion::Tabs(_) | ContainerInsertion::Grid(_) => rect,
+        ContainerInsertion::Horizontal(0) => rect.split_left_right_at_fraction(0.5).0,
+        ContainerInsertion::Horizontal(_) => rect.split_left_right_at_fraction(0.5).1,
+        ContainerInsertion::Vertical(0) => rect.split_top_bottom_at_fraction(0.5).0,
+        ContainerInsertion::Vertical(_) => rect.split_top_bottom_at_fraction(0.5).1,
+    })
+}
+
+impl<Pane> DockingMultiViewport<Pane> {
+    /// Install a [`DockingPolicy`], replacing any previous one.
+    pub fn set_docking_policy(&mut self, policy: impl DockingPolicy<Pane> + 'static) {
+        self.docking_policy = DockingPolicySlot(Some(Box::new(policy)));
+    }
+
+    /// Remove the installed [`DockingPolicy`]; every drop is accepted again.
+    pub fn clear_docking_policy(&mut self) {
+        self.docking_policy = DockingPolicySlot(None);
+    }
+
+    /// Ask the installed policy whether `tile_id` of `source` may move into a new window.
+    pub(super) fn docking_policy_allows_tear_off(
+        &self,
+        source: PaneHost,
+        tree: &Tree<Pane>,
+        tile_id: TileId,
+        new_window: NewWindowKind,
+        is_programmatic: bool,
+    ) -> bool {
+        let Some(policy) = self.docking_policy.0.as_deref() else {
+            return true;
+        };
+        let mut panes = Vec::new();
+        collect_panes(tree, tile_id, &mut panes);
+        policy.check_tear_off(&TearOffContext {
+            source,
+            panes: &panes,
+            new_window,
+            is_programmatic,
+        }) == TearOffVerdict::Accept
+    }
+
+    /// Whether a drag may tear `tile_id` of `source` off into a new window: it must not be pinned
+    /// by [`super::DockNodeFlags::NO_UNDOCKING`], and the policy must accept it.
+    pub(super) fn drag_tear_off_allowed(
+        &self,
+        source: PaneHost,
+        tree: &Tree<Pane>,
+        tile_id: TileId,
+        new_window: NewWindowKind,
+    ) -> bool {
+        self.node_flags_view(source)
+            .undocking_anchor(tree, tile_id)
+            .is_none()
+            && self.docking_policy_allows_tear_off(source, tree, tile_id, new_window, false)
+    }
+
+    /// Build the policy question for moving `dragged_tile` (or the whole tree) out of `source`
+    /// into `target`. Returns `None` when no policy is installed and no node has flags.
+    ///
+    /// `target_tree` is used as the source tree for internal drags, since the caller may be
+    /// holding a tree that is temporarily detached from `self`.
+    pub(super) fn docking_policy_query<'a>(
+        &'a self,
+        source: WindowHost,
+        dragged_tile: Option<TileId>,
+        target: WindowHost,
+        target_tree: &'a Tree<Pane>,
+    ) -> Option<PolicyQuery<'a, Pane>> {
+        let policy = self.docking_policy.0.as_deref();
+        if policy.is_none() && self.node_flags.is_empty() {
+            return None;
+        }
+        let source = source.pane_host();
+        let target = target.pane_host();
+
+        let source_tree = if source == target {
+            Some(target_tree)
+        } else {
+            self.tree_for_pane_host(source)
+        };
+        let mut dragged_panes = Vec::new();
+        let mut undocking_anchor = None;
+        if let Some(tree) = source_tree
+            && let Some(tile_id) = dragged_tile.or(tree.root)
+        {
+            if policy.is_some() {
+                collect_panes(tree, tile_id, &mut dragged_panes);
+            }
+            undocking_anchor = self.node_flags_view(source).undocking_anchor(tree, tile_id);
+        }
+
+        Some(PolicyQuery {
+            policy,
+            target_flags: self.node_flags_view(target),
+            undocking_anchor,
+            source,
+            dragged_tile,
+            dragged_panes,
+            is_window_move: dragged_tile.is_none(),
+            target,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree_two_tabs() -> (Tree<&'static str>, TileId, TileId) {
+        let mut tiles = egui_tiles::Tiles::default();
+        let scene = tiles.insert_pane("scene");
+        let tabs = tiles.insert_tab_tile(vec![scene]);
+        let console = tiles.insert_pane("console");
+        let root = tiles.insert_horizontal_tile(vec![tabs, console]);
+        (Tree::new(egui::Id::new("policy"), root, tiles), tabs, console)
+    }
+
+    #[test]
+    fn policy_can_reject_and_rewrite() {
+        let (tree, tabs, console) = tree_two_tabs();
+        let policy = |drop: &DropContext<'_, &'static str>| {
+            if drop.dragged_panes.contains(&&"console")
+                && drop.is_tab_insertion()
+                && drop.target_panes().contains(&&"scene")
+            {
+                DropVerdict::Reject
+            } else if drop.insertion.parent_id == console {
+                DropVerdict::Rewrite(InsertionPoint::new(
+                    tabs,
+                    ContainerInsertion::Horizontal(usize::MAX),
+                ))
+            } else {
+                DropVerdict::Accept
+            }
+        };
+        let no_flags = std::collections::BTreeMap::new();
+        let query = PolicyQuery {
+            policy: Some(&policy),
+            target_flags: NodeFlagsView::new(&no_flags, PaneHost::RootDock),
+            undocking_anchor: None,
+            source: PaneHost::RootDock,
+            dragged_tile: Some(console),
+            dragged_panes: vec![&"console"],
+            is_window_move: false,
+            target: PaneHost::RootDock,
+        };
+
+        let into_scene_tabs = InsertionPoint::new(tabs, ContainerInsertion::Tabs(usize::MAX));
+        assert_eq!(query.verdict(&tree, into_scene_tabs), DropVerdict::Reject);
+
+        let split_scene = InsertionPoint::new(tabs, ContainerInsertion::Vertical(0));
+        assert_eq!(query.verdict(&tree, split_scene), DropVerdict::Accept);
+
+        let onto_console = InsertionPoint::new(console, ContainerInsertion::Tabs(0));
+        assert_eq!(
+            query.verdict(&tree, onto_console),
+            DropVerdict::Rewrite(InsertionPoint::new(
+                tabs,
+                ContainerInsertion::Horizontal(usize::MAX)
+            ))
+        );
+
+        // A rewrite into the dragged subtree itself is invalid and becomes a rejection.
+        let onto_self = InsertionPoint::new(console, ContainerInsertion::Vertical(0));
+        let bad_policy =
+            |_: &DropContext<'_, &'static str>| DropVerdict::Rewrite(onto_self);
+        let bad_query = PolicyQuery {
+            policy: Some(&bad_policy),
+            ..query
+        };
+        assert_eq!(bad_query.verdict(&tree, split_scene), DropVerdict::Reject);
+    }
+}
@@ -2,6 +2,7 @@ use egui::{Context, Modifiers, Pos2, ViewportId};
 use std::collections::BTreeMap;
 
 use super::backend_hints::{backend_mouse_hovered_viewport_id, backend_pointer_global_points};
+use super::debug_log::DebugLogRecord;
 use super::geometry::{pointer_pos_in_global, viewport_under_pointer_global};
 use super::session::DragSession;
 use super::types::DockPayload;
@@ -26,7 +27,7 @@ impl DragState {
         self.any_pointer_released_this_frame = false;
     }
 
-    pub(super) fn end_frame(&mut self, frame: u64) -> Option<String> {
+    pub(super) fn end_frame(&mut self, frame: u64) -> Option<DebugLogRecord> {
         self.session.end_frame(frame)
     }
 
@@ -165,7 +166,7 @@ impl DragState {
         &mut self,
         frame: u64,
         source: &'static str,
-    ) -> Option<String> {
+    ) -> Option<DebugLogRecord> {
         self.session.observe_active(frame, source)
     }
 
@@ -204,7 +205,7 @@ impl DragState {
         &mut self,
         frame: u64,
         kind: &'static str,
-    ) -> (bool, Option<String>) {
+    ) -> (bool, Option<DebugLogRecord>) {
         self.session.take_release_action(frame, kind)
     }
 }
@@ -1,15 +1,20 @@
 use egui::{Context, ViewportId};
-use egui_tiles::{Behavior, EditAction, TileId, Tree};
+use egui_tiles::{Behavior, EditAction, InsertionPoint, TileId, Tree};
 
 use super::DockingMultiViewport;
+use super::debug_log::DebugLogRecord;
 use super::drop_policy;
 use super::drop_sanitize;
+use super::events::DockEvent;
 use super::host::WindowHost;
 use super::integrity;
 use super::overlay_decision::{decide_overlay_for_tree, DragKind};
+use super::pane_query::PaneHost;
 use super::surface::DockSurface;
 use super::title::title_for_detached_tree;
-use super::types::{DockPayload, ResolvedDrop, ResolvedDropTarget};
+use super::types::{
+    DockPayload, FloatingId, PendingInternalDrop, ResolvedDrop, ResolvedDropTarget,
+};
 
 fn force_subtree_visible<Pane>(subtree: &mut egui_tiles::SubTree<Pane>) {
     let ids: Vec<egui_tiles::TileId> = subtree.tiles.tile_ids().collect();
@@ -115,12 +120,22 @@ impl<Pane> DockingMultiViewport<Pane> {
                 return;
             }
         }
+        let source_host = pending.payload.source_host();
+        let policy = self.tree_for_host(pending.target_host).and_then(|target_tree| {
+            self.docking_policy_query(
+                source_host,
+                pending.payload.tile_id,
+                pending.target_host,
+                target_tree,
+            )
+        });
         let insertion = if is_moving_floating_window {
             self.window_move_insertion_at_pointer_local(
                 behavior,
                 ctx.global_style().as_ref(),
                 pending.target_surface,
                 pending.pointer_local,
+                policy.as_ref(),
             )
         } else {
             self.insertion_at_pointer_local(
@@ -129,8 +144,24 @@ impl<Pane> DockingMultiViewport<Pane> {
                 pending.target_surface,
                 pending.pointer_local,
                 pending.payload.tile_id,
+                policy.as_ref(),
             )
         };
+        let Ok(insertion) = insertion else {
+            self.debug_log_record(DebugLogRecord::DropDecision {
+                stage: "local",
+                window_move: pending.payload.tile_id.is_none(),
+                rejected: true,
+                source_host: source_host.pane_host(),
+                target_host: pending.target_host.pane_host(),
+                tile_id: pending.payload.tile_id,
+                target_surface: Some(pending.target_surface.pane_host()),
+                insertion: None,
+                pointer_global: None,
+                pointer_local: Some(pending.pointer_local),
+            });
+            return;
+        };
         if is_moving_floating_window && insertion.is_none() {
             if self.options.debug_event_log {
                 self.debug_log_event(format!(
@@ -144,7 +175,6 @@ impl<Pane> DockingMultiViewport<Pane> {
             return;
         }
 
-        let source_host = pending.payload.source_host();
         let subtree = match pending.payload.tile_id {
             Some(tile_id) => {
                 self.take_subtree_from_host_for_drop(ctx, behavior, source_host, tile_id)
@@ -186,20 +216,32 @@ impl<Pane> DockingMultiViewport<Pane> {
             Ok(()) => None,
             Err(subtree) => Some(subtree),
         };
+        let mut applied_host = target_host;
         if let Some(subtree) = subtree {
             // Target host disappeared; fall back to dock tree in the same viewport.
             let fallback = WindowHost::DockTree {
                 viewport: pending.target_host.viewport(),
             };
             let _ = self.insert_subtree_into_host(fallback, subtree, insertion_sanitized);
+            applied_host = fallback;
         }
         behavior.on_edit(egui_tiles::EditAction::TileDropped);
-        if self.options.debug_event_log {
-            self.debug_log_event(format!(
-                "apply_local_drop OK target_surface={:?} insertion={insertion_sanitized:?}",
-                pending.target_surface
-            ));
-        }
+        self.push_dock_event(DockEvent::DropApplied {
+            source: source_host.pane_host(),
+            target: applied_host.pane_host(),
+        });
+        self.debug_log_record(DebugLogRecord::DropDecision {
+            stage: "local",
+            window_move: pending.payload.tile_id.is_none(),
+            rejected: false,
+            source_host: source_host.pane_host(),
+            target_host: applied_host.pane_host(),
+            tile_id: pending.payload.tile_id,
+            target_surface: Some(pending.target_surface.pane_host()),
+            insertion: insertion_sanitized,
+            pointer_global: None,
+            pointer_local: Some(pending.pointer_local),
+        });
     }
 
     pub(super) fn apply_pending_drop(&mut self, ctx: &Context, behavior: &mut dyn Behavior<Pane>) {
@@ -271,8 +313,14 @@ impl<Pane> DockingMultiViewport<Pane> {
             return None;
         }
 
+        let target_host = match target_surface {
+            DockSurface::DockTree { viewport } => WindowHost::DockTree { viewport },
+            DockSurface::Floating { viewport, floating } => WindowHost::Floating { viewport, floating },
+        };
         let dock_rect = self.dock_rect_for_surface(target_surface)?;
         let tree = self.tree_for_surface(target_surface)?;
+        let policy =
+            self.docking_policy_query(payload.source_host(), payload.tile_id, target_host, tree);
         let style = ctx.global_style();
         let drag_kind = if is_window_move {
             DragKind::WindowMove {
@@ -294,13 +342,28 @@ impl<Pane> DockingMultiViewport<Pane> {
             pointer_local,
             self.options.show_outer_overlay_targets,
             drag_kind,
+            policy.as_ref(),
         );
+        if decision.policy_rejected {
+            self.debug_log_record(DebugLogRecord::DropDecision {
+                stage: "cross_viewport",
+                window_move: is_window_move,
+                rejected: true,
+                source_host: payload.source_host().pane_host(),
+                target_host: target_host.pane_host(),
+                tile_id: payload.tile_id,
+                target_surface: Some(target_surface.pane_host()),
+                insertion: None,
+                pointer_global: Some(pointer_global),
+                pointer_local: Some(pointer_local),
+            });
+            if is_window_move {
+                self.clear_detached_window_move_state(ctx, payload.source_viewport);
+            }
+            return None;
+        }
         let insertion = decision.insertion_final;
 
-        let target_host = match target_surface {
-            DockSurface::DockTree { viewport } => WindowHost::DockTree { viewport },
-            DockSurface::Floating { viewport, floating } => WindowHost::Floating { viewport, floating },
-        };
         let resolved = ResolvedDrop {
             payload,
             pointer_global,
@@ -312,20 +375,18 @@ impl<Pane> DockingMultiViewport<Pane> {
             },
         };
 
-        if self.options.debug_event_log {
-            self.debug_log_event(format!(
-                "resolve_cross_viewport_drop window_move={is_window_move} source_host={:?} payload_tile_id={:?} pointer_global=({:.1},{:.1}) target_host={:?} target_surface={:?} pointer_local=({:.1},{:.1}) insertion={:?}",
-                resolved.payload.source_host(),
-                resolved.payload.tile_id,
-                resolved.pointer_global.x,
-                resolved.pointer_global.y,
-                resolved.target.target_host,
-                resolved.target.target_surface,
-                resolved.target.pointer_local.x,
-                resolved.target.pointer_local.y,
-                resolved.target.insertion,
-            ));
-        }
+        self.debug_log_record(DebugLogRecord::DropDecision {
+            stage: "cross_viewport",
+            window_move: is_window_move,
+            rejected: false,
+            source_host: resolved.payload.source_host().pane_host(),
+            target_host: resolved.target.target_host.pane_host(),
+            tile_id: resolved.payload.tile_id,
+            target_surface: Some(resolved.target.target_surface.pane_host()),
+            insertion: resolved.target.insertion,
+            pointer_global: Some(resolved.pointer_global),
+            pointer_local: Some(resolved.target.pointer_local),
+        });
 
         Some(resolved)
     }
@@ -394,12 +455,14 @@ impl<Pane> DockingMultiViewport<Pane> {
             Ok(()) => None,
             Err(subtree) => Some(subtree),
         };
+        let mut applied_host = target_host;
         if let Some(subtree) = subtree {
             // Target host disappeared; fall back to dock tree in the same viewport.
             let fallback = WindowHost::DockTree {
                 viewport: target_host.viewport(),
             };
             let _ = self.insert_subtree_into_host(fallback, subtree, insertion_sanitized);
+            applied_host = fallback;
         }
 
         // Keep detached window title in sync to avoid one-frame mismatch after drops.
@@ -414,6 +477,10 @@ impl<Pane> DockingMultiViewport<Pane> {
         }
 
         behavior.on_edit(egui_tiles::EditAction::TileDropped);
+        self.push_dock_event(DockEvent::DropApplied {
+            source: source_host.pane_host(),
+            target: applied_host.pane_host(),
+        });
 
         // If we were in "window move" mode for a detached native viewport, make sure we clear any
         // per-viewport move state. OS-level window moves may swallow the mouse-up for the source
@@ -443,6 +510,22 @@ impl<Pane> DockingMultiViewport<Pane> {
         }
     }
 
+    fn debug_log_internal_drop_decision(&mut self, pending: &PendingInternalDrop) {
+        let host = pending.host().pane_host();
+        self.debug_log_record(DebugLogRecord::DropDecision {
+            stage: "internal",
+            window_move: false,
+            rejected: false,
+            source_host: host,
+            target_host: host,
+            tile_id: Some(pending.tile_id),
+            target_surface: Some(host),
+            insertion: pending.insertion,
+            pointer_global: None,
+            pointer_local: None,
+        });
+    }
+
     pub(super) fn apply_pending_actions(
         &mut self,
         ctx: &Context,
@@ -457,13 +540,32 @@ impl<Pane> DockingMultiViewport<Pane> {
         let Some(pending) = self.pending_internal_drop.take() else {
             return;
         };
-
-        if pending.viewport == ViewportId::ROOT {
+        let Some(pending_insertion) = pending.insertion else {
+            // The docking policy vetoed this drop; we only took it over to keep `egui_tiles` from
+            // applying its own.
             if self.options.debug_event_log {
                 self.debug_log_event(format!(
-                    "apply_internal_drop BEGIN viewport=ROOT tile_id={:?} insertion={:?}",
-                    pending.tile_id, pending.insertion
+                    "apply_internal_drop SKIP (rejected by docking policy) viewport={:?} tile_id={:?}",
+                    pending.viewport, pending.tile_id
                 ));
+            }
+            return;
+        };
+
+        self.debug_log_internal_drop_decision(&pending);
+        if let Some(floating) = pending.floating {
+            self.apply_pending_internal_drop_in_floating(
+                behavior,
+                pending.viewport,
+                floating,
+                pending.tile_id,
+                pending_insertion,
+            );
+            return;
+        }
+
+        if pending.viewport == ViewportId::ROOT {
+            if self.options.debug_event_log {
                 self.debug_log_event(format!(
                     "tree_before:\n{}",
                     debug_tree_summary(&self.tree, 80)
@@ -481,11 +583,15 @@ impl<Pane> DockingMultiViewport<Pane> {
             let insertion = self
                 .tree
                 .tiles
-                .get(pending.insertion.parent_id)
+                .get(pending_insertion.parent_id)
                 .is_some()
-                .then_some(pending.insertion);
+                .then_some(pending_insertion);
             behavior.on_edit(EditAction::TileDropped);
             self.tree.insert_subtree_at(subtree, insertion);
+            self.push_dock_event(DockEvent::DropApplied {
+                source: PaneHost::RootDock,
+                target: PaneHost::RootDock,
+            });
             if self.options.debug_event_log {
                 for issue in integrity::tree_integrity_issues(&self.tree) {
                     self.debug_log_event(issue);
@@ -507,10 +613,6 @@ impl<Pane> DockingMultiViewport<Pane> {
         };
 
         if self.options.debug_event_log {
-            self.debug_log_event(format!(
-                "apply_internal_drop BEGIN viewport={:?} tile_id={:?} insertion={:?}",
-                pending.viewport, pending.tile_id, pending.insertion
-            ));
             self.debug_log_event(format!(
                 "detached_tree_before:\n{}",
                 debug_tree_summary(&detached.tree, 80)
@@ -530,11 +632,18 @@ impl<Pane> DockingMultiViewport<Pane> {
         let insertion = detached
             .tree
             .tiles
-            .get(pending.insertion.parent_id)
+            .get(pending_insertion.parent_id)
             .is_some()
-            .then_some(pending.insertion);
+            .then_some(pending_insertion);
         behavior.on_edit(EditAction::TileDropped);
         detached.tree.insert_subtree_at(subtree, insertion);
+        let host = PaneHost::DetachedViewport {
+            viewport: pending.viewport,
+        };
+        self.push_dock_event(DockEvent::DropApplied {
+            source: host,
+            target: host,
+        });
         if self.options.debug_event_log {
             for issue in integrity::tree_integrity_issues(&detached.tree) {
                 self.debug_log_event(issue);
@@ -552,6 +661,54 @@ impl<Pane> DockingMultiViewport<Pane> {
             .with_title(title_for_detached_tree(&detached.tree, behavior));
         self.detached.insert(pending.viewport, detached);
     }
+
+    fn apply_pending_internal_drop_in_floating(
+        &mut self,
+        behavior: &mut dyn Behavior<Pane>,
+        viewport: ViewportId,
+        floating: FloatingId,
+        tile_id: TileId,
+        insertion: InsertionPoint,
+    ) {
+        let Some(window) = self
+            .floating
+            .get_mut(&viewport)
+            .and_then(|manager| manager.windows.get_mut(&floating))
+        else {
+            self.debug_log_event(format!(
+                "apply_internal_drop FAILED: missing floating window viewport={viewport:?} floating={floating:?}"
+            ));
+            return;
+        };
+        let Some(mut subtree) = window.tree.extract_subtree_no_reserve(tile_id) else {
+            self.debug_log_event(
+                "apply_internal_drop FAILED extract_subtree_no_reserve returned None (floating)"
+                    .to_owned(),
+            );
+            return;
+        };
+        force_subtree_visible(&mut subtree);
+
+        let insertion = window
+            .tree
+            .tiles
+            .get(insertion.parent_id)
+            .is_some()
+            .then_some(insertion);
+        behavior.on_edit(EditAction::TileDropped);
+        window.tree.insert_subtree_at(subtree, insertion);
+
+        let host = PaneHost::Floating { viewport, floating };
+        self.push_dock_event(DockEvent::DropApplied {
+            source: host,
+            target: host,
+        });
+        if self.options.debug_event_log {
+            self.debug_log_event(format!(
+                "apply_internal_drop END viewport={viewport:?} floating={floating:?}"
+            ));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -605,6 +762,7 @@ mod tests {
                 serial: 1,
                 tree: detached_tree,
                 builder: egui::ViewportBuilder::default(),
+                options: Default::default(),
             },
         );
         let root_tiles_before = docking.tree.tiles.tile_ids().count();
@@ -670,6 +828,7 @@ mod tests {
                 serial: 1,
                 tree: new_tree_tabs(egui::Id::new("detached_tree"), 1),
                 builder: egui::ViewportBuilder::default(),
+                options: Default::default(),
             },
         );
 
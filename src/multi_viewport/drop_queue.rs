@@ -7,7 +7,9 @@ use super::geometry::{
     pointer_pos_in_target_viewport_space, viewport_under_pointer_global,
     viewport_under_pointer_global_excluding,
 };
-use super::overlay_decision::{decide_overlay_for_tree, DragKind};
+use super::overlay_decision::{
+    decide_overlay_for_tree, decide_tiles_drop_for_tree, DragKind, OverlayDecision,
+};
 use super::surface::DockSurface;
 use super::host::WindowHost;
 use super::types::{DockPayload, FloatingId, PendingDrop, PendingInternalDrop, PendingLocalDrop};
@@ -298,12 +300,9 @@ impl<Pane> DockingMultiViewport<Pane> {
         ctx: &Context,
         behavior: &dyn Behavior<Pane>,
         dock_rect: Rect,
-        viewport_id: ViewportId,
+        host: WindowHost,
         tree: &Tree<Pane>,
     ) -> Option<PendingInternalDrop> {
-        if !self.options.show_overlay_for_internal_drags {
-            return None;
-        }
         if self.options.detach_on_alt_release_anywhere && ctx.input(|i| i.modifiers.alt) {
             return None;
         }
@@ -316,6 +315,7 @@ impl<Pane> DockingMultiViewport<Pane> {
         if !dock_rect.contains(pointer_local) {
             return None;
         }
+        let viewport_id = host.viewport();
         if self
             .floating_tree_id_under_pointer(viewport_id, pointer_local)
             .is_some_and(|floating_tree_id| floating_tree_id != tree.id())
@@ -324,24 +324,72 @@ impl<Pane> DockingMultiViewport<Pane> {
         }
 
         let style = ctx.global_style();
-        let decision = decide_overlay_for_tree(
-            tree,
+        let decision = self.internal_drag_decision(
             behavior,
             &style,
             dock_rect,
             pointer_local,
-            self.options.show_outer_overlay_targets,
-            DragKind::Subtree {
-                dragged_tile: Some(dragged_tile),
-                internal: true,
-            },
-        );
-        let insertion = decision.insertion_final?;
+            host,
+            tree,
+            dragged_tile,
+        )?;
+        let insertion = if decision.policy_rejected {
+            None
+        } else {
+            Some(decision.insertion_final?)
+        };
 
         Some(PendingInternalDrop {
             viewport: viewport_id,
+            floating: match host {
+                WindowHost::Floating { floating, .. } => Some(floating),
+                WindowHost::DockTree { .. } | WindowHost::NativeViewport { .. } => None,
+            },
             tile_id: dragged_tile,
             insertion,
         })
     }
+
+    /// Decision for dragging `dragged_tile` within `tree` (held by `host`).
+    ///
+    /// With [`super::DockingMultiViewportOptions::show_overlay_for_internal_drags`] off, `egui_tiles`
+    /// previews and applies the drop itself, but the docking policy and node flags still get the
+    /// last word: `None` unless they reject or rewrite tiles' own drop.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn internal_drag_decision(
+        &self,
+        behavior: &dyn Behavior<Pane>,
+        style: &egui::Style,
+        dock_rect: Rect,
+        pointer_local: egui::Pos2,
+        host: WindowHost,
+        tree: &Tree<Pane>,
+        dragged_tile: TileId,
+    ) -> Option<OverlayDecision> {
+        let policy = self.docking_policy_query(host, Some(dragged_tile), host, tree);
+        if !self.options.show_overlay_for_internal_drags {
+            let decision = decide_tiles_drop_for_tree(
+                tree,
+                behavior,
+                style,
+                pointer_local,
+                dragged_tile,
+                policy.as_ref()?,
+            );
+            return decision.overrides_tiles().then_some(decision);
+        }
+        Some(decide_overlay_for_tree(
+            tree,
+            behavior,
+            style,
+            dock_rect,
+            pointer_local,
+            self.options.show_outer_overlay_targets,
+            DragKind::Subtree {
+                dragged_tile: Some(dragged_tile),
+                internal: true,
+            },
+            policy.as_ref(),
+        ))
+    }
 }
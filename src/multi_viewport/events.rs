@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use egui::ViewportId;
+use egui_tiles::{Container, Tile, TileId, Tree};
+
+use super::DockingMultiViewport;
+use super::behavior_wrap::PaneBackgroundBehavior;
+use super::debug_log::DebugLogRecord;
+use super::pane_query::PaneHost;
+use super::types::FloatingId;
+
+/// Upper bound for undrained events, so apps that never call `take_events` don't leak memory.
+const MAX_PENDING_DOCK_EVENTS: usize = 1024;
+
+/// A layout change observed by [`DockingMultiViewport`].
+///
+/// Events are collected while [`DockingMultiViewport::ui`] runs (and by the programmatic
+/// pane APIs), then drained with [`DockingMultiViewport::take_events`].
+///
+/// Unlike `Behavior::on_edit`, events carry enough context to drive autosave, telemetry and
+/// "Window" menus without diffing trees.
+///
+/// New kinds of events may be added, so matches outside this crate need a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DockEvent {
+    /// A pane (or subtree) was torn off into a new detached native viewport.
+    PaneDetached { source: PaneHost, viewport: ViewportId },
+
+    /// A detached viewport or floating window was docked back without a drop
+    /// (close button, "Dock" menu, aborted ghost drag, or API call).
+    PaneRedocked { source: PaneHost, target: PaneHost },
+
+    /// A contained floating window was created.
+    FloatingCreated {
+        viewport: ViewportId,
+        floating: FloatingId,
+    },
+
+    /// A contained floating window was closed, docked or emptied.
+    FloatingClosed {
+        viewport: ViewportId,
+        floating: FloatingId,
+    },
+
+    /// A detached native viewport was closed (re-docked, emptied or moved as a whole).
+    ViewportClosed { viewport: ViewportId },
+
+    /// A drag-and-drop moved a pane or subtree. `source == target` for drops inside one dock tree.
+    DropApplied { source: PaneHost, target: PaneHost },
+
+    /// A different tab became active in the `tabs` container of `host`.
+    TabActivated {
+        host: PaneHost,
+        tabs: TileId,
+        tile_id: TileId,
+    },
+}
+
+fn collect_active_tabs<Pane>(
+    host: PaneHost,
+    tree: &Tree<Pane>,
+    out: &mut BTreeMap<(PaneHost, TileId), TileId>,
+) {
+    for (&tile_id, tile) in tree.tiles.iter() {
+        if let Tile::Container(Container::Tabs(tabs)) = tile
+            && let Some(active) = tabs.active
+        {
+            out.insert((host, tile_id), active);
+        }
+    }
+}
+
+impl<Pane> DockingMultiViewport<Pane> {
+    /// Drain all events collected since the last call.
+    ///
+    /// Call this once per frame after [`Self::ui`].
+    pub fn take_events(&mut self) -> Vec<DockEvent> {
+        self.events.drain(..).collect()
+    }
+
+    pub(super) fn push_dock_event(&mut self, event: DockEvent) {
+        if self.events.len() >= MAX_PENDING_DOCK_EVENTS {
+            self.events.pop_front();
+        }
+        self.debug_log_record(DebugLogRecord::DockEvent(event));
+        if matches!(event, DockEvent::TabActivated { .. }) {
+            self.bump_layout_generation();
+        } else {
+            self.mark_layout_changed();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Emit [`DockEvent::DropApplied`] if egui_tiles applied a drop inside `host` during the
+    /// `tree.ui` call that just returned.
+    pub(super) fn emit_tiles_drop_event(
+        &mut self,
+        behavior: &mut PaneBackgroundBehavior<'_, Pane>,
+        host: PaneHost,
+    ) {
+        if behavior.take_tile_dropped() {
+            self.push_dock_event(DockEvent::DropApplied {
+                source: host,
+                target: host,
+            });
+        }
+    }
+
+    /// Emit [`DockEvent::TabActivated`] for every `Tabs` container whose active child changed
+    /// since the previous call.
+    ///
+    /// Containers that just appeared (e.g. created by a drop) don't emit anything.
+    pub(super) fn emit_tab_activated_events(&mut self) {
+        let mut active_tabs: BTreeMap<(PaneHost, TileId), TileId> = BTreeMap::new();
+        collect_active_tabs(PaneHost::RootDock, &self.tree, &mut active_tabs);
+        for (&viewport, detached) in &self.detached {
+            collect_active_tabs(
+                PaneHost::DetachedViewport { viewport },
+                &detached.tree,
+                &mut active_tabs,
+            );
+        }
+        for (&viewport, manager) in &self.floating {
+            for (&floating, window) in &manager.windows {
+                collect_active_tabs(
+                    PaneHost::Floating { viewport, floating },
+                    &window.tree,
+                    &mut active_tabs,
+                );
+            }
+        }
+
+        let changed: Vec<DockEvent> = active_tabs
+            .iter()
+            .filter_map(|(&(host, tabs), &tile_id)| {
+                let prev = self.last_active_tabs.get(&(host, tabs))?;
+                (*prev != tile_id).then_some(DockEvent::TabActivated {
+                    host,
+                    tabs,
+                    tile_id,
+                })
+            })
+            .collect();
+
+        self.last_active_tabs = active_tabs;
+        for event in changed {
+            self.push_dock_event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_activated_is_emitted_only_on_change() {
+        let mut tiles = egui_tiles::Tiles::default();
+        let a = tiles.insert_pane(1usize);
+        let b = tiles.insert_pane(2usize);
+        let root = tiles.insert_tab_tile(vec![a, b]);
+        let mut docking = DockingMultiViewport::new(Tree::new(egui::Id::new("root"), root, tiles));
+
+        docking.emit_tab_activated_events();
+        assert!(docking.take_events().is_empty());
+
+        if let Some(Tile::Container(Container::Tabs(tabs))) = docking.tree.tiles.get_mut(root) {
+            tabs.set_active(b);
+        }
+        docking.emit_tab_activated_events();
+        docking.emit_tab_activated_events();
+        assert_eq!(
+            docking.take_events(),
+            vec![DockEvent::TabActivated {
+                host: PaneHost::RootDock,
+                tabs: root,
+                tile_id: b
+            }]
+        );
+    }
+}
@@ -1,14 +1,22 @@
+use std::hash::{Hash as _, Hasher as _};
+
 use egui::emath::GuiRounding as _;
 use egui::epaint::MarginF32;
 use egui::{Context, Order, Pos2, Rect, Vec2, ViewportBuilder, ViewportId};
 use egui_tiles::{Behavior, InsertionPoint, TileId, Tree};
 
 use super::DockingMultiViewport;
+use super::behavior_wrap::PaneBackgroundBehavior;
+use super::debug_log::DebugLogRecord;
+use super::docking_policy::NewWindowKind;
+use super::events::DockEvent;
 use super::geometry::pointer_pos_in_viewport_space;
+use super::host::WindowHost;
+use super::pane_query::PaneHost;
 use super::title::title_for_detached_tree;
 use super::types::{
-    DockPayload, FloatingDockWindow, FloatingDragState, FloatingId, FloatingResizeState, GhostDrag,
-    GhostDragMode,
+    DockPayload, FLOATING_MIN_SIZE, FloatingDockWindow, FloatingDragState, FloatingId,
+    FloatingResizeState, GhostDrag, GhostDragMode,
 };
 
 impl<Pane> DockingMultiViewport<Pane> {
@@ -58,7 +66,7 @@ impl<Pane> DockingMultiViewport<Pane> {
                 }
             }
             if let Some(resize) = window.resize {
-                let min_size = Vec2::new(220.0, 120.0);
+                let min_size = FLOATING_MIN_SIZE;
                 if let Some(pointer) = ctx.input(|i| i.pointer.latest_pos()) {
                     let delta = pointer - resize.pointer_start;
                     window.size = (resize.size_start + delta).max(min_size);
@@ -82,7 +90,7 @@ impl<Pane> DockingMultiViewport<Pane> {
             );
             let title_height = title_bar_metrics.height_with_margin;
 
-            let min_size = Vec2::new(220.0, 120.0);
+            let min_size = FLOATING_MIN_SIZE;
             window.size.x = window.size.x.max(min_size.x);
             window.size.y = window.size.y.max(min_size.y);
 
@@ -152,12 +160,26 @@ impl<Pane> DockingMultiViewport<Pane> {
             .y
             .clamp(0.0, (dock_rect.height() - size.y).max(0.0));
 
+        self.insert_floating_window(viewport_id, subtree, offset_in_dock, size);
+
+        let _ = title; // title currently derived from the tree each frame; keep the param for future customization.
+        ctx.request_repaint_of(ViewportId::ROOT);
+    }
+
+    /// Wrap `subtree` into a new contained floating window on top of `viewport_id`'s z-order.
+    pub(super) fn insert_floating_window(
+        &mut self,
+        viewport_id: ViewportId,
+        subtree: egui_tiles::SubTree<Pane>,
+        offset_in_dock: Vec2,
+        size: Vec2,
+    ) -> FloatingId {
         let floating_id = self.allocate_floating_id();
         let floating_tree_id =
             egui::Id::new((self.tree.id(), "egui_docking_floating_tree", floating_id));
         let floating_tree = Tree::new(floating_tree_id, subtree.root, subtree.tiles);
 
-        let mut manager = self.floating.remove(&viewport_id).unwrap_or_default();
+        let manager = self.floating.entry(viewport_id).or_default();
         manager.windows.insert(
             floating_id,
             FloatingDockWindow {
@@ -170,16 +192,17 @@ impl<Pane> DockingMultiViewport<Pane> {
             },
         );
         manager.bring_to_front(floating_id);
-        self.floating.insert(viewport_id, manager);
-
-        let _ = title; // title currently derived from the tree each frame; keep the param for future customization.
-        ctx.request_repaint_of(ViewportId::ROOT);
+        self.push_dock_event(DockEvent::FloatingCreated {
+            viewport: viewport_id,
+            floating: floating_id,
+        });
+        floating_id
     }
 
     pub(super) fn ui_floating_windows_in_viewport(
         &mut self,
         ui: &mut egui::Ui,
-        behavior: &mut dyn Behavior<Pane>,
+        behavior: &mut PaneBackgroundBehavior<'_, Pane>,
         dock_rect: Rect,
         viewport_id: ViewportId,
     ) {
@@ -204,7 +227,18 @@ impl<Pane> DockingMultiViewport<Pane> {
 
                 let should_upgrade = self.options.ghost_upgrade_to_native_on_leave_viewport
                     && pointer_global.is_some()
-                    && pointer_pos_in_viewport_space(ctx, pointer_global).is_none();
+                    && pointer_pos_in_viewport_space(ctx, pointer_global).is_none()
+                    && manager.windows.get(&floating).is_some_and(|window| {
+                        window.tree.root.is_some_and(|root| {
+                            self.docking_policy_allows_tear_off(
+                                PaneHost::Floating { viewport, floating },
+                                &window.tree,
+                                root,
+                                NewWindowKind::Native,
+                                false,
+                            )
+                        })
+                    });
 
                 if should_upgrade {
                     if let (Some(pointer_global), Some(mut window)) =
@@ -236,6 +270,7 @@ impl<Pane> DockingMultiViewport<Pane> {
                                     serial,
                                     tree: detached_tree,
                                     builder,
+                                    options: Default::default(),
                                 },
                             );
 
@@ -255,6 +290,11 @@ impl<Pane> DockingMultiViewport<Pane> {
                                 },
                                 grab_offset,
                             });
+                            self.debug_log_record(DebugLogRecord::GhostUpgrade {
+                                viewport: viewport_id,
+                                floating,
+                                native_viewport: ghost_viewport_id,
+                            });
                             ctx.request_repaint_of(ViewportId::ROOT);
                         } else {
                             // Empty tree; drop the ghost.
@@ -321,7 +361,7 @@ impl<Pane> DockingMultiViewport<Pane> {
                     + 2.0 * title_frame.stroke.width)
                     .max(96.0)
             };
-            let min_size = Vec2::new(220.0, 120.0);
+            let min_size = FLOATING_MIN_SIZE;
             window.size.x = window.size.x.max(min_size.x);
             window.size.y = window.size.y.max(min_size.y);
 
@@ -544,9 +584,27 @@ impl<Pane> DockingMultiViewport<Pane> {
                                 ctx.input(|i| i.pointer.latest_pos()),
                                 window.tree.dragged_id_including_root(&ctx),
                             ) {
-                                if !alloc_rect
-                                    .expand(self.options.ghost_tear_off_threshold)
-                                    .contains(pointer_local)
+                                let detach_tile = super::pick_detach_tile_for_tree(
+                                    &ctx,
+                                    &self.options,
+                                    &window.tree,
+                                    dragged_tile,
+                                );
+                                let tear_off_allowed = self.drag_tear_off_allowed(
+                                    PaneHost::Floating {
+                                        viewport: viewport_id,
+                                        floating: floating_id,
+                                    },
+                                    &window.tree,
+                                    detach_tile,
+                                    NewWindowKind::Floating {
+                                        viewport: viewport_id,
+                                    },
+                                );
+                                if tear_off_allowed
+                                    && !alloc_rect
+                                        .expand(self.options.ghost_tear_off_threshold)
+                                        .contains(pointer_local)
                                 {
                                     ghost_from_floating =
                                         Some((floating_id, dragged_tile, pointer_local));
@@ -570,11 +628,31 @@ impl<Pane> DockingMultiViewport<Pane> {
                             }
                         }
 
-                        self.set_tiles_disable_drop_preview_if_overlay_hovered(
+                        let took_over_internal_drop = self.process_release_before_tree_ui(
                             &ctx,
                             behavior,
                             content_rect,
+                            WindowHost::Floating {
+                                viewport: viewport_id,
+                                floating: floating_id,
+                            },
+                            &window.tree,
+                            "internal_overlay_drop_floating",
+                        );
+                        self.set_tiles_disable_drop_apply_if_taken_over(
+                            &ctx,
+                            window.tree.id(),
                             viewport_id,
+                            took_over_internal_drop,
+                        );
+                        self.set_tiles_disable_drop_preview_if_overlay_hovered(
+                            &ctx,
+                            behavior,
+                            content_rect,
+                            WindowHost::Floating {
+                                viewport: viewport_id,
+                                floating: floating_id,
+                            },
                             &window.tree,
                         );
 
@@ -583,8 +661,20 @@ impl<Pane> DockingMultiViewport<Pane> {
                                 ui.new_child(egui::UiBuilder::new().max_rect(content_rect));
                             content_ui
                                 .set_clip_rect(content_ui.clip_rect().intersect(content_rect));
+                            behavior.set_node_flags(self.node_flags_in_host(PaneHost::Floating {
+                                viewport: viewport_id,
+                                floating: floating_id,
+                            }));
                             window.tree.ui(behavior, &mut content_ui);
+                            behavior.lock_splitters(&mut content_ui, &window.tree);
                         }
+                        self.emit_tiles_drop_event(
+                            behavior,
+                            PaneHost::Floating {
+                                viewport: viewport_id,
+                                floating: floating_id,
+                            },
+                        );
 
                         // ImGui-like: double-click tab-bar background toggles collapse for floating hosts.
                         if let Some(root_tabs) = window
@@ -682,7 +772,10 @@ impl<Pane> DockingMultiViewport<Pane> {
                             behavior,
                             &window.tree,
                             content_rect,
-                            viewport_id,
+                            WindowHost::Floating {
+                                viewport: viewport_id,
+                                floating: floating_id,
+                            },
                         );
                     }
                 });
@@ -784,9 +877,31 @@ impl<Pane> DockingMultiViewport<Pane> {
                 egui_tiles::SubTree { root, tiles },
                 None,
             );
+            self.push_dock_event(DockEvent::PaneRedocked {
+                source: PaneHost::Floating {
+                    viewport: viewport_id,
+                    floating: id,
+                },
+                target: if viewport_id == ViewportId::ROOT {
+                    PaneHost::RootDock
+                } else {
+                    PaneHost::DetachedViewport {
+                        viewport: viewport_id,
+                    }
+                },
+            });
+            self.push_dock_event(DockEvent::FloatingClosed {
+                viewport: viewport_id,
+                floating: id,
+            });
         }
         for id in close_windows {
-            manager.windows.remove(&id);
+            if manager.windows.remove(&id).is_some() {
+                self.push_dock_event(DockEvent::FloatingClosed {
+                    viewport: viewport_id,
+                    floating: id,
+                });
+            }
         }
         manager
             .z_order
@@ -806,6 +921,27 @@ impl<Pane> DockingMultiViewport<Pane> {
         }
     }
 
+    /// Bump the layout generation if a floating window moved, resized or (un)collapsed since the
+    /// last call. Drags and resizes update the geometry in several places, so the result is
+    /// compared once per frame instead.
+    pub(super) fn note_floating_geometry_changes(&mut self) {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for (viewport_id, manager) in &self.floating {
+            for (floating_id, window) in &manager.windows {
+                (viewport_id, floating_id, window.collapsed).hash(&mut hasher);
+                let (offset, size) = (window.offset_in_dock, window.size);
+                [offset.x, offset.y, size.x, size.y]
+                    .map(f32::to_bits)
+                    .hash(&mut hasher);
+            }
+        }
+        let hash = hasher.finish();
+        if hash != self.floating_geometry_hash {
+            self.floating_geometry_hash = hash;
+            self.bump_layout_generation();
+        }
+    }
+
     pub(super) fn dock_subtree_into_dock_tree(
         &mut self,
         viewport_id: ViewportId,
@@ -842,6 +978,10 @@ impl<Pane> DockingMultiViewport<Pane> {
                     ));
                 }
                 manager.windows.remove(&floating_id);
+                self.push_dock_event(DockEvent::FloatingClosed {
+                    viewport: viewport_id,
+                    floating: floating_id,
+                });
             }
         }
 
@@ -869,6 +1009,10 @@ impl<Pane> DockingMultiViewport<Pane> {
         if !manager.windows.is_empty() {
             self.floating.insert(viewport_id, manager);
         }
+        self.push_dock_event(DockEvent::FloatingClosed {
+            viewport: viewport_id,
+            floating: floating_id,
+        });
 
         let root = window.tree.root.take()?;
         let tiles = std::mem::take(&mut window.tree.tiles);
@@ -2,8 +2,12 @@ use egui::{Context, Pos2, Rect, Vec2, ViewportBuilder, ViewportId};
 use egui_tiles::{Behavior, Tree};
 
 use super::DockingMultiViewport;
+use super::debug_log::DebugLogRecord;
+use super::docking_policy::NewWindowKind;
+use super::events::DockEvent;
 use super::geometry::{infer_detached_geometry, pointer_pos_in_global, root_inner_rect_in_global};
 use super::monitor_clamp::clamp_outer_pos_if_monitors_available;
+use super::pane_query::PaneHost;
 use super::title::title_for_detached_subtree;
 use super::types::{DetachedDock, DockPayload, FloatingDockWindow, GhostDrag, GhostDragMode};
 
@@ -50,10 +54,12 @@ impl<Pane> DockingMultiViewport<Pane> {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn spawn_native_ghost_from_subtree(
         &mut self,
         ctx: &Context,
         behavior: &mut dyn Behavior<Pane>,
+        source: PaneHost,
         subtree: egui_tiles::SubTree<Pane>,
         size: Vec2,
         grab_offset: Vec2,
@@ -86,6 +92,7 @@ impl<Pane> DockingMultiViewport<Pane> {
                 serial,
                 tree: detached_tree,
                 builder,
+                options: Default::default(),
             },
         );
 
@@ -112,6 +119,10 @@ impl<Pane> DockingMultiViewport<Pane> {
                 size.x, size.y, grab_offset.x, grab_offset.y
             ));
         }
+        self.push_dock_event(DockEvent::PaneDetached {
+            source,
+            viewport: viewport_id,
+        });
 
         viewport_id
     }
@@ -177,6 +188,26 @@ impl<Pane> DockingMultiViewport<Pane> {
                 return;
             }
         }
+        let ctrl_floating =
+            self.options.tear_off_to_floating_on_ctrl && ctx.input(|i| i.modifiers.ctrl);
+        let new_window = if ctrl_floating {
+            NewWindowKind::Floating {
+                viewport: ViewportId::ROOT,
+            }
+        } else {
+            NewWindowKind::Native
+        };
+        if !self.drag_tear_off_allowed(PaneHost::RootDock, &self.tree, detach_tile, new_window) {
+            self.debug_log_record(DebugLogRecord::TearOff {
+                source_host: PaneHost::RootDock,
+                tile_id: detach_tile,
+                floating: ctrl_floating,
+                api: false,
+                accepted: false,
+                new_host: None,
+            });
+            return;
+        }
 
         // Prevent egui_tiles from applying an internal drop this frame.
         ctx.stop_dragging();
@@ -203,8 +234,6 @@ impl<Pane> DockingMultiViewport<Pane> {
         );
         let pos = clamp_outer_pos_if_monitors_available(ctx, pos, size);
 
-        let ctrl_floating =
-            self.options.tear_off_to_floating_on_ctrl && ctx.input(|i| i.modifiers.ctrl);
         if ctrl_floating {
             self.spawn_floating_subtree_in_viewport(
                 ctx,
@@ -235,8 +264,13 @@ impl<Pane> DockingMultiViewport<Pane> {
                 serial,
                 tree: detached_tree,
                 builder,
+                options: Default::default(),
             },
         );
+        self.push_dock_event(DockEvent::PaneDetached {
+            source: PaneHost::RootDock,
+            viewport: viewport_id,
+        });
 
         ctx.request_repaint();
     }
@@ -280,6 +314,29 @@ impl<Pane> DockingMultiViewport<Pane> {
                 return;
             }
         }
+        let host = PaneHost::DetachedViewport {
+            viewport: current_viewport,
+        };
+        let ctrl_floating =
+            self.options.tear_off_to_floating_on_ctrl && ctx.input(|i| i.modifiers.ctrl);
+        let new_window = if ctrl_floating {
+            NewWindowKind::Floating {
+                viewport: current_viewport,
+            }
+        } else {
+            NewWindowKind::Native
+        };
+        if !self.drag_tear_off_allowed(host, tree, dragged_tile, new_window) {
+            self.debug_log_record(DebugLogRecord::TearOff {
+                source_host: host,
+                tile_id: dragged_tile,
+                floating: ctrl_floating,
+                api: false,
+                accepted: false,
+                new_host: None,
+            });
+            return;
+        }
 
         ctx.stop_dragging();
         if let Some(payload) = egui::DragAndDrop::payload::<DockPayload>(ctx) {
@@ -305,8 +362,6 @@ impl<Pane> DockingMultiViewport<Pane> {
         );
         let pos = clamp_outer_pos_if_monitors_available(ctx, pos, size);
 
-        let ctrl_floating =
-            self.options.tear_off_to_floating_on_ctrl && ctx.input(|i| i.modifiers.ctrl);
         if ctrl_floating {
             self.spawn_floating_subtree_in_viewport(
                 ctx,
@@ -340,8 +395,15 @@ impl<Pane> DockingMultiViewport<Pane> {
                 serial,
                 tree: detached_tree,
                 builder,
+                options: Default::default(),
             },
         );
+        self.push_dock_event(DockEvent::PaneDetached {
+            source: PaneHost::DetachedViewport {
+                viewport: current_viewport,
+            },
+            viewport: viewport_id,
+        });
 
         if tree.root.is_none() {
             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
@@ -396,12 +458,26 @@ impl<Pane> DockingMultiViewport<Pane> {
             return;
         }
 
-        let tree = &mut self.tree;
-
-        let Some(dragged_tile) = tree.dragged_id_including_root(ctx) else {
+        let Some(dragged_tile) = self.tree.dragged_id_including_root(ctx) else {
             return;
         };
-        let detach_tile = super::pick_detach_tile_for_tree(ctx, &self.options, tree, dragged_tile);
+        let detach_tile =
+            super::pick_detach_tile_for_tree(ctx, &self.options, &self.tree, dragged_tile);
+        let ctrl_floating =
+            self.options.tear_off_to_floating_on_ctrl && ctx.input(|i| i.modifiers.ctrl);
+        let spawn_native = self.options.ghost_spawn_native_on_leave_dock && !ctrl_floating;
+        let new_window = if spawn_native {
+            NewWindowKind::Native
+        } else {
+            NewWindowKind::Floating {
+                viewport: viewport_id,
+            }
+        };
+        if !self.drag_tear_off_allowed(PaneHost::RootDock, &self.tree, detach_tile, new_window) {
+            return;
+        }
+
+        let tree = &mut self.tree;
 
         // Transfer authority away from egui_tiles internal drag-drop as soon as we switch
         // to a cross-surface "ghost" payload.
@@ -417,12 +493,11 @@ impl<Pane> DockingMultiViewport<Pane> {
             .unwrap_or(self.options.default_detached_inner_size);
 
         let grab_offset = Vec2::new(20.0, 10.0);
-        let ctrl_floating =
-            self.options.tear_off_to_floating_on_ctrl && ctx.input(|i| i.modifiers.ctrl);
-        if self.options.ghost_spawn_native_on_leave_dock && !ctrl_floating {
+        if spawn_native {
             self.spawn_native_ghost_from_subtree(
                 ctx,
                 behavior,
+                PaneHost::RootDock,
                 subtree,
                 size,
                 grab_offset,
@@ -466,6 +541,10 @@ impl<Pane> DockingMultiViewport<Pane> {
         );
         manager.bring_to_front(floating_id);
         self.floating.insert(viewport_id, manager);
+        self.push_dock_event(DockEvent::FloatingCreated {
+            viewport: viewport_id,
+            floating: floating_id,
+        });
 
         // Use a "whole tree" payload while dragging the ghost surface around.
         egui::DragAndDrop::set_payload(
@@ -544,6 +623,22 @@ impl<Pane> DockingMultiViewport<Pane> {
             return;
         };
         let detach_tile = super::pick_detach_tile_for_tree(ctx, &self.options, tree, dragged_tile);
+        let ctrl_floating =
+            self.options.tear_off_to_floating_on_ctrl && ctx.input(|i| i.modifiers.ctrl);
+        let spawn_native = self.options.ghost_spawn_native_on_leave_dock && !ctrl_floating;
+        let new_window = if spawn_native {
+            NewWindowKind::Native
+        } else {
+            NewWindowKind::Floating {
+                viewport: viewport_id,
+            }
+        };
+        let host = PaneHost::DetachedViewport {
+            viewport: viewport_id,
+        };
+        if !self.drag_tear_off_allowed(host, tree, detach_tile, new_window) {
+            return;
+        }
 
         ctx.stop_dragging();
 
@@ -557,12 +652,13 @@ impl<Pane> DockingMultiViewport<Pane> {
             .unwrap_or(self.options.default_detached_inner_size);
 
         let grab_offset = Vec2::new(20.0, 10.0);
-        let ctrl_floating =
-            self.options.tear_off_to_floating_on_ctrl && ctx.input(|i| i.modifiers.ctrl);
-        if self.options.ghost_spawn_native_on_leave_dock && !ctrl_floating {
+        if spawn_native {
             self.spawn_native_ghost_from_subtree(
                 ctx,
                 behavior,
+                PaneHost::DetachedViewport {
+                    viewport: viewport_id,
+                },
                 subtree,
                 size,
                 grab_offset,
@@ -609,6 +705,10 @@ impl<Pane> DockingMultiViewport<Pane> {
         );
         manager.bring_to_front(floating_id);
         self.floating.insert(viewport_id, manager);
+        self.push_dock_event(DockEvent::FloatingCreated {
+            viewport: viewport_id,
+            floating: floating_id,
+        });
 
         egui::DragAndDrop::set_payload(
             ctx,
@@ -666,11 +766,15 @@ impl<Pane> DockingMultiViewport<Pane> {
                 GhostDragMode::Contained { viewport, floating } => {
                     if let Some(subtree) = self.take_whole_floating_tree(viewport, floating) {
                         self.dock_subtree_into_root(subtree, None);
+                        self.push_dock_event(DockEvent::PaneRedocked {
+                            source: PaneHost::Floating { viewport, floating },
+                            target: PaneHost::RootDock,
+                        });
                     }
                 }
                 GhostDragMode::Native { viewport } => {
                     if let Some(detached) = self.detached.remove(&viewport) {
-                        self.dock_tree_into_root(detached.tree, None);
+                        self.redock_detached_tree_into_root(viewport, detached.tree);
                         ctx.send_viewport_cmd_to(viewport, egui::ViewportCommand::Close);
                     }
                 }
@@ -0,0 +1,552 @@
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use super::DockingMultiViewport;
+use super::snapshot::LayoutSnapshot;
+
+/// How history steps name panes: a key for every live pane, and a way to recreate a pane whose
+/// key is no longer live (e.g. a closed tab brought back by undo).
+trait HistoryPanes<Pane> {
+    type PaneId: Clone + Eq + Hash;
+
+    fn pane_id(&mut self, pane: &Pane) -> Self::PaneId;
+    fn pane_from_id(&mut self, id: Self::PaneId) -> Option<Pane>;
+}
+
+struct PaneIdFns<ToId, FromId> {
+    to_id: ToId,
+    from_id: FromId,
+}
+
+impl<Pane, PaneId, ToId, FromId> HistoryPanes<Pane> for PaneIdFns<ToId, FromId>
+where
+    PaneId: Clone + Eq + Hash,
+    ToId: FnMut(&Pane) -> PaneId,
+    FromId: FnMut(PaneId) -> Option<Pane>,
+{
+    type PaneId = PaneId;
+
+    fn pane_id(&mut self, pane: &Pane) -> PaneId {
+        (self.to_id)(pane)
+    }
+
+    fn pane_from_id(&mut self, id: PaneId) -> Option<Pane> {
+        (self.from_id)(id)
+    }
+}
+
+#[cfg(feature = "persistence")]
+struct RegistryPanes<R>(R);
+
+#[cfg(feature = "persistence")]
+impl<Pane, R> HistoryPanes<Pane> for RegistryPanes<R>
+where
+    R: super::PaneRegistry<Pane>,
+{
+    type PaneId = R::PaneId;
+
+    fn pane_id(&mut self, pane: &Pane) -> R::PaneId {
+        self.0.pane_id(pane)
+    }
+
+    fn pane_from_id(&mut self, id: R::PaneId) -> Option<Pane> {
+        self.0.try_pane_from_id(id)
+    }
+}
+
+/// [`LayoutHistory`] without its `PaneId`, as stored on [`DockingMultiViewport`].
+///
+/// The history is taken out of the docking state while it captures or restores a layout.
+pub(super) trait ErasedLayoutHistory<Pane>: std::fmt::Debug {
+    fn set_depth(&mut self, depth: usize);
+    fn can_undo(&self) -> bool;
+    fn can_redo(&self) -> bool;
+
+    /// Push the last recorded layout as an undo step; `docking` becomes the current layout.
+    fn record(&mut self, docking: &DockingMultiViewport<Pane>);
+
+    /// Drop every step; `docking` becomes the starting point.
+    fn reset(&mut self, docking: &DockingMultiViewport<Pane>);
+
+    fn undo(&mut self, docking: &mut DockingMultiViewport<Pane>) -> bool;
+    fn redo(&mut self, docking: &mut DockingMultiViewport<Pane>) -> bool;
+}
+
+/// Undo/redo stacks of whole-layout snapshots (root, detached and floating hosts).
+///
+/// Panes are stored by id, like a saved layout: restoring a step moves the live panes into it, so
+/// their state is never rolled back.
+struct LayoutHistory<Pane, P: HistoryPanes<Pane>> {
+    depth: usize,
+    panes: P,
+
+    /// The layout as of the last recorded step; pushed onto `undo` once the layout changes.
+    current: LayoutSnapshot<P::PaneId>,
+    undo: VecDeque<LayoutSnapshot<P::PaneId>>,
+    redo: Vec<LayoutSnapshot<P::PaneId>>,
+    _pane: std::marker::PhantomData<fn(&Pane)>,
+}
+
+impl<Pane, P: HistoryPanes<Pane>> std::fmt::Debug for LayoutHistory<Pane, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LayoutHistory")
+            .field("depth", &self.depth)
+            .field("undo", &self.undo.len())
+            .field("redo", &self.redo.len())
+            .finish()
+    }
+}
+
+impl<Pane, P: HistoryPanes<Pane>> LayoutHistory<Pane, P> {
+    fn new(depth: usize, mut panes: P, docking: &DockingMultiViewport<Pane>) -> Self {
+        Self {
+            depth,
+            current: docking.snapshot_layout_impl(|pane| panes.pane_id(pane)),
+            panes,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            _pane: std::marker::PhantomData,
+        }
+    }
+
+    fn capture(&mut self, docking: &DockingMultiViewport<Pane>) -> LayoutSnapshot<P::PaneId> {
+        docking.snapshot_layout_impl(|pane| self.panes.pane_id(pane))
+    }
+
+    fn push_undo(&mut self, snapshot: LayoutSnapshot<P::PaneId>) {
+        self.undo.push_back(snapshot);
+        while self.undo.len() > self.depth {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Rebuild `target` from the live panes (docked, detached, floating and parked), like a
+    /// workspace preset switch. Panes `target` doesn't use are parked.
+    fn restore(
+        &mut self,
+        docking: &mut DockingMultiViewport<Pane>,
+        target: LayoutSnapshot<P::PaneId>,
+    ) {
+        let panes = &mut self.panes;
+        let mut pool = docking.pool_panes(|pane| panes.pane_id(pane));
+        docking.restore_layout_snapshot(target, |id| {
+            pool.get_mut(&id)
+                .and_then(Vec::pop)
+                .or_else(|| panes.pane_from_id(id))
+        });
+        docking.parked_panes.extend(pool.into_values().flatten());
+    }
+}
+
+impl<Pane, P: HistoryPanes<Pane>> ErasedLayoutHistory<Pane> for LayoutHistory<Pane, P> {
+    fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        while self.undo.len() > depth {
+            self.undo.pop_front();
+        }
+    }
+
+    fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    fn record(&mut self, docking: &DockingMultiViewport<Pane>) {
+        let snapshot = self.capture(docking);
+        let previous = std::mem::replace(&mut self.current, snapshot);
+        self.push_undo(previous);
+        self.redo.clear();
+    }
+
+    fn reset(&mut self, docking: &DockingMultiViewport<Pane>) {
+        self.current = self.capture(docking);
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn undo(&mut self, docking: &mut DockingMultiViewport<Pane>) -> bool {
+        let Some(target) = self.undo.pop_back() else {
+            return false;
+        };
+        let current = std::mem::replace(&mut self.current, target.clone());
+        self.redo.push(current);
+        self.restore(docking, target);
+        true
+    }
+
+    fn redo(&mut self, docking: &mut DockingMultiViewport<Pane>) -> bool {
+        let Some(target) = self.redo.pop() else {
+            return false;
+        };
+        let current = std::mem::replace(&mut self.current, target.clone());
+        self.push_undo(current);
+        self.restore(docking, target);
+        true
+    }
+}
+
+impl<Pane: 'static> DockingMultiViewport<Pane> {
+    /// Record an undo step before every applied drop, tear-off, float or close, keeping at most
+    /// `depth` steps. `depth == 0` disables the history.
+    ///
+    /// Steps store panes by `pane_to_id`, like a saved layout. Undo and redo move the live panes
+    /// into the restored layout (so pane state is kept), call `pane_from_id` only for panes that
+    /// no longer exist (e.g. a closed tab; return `None` to leave it out), and park live panes
+    /// the restored layout doesn't use (see [`Self::parked_panes`]).
+    ///
+    /// Calling this again restarts the history with the new mapping.
+    pub fn enable_layout_history<PaneId>(
+        &mut self,
+        depth: usize,
+        pane_to_id: impl FnMut(&Pane) -> PaneId + 'static,
+        pane_from_id: impl FnMut(PaneId) -> Option<Pane> + 'static,
+    ) where
+        PaneId: Clone + Eq + Hash + 'static,
+    {
+        self.enable_layout_history_impl(
+            depth,
+            PaneIdFns {
+                to_id: pane_to_id,
+                from_id: pane_from_id,
+            },
+        );
+    }
+
+    /// Like [`Self::enable_layout_history`], using a [`super::PaneRegistry`] for pane ids.
+    #[cfg(feature = "persistence")]
+    pub fn enable_layout_history_with_registry<R>(&mut self, depth: usize, registry: R)
+    where
+        R: super::PaneRegistry<Pane> + 'static,
+        R::PaneId: 'static,
+    {
+        self.enable_layout_history_impl(depth, RegistryPanes(registry));
+    }
+
+    fn enable_layout_history_impl<P>(&mut self, depth: usize, panes: P)
+    where
+        P: HistoryPanes<Pane> + 'static,
+    {
+        if depth == 0 {
+            self.disable_layout_history();
+            return;
+        }
+        self.layout_changed = false;
+        self.history = Some(Box::new(LayoutHistory::new(depth, panes, self)));
+    }
+}
+
+impl<Pane> DockingMultiViewport<Pane> {
+    /// Change how many steps the history keeps, dropping the oldest ones. Does nothing while the
+    /// history is disabled.
+    pub fn set_layout_history_depth(&mut self, depth: usize) {
+        if depth == 0 {
+            self.disable_layout_history();
+        } else if let Some(history) = &mut self.history {
+            history.set_depth(depth);
+        }
+    }
+
+    /// Restore the layout from before the last recorded step.
+    ///
+    /// Returns `false` if there is nothing to undo (or the history is disabled).
+    /// Call this between frames; an in-flight drag is cancelled.
+    pub fn undo(&mut self) -> bool {
+        self.commit_layout_history();
+        let Some(mut history) = self.history.take() else {
+            return false;
+        };
+        let undone = history.undo(self);
+        self.history = Some(history);
+        if undone {
+            self.layout_changed = false;
+        }
+        undone
+    }
+
+    /// Re-apply the step reverted by the last [`Self::undo`].
+    ///
+    /// Returns `false` if there is nothing to redo. Any new layout change clears the redo stack.
+    pub fn redo(&mut self) -> bool {
+        self.commit_layout_history();
+        let Some(mut history) = self.history.take() else {
+            return false;
+        };
+        let redone = history.redo(self);
+        self.history = Some(history);
+        if redone {
+            self.layout_changed = false;
+        }
+        redone
+    }
+}
+
+impl<Pane> DockingMultiViewport<Pane> {
+    /// Stop recording layout history and drop all recorded steps.
+    pub fn disable_layout_history(&mut self) {
+        self.history = None;
+    }
+
+    /// Drop all recorded undo/redo steps; the current layout becomes the new starting point
+    /// (e.g. right after loading a saved layout). The history stays enabled.
+    pub fn clear_layout_history(&mut self) {
+        let Some(mut history) = self.history.take() else {
+            return;
+        };
+        self.layout_changed = false;
+        history.reset(self);
+        self.history = Some(history);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.as_ref().is_some_and(|h| h.can_undo())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.as_ref().is_some_and(|h| h.can_redo())
+    }
+
+    /// Counter bumped whenever the layout may have changed: docking edits, tab switches, split
+    /// drags and window moves or resizes. Comparing it across frames is much cheaper than
+    /// snapshotting the layout ([`super::LayoutAutosave`] only snapshots when it moves).
+    ///
+    /// Unlike the undo history, this isn't reset when a step is recorded.
+    pub fn layout_generation(&self) -> u64 {
+        self.layout_generation
+    }
+
+    /// Note that the layout changed; the step is recorded by the next
+    /// [`Self::commit_layout_history`].
+    pub(super) fn mark_layout_changed(&mut self) {
+        self.layout_changed = true;
+        self.bump_layout_generation();
+    }
+
+    /// Note a change the undo history doesn't record (tab switches, split drags, window geometry).
+    pub(super) fn bump_layout_generation(&mut self) {
+        self.layout_generation = self.layout_generation.wrapping_add(1);
+    }
+
+    /// Turn a pending layout change into an undo step.
+    ///
+    /// Deferred while a ghost drag is alive, so a whole tear-off/re-dock gesture is one step.
+    pub(super) fn commit_layout_history(&mut self) {
+        if !self.layout_changed || self.ghost.is_some() {
+            return;
+        }
+        self.layout_changed = false;
+
+        let Some(mut history) = self.history.take() else {
+            return;
+        };
+        history.record(self);
+        self.history = Some(history);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Pos2, Rect, Vec2, ViewportId};
+    use egui_tiles::{ContainerKind, Tile};
+
+    use super::*;
+    use crate::multi_viewport::simulation::{DockingSimulation, SimulatedDropTarget};
+    use crate::multi_viewport::test_support::{DummyBehavior, docking_with_tabs, root_panes};
+    use crate::multi_viewport::{DockEvent, PaneHost};
+
+    fn enable_history(docking: &mut DockingMultiViewport<usize>, depth: usize) {
+        docking.enable_layout_history(depth, |&pane: &usize| pane, Some);
+    }
+
+    /// Float `pane` and end the frame, like a user action followed by `DockingMultiViewport::ui`.
+    fn float(docking: &mut DockingMultiViewport<usize>, pane: usize) {
+        let ctx = egui::Context::default();
+        docking
+            .float_pane(
+                &ctx,
+                &mut DummyBehavior,
+                |&p| p == pane,
+                ViewportId::ROOT,
+                Vec2::ZERO,
+                Vec2::new(320.0, 240.0),
+            )
+            .unwrap();
+        docking.commit_layout_history();
+    }
+
+    #[test]
+    fn undo_and_redo_restore_every_host() {
+        let mut docking = docking_with_tabs(&[1, 2, 3]);
+        enable_history(&mut docking, 8);
+        assert!(!docking.can_undo());
+
+        float(&mut docking, 2);
+        assert_eq!(root_panes(&docking), vec![1, 3]);
+        assert!(docking.can_undo());
+
+        assert!(docking.undo());
+        assert_eq!(root_panes(&docking), vec![1, 2, 3]);
+        assert_eq!(docking.floating_window_count(), 0);
+        assert!(docking.can_redo());
+
+        assert!(docking.redo());
+        assert_eq!(root_panes(&docking), vec![1, 3]);
+        assert_eq!(docking.floating_window_count(), 1);
+        assert!(!docking.redo());
+    }
+
+    #[test]
+    fn history_depth_drops_oldest_steps() {
+        let mut docking = docking_with_tabs(&[1, 2, 3, 4]);
+        enable_history(&mut docking, 2);
+
+        for pane in [1, 2, 3] {
+            float(&mut docking, pane);
+        }
+
+        assert!(docking.undo());
+        assert!(docking.undo());
+        assert!(!docking.undo());
+        assert_eq!(root_panes(&docking), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn new_change_clears_redo() {
+        let mut docking = docking_with_tabs(&[1, 2, 3]);
+        enable_history(&mut docking, 8);
+
+        float(&mut docking, 1);
+        assert!(docking.undo());
+        assert!(docking.can_redo());
+
+        float(&mut docking, 3);
+        assert!(!docking.can_redo());
+    }
+
+    #[test]
+    fn disabled_history_records_nothing() {
+        let mut docking = docking_with_tabs(&[1, 2]);
+
+        float(&mut docking, 1);
+        assert!(!docking.can_undo());
+        assert!(!docking.undo());
+    }
+
+    /// A pane whose state (`edits`) is not part of its id.
+    #[derive(Debug)]
+    struct Doc {
+        id: usize,
+        edits: u32,
+    }
+
+    struct DocBehavior;
+
+    impl egui_tiles::Behavior<Doc> for DocBehavior {
+        fn pane_ui(
+            &mut self,
+            _ui: &mut egui::Ui,
+            _tile_id: egui_tiles::TileId,
+            _pane: &mut Doc,
+        ) -> egui_tiles::UiResponse {
+            Default::default()
+        }
+
+        fn tab_title_for_pane(&mut self, pane: &Doc) -> egui::WidgetText {
+            format!("doc {}", pane.id).into()
+        }
+    }
+
+    #[test]
+    fn undo_keeps_the_state_of_live_panes() {
+        let mut tiles = egui_tiles::Tiles::default();
+        let docs = (1..=3)
+            .map(|id| tiles.insert_pane(Doc { id, edits: 0 }))
+            .collect();
+        let root = tiles.insert_tab_tile(docs);
+        let mut docking =
+            DockingMultiViewport::new(egui_tiles::Tree::new(egui::Id::new("root"), root, tiles));
+        docking.enable_layout_history(8, |doc: &Doc| doc.id, |_| None);
+
+        let ctx = egui::Context::default();
+        docking
+            .float_pane(
+                &ctx,
+                &mut DocBehavior,
+                |doc| doc.id == 2,
+                ViewportId::ROOT,
+                Vec2::ZERO,
+                Vec2::new(320.0, 240.0),
+            )
+            .unwrap();
+        docking.commit_layout_history();
+
+        // Edit every pane after the step, docked and floating.
+        let floating = docking.find_pane(|doc| doc.id == 2).unwrap().host;
+        for host in [PaneHost::RootDock, floating] {
+            let tree = docking.tree_mut_for_host(host.window_host()).unwrap();
+            let tile_ids: Vec<egui_tiles::TileId> = tree.tiles.tile_ids().collect();
+            for tile_id in tile_ids {
+                if let Some(Tile::Pane(doc)) = tree.tiles.get_mut(tile_id) {
+                    doc.edits += 1;
+                }
+            }
+        }
+
+        assert!(docking.undo());
+        assert_eq!(docking.floating_window_count(), 0);
+        let mut docs: Vec<(usize, u32)> = docking
+            .tree
+            .tiles
+            .iter()
+            .filter_map(|(_, tile)| match tile {
+                Tile::Pane(doc) => Some((doc.id, doc.edits)),
+                Tile::Container(_) => None,
+            })
+            .collect();
+        docs.sort_unstable();
+        assert_eq!(docs, vec![(1, 1), (2, 1), (3, 1)]);
+        assert_eq!(docking.parked_panes().count(), 0);
+    }
+
+    fn root_kind(docking: &DockingMultiViewport<usize>) -> Option<ContainerKind> {
+        match docking.tree.tiles.get(docking.tree.root?)? {
+            Tile::Container(container) => Some(container.kind()),
+            Tile::Pane(_) => None,
+        }
+    }
+
+    #[test]
+    fn undo_restores_a_drag_inside_the_root_tree() {
+        let mut sim =
+            DockingSimulation::new(Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0)));
+        let mut docking = docking_with_tabs(&[1, 2]);
+        docking.options.ghost_tear_off = false;
+        enable_history(&mut docking, 8);
+        let mut behavior = DummyBehavior;
+        sim.run_frames(3, &mut docking, &mut behavior);
+        docking.take_events();
+
+        assert_eq!(root_kind(&docking), Some(ContainerKind::Tabs));
+
+        let tab = sim
+            .tab_center(&docking, |&p| p == 1)
+            .expect("tab 1 is shown");
+        let left = sim
+            .drop_target_pos(&docking, ViewportId::ROOT, SimulatedDropTarget::Left)
+            .expect("root dock offers a left target");
+        sim.drag(&mut docking, &mut behavior, tab, left);
+
+        assert_ne!(root_kind(&docking), Some(ContainerKind::Tabs));
+        assert!(docking.take_events().contains(&DockEvent::DropApplied {
+            source: PaneHost::RootDock,
+            target: PaneHost::RootDock,
+        }));
+        assert!(docking.can_undo());
+
+        assert!(docking.undo());
+        assert_eq!(root_kind(&docking), Some(ContainerKind::Tabs));
+        assert_eq!(root_panes(&docking), vec![1, 2]);
+    }
+}
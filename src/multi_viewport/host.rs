@@ -3,6 +3,7 @@ use egui::ViewportId;
 use egui_tiles::{Behavior, TileId, Tree};
 
 use super::DockingMultiViewport;
+use super::events::DockEvent;
 use super::title::title_for_detached_tree;
 use super::types::FloatingId;
 
@@ -134,6 +135,27 @@ impl<Pane> DockingMultiViewport<Pane> {
         }
     }
 
+    pub(super) fn tree_mut_for_host(&mut self, host: WindowHost) -> Option<&mut Tree<Pane>> {
+        match host {
+            WindowHost::DockTree { viewport } => {
+                if viewport == ViewportId::ROOT {
+                    Some(&mut self.tree)
+                } else {
+                    self.detached.get_mut(&viewport).map(|d| &mut d.tree)
+                }
+            }
+            WindowHost::Floating { viewport, floating } => self
+                .floating
+                .get_mut(&viewport)?
+                .windows
+                .get_mut(&floating)
+                .map(|w| &mut w.tree),
+            WindowHost::NativeViewport { viewport } => {
+                self.detached.get_mut(&viewport).map(|d| &mut d.tree)
+            }
+        }
+    }
+
     pub(super) fn take_subtree_from_host_for_drop(
         &mut self,
         ctx: &egui::Context,
@@ -164,6 +186,7 @@ impl<Pane> DockingMultiViewport<Pane> {
                                 ));
                             }
                             ctx.send_viewport_cmd_to(viewport, egui::ViewportCommand::Close);
+                            self.push_dock_event(DockEvent::ViewportClosed { viewport });
                         }
                     } else {
                         self.detached.insert(viewport, source);
@@ -211,6 +234,7 @@ impl<Pane> DockingMultiViewport<Pane> {
                     ));
                 }
                 ctx.send_viewport_cmd_to(viewport, egui::ViewportCommand::Close);
+                self.push_dock_event(DockEvent::ViewportClosed { viewport });
                 Some(egui_tiles::SubTree { root, tiles })
             }
 
@@ -311,54 +335,27 @@ impl<Pane> DockingMultiViewport<Pane> {
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[derive(Default)]
-    struct DummyBehavior;
-
-    impl egui_tiles::Behavior<()> for DummyBehavior {
-        fn pane_ui(
-            &mut self,
-            _ui: &mut egui::Ui,
-            _tile_id: egui_tiles::TileId,
-            _pane: &mut (),
-        ) -> egui_tiles::UiResponse {
-            Default::default()
-        }
-
-        fn tab_title_for_pane(&mut self, _pane: &()) -> egui::WidgetText {
-            egui::WidgetText::from("pane")
-        }
-    }
-
-    fn new_tree_tabs(id: egui::Id, panes: usize) -> egui_tiles::Tree<()> {
-        let panes = panes.max(1);
-        let mut tiles = egui_tiles::Tiles::default();
-        let mut ids = Vec::with_capacity(panes);
-        for _ in 0..panes {
-            ids.push(tiles.insert_pane(()));
-        }
-        let root = tiles.insert_tab_tile(ids);
-        egui_tiles::Tree::new(id, root, tiles)
-    }
+    use crate::multi_viewport::test_support::{DummyBehavior, tabs_tree};
 
     #[test]
     fn take_whole_detached_tree_removes_viewport() {
-        let root_tree = new_tree_tabs(egui::Id::new("root"), 2);
+        let root_tree = tabs_tree("root", &[1, 2]);
         let mut docking = DockingMultiViewport::new(root_tree);
 
         let viewport = ViewportId::from_hash_of("detached");
-        let detached_tree = new_tree_tabs(egui::Id::new("detached_tree"), 3);
+        let detached_tree = tabs_tree("detached_tree", &[3, 4, 5]);
         docking.detached.insert(
             viewport,
             super::super::types::DetachedDock {
                 serial: 1,
                 tree: detached_tree,
                 builder: egui::ViewportBuilder::default(),
+                options: Default::default(),
             },
         );
 
         let ctx = egui::Context::default();
-        let mut behavior = DummyBehavior::default();
+        let mut behavior = DummyBehavior;
         let subtree = docking.take_whole_tree_from_host_for_drop(
             &ctx,
             &mut behavior,
@@ -370,11 +367,11 @@ mod tests {
 
     #[test]
     fn take_subtree_from_detached_keeps_viewport_if_non_empty() {
-        let root_tree = new_tree_tabs(egui::Id::new("root"), 2);
+        let root_tree = tabs_tree("root", &[1, 2]);
         let mut docking = DockingMultiViewport::new(root_tree);
 
         let viewport = ViewportId::from_hash_of("detached");
-        let detached_tree = new_tree_tabs(egui::Id::new("detached_tree"), 3);
+        let detached_tree = tabs_tree("detached_tree", &[3, 4, 5]);
         let pane_id = detached_tree
             .tiles
             .tile_ids()
@@ -387,11 +384,12 @@ mod tests {
                 serial: 1,
                 tree: detached_tree,
                 builder: egui::ViewportBuilder::default(),
+                options: Default::default(),
             },
         );
 
         let ctx = egui::Context::default();
-        let mut behavior = DummyBehavior::default();
+        let mut behavior = DummyBehavior;
         let subtree = docking.take_subtree_from_host_for_drop(
             &ctx,
             &mut behavior,
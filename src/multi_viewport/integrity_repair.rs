@@ -0,0 +1,407 @@
+use std::collections::{HashMap, HashSet};
+
+use egui::ViewportId;
+use egui_tiles::{Container, Tile, TileId, Tiles, Tree};
+
+use super::DockingMultiViewport;
+use super::events::DockEvent;
+use super::integrity::tree_integrity_issues;
+use super::pane_query::PaneHost;
+
+/// What [`DockingMultiViewport::repair_integrity`] fixed.
+///
+/// A clean report means nothing was touched.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntegrityRepairReport {
+    /// The integrity issues found before repairing, prefixed with the host of the tree.
+    pub issues: Vec<String>,
+
+    /// Trees whose root tile was missing.
+    pub repaired_roots: usize,
+
+    /// Child references to missing tiles, removed from their parent.
+    pub dangling_children: usize,
+
+    /// Repeated child references (twice in one parent, or in a second parent), removed.
+    pub duplicate_children: usize,
+
+    /// Tabs containers whose active tab was not one of their children.
+    pub repaired_tabs_active: usize,
+
+    /// Empty containers removed (the root of the root dock and the central node are kept).
+    pub pruned_containers: usize,
+
+    /// Containers no longer reachable from the root of their tree, removed.
+    pub removed_unreachable: usize,
+
+    /// Panes no longer reachable from the root of their tree, moved into the root dock.
+    pub rehomed_panes: usize,
+
+    /// Detached viewports and floating windows left without any tile, closed.
+    pub closed_hosts: usize,
+}
+
+impl IntegrityRepairReport {
+    /// `true` if no tree needed repairs.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+            && self.repaired_roots == 0
+            && self.dangling_children == 0
+            && self.duplicate_children == 0
+            && self.repaired_tabs_active == 0
+            && self.pruned_containers == 0
+            && self.removed_unreachable == 0
+            && self.rehomed_panes == 0
+            && self.closed_hosts == 0
+    }
+}
+
+/// Repair `tree` in place, keeping the `keep` containers even when empty. An empty root that is
+/// not kept is removed, leaving `tree.root == None`.
+///
+/// Returns the unreachable panes, which the caller must re-home.
+fn repair_tree<Pane>(
+    tree: &mut Tree<Pane>,
+    host: PaneHost,
+    keep: &[TileId],
+    report: &mut IntegrityRepairReport,
+) -> Vec<Pane> {
+    report.issues.extend(
+        tree_integrity_issues(tree)
+            .into_iter()
+            .map(|issue| format!("{host:?} {issue}")),
+    );
+
+    if let Some(root) = tree.root
+        && tree.tiles.get(root).is_none()
+    {
+        tree.root = None;
+        report.repaired_roots += 1;
+    }
+
+    // Walk from the root; every tile is claimed by the first parent that reaches it.
+    let mut reachable: HashSet<TileId> = HashSet::new();
+    let mut parent_of: HashMap<TileId, TileId> = HashMap::new();
+    let mut stack: Vec<TileId> = tree.root.into_iter().collect();
+    reachable.extend(tree.root);
+    while let Some(tile_id) = stack.pop() {
+        let Some(Tile::Container(container)) = tree.tiles.get(tile_id) else {
+            continue;
+        };
+
+        let mut valid: HashSet<TileId> = HashSet::new();
+        for &child in container.children() {
+            if tree.tiles.get(child).is_none() {
+                report.dangling_children += 1;
+            } else if reachable.insert(child) {
+                valid.insert(child);
+                parent_of.insert(child, tile_id);
+                stack.push(child);
+            } else {
+                report.duplicate_children += 1;
+            }
+        }
+
+        let Some(Tile::Container(container)) = tree.tiles.get_mut(tile_id) else {
+            continue;
+        };
+        let mut kept: HashSet<TileId> = HashSet::new();
+        container.retain(|child| valid.contains(&child) && kept.insert(child));
+        if repair_tabs_active(container) {
+            report.repaired_tabs_active += 1;
+        }
+    }
+
+    // Prune empty containers bottom-up: a parent may become empty once its child is gone.
+    let is_prunable = |tiles: &Tiles<Pane>, tile_id: TileId| {
+        !keep.contains(&tile_id)
+            && matches!(
+                tiles.get(tile_id),
+                Some(Tile::Container(container)) if container.children().next().is_none()
+            )
+    };
+    let mut empty: Vec<TileId> = reachable
+        .iter()
+        .copied()
+        .filter(|&tile_id| is_prunable(&tree.tiles, tile_id))
+        .collect();
+    while let Some(tile_id) = empty.pop() {
+        tree.tiles.remove(tile_id);
+        reachable.remove(&tile_id);
+        report.pruned_containers += 1;
+        if tree.root == Some(tile_id) {
+            tree.root = None;
+        }
+
+        let Some(&parent) = parent_of.get(&tile_id) else {
+            continue;
+        };
+        if let Some(Tile::Container(container)) = tree.tiles.get_mut(parent) {
+            container.retain(|child| child != tile_id);
+            repair_tabs_active(container);
+        }
+        if is_prunable(&tree.tiles, parent) {
+            empty.push(parent);
+        }
+    }
+
+    let unreachable: Vec<TileId> = tree
+        .tiles
+        .tile_ids()
+        .filter(|tile_id| !reachable.contains(tile_id) && !keep.contains(tile_id))
+        .collect();
+    let mut orphans = Vec::new();
+    for tile_id in unreachable {
+        match tree.tiles.remove(tile_id) {
+            Some(Tile::Pane(pane)) => orphans.push(pane),
+            Some(Tile::Container(_)) => report.removed_unreachable += 1,
+            None => {}
+        }
+    }
+    orphans
+}
+
+/// Point `Tabs::active` at a child again; `true` if it had to change.
+fn repair_tabs_active(container: &mut Container) -> bool {
+    let Container::Tabs(tabs) = container else {
+        return false;
+    };
+    match tabs.active {
+        Some(active) if !tabs.children.contains(&active) => {
+            tabs.active = tabs.children.first().copied();
+            true
+        }
+        _ => false,
+    }
+}
+
+impl<Pane> DockingMultiViewport<Pane> {
+    /// Repair every dock tree (root, detached viewports and floating windows).
+    ///
+    /// Dangling and duplicate child references are dropped, `Tabs::active` is pointed at an
+    /// existing child, empty containers are pruned and unreachable tiles are removed. Panes that
+    /// were unreachable are docked into the root tree, so no pane is lost. Detached viewports and
+    /// floating windows left empty are closed.
+    ///
+    /// The issues found by the `debug_integrity` checks are listed in the report, but trees are
+    /// repaired from their own walk, so defects those checks don't flag (e.g. empty containers)
+    /// are fixed too.
+    ///
+    /// Runs automatically at the end of every frame that changed the layout when
+    /// `options.integrity_auto_repair` is set.
+    pub fn repair_integrity(&mut self) -> IntegrityRepairReport {
+        let mut report = IntegrityRepairReport::default();
+        let keep: Vec<TileId> = self
+            .tree
+            .root
+            .into_iter()
+            .chain(self.central_node())
+            .collect();
+
+        let mut orphans = repair_tree(&mut self.tree, PaneHost::RootDock, &keep, &mut report);
+        for (&viewport, detached) in &mut self.detached {
+            let host = PaneHost::DetachedViewport { viewport };
+            orphans.extend(repair_tree(&mut detached.tree, host, &[], &mut report));
+        }
+        for (&viewport, manager) in &mut self.floating {
+            for (&floating, window) in &mut manager.windows {
+                let host = PaneHost::Floating { viewport, floating };
+                orphans.extend(repair_tree(&mut window.tree, host, &[], &mut report));
+            }
+        }
+
+        let empty_detached: Vec<ViewportId> = self
+            .detached
+            .iter()
+            .filter(|(_, detached)| detached.tree.root.is_none())
+            .map(|(&viewport, _)| viewport)
+            .collect();
+        for viewport in empty_detached {
+            self.detached.remove(&viewport);
+            report.closed_hosts += 1;
+            self.push_dock_event(DockEvent::ViewportClosed { viewport });
+        }
+        let mut empty_floating: Vec<(ViewportId, u64)> = Vec::new();
+        for (&viewport, manager) in &mut self.floating {
+            manager.windows.retain(|&floating, window| {
+                let keep = window.tree.root.is_some();
+                if !keep {
+                    empty_floating.push((viewport, floating));
+                }
+                keep
+            });
+            manager
+                .z_order
+                .retain(|floating| manager.windows.contains_key(floating));
+        }
+        self.floating
+            .retain(|_, manager| !manager.windows.is_empty());
+        for (viewport, floating) in empty_floating {
+            report.closed_hosts += 1;
+            self.push_dock_event(DockEvent::FloatingClosed { viewport, floating });
+        }
+
+        report.rehomed_panes = orphans.len();
+        for pane in orphans {
+            let mut tiles = Tiles::default();
+            let root = tiles.insert_pane(pane);
+            self.dock_subtree_into_root(egui_tiles::SubTree { root, tiles }, None);
+        }
+
+        if !report.is_clean() {
+            self.mark_layout_changed();
+            self.debug_integrity_log_event(format!("integrity REPAIR {report:?}"));
+        }
+        report
+    }
+
+    /// Repair after a layout change (drops applied by us or by egui_tiles, ghost drops,
+    /// window-move docking, ...) when `options.integrity_auto_repair` is set.
+    pub(super) fn auto_repair_integrity(&mut self) {
+        if !self.options.integrity_auto_repair || !self.layout_changed {
+            return;
+        }
+        self.repair_integrity();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Vec2, ViewportBuilder};
+    use egui_tiles::Tabs;
+
+    use super::*;
+    use crate::multi_viewport::test_support::{
+        DummyBehavior, docking_with_tabs, pane_host, tabs_tree,
+    };
+
+    fn root_tabs_mut(tree: &mut Tree<usize>) -> &mut Tabs {
+        let root = tree.root.unwrap();
+        let Some(Tile::Container(Container::Tabs(tabs))) = tree.tiles.get_mut(root) else {
+            panic!("root is a tabs container");
+        };
+        tabs
+    }
+
+    #[test]
+    fn clean_tree_is_untouched() {
+        let mut docking = docking_with_tabs(&[1, 2]);
+        let report = docking.repair_integrity();
+        assert!(report.is_clean(), "{report:?}");
+        assert_eq!(docking.tree.tiles.tile_ids().count(), 3);
+    }
+
+    #[test]
+    fn repairs_dangling_children_and_active_tab() {
+        let mut docking = docking_with_tabs(&[1, 2]);
+        let missing = TileId::from_u64(9_999);
+        let tabs = root_tabs_mut(&mut docking.tree);
+        let panes = tabs.children.clone();
+        tabs.children.push(missing);
+        tabs.children.push(panes[0]);
+        tabs.active = Some(missing);
+
+        let report = docking.repair_integrity();
+        assert_eq!(report.dangling_children, 1);
+        assert_eq!(report.duplicate_children, 1);
+        assert_eq!(report.repaired_tabs_active, 1);
+        assert!(tree_integrity_issues(&docking.tree).is_empty());
+
+        let tabs = root_tabs_mut(&mut docking.tree);
+        assert_eq!(tabs.children, panes);
+        assert_eq!(tabs.active, Some(panes[0]));
+    }
+
+    #[test]
+    fn rehomes_orphaned_panes_and_prunes_empty_containers() {
+        let mut docking = docking_with_tabs(&[1]);
+        let tree = &mut docking.tree;
+        let empty = tree.tiles.insert_tab_tile(Vec::new());
+        let orphan = tree.tiles.insert_pane(2);
+        tree.tiles.insert_tab_tile(vec![orphan]);
+        root_tabs_mut(tree).children.push(empty);
+
+        let report = docking.repair_integrity();
+        assert_eq!(report.pruned_containers, 1);
+        assert_eq!(report.removed_unreachable, 1);
+        assert_eq!(report.rehomed_panes, 1);
+        assert!(tree_integrity_issues(&docking.tree).is_empty());
+        assert_eq!(pane_host(&docking, 2), Some(PaneHost::RootDock));
+    }
+
+    #[test]
+    fn prunes_empty_containers_the_checks_do_not_flag() {
+        let mut docking = docking_with_tabs(&[1]);
+        let empty = docking.tree.tiles.insert_tab_tile(Vec::new());
+        root_tabs_mut(&mut docking.tree).children.push(empty);
+        assert!(tree_integrity_issues(&docking.tree).is_empty());
+
+        let report = docking.repair_integrity();
+        assert!(report.issues.is_empty(), "{report:?}");
+        assert_eq!(report.pruned_containers, 1);
+        assert!(docking.tree.tiles.get(empty).is_none());
+    }
+
+    #[test]
+    fn closes_detached_and_floating_hosts_left_empty() {
+        let mut docking = docking_with_tabs(&[1, 2]);
+        let detached = docking
+            .add_detached_viewport(tabs_tree("detached", &[]), ViewportBuilder::default())
+            .expect("tree has a root");
+        docking
+            .float_pane(
+                &egui::Context::default(),
+                &mut DummyBehavior,
+                |&p| p == 2,
+                ViewportId::ROOT,
+                Vec2::ZERO,
+                Vec2::new(320.0, 240.0),
+            )
+            .unwrap();
+        let Some(PaneHost::Floating { viewport, floating }) = pane_host(&docking, 2) else {
+            panic!("pane 2 is floating");
+        };
+        docking.take_events();
+
+        // Point the floating window at an empty root: its pane becomes unreachable.
+        let window = docking
+            .floating
+            .get_mut(&viewport)
+            .and_then(|manager| manager.windows.get_mut(&floating))
+            .unwrap();
+        window.tree.root = Some(window.tree.tiles.insert_tab_tile(Vec::new()));
+
+        let report = docking.repair_integrity();
+        assert_eq!(report.closed_hosts, 2);
+        assert_eq!(report.rehomed_panes, 1);
+        assert_eq!(pane_host(&docking, 2), Some(PaneHost::RootDock));
+        assert!(!docking.detached.contains_key(&detached));
+        assert_eq!(docking.floating_window_count(), 0);
+
+        let events = docking.take_events();
+        assert!(events.contains(&DockEvent::ViewportClosed { viewport: detached }));
+        assert!(events.contains(&DockEvent::FloatingClosed { viewport, floating }));
+    }
+
+    #[test]
+    fn auto_repair_runs_once_a_drop_changed_the_layout() {
+        let mut docking = docking_with_tabs(&[1, 2]);
+        docking.options.integrity_auto_repair = true;
+        let empty = docking.tree.tiles.insert_tab_tile(Vec::new());
+        root_tabs_mut(&mut docking.tree).children.push(empty);
+
+        docking.auto_repair_integrity();
+        assert!(
+            docking.tree.tiles.get(empty).is_some(),
+            "the layout did not change"
+        );
+
+        // What `ui` does for a drop applied by egui_tiles inside the root tree.
+        docking.push_dock_event(DockEvent::DropApplied {
+            source: PaneHost::RootDock,
+            target: PaneHost::RootDock,
+        });
+        docking.auto_repair_integrity();
+        assert!(docking.tree.tiles.get(empty).is_none());
+    }
+}
@@ -0,0 +1,77 @@
+use egui::{Pos2, ViewportId};
+
+/// What a layout load had to drop, repair or clamp to produce a valid layout.
+///
+/// Returned by every `load_layout_*` entry point. A clean report means the snapshot was applied
+/// exactly as saved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoadReport<PaneId> {
+    /// Pane ids the app could not provide (the pane was dropped from the layout).
+    pub missing_panes: Vec<PaneId>,
+
+    /// Pane ids [`crate::PaneRegistry::migrate_pane_id`] retired (e.g. a tool merged into
+    /// another), as saved. Only registry loaders fill this in.
+    pub migrated_away_panes: Vec<PaneId>,
+
+    /// Pane ids that appear more than once in the snapshot, once per extra occurrence.
+    pub duplicate_panes: Vec<PaneId>,
+
+    /// Serials of detached viewports that were not restored because none of their panes were.
+    pub pruned_detached: Vec<u64>,
+
+    /// Ids of floating windows that were not restored because none of their panes were.
+    pub pruned_floating: Vec<u64>,
+
+    /// Containers dropped because all of their children were.
+    pub pruned_containers: usize,
+
+    /// Tabs containers whose saved active tab could not be restored.
+    pub repaired_tabs_active: usize,
+
+    /// Linear/grid shares that were not positive and finite, reset to the default share.
+    pub repaired_shares: usize,
+
+    /// Floating z-order entries dropped (unknown window) or appended (window missing from it).
+    pub repaired_z_order: usize,
+
+    /// Viewports moved because their saved monitor changed: `(viewport, saved, remapped)`.
+    pub remapped_viewports: Vec<(ViewportId, Pos2, Pos2)>,
+
+    /// Viewports whose saved position was moved onto a monitor: `(viewport, saved, clamped)`.
+    pub clamped_viewports: Vec<(ViewportId, Pos2, Pos2)>,
+}
+
+impl<PaneId> Default for LoadReport<PaneId> {
+    fn default() -> Self {
+        Self {
+            missing_panes: Vec::new(),
+            migrated_away_panes: Vec::new(),
+            duplicate_panes: Vec::new(),
+            pruned_detached: Vec::new(),
+            pruned_floating: Vec::new(),
+            pruned_containers: 0,
+            repaired_tabs_active: 0,
+            repaired_shares: 0,
+            repaired_z_order: 0,
+            remapped_viewports: Vec::new(),
+            clamped_viewports: Vec::new(),
+        }
+    }
+}
+
+impl<PaneId> LoadReport<PaneId> {
+    /// `true` if nothing was dropped, repaired, remapped or clamped.
+    pub fn is_clean(&self) -> bool {
+        self.missing_panes.is_empty()
+            && self.migrated_away_panes.is_empty()
+            && self.duplicate_panes.is_empty()
+            && self.pruned_detached.is_empty()
+            && self.pruned_floating.is_empty()
+            && self.pruned_containers == 0
+            && self.repaired_tabs_active == 0
+            && self.repaired_shares == 0
+            && self.repaired_z_order == 0
+            && self.remapped_viewports.is_empty()
+            && self.clamped_viewports.is_empty()
+    }
+}
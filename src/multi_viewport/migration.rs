@@ -0,0 +1,170 @@
+use super::persistence::LayoutPersistenceError;
+use super::snapshot::{LAYOUT_SNAPSHOT_VERSION, LayoutSnapshot, NodeSnapshot, TreeSnapshot};
+
+/// Just the `version` of a stored snapshot, read before the rest so that a snapshot from a newer
+/// version of this crate is reported as such even when its contents no longer parse.
+#[derive(serde::Deserialize)]
+#[serde(rename = "LayoutSnapshot")]
+pub(super) struct LayoutVersionHeader {
+    pub(super) version: u32,
+}
+
+/// Fail with [`LayoutPersistenceError::UnsupportedVersion`] if `version` is newer than
+/// [`LAYOUT_SNAPSHOT_VERSION`].
+pub(super) fn check_layout_snapshot_version(version: u32) -> Result<(), LayoutPersistenceError> {
+    if version > LAYOUT_SNAPSHOT_VERSION {
+        return Err(LayoutPersistenceError::UnsupportedVersion {
+            found: version,
+            expected: LAYOUT_SNAPSHOT_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// Parse a RON snapshot of any supported version: the version is checked first, then the whole
+/// snapshot is read. Loaders run [`migrate_layout_snapshot`] on the result.
+pub(super) fn layout_snapshot_from_ron_str<PaneId>(
+    ron_str: &str,
+) -> Result<LayoutSnapshot<PaneId>, LayoutPersistenceError>
+where
+    PaneId: for<'de> serde::Deserialize<'de>,
+{
+    let header: LayoutVersionHeader = ron::from_str(ron_str)?;
+    check_layout_snapshot_version(header.version)?;
+    Ok(ron::from_str(ron_str)?)
+}
+
+/// Upgrade steps in version order; each turns a snapshot of the previous version into `version`.
+///
+/// Fields added by a version are `serde(default)`, so older snapshots parse into the current
+/// type and the steps run on that. (They can't run on a `ron::Value`: it drops enum variant
+/// names, so nodes could not be read back from it.) A step states what its new fields mean for
+/// data written before they existed, and rewrites old data whose meaning changed.
+fn migration_steps<PaneId>() -> [(u32, fn(&mut LayoutSnapshot<PaneId>)); 3] {
+    [(3, upgrade_to_v3), (4, upgrade_to_v4), (5, upgrade_to_v5)]
+}
+
+/// v3 added node flags and per-window options: older nodes have no flags, older detached windows
+/// follow the global options.
+fn upgrade_to_v3<PaneId>(snapshot: &mut LayoutSnapshot<PaneId>) {
+    for_each_tree(snapshot, |tree| tree.node_flags.clear());
+    for detached in &mut snapshot.detached {
+        detached.options = Default::default();
+    }
+}
+
+/// v4 added pane state blobs: older panes have none.
+fn upgrade_to_v4<PaneId>(snapshot: &mut LayoutSnapshot<PaneId>) {
+    for_each_tree(snapshot, |tree| {
+        for node in &mut tree.nodes {
+            if let NodeSnapshot::Pane { state, .. } = node {
+                *state = None;
+            }
+        }
+    });
+}
+
+/// v5 added the saved monitor: older windows are placed by their position alone.
+fn upgrade_to_v5<PaneId>(snapshot: &mut LayoutSnapshot<PaneId>) {
+    for detached in &mut snapshot.detached {
+        detached.viewport.monitor = None;
+    }
+}
+
+fn for_each_tree<PaneId>(
+    snapshot: &mut LayoutSnapshot<PaneId>,
+    mut f: impl FnMut(&mut TreeSnapshot<PaneId>),
+) {
+    f(&mut snapshot.root);
+    for detached in &mut snapshot.detached {
+        f(&mut detached.tree);
+    }
+    for window in snapshot.floating.iter_mut().flat_map(|m| m.windows.iter_mut()) {
+        f(&mut window.tree);
+    }
+}
+
+/// Upgrade `snapshot` to [`LAYOUT_SNAPSHOT_VERSION`], running every step newer than its version
+/// in order.
+///
+/// Fails with [`LayoutPersistenceError::UnsupportedVersion`] only for snapshots written by a newer
+/// version of this crate.
+pub fn migrate_layout_snapshot<PaneId>(
+    mut snapshot: LayoutSnapshot<PaneId>,
+) -> Result<LayoutSnapshot<PaneId>, LayoutPersistenceError> {
+    check_layout_snapshot_version(snapshot.version)?;
+    for (version, step) in migration_steps() {
+        if snapshot.version < version {
+            step(&mut snapshot);
+            snapshot.version = version;
+        }
+    }
+    debug_assert_eq!(snapshot.version, LAYOUT_SNAPSHOT_VERSION);
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::ViewportId;
+
+    use super::*;
+    use crate::multi_viewport::node_flags::DockNodeFlags;
+    use crate::multi_viewport::pane_query::PaneHost;
+    use crate::multi_viewport::snapshot::detached_viewport_id_from_serial;
+    use crate::multi_viewport::test_support::{docking_with_tabs, pane_host, root_panes};
+
+    /// A layout as the version 2 code wrote it: root `[[1, 2] | 3]`, pane 4 in a detached window and
+    /// pane 5 in a floating window.
+    const LAYOUT_V2: &str = include_str!("fixtures/layout_v2.ron");
+
+    #[test]
+    fn version_2_layouts_load() {
+        let snapshot = layout_snapshot_from_ron_str::<usize>(LAYOUT_V2).unwrap();
+        assert_eq!(snapshot.version, 2);
+        let snapshot = migrate_layout_snapshot(snapshot).unwrap();
+        assert_eq!(snapshot.version, LAYOUT_SNAPSHOT_VERSION);
+
+        let mut docking = docking_with_tabs(&[]);
+        let report = docking
+            .load_layout_from_ron_str::<usize>(LAYOUT_V2, |id| id)
+            .unwrap();
+        assert!(report.is_clean());
+        assert_eq!(root_panes(&docking), vec![1, 2, 3]);
+        assert_eq!(
+            pane_host(&docking, 4),
+            Some(PaneHost::DetachedViewport {
+                viewport: detached_viewport_id_from_serial(1),
+            })
+        );
+        assert_eq!(
+            pane_host(&docking, 5),
+            Some(PaneHost::Floating {
+                viewport: ViewportId::ROOT,
+                floating: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn steps_run_from_the_stored_version() {
+        let mut snapshot = layout_snapshot_from_ron_str::<usize>(LAYOUT_V2).unwrap();
+        snapshot.version = 4;
+        snapshot.root.node_flags.insert(0, DockNodeFlags::NO_SPLIT);
+        let snapshot = migrate_layout_snapshot(snapshot).unwrap();
+        // The v3 step didn't run, so the flags written by a v4 snapshot are kept.
+        assert_eq!(snapshot.root.node_flags.len(), 1);
+    }
+
+    #[test]
+    fn newer_snapshots_are_rejected_before_parsing() {
+        let newer = format!(
+            "(version: {}, root: SomethingNew(depth: 3), extra: [1, 2])",
+            LAYOUT_SNAPSHOT_VERSION + 1
+        );
+        assert!(matches!(
+            layout_snapshot_from_ron_str::<usize>(&newer),
+            Err(LayoutPersistenceError::UnsupportedVersion { found, expected })
+                if found == LAYOUT_SNAPSHOT_VERSION + 1 && expected == LAYOUT_SNAPSHOT_VERSION
+        ));
+    }
+}
@@ -6,32 +6,62 @@ use egui::{Context, LayerId, Order, Rect, Vec2, ViewportBuilder, ViewportId};
 use egui_tiles::{Behavior, ContainerKind, InsertionPoint, Tile, TileId, Tree};
 
 mod debug;
+mod debug_log;
+#[cfg(feature = "persistence")]
+mod autosave;
 mod backend_hints;
 mod behavior_wrap;
+mod central_node;
 mod drag_state;
 mod detached;
+mod docking_policy;
 mod drop_apply;
 mod drop_policy;
 mod drop_queue;
 mod drop_sanitize;
+mod events;
 mod floating;
 mod geometry;
 mod ghost;
+mod history;
 mod host;
 mod integrity;
+mod integrity_repair;
+mod load_report;
+#[cfg(feature = "persistence")]
+mod migration;
 mod monitor_clamp;
+mod node_flags;
 mod options;
 mod overlay;
 mod overlay_decision;
+mod pane_ops;
+mod pane_query;
 #[cfg(feature = "persistence")]
 mod persistence;
+#[cfg(feature = "persistence-json")]
+mod persistence_json;
+#[cfg(feature = "persistence-msgpack")]
+mod persistence_msgpack;
+mod presets;
 #[cfg(feature = "persistence")]
 mod pane_registry;
+#[cfg(feature = "persistence")]
+mod recording;
 mod release;
 mod session;
+mod snapshot;
+#[cfg(any(test, feature = "testing"))]
+mod simulation;
+#[cfg(feature = "persistence")]
+mod storage;
 mod surface;
 mod title;
+#[cfg(feature = "tracing")]
+mod trace;
 mod types;
+#[cfg(feature = "persistence")]
+mod validation;
 
 #[cfg(test)]
 mod model_tests;
@@ -41,8 +71,21 @@ mod world_model_tests;
 mod overlay_decision_tests;
 #[cfg(test)]
 mod ghost_tests;
+#[cfg(test)]
+mod simulation_tests;
+#[cfg(test)]
+mod test_support;
 
-pub use options::DockingMultiViewportOptions;
+pub use debug_log::{DebugLogEntry, DebugLogRecord, WindowMoveCommand};
+pub use docking_policy::{
+    DockingPolicy, DropContext, DropVerdict, NewWindowKind, TearOffContext, TearOffVerdict,
+};
+pub use events::DockEvent;
+pub use integrity_repair::IntegrityRepairReport;
+pub use node_flags::DockNodeFlags;
+pub use options::{DebugLogFileFormat, DetachedViewportOptions, DockingMultiViewportOptions};
+pub use pane_query::{PaneHost, PaneLocation};
+pub use types::{FLOATING_MIN_SIZE, FloatingId};
 pub use backend_hints::{
     backend_monitors_outer_rects_points, backend_mouse_hovered_viewport_id,
     backend_pointer_global_points, clear_backend_monitors_outer_rects_points,
@@ -50,14 +93,37 @@ pub use backend_hints::{
     BACKEND_MOUSE_HOVERED_VIEWPORT_ID_KEY, BACKEND_POINTER_GLOBAL_POINTS_KEY,
 };
 #[cfg(feature = "persistence")]
-pub use persistence::{LayoutPersistenceError, LayoutSnapshot, LAYOUT_SNAPSHOT_VERSION};
+pub use autosave::LayoutAutosave;
+#[cfg(feature = "persistence")]
+pub use migration::migrate_layout_snapshot;
+#[cfg(feature = "persistence")]
+pub use persistence::LayoutPersistenceError;
+pub use load_report::LoadReport;
+pub use presets::WorkspacePresets;
+pub use snapshot::{LayoutSnapshot, LAYOUT_SNAPSHOT_VERSION};
+#[cfg(any(test, feature = "testing"))]
+pub use simulation::{DockingSimulation, SimulatedDropTarget};
+#[cfg(feature = "persistence")]
+pub use pane_registry::{PaneRegistry, PaneStateRegistry, SimplePaneRegistry};
+#[cfg(feature = "persistence")]
+pub use recording::{
+    DragSessionRecorder, DragSessionRecording, DragSessionRecordingError, RecordedFrame,
+    DRAG_SESSION_RECORDING_VERSION,
+};
+#[cfg(feature = "persistence")]
+pub use storage::{LayoutStorage, DEFAULT_LAYOUT_STORAGE_KEY};
 #[cfg(feature = "persistence")]
-pub use pane_registry::{PaneRegistry, SimplePaneRegistry};
+pub use validation::{
+    validate_layout_snapshot, LAYOUT_SNAPSHOT_MAX_DEPTH, LAYOUT_SNAPSHOT_MAX_NODES,
+};
 
 use debug::{debug_clear_event_log_id, last_drop_debug_text_id, tiles_debug_visit_enabled_id};
 use behavior_wrap::PaneBackgroundBehavior;
+use central_node::CentralNodeEmptyUiSlot;
+use docking_policy::DockingPolicySlot;
 use drag_state::DragState;
 use geometry::pointer_pos_in_viewport_space;
+use host::WindowHost;
 use overlay::{paint_outer_overlay, paint_overlay, pointer_in_outer_band};
 use overlay_decision::{decide_overlay_for_tree, DragKind, OverlayPaint};
 use types::*;
@@ -109,7 +175,7 @@ pub struct DockingMultiViewport<Pane> {
 
     ghost: Option<GhostDrag>,
 
-    debug_log: VecDeque<String>,
+    debug_log: VecDeque<DebugLogEntry>,
     debug_frame: u64,
     debug_last_disable_drop_apply: BTreeMap<(u64, ViewportId), bool>,
     debug_last_integrity_hash: BTreeMap<(u64, ViewportId), u64>,
@@ -121,8 +187,33 @@ pub struct DockingMultiViewport<Pane> {
 
     detached_rendered_frame: BTreeMap<ViewportId, u64>,
 
+    events: VecDeque<DockEvent>,
+    last_active_tabs: BTreeMap<(PaneHost, TileId), TileId>,
+
+    docking_policy: DockingPolicySlot<Pane>,
+    node_flags: BTreeMap<(PaneHost, TileId), DockNodeFlags>,
+    central_node_empty_ui: CentralNodeEmptyUiSlot,
+
+    last_viewport_runtime: BTreeMap<ViewportId, snapshot::ViewportRuntime>,
+    /// Saved scale factor of detached viewports restored before any frame reported the current one.
+    pending_dpi_rescale: BTreeMap<ViewportId, f32>,
+    /// Detached viewports loaded before the monitors were known, with the monitor they were saved
+    /// on; placed by `apply_pending_viewport_placement`.
     #[cfg(feature = "persistence")]
-    last_viewport_runtime: BTreeMap<ViewportId, persistence::ViewportRuntime>,
+    pending_viewport_placement: BTreeMap<ViewportId, Option<monitor_clamp::SavedMonitor>>,
+
+    history: Option<Box<dyn history::ErasedLayoutHistory<Pane>>>,
+    layout_changed: bool,
+    /// See [`Self::layout_generation`].
+    layout_generation: u64,
+    /// Hash of every floating window's geometry at the end of the last frame.
+    floating_geometry_hash: u64,
+
+    /// Panes not placed by the active workspace preset, kept for the next preset switch.
+    parked_panes: Vec<Pane>,
+
+    #[cfg(feature = "tracing")]
+    trace: trace::TraceState,
 }
 
 impl<Pane> DockingMultiViewport<Pane> {
@@ -157,8 +248,22 @@ impl<Pane> DockingMultiViewport<Pane> {
             debug_log_file_inited_for_path: false,
             debug_log_file_last_error: None,
             detached_rendered_frame: BTreeMap::new(),
-            #[cfg(feature = "persistence")]
+            events: VecDeque::new(),
+            last_active_tabs: BTreeMap::new(),
+            docking_policy: DockingPolicySlot::default(),
+            node_flags: BTreeMap::new(),
+            central_node_empty_ui: CentralNodeEmptyUiSlot::default(),
             last_viewport_runtime: BTreeMap::new(),
+            pending_dpi_rescale: BTreeMap::new(),
+            #[cfg(feature = "persistence")]
+            pending_viewport_placement: BTreeMap::new(),
+            history: None,
+            layout_changed: false,
+            layout_generation: 0,
+            floating_geometry_hash: 0,
+            parked_panes: Vec::new(),
+            #[cfg(feature = "tracing")]
+            trace: trace::TraceState::default(),
         }
     }
 
@@ -187,11 +292,10 @@ impl<Pane> DockingMultiViewport<Pane> {
         self.last_floating_content_rects.clear();
         self.viewport_outer_from_inner_offset.clear();
         self.detached_rendered_frame.clear();
-
-        #[cfg(feature = "persistence")]
-        {
-            self.last_viewport_runtime.clear();
-        }
+        self.last_active_tabs.clear();
+        self.node_flags.clear();
+        self.last_viewport_runtime.clear();
+        self.mark_layout_changed();
     }
 
     /// Like [`Self::set_root_tree`], but also clears any active `egui::DragAndDrop` payload in `ctx`.
@@ -208,11 +312,23 @@ impl<Pane> DockingMultiViewport<Pane> {
     /// Notes:
     /// - `tree` will be re-wrapped into an internal tree id to avoid collisions between multiple
     ///   detached viewports.
-    /// - Detached viewports currently share global options (decorations/CSD) from `self.options`.
+    /// - The window uses the global decoration/CSD options; see
+    ///   [`Self::add_detached_viewport_with_options`] for per-window overrides.
     pub fn add_detached_viewport(
+        &mut self,
+        tree: Tree<Pane>,
+        builder: ViewportBuilder,
+    ) -> Option<ViewportId> {
+        self.add_detached_viewport_with_options(tree, builder, DetachedViewportOptions::default())
+    }
+
+    /// Like [`Self::add_detached_viewport`], with per-window overrides of the global
+    /// decoration/CSD options.
+    pub fn add_detached_viewport_with_options(
         &mut self,
         mut tree: Tree<Pane>,
         builder: ViewportBuilder,
+        options: DetachedViewportOptions,
     ) -> Option<ViewportId> {
         let Some(root) = tree.root.take() else {
             return None;
@@ -223,7 +339,7 @@ impl<Pane> DockingMultiViewport<Pane> {
         let detached_tree_id = egui::Id::new((self.tree.id(), "egui_docking_detached_tree", serial));
         let detached_tree = Tree::new(detached_tree_id, root, tiles);
 
-        let builder = builder.with_decorations(self.options.detached_viewport_decorations);
+        let builder = options.resolve(&self.options).apply_to_builder(builder);
 
         self.detached.insert(
             viewport_id,
@@ -231,27 +347,76 @@ impl<Pane> DockingMultiViewport<Pane> {
                 serial,
                 tree: detached_tree,
                 builder,
+                options,
             },
         );
 
         Some(viewport_id)
     }
 
+    /// Per-window option overrides of a detached native viewport (`None` if it doesn't exist).
+    pub fn detached_viewport_options(&self, viewport: ViewportId) -> Option<DetachedViewportOptions> {
+        self.detached.get(&viewport).map(|d| d.options)
+    }
+
+    /// Replace the per-window option overrides of a detached native viewport.
+    ///
+    /// Takes effect on the next frame (the live OS window is patched by egui).
+    /// Returns `false` if `viewport` is not a detached viewport.
+    pub fn set_detached_viewport_options(
+        &mut self,
+        viewport: ViewportId,
+        options: DetachedViewportOptions,
+    ) -> bool {
+        let resolved = options.resolve(&self.options);
+        let Some(detached) = self.detached.get_mut(&viewport) else {
+            return false;
+        };
+        if detached.options.always_on_top && !options.always_on_top {
+            detached.builder.window_level = Some(egui::WindowLevel::Normal);
+        }
+        detached.options = options;
+        detached.builder = resolved.apply_to_builder(detached.builder.clone());
+        true
+    }
+
+    pub(super) fn resolved_detached_options(
+        &self,
+        viewport: ViewportId,
+    ) -> options::ResolvedDetachedOptions {
+        self.detached
+            .get(&viewport)
+            .map(|d| d.options)
+            .unwrap_or_default()
+            .resolve(&self.options)
+    }
+
     /// Apply a scripted workspace preset (root tree + detached native viewport trees).
     ///
     /// This clears any existing detached/floating windows and cancels in-flight drags.
     pub fn set_workspace_layout_in_ctx(&mut self, ctx: &Context, layout: WorkspaceLayout<Pane>) {
-        let WorkspaceLayout { root, detached } = layout;
-        self.set_root_tree_in_ctx(ctx, root);
-
-        for spec in detached {
-            let _ = self.add_detached_viewport(spec.tree, spec.builder);
-        }
+        egui::DragAndDrop::clear_payload(ctx);
+        ctx.stop_dragging();
+        self.apply_workspace_layout(layout);
 
         ctx.request_repaint();
         ctx.request_repaint_of(ViewportId::ROOT);
     }
 
+    pub(super) fn apply_workspace_layout(&mut self, layout: WorkspaceLayout<Pane>) {
+        let WorkspaceLayout {
+            root,
+            root_node_flags,
+            detached,
+        } = layout;
+        self.set_root_tree(root);
+        self.apply_node_flags(PaneHost::RootDock, root_node_flags);
+
+        for spec in detached {
+            let _ = self.add_detached_viewport_with_options(spec.tree, spec.builder, spec.options);
+        }
+    }
+
     pub(super) fn update_viewport_outer_from_inner_offset(&mut self, ctx: &Context) {
         let viewport_id = ctx.viewport_id();
         let offset = ctx.input(|i| {
@@ -272,7 +437,8 @@ impl<Pane> DockingMultiViewport<Pane> {
 
         // For borderless detached windows we prefer assuming no decoration offset (0,0) over using
         // the root window's offset (which often includes titlebar height and would cause drift).
-        if viewport_id != ViewportId::ROOT && !self.options.detached_viewport_decorations {
+        if viewport_id != ViewportId::ROOT && !self.resolved_detached_options(viewport_id).decorations
+        {
             return Vec2::ZERO;
         }
 
@@ -297,14 +463,21 @@ impl<Pane> DockingMultiViewport<Pane> {
     /// Call this from your `eframe::App::update` (or equivalent).
     pub fn ui(&mut self, ctx: &Context, behavior: &mut dyn Behavior<Pane>) {
         let mut behavior = PaneBackgroundBehavior::new(behavior, self.options.fill_pane_background);
+        #[cfg(feature = "tracing")]
+        let _session_span = self.trace_enter_session();
 
         self.debug_frame = self.debug_frame.wrapping_add(1);
         self.drag_state.begin_frame();
         self.update_viewport_outer_from_inner_offset(ctx);
-        #[cfg(feature = "persistence")]
         self.capture_viewport_runtime(ctx);
+        self.apply_pending_dpi_rescale(ctx);
+        #[cfg(feature = "persistence")]
+        self.apply_pending_viewport_placement(ctx);
         self.debug_log_file_prepare_if_needed();
         self.debug_log_backend_hints_if_changed(ctx);
+        // Changes made through the API since the last frame form their own undo step.
+        self.commit_layout_history();
+        let locked_shares = self.capture_locked_shares();
         // Important: detached viewports are rendered before the root dock UI. When the pointer is
         // above the root window (common while re-docking), we still want detached window-move
         // logic to see a fresh global pointer position in the same frame.
@@ -373,7 +546,9 @@ impl<Pane> DockingMultiViewport<Pane> {
                 ui.ctx(),
                 &behavior,
                 dock_rect,
-                ViewportId::ROOT,
+                WindowHost::DockTree {
+                    viewport: ViewportId::ROOT,
+                },
                 &self.tree,
             );
 
@@ -395,7 +570,15 @@ impl<Pane> DockingMultiViewport<Pane> {
             self.maybe_start_ghost_from_root(ui.ctx(), &mut behavior, dock_rect);
 
             self.set_tiles_debug_visit_enabled(ui.ctx(), self.tree.id(), ViewportId::ROOT);
+            behavior.set_node_flags(self.node_flags_in_host(PaneHost::RootDock));
+            let simplification =
+                self.simplify_root_keeping_central_node(behavior.simplification_options());
+            behavior.set_simplification_override(simplification);
             self.tree.ui(&mut behavior, ui);
+            behavior.lock_splitters(ui, &self.tree);
+            behavior.set_simplification_override(None);
+            self.emit_tiles_drop_event(&mut behavior, PaneHost::RootDock);
+            self.ui_central_node_empty_state(ui, behavior.tab_bar_height(ui.style()));
 
             self.set_payload_from_root_drag_if_any(ui.ctx());
             self.paint_drop_preview_if_any_for_tree(
@@ -403,7 +586,9 @@ impl<Pane> DockingMultiViewport<Pane> {
                 &mut behavior,
                 &self.tree,
                 dock_rect,
-                ViewportId::ROOT,
+                WindowHost::DockTree {
+                    viewport: ViewportId::ROOT,
+                },
             );
 
             self.ui_floating_windows_in_viewport(ui, &mut behavior, dock_rect, ViewportId::ROOT);
@@ -423,20 +608,39 @@ impl<Pane> DockingMultiViewport<Pane> {
             self.ui_debug_window(ctx, ViewportId::ROOT, self.tree.id());
         }
 
+        // Undo splitter drags on `NO_RESIZE` nodes that slipped past `lock_splitters` before any
+        // drop reshapes the trees.
+        self.restore_locked_shares(locked_shares);
+
         // Apply after all viewports have had a chance to run `tree.ui` this frame so we can use
         // the computed rectangles for accurate docking.
         self.apply_pending_actions(ctx, &mut behavior);
+        // These drops already pushed their own `DropApplied`.
+        behavior.take_tile_dropped();
         self.cleanup_bridge_payload_if_no_pointer_down(ctx);
         self.clear_bridge_payload_on_release(ctx);
         self.cleanup_detached_window_move_sessions(ctx);
         self.finish_ghost_if_released_or_aborted(ctx);
 
-        if self.options.debug_integrity {
+        if behavior.take_closed_tab() {
+            self.mark_layout_changed();
+        }
+        if behavior.take_tile_resized() {
+            self.bump_layout_generation();
+        }
+        self.note_floating_geometry_changes();
+        self.auto_repair_integrity();
+
+        if self.integrity_checks_enabled() {
             self.debug_check_integrity_all();
         }
 
-        if let Some(msg) = self.drag_state.end_frame(self.debug_frame) {
-            self.debug_log_event(msg);
+        self.emit_tab_activated_events();
+        self.prune_node_flags();
+        self.commit_layout_history();
+
+        if let Some(record) = self.drag_state.end_frame(self.debug_frame) {
+            self.debug_log_record(record);
         }
     }
 
@@ -619,8 +823,8 @@ impl<Pane> DockingMultiViewport<Pane> {
 
     fn observe_drag_sources_in_ctx(&mut self, ctx: &Context) {
         if self.ghost.is_some() {
-            if let Some(msg) = self.drag_state.observe_source(self.debug_frame, "ghost") {
-                self.debug_log_event(msg);
+            if let Some(record) = self.drag_state.observe_source(self.debug_frame, "ghost") {
+                self.debug_log_record(record);
             }
         }
 
@@ -630,36 +834,42 @@ impl<Pane> DockingMultiViewport<Pane> {
         else {
             return;
         };
-        if let Some(msg) = self.drag_state.observe_source(self.debug_frame, "payload") {
-            self.debug_log_event(msg);
+        if let Some(record) = self.drag_state.observe_source(self.debug_frame, "payload") {
+            self.debug_log_record(record);
         }
     }
 
     fn observe_tiles_drag_root(&mut self) {
-        if let Some(msg) = self.drag_state.observe_source(self.debug_frame, "tiles_drag_root") {
-            self.debug_log_event(msg);
+        if let Some(record) = self
+            .drag_state
+            .observe_source(self.debug_frame, "tiles_drag_root")
+        {
+            self.debug_log_record(record);
         }
     }
 
     fn observe_tiles_drag_detached(&mut self) {
-        if let Some(msg) = self.drag_state.observe_source(self.debug_frame, "tiles_drag_detached") {
-            self.debug_log_event(msg);
+        if let Some(record) = self
+            .drag_state
+            .observe_source(self.debug_frame, "tiles_drag_detached")
+        {
+            self.debug_log_record(record);
         }
     }
 
     fn try_take_release_action(&mut self, kind: &'static str) -> bool {
-        let (ok, msg) = self.drag_state.take_release_action(self.debug_frame, kind);
-        if let Some(msg) = msg {
-            self.debug_log_event(msg);
+        let (ok, record) = self.drag_state.take_release_action(self.debug_frame, kind);
+        if let Some(record) = record {
+            self.debug_log_record(record);
         }
         ok
     }
 
     fn try_take_release_action_silent_if_taken(&mut self, kind: &'static str) -> bool {
-        let (ok, msg) = self.drag_state.take_release_action(self.debug_frame, kind);
+        let (ok, record) = self.drag_state.take_release_action(self.debug_frame, kind);
         if ok {
-            if let Some(msg) = msg {
-                self.debug_log_event(msg);
+            if let Some(record) = record {
+                self.debug_log_record(record);
             }
         }
         ok
@@ -697,12 +907,13 @@ impl<Pane> DockingMultiViewport<Pane> {
         ctx: &Context,
         behavior: &dyn Behavior<Pane>,
         dock_rect: Rect,
-        viewport_id: ViewportId,
+        host: WindowHost,
         tree: &Tree<Pane>,
     ) {
-        let disable_preview = if !self.options.show_overlay_for_internal_drags {
-            false
-        } else if self.options.detach_on_alt_release_anywhere && ctx.input(|i| i.modifiers.alt) {
+        let viewport_id = host.viewport();
+        let disable_preview = if self.options.detach_on_alt_release_anywhere
+            && ctx.input(|i| i.modifiers.alt)
+        {
             false
         } else {
             let dragged_tile = tree.dragged_id_including_root(ctx);
@@ -721,19 +932,16 @@ impl<Pane> DockingMultiViewport<Pane> {
                 }
 
                 let style = ctx.global_style();
-                let decision = decide_overlay_for_tree(
-                    tree,
+                self.internal_drag_decision(
                     behavior,
                     &style,
                     dock_rect,
                     pointer_local,
-                    self.options.show_outer_overlay_targets,
-                    DragKind::Subtree {
-                        dragged_tile: Some(dragged_tile),
-                        internal: true,
-                    },
-                );
-                decision.disable_tiles_preview
+                    host,
+                    tree,
+                    dragged_tile,
+                )
+                .is_some_and(|decision| decision.disable_tiles_preview)
             })
         };
 
@@ -749,6 +957,16 @@ impl<Pane> DockingMultiViewport<Pane> {
         });
     }
 
+    /// Re-dock a detached viewport's whole tree into the root dock (close button, aborted ghost).
+    fn redock_detached_tree_into_root(&mut self, viewport: ViewportId, tree: Tree<Pane>) {
+        self.dock_tree_into_root(tree, None);
+        self.push_dock_event(DockEvent::PaneRedocked {
+            source: PaneHost::DetachedViewport { viewport },
+            target: PaneHost::RootDock,
+        });
+        self.push_dock_event(DockEvent::ViewportClosed { viewport });
+    }
+
     fn dock_tree_into_root(
         &mut self,
         mut detached_tree: Tree<Pane>,
@@ -783,8 +1001,9 @@ impl<Pane> DockingMultiViewport<Pane> {
         behavior: &dyn Behavior<Pane>,
         tree: &Tree<Pane>,
         dock_rect: Rect,
-        target_viewport: ViewportId,
+        target_host: WindowHost,
     ) {
+        let target_viewport = target_host.viewport();
         let Some(payload) = egui::DragAndDrop::payload::<DockPayload>(ui.ctx()) else {
             return;
         };
@@ -988,15 +1207,30 @@ impl<Pane> DockingMultiViewport<Pane> {
                 internal: internal_dragged_tile.is_some(),
             }
         };
-        if matches!(drag_kind, DragKind::Subtree { internal: true, .. })
+        let style = ui.ctx().global_style();
+        let decision = if let DragKind::Subtree {
+            dragged_tile: Some(dragged_tile),
+            internal: true,
+        } = drag_kind
             && !self.options.show_overlay_for_internal_drags
         {
-            ui.ctx().request_repaint();
-            return;
-        }
-
-        let style = ui.ctx().global_style();
-        let decision = if window_move_docking_enabled {
+            // `egui_tiles` previews this drag itself; only show where a policy rewrite lands.
+            let Some(decision) = self.internal_drag_decision(
+                behavior,
+                &style,
+                dock_rect,
+                pointer_local,
+                target_host,
+                tree,
+                dragged_tile,
+            ) else {
+                ui.ctx().request_repaint();
+                return;
+            };
+            decision
+        } else if window_move_docking_enabled {
+            let policy =
+                self.docking_policy_query(payload.source_host(), payload.tile_id, target_host, tree);
             decide_overlay_for_tree(
                 tree,
                 behavior,
@@ -1005,15 +1239,10 @@ impl<Pane> DockingMultiViewport<Pane> {
                 pointer_local,
                 self.options.show_outer_overlay_targets,
                 drag_kind,
+                policy.as_ref(),
             )
         } else {
-            overlay_decision::OverlayDecision {
-                paint: None,
-                insertion_explicit: None,
-                fallback_zone: None,
-                insertion_final: None,
-                disable_tiles_preview: false,
-            }
+            overlay_decision::OverlayDecision::NONE
         };
 
         if window_move_docking_enabled {
@@ -1057,6 +1286,18 @@ impl<Pane> DockingMultiViewport<Pane> {
             }
         }
 
+        // Docking policy rewrote the insertion: preview where the drop will actually land.
+        if let Some(zone) = decision.policy_preview {
+            let stroke = ui.visuals().selection.stroke;
+            let fill = stroke.color.gamma_multiply(0.25);
+            let painter = ui.ctx().layer_painter(LayerId::new(
+                Order::Foreground,
+                egui::Id::new((tree.id(), target_viewport, "egui_docking_policy_preview")),
+            ));
+            let painter = painter.with_clip_rect(dock_rect);
+            painter.rect(zone.preview_rect, 1.0, fill, stroke, egui::StrokeKind::Inside);
+        }
+
         if self.options.debug_drop_targets {
             let mut lines: Vec<String> = Vec::new();
             lines.push(format!("viewport={target_viewport:?}"));
@@ -1146,6 +1387,11 @@ impl<Pane> DockingMultiViewport<Pane> {
                 "disable_tiles_preview={}",
                 decision.disable_tiles_preview
             ));
+            lines.push(format!(
+                "policy_rejected={} policy_preview={:?}",
+                decision.policy_rejected,
+                decision.policy_preview.map(|z| z.insertion_point)
+            ));
 
             show_debug(lines.join("\n"));
         }
@@ -1158,7 +1404,11 @@ impl<Pane> DockingMultiViewport<Pane> {
     }
 
     fn debug_log_backend_hints_if_changed(&mut self, ctx: &Context) {
-        if !self.options.debug_event_log {
+        #[cfg(feature = "tracing")]
+        let traced = tracing::enabled!(target: "egui_docking", tracing::Level::DEBUG);
+        #[cfg(not(feature = "tracing"))]
+        let traced = false;
+        if !self.options.debug_event_log && !traced {
             return;
         }
 
@@ -1177,22 +1427,16 @@ impl<Pane> DockingMultiViewport<Pane> {
 
         ctx.data_mut(|d| d.insert_temp(state_id, next_state));
 
-        match monitors.as_ref() {
-            Some(monitors) => {
-                self.debug_log_event(format!(
-                    "backend_hints hovered={hovered:?} pointer={pointer:?} monitors_outer_rects_points={} first={:?}",
-                    monitors.len(),
-                    monitors.first().map(|r| (r.min, r.max))
-                ));
-            }
-            None => {
-                self.debug_log_event(format!(
-                    "backend_hints hovered={hovered:?} pointer={pointer:?} monitors_outer_rects_points=<missing>"
-                ));
-                self.debug_log_event(
-                    "backend_hints_tip: update your egui/eframe fork to write `egui-winit::monitors_outer_rects_points`, then run `cargo update -p egui -p eframe -p egui-winit` (Cargo.lock pins git rev).",
-                );
-            }
+        let monitors_missing = monitors.is_none();
+        self.debug_log_record(DebugLogRecord::BackendHints {
+            hovered_viewport: hovered,
+            pointer_global: pointer,
+            monitors,
+        });
+        if monitors_missing {
+            self.debug_log_event(
+                "backend_hints_tip: update your egui/eframe fork to write `egui-winit::monitors_outer_rects_points`, then run `cargo update -p egui -p eframe -p egui-winit` (Cargo.lock pins git rev).",
+            );
         }
     }
 
@@ -1,6 +1,7 @@
 use egui::{Context, Pos2, Rect, Vec2};
 
 use super::backend_hints::backend_monitors_outer_rects_points;
+use super::snapshot::MonitorSnapshot;
 
 pub(super) fn clamp_outer_pos_if_monitors_available(ctx: &Context, pos: Pos2, size: Vec2) -> Pos2 {
     if let Some(monitors) = backend_monitors_outer_rects_points(ctx)
@@ -94,3 +95,147 @@ fn clamp_pos_to_monitors_best_effort(pos: Pos2, size: Vec2, monitors: &[Rect]) -
     let max = egui::pos2(max_unclamped.x.max(min.x), max_unclamped.y.max(min.y));
     egui::pos2(pos.x.clamp(min.x, max.x), pos.y.clamp(min.y, max.y))
 }
+
+/// The monitor `window` overlaps most, with the window position relative to it.
+pub(super) fn monitor_snapshot_for_window(
+    window: Rect,
+    monitors: &[Rect],
+) -> Option<MonitorSnapshot> {
+    let (index, rect) = monitors
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, m)| m.is_positive())
+        .map(|(index, m)| {
+            let inter = window.intersect(m);
+            let area = if inter.is_positive() {
+                inter.width() * inter.height()
+            } else {
+                0.0
+            };
+            (index, m, area)
+        })
+        .filter(|&(_, _, area)| area > 0.0)
+        .max_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(index, m, _)| (index, m))?;
+    Some(MonitorSnapshot {
+        index,
+        rect,
+        relative_pos: (window.min - rect.min) / rect.size(),
+    })
+}
+
+/// The monitor a detached window was saved on, with the scale factor it was saved at.
+#[cfg(feature = "persistence")]
+#[derive(Clone, Copy, Debug)]
+pub(super) struct SavedMonitor {
+    pub monitor: MonitorSnapshot,
+    pub pixels_per_point: Option<f32>,
+}
+
+/// Where a window saved on `saved` should go now that the monitors are `monitors` (in points at
+/// `pixels_per_point`).
+///
+/// Returns `None` if the saved monitor is still there (the absolute position is kept) or no
+/// monitors are known. Otherwise the window keeps its relative position on the monitor with the
+/// same index if that one has the same size, or on the primary (first) monitor.
+///
+/// Monitors are compared in physical pixels, so a changed scale factor alone doesn't move the
+/// window.
+#[cfg(feature = "persistence")]
+pub(super) fn remap_outer_pos_to_monitors(
+    saved: &SavedMonitor,
+    monitors: &[Rect],
+    pixels_per_point: f32,
+) -> Option<Pos2> {
+    // In physical pixels.
+    const TOLERANCE: f32 = 1.0;
+    let valid = |ppp: &f32| ppp.is_finite() && *ppp > 0.0;
+    let current_ppp = Some(pixels_per_point).filter(valid).unwrap_or(1.0);
+    let saved_ppp = saved.pixels_per_point.filter(valid).unwrap_or(current_ppp);
+    let saved_rect = saved.monitor.rect;
+    let same_size =
+        |m: &Rect| (m.size() * current_ppp - saved_rect.size() * saved_ppp).length() < TOLERANCE;
+    let same_rect = |m: &Rect| {
+        same_size(m)
+            && (m.min.to_vec2() * current_ppp - saved_rect.min.to_vec2() * saved_ppp).length()
+                < TOLERANCE
+    };
+
+    let primary = *monitors.first()?;
+    if monitors.iter().any(same_rect) {
+        return None;
+    }
+    let target = monitors
+        .get(saved.monitor.index)
+        .copied()
+        .filter(same_size)
+        .unwrap_or(primary);
+    Some(target.min + saved.monitor.relative_pos * target.size())
+}
+
+#[cfg(all(test, feature = "persistence"))]
+mod tests {
+    use egui::{pos2, vec2};
+
+    use super::*;
+
+    #[test]
+    fn window_is_remapped_to_its_saved_monitor() {
+        let left = Rect::from_min_size(pos2(0.0, 0.0), vec2(1920.0, 1080.0));
+        let right = Rect::from_min_size(pos2(1920.0, 0.0), vec2(1280.0, 1024.0));
+        let window = Rect::from_min_size(pos2(2240.0, 256.0), vec2(400.0, 300.0));
+
+        let monitor = monitor_snapshot_for_window(window, &[left, right]).unwrap();
+        assert_eq!(monitor.index, 1);
+        assert_eq!(monitor.relative_pos, vec2(0.25, 0.25));
+        let saved = SavedMonitor {
+            monitor,
+            pixels_per_point: Some(1.0),
+        };
+
+        // Same monitors: keep the absolute position.
+        assert_eq!(
+            remap_outer_pos_to_monitors(&saved, &[left, right], 1.0),
+            None
+        );
+
+        // The monitor moved to the left of the primary.
+        let moved = Rect::from_min_size(pos2(-1280.0, 0.0), vec2(1280.0, 1024.0));
+        assert_eq!(
+            remap_outer_pos_to_monitors(&saved, &[left, moved], 1.0),
+            Some(pos2(-960.0, 256.0))
+        );
+
+        // The monitor is gone: same relative position on the primary.
+        assert_eq!(
+            remap_outer_pos_to_monitors(&saved, &[left], 1.0),
+            Some(pos2(480.0, 270.0))
+        );
+        assert_eq!(remap_outer_pos_to_monitors(&saved, &[], 1.0), None);
+    }
+
+    #[test]
+    fn monitors_are_compared_in_physical_pixels() {
+        let left = Rect::from_min_size(pos2(0.0, 0.0), vec2(1920.0, 1080.0));
+        let right = Rect::from_min_size(pos2(1920.0, 0.0), vec2(1280.0, 1024.0));
+        let window = Rect::from_min_size(pos2(2240.0, 256.0), vec2(400.0, 300.0));
+        let saved = SavedMonitor {
+            monitor: monitor_snapshot_for_window(window, &[left, right]).unwrap(),
+            pixels_per_point: Some(1.0),
+        };
+
+        // Same displays, now at 200%: the monitors are half as big in points.
+        let scaled = |r: Rect| Rect::from_min_max(r.min / 2.0, r.max / 2.0);
+        assert_eq!(
+            remap_outer_pos_to_monitors(&saved, &[scaled(left), scaled(right)], 2.0),
+            None
+        );
+
+        // The same sizes in points at another scale factor are other displays.
+        assert_eq!(
+            remap_outer_pos_to_monitors(&saved, &[left, right], 2.0),
+            Some(pos2(480.0, 270.0))
+        );
+    }
+}
@@ -0,0 +1,498 @@
+use std::collections::BTreeMap;
+use std::ops::{BitOr, BitOrAssign};
+
+use egui::{CursorIcon, Rect, Sense, Ui, vec2};
+use egui_tiles::{
+    Container, ContainerInsertion, ContainerKind, InsertionPoint, LinearDir, Tile, TileId, Tree,
+};
+
+use super::DockingMultiViewport;
+use super::overlay::tile_contains_descendant;
+use super::pane_query::PaneHost;
+
+/// Per-node docking restrictions, modelled on Dear ImGui's `ImGuiDockNodeFlags`.
+///
+/// Flags are attached to one tile of one host's dock tree, usually a `Tabs` container (a
+/// [`crate::DockBuilder`] leaf node). A pane inside a `Tabs` container also gets the flags of
+/// that container.
+///
+/// They only restrict what the user can do with the mouse; programmatic APIs such as
+/// [`DockingMultiViewport::float_pane`] ignore them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "persistence",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(transparent)
+)]
+pub struct DockNodeFlags(u32);
+
+impl DockNodeFlags {
+    pub const NONE: Self = Self(0);
+
+    /// Never split this node: its left/right/top/bottom overlay targets are hidden, and drops
+    /// that would split it are refused. Docking as a tab is still allowed.
+    pub const NO_SPLIT: Self = Self(1 << 0);
+
+    /// Never dock anything as a tab into this node, so it keeps the panes it was built with.
+    ///
+    /// The tab bar is still shown; combine with [`Self::NO_TAB_BAR`] to hide it as well.
+    pub const NO_TAB_INSERT: Self = Self(1 << 1);
+
+    /// Nothing can be docked into or next to this node: no overlay targets are shown over it.
+    pub const NO_DOCKING_OVER_ME: Self = Self(1 << 2);
+
+    /// Panes can't be dragged out of this node: no tear-off (native or floating), and drops
+    /// outside of the node are refused. Reordering inside the node still works.
+    pub const NO_UNDOCKING: Self = Self(1 << 3);
+
+    /// The splitters of this node (and of the split that contains it) can't be dragged.
+    pub const NO_RESIZE: Self = Self(1 << 4);
+
+    /// Skip `Behavior::top_bar_left_ui` / `top_bar_right_ui` for this node's tab bar, which is
+    /// where apps usually put their window menu button.
+    pub const NO_WINDOW_MENU_BUTTON: Self = Self(1 << 5);
+
+    /// The central node of the root dock: a `Tabs` container that is never pruned when it
+    /// becomes empty, shows the empty-state UI, and receives
+    /// [`DockingMultiViewport::add_pane_to_central_node`].
+    ///
+    /// Only valid on a `Tabs` container of [`PaneHost::RootDock`], and only one node has it.
+    pub const CENTRAL_NODE: Self = Self(1 << 6);
+
+    /// Hide this node's tab bar (ImGui's `NoTabBar`): only the active tab is shown, and its
+    /// window menu buttons go with the bar. Other panes can still be docked into the node.
+    ///
+    /// Only meaningful on a `Tabs` container. Implemented with `Behavior::show_tab_bar`, which the
+    /// docking wrapper answers from the flags before asking the app's behavior.
+    pub const NO_TAB_BAR: Self = Self(1 << 7);
+
+    pub const ALL: Self = Self((1 << 8) - 1);
+
+    pub const fn empty() -> Self {
+        Self::NONE
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Unknown bits are dropped.
+    pub const fn from_bits_truncate(bits: u32) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// `true` if every flag of `other` is set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// `true` if any flag of `other` is set.
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    pub fn set(&mut self, other: Self, value: bool) {
+        if value {
+            self.insert(other);
+        } else {
+            self.remove(other);
+        }
+    }
+}
+
+impl BitOr for DockNodeFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for DockNodeFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.insert(rhs);
+    }
+}
+
+/// The node flags of one host, as used by overlay targeting, drop decisions and tear-off.
+#[derive(Clone, Copy)]
+pub(super) struct NodeFlagsView<'a> {
+    flags: &'a BTreeMap<(PaneHost, TileId), DockNodeFlags>,
+    host: PaneHost,
+}
+
+impl<'a> NodeFlagsView<'a> {
+    pub(super) fn new(flags: &'a BTreeMap<(PaneHost, TileId), DockNodeFlags>, host: PaneHost) -> Self {
+        Self { flags, host }
+    }
+
+    fn get(self, tile_id: TileId) -> DockNodeFlags {
+        self.flags
+            .get(&(self.host, tile_id))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Flags of `tile_id`, plus those of its `Tabs` container if it is a tab pane.
+    pub(super) fn effective<Pane>(self, tree: &Tree<Pane>, tile_id: TileId) -> DockNodeFlags {
+        let mut flags = self.get(tile_id);
+        if matches!(tree.tiles.get(tile_id), Some(Tile::Pane(_)))
+            && let Some(parent) = tree.tiles.parent_of(tile_id)
+            && tree.tiles.get(parent).and_then(|t| t.kind()) == Some(ContainerKind::Tabs)
+        {
+            flags |= self.get(parent);
+        }
+        flags
+    }
+
+    /// Whether the flags of the insertion parent allow docking there.
+    pub(super) fn allows_insertion<Pane>(self, tree: &Tree<Pane>, insertion: InsertionPoint) -> bool {
+        let flags = self.effective(tree, insertion.parent_id);
+        if flags.contains(DockNodeFlags::NO_DOCKING_OVER_ME) {
+            return false;
+        }
+        match insertion.insertion {
+            ContainerInsertion::Tabs(_) => !flags.contains(DockNodeFlags::NO_TAB_INSERT),
+            _ => !flags.contains(DockNodeFlags::NO_SPLIT),
+        }
+    }
+
+    /// The closest ancestor-or-self of `tile_id` flagged [`DockNodeFlags::NO_UNDOCKING`]:
+    /// a drag of `tile_id` has to stay inside of it.
+    ///
+    /// The central node is its own anchor: its panes can leave, the node itself can't.
+    pub(super) fn undocking_anchor<Pane>(self, tree: &Tree<Pane>, tile_id: TileId) -> Option<TileId> {
+        let mut current = Some(tile_id);
+        while let Some(tile) = current {
+            let flags = self.get(tile);
+            if flags.contains(DockNodeFlags::NO_UNDOCKING)
+                || (tile == tile_id && flags.contains(DockNodeFlags::CENTRAL_NODE))
+            {
+                return Some(tile);
+            }
+            current = tree.tiles.parent_of(tile);
+        }
+        None
+    }
+}
+
+/// Whether `insertion` keeps the dragged tile inside `anchor` (see [`NodeFlagsView::undocking_anchor`]).
+///
+/// Inserting into `anchor` itself with a different container kind would wrap it into a new
+/// container, i.e. move the tile next to the node rather than into it.
+pub(super) fn insertion_stays_inside<Pane>(
+    tree: &Tree<Pane>,
+    anchor: TileId,
+    insertion: InsertionPoint,
+) -> bool {
+    if insertion.parent_id == anchor {
+        tree.tiles.get(anchor).and_then(|t| t.kind()) == Some(insertion.insertion.kind())
+    } else {
+        tile_contains_descendant(tree, anchor, insertion.parent_id)
+    }
+}
+
+/// The `Linear` containers whose splitters a [`DockNodeFlags::NO_RESIZE`] flag on `tile_id`
+/// locks: its own splits, and the split it sits in.
+fn locked_linears<Pane>(tree: &Tree<Pane>, tile_id: TileId) -> impl Iterator<Item = TileId> + '_ {
+    [Some(tile_id), tree.tiles.parent_of(tile_id)]
+        .into_iter()
+        .flatten()
+        .filter(|&id| {
+            matches!(
+                tree.tiles.get(id),
+                Some(Tile::Container(Container::Linear(_)))
+            )
+        })
+}
+
+/// Cover the splitters locked by [`DockNodeFlags::NO_RESIZE`] nodes of `tree` with a drag sensor,
+/// so `egui_tiles` never starts resizing them and the layout doesn't move.
+///
+/// Call right after `tree.ui` on the same `Ui`: the widget added last wins the hit test.
+pub(super) fn lock_splitters<Pane>(
+    ui: &mut Ui,
+    tree: &Tree<Pane>,
+    node_flags: &BTreeMap<TileId, DockNodeFlags>,
+) {
+    let grab_radius = ui.style().interaction.resize_grab_radius_side;
+    for (&tile_id, flags) in node_flags {
+        if !flags.contains(DockNodeFlags::NO_RESIZE) {
+            continue;
+        }
+        for linear_id in locked_linears(tree, tile_id) {
+            let Some(Tile::Container(Container::Linear(linear))) = tree.tiles.get(linear_id) else {
+                continue;
+            };
+            let Some(parent_rect) = tree.tiles.rect(linear_id) else {
+                continue;
+            };
+            let child_rects: Vec<Rect> = linear
+                .children
+                .iter()
+                .filter(|&&child| tree.tiles.is_visible(child))
+                .filter_map(|&child| tree.tiles.rect(child))
+                .collect();
+            for (i, pair) in child_rects.windows(2).enumerate() {
+                let splitter = match linear.dir {
+                    LinearDir::Horizontal => Rect::from_x_y_ranges(
+                        pair[0].right()..=pair[1].left(),
+                        parent_rect.y_range(),
+                    )
+                    .expand2(vec2(grab_radius, 0.0)),
+                    LinearDir::Vertical => Rect::from_x_y_ranges(
+                        parent_rect.x_range(),
+                        pair[0].bottom()..=pair[1].top(),
+                    )
+                    .expand2(vec2(0.0, grab_radius)),
+                };
+                let id = ui.id().with((tree.id(), linear_id, "locked_splitter", i));
+                let response = ui.interact(splitter, id, Sense::drag());
+                if response.hovered() || response.dragged() {
+                    ui.ctx().set_cursor_icon(CursorIcon::Default);
+                }
+            }
+        }
+    }
+}
+
+/// Shares of a `Linear` container, captured before the frame so a [`DockNodeFlags::NO_RESIZE`]
+/// node can be put back if `egui_tiles` still applied a splitter drag (one that started before
+/// the flag was set).
+pub(super) struct LockedShares {
+    host: PaneHost,
+    linear: TileId,
+    shares: Vec<(TileId, f32)>,
+}
+
+impl<Pane> DockingMultiViewport<Pane> {
+    /// Set the [`DockNodeFlags`] of `tile_id` in `host`, replacing the previous ones.
+    ///
+    /// Flags belong to that host and tile: they are dropped when the tile leaves the host (it is
+    /// torn off, floated, docked elsewhere or closed), and when the whole layout is replaced
+    /// ([`Self::set_root_tree`], workspace presets, loading a snapshot).
+    ///
+    /// Returns `false` (and does nothing) if the tile does not exist in `host`, or if `flags`
+    /// contains [`DockNodeFlags::CENTRAL_NODE`] and the tile is not a `Tabs` container of the
+    /// root dock.
+    pub fn set_node_flags(&mut self, host: PaneHost, tile_id: TileId, flags: DockNodeFlags) -> bool {
+        let Some(kind) = self
+            .tree_for_pane_host(host)
+            .and_then(|tree| tree.tiles.get(tile_id))
+            .map(|tile| tile.kind())
+        else {
+            return false;
+        };
+        if flags.contains(DockNodeFlags::CENTRAL_NODE) {
+            if host != PaneHost::RootDock || kind != Some(ContainerKind::Tabs) {
+                return false;
+            }
+            self.clear_central_node();
+        }
+        if flags.is_empty() {
+            self.node_flags.remove(&(host, tile_id));
+        } else {
+            self.node_flags.insert((host, tile_id), flags);
+        }
+        true
+    }
+
+    /// Set every `(tile, flags)` pair of `host`, e.g. the output of
+    /// [`crate::DockBuilder::finish_with_node_flags`].
+    pub fn apply_node_flags(
+        &mut self,
+        host: PaneHost,
+        flags: impl IntoIterator<Item = (TileId, DockNodeFlags)>,
+    ) {
+        for (tile_id, flags) in flags {
+            self.set_node_flags(host, tile_id, flags);
+        }
+    }
+
+    /// The flags set on `tile_id` in `host` (not including the ones inherited from its `Tabs`).
+    pub fn node_flags(&self, host: PaneHost, tile_id: TileId) -> DockNodeFlags {
+        NodeFlagsView::new(&self.node_flags, host).get(tile_id)
+    }
+
+    pub(super) fn node_flags_view(&self, host: PaneHost) -> NodeFlagsView<'_> {
+        NodeFlagsView::new(&self.node_flags, host)
+    }
+
+    /// All flags of `host`, keyed by tile.
+    pub(super) fn node_flags_in_host(&self, host: PaneHost) -> BTreeMap<TileId, DockNodeFlags> {
+        self.node_flags
+            .iter()
+            .filter(|((h, _), _)| *h == host)
+            .map(|((_, tile_id), flags)| (*tile_id, *flags))
+            .collect()
+    }
+
+    /// Forget flags whose tile no longer exists in its host.
+    ///
+    /// The central node is kept: it is put back at the start of the next frame.
+    pub(super) fn prune_node_flags(&mut self) {
+        if self.node_flags.is_empty() {
+            return;
+        }
+        let stale: Vec<(PaneHost, TileId)> = self
+            .node_flags
+            .iter()
+            .filter(|&(&(host, tile_id), flags)| {
+                !flags.contains(DockNodeFlags::CENTRAL_NODE)
+                    && !self
+                        .tree_for_pane_host(host)
+                        .is_some_and(|tree| tree.tiles.get(tile_id).is_some())
+            })
+            .map(|(&key, _)| key)
+            .collect();
+        for key in stale {
+            self.node_flags.remove(&key);
+        }
+    }
+
+    /// Capture the shares of every `Linear` container locked by a [`DockNodeFlags::NO_RESIZE`]
+    /// node.
+    pub(super) fn capture_locked_shares(&self) -> Vec<LockedShares> {
+        let mut out = Vec::new();
+        for (&(host, tile_id), flags) in &self.node_flags {
+            if !flags.contains(DockNodeFlags::NO_RESIZE) {
+                continue;
+            }
+            let Some(tree) = self.tree_for_pane_host(host) else {
+                continue;
+            };
+            for linear_id in locked_linears(tree, tile_id) {
+                if let Some(Tile::Container(Container::Linear(linear))) = tree.tiles.get(linear_id) {
+                    out.push(LockedShares {
+                        host,
+                        linear: linear_id,
+                        shares: linear
+                            .children
+                            .iter()
+                            .map(|&child| (child, linear.shares[child]))
+                            .collect(),
+                    });
+                }
+            }
+        }
+        out
+    }
+
+    /// Undo splitter drags on locked containers. Containers whose children changed this frame
+    /// (a drop landed in them) are left alone.
+    pub(super) fn restore_locked_shares(&mut self, locked: Vec<LockedShares>) {
+        for entry in locked {
+            let Some(tree) = self.tree_mut_for_host(entry.host.window_host()) else {
+                continue;
+            };
+            let Some(Tile::Container(Container::Linear(linear))) = tree.tiles.get_mut(entry.linear)
+            else {
+                continue;
+            };
+            let same_children = linear.children.len() == entry.shares.len()
+                && linear
+                    .children
+                    .iter()
+                    .zip(&entry.shares)
+                    .all(|(&child, &(locked_child, _))| child == locked_child);
+            if !same_children {
+                continue;
+            }
+            for (child, share) in entry.shares {
+                linear.shares.set_share(child, share);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_restrict_insertions_and_undocking() {
+        let mut tiles = egui_tiles::Tiles::default();
+        let toolbar = tiles.insert_pane("toolbar");
+        let toolbar_tabs = tiles.insert_tab_tile(vec![toolbar]);
+        let scene = tiles.insert_pane("scene");
+        let scene_tabs = tiles.insert_tab_tile(vec![scene]);
+        let root = tiles.insert_vertical_tile(vec![toolbar_tabs, scene_tabs]);
+        let tree = Tree::new(egui::Id::new("flags"), root, tiles);
+
+        let flags = BTreeMap::from([
+            (
+                (PaneHost::RootDock, toolbar_tabs),
+                DockNodeFlags::NO_DOCKING_OVER_ME | DockNodeFlags::NO_UNDOCKING,
+            ),
+            ((PaneHost::RootDock, scene_tabs), DockNodeFlags::NO_SPLIT),
+        ]);
+        let view = NodeFlagsView::new(&flags, PaneHost::RootDock);
+
+        // A tab pane inherits the flags of its `Tabs` node.
+        assert!(view.effective(&tree, toolbar).contains(DockNodeFlags::NO_UNDOCKING));
+
+        let tab_into = |parent| InsertionPoint::new(parent, ContainerInsertion::Tabs(usize::MAX));
+        let split = |parent| InsertionPoint::new(parent, ContainerInsertion::Horizontal(0));
+        assert!(!view.allows_insertion(&tree, tab_into(toolbar_tabs)));
+        assert!(!view.allows_insertion(&tree, split(toolbar)));
+        assert!(view.allows_insertion(&tree, tab_into(scene_tabs)));
+        assert!(!view.allows_insertion(&tree, split(scene_tabs)));
+        assert!(view.allows_insertion(&tree, split(root)));
+
+        assert_eq!(view.undocking_anchor(&tree, toolbar), Some(toolbar_tabs));
+        assert_eq!(view.undocking_anchor(&tree, scene), None);
+        assert!(insertion_stays_inside(&tree, toolbar_tabs, tab_into(toolbar_tabs)));
+        assert!(!insertion_stays_inside(&tree, toolbar_tabs, split(toolbar_tabs)));
+        assert!(!insertion_stays_inside(&tree, toolbar_tabs, tab_into(scene_tabs)));
+        // Other hosts are not affected.
+        let detached = NodeFlagsView::new(
+            &flags,
+            PaneHost::DetachedViewport {
+                viewport: egui::ViewportId::from_hash_of("other"),
+            },
+        );
+        assert!(detached.allows_insertion(&tree, tab_into(toolbar_tabs)));
+    }
+
+    #[test]
+    fn locked_shares_undo_splitter_drags() {
+        let mut tiles = egui_tiles::Tiles::default();
+        let a = tiles.insert_pane(1);
+        let b = tiles.insert_pane(2);
+        let root = tiles.insert_horizontal_tile(vec![a, b]);
+        let mut docking = DockingMultiViewport::new(Tree::new(egui::Id::new("root"), root, tiles));
+        assert!(docking.set_node_flags(PaneHost::RootDock, a, DockNodeFlags::NO_RESIZE));
+        assert!(!docking.set_node_flags(PaneHost::RootDock, TileId::from_u64(999), DockNodeFlags::ALL));
+
+        let locked = docking.capture_locked_shares();
+        if let Some(Tile::Container(Container::Linear(linear))) = docking.tree.tiles.get_mut(root) {
+            linear.shares.set_share(a, 3.0);
+        }
+        docking.restore_locked_shares(locked);
+        let Some(Tile::Container(Container::Linear(linear))) = docking.tree.tiles.get(root) else {
+            panic!("root must stay a Linear container");
+        };
+        assert_eq!(linear.shares[a], linear.shares[b]);
+
+        docking.tree.tiles.remove(a);
+        docking.prune_node_flags();
+        assert!(docking.node_flags(PaneHost::RootDock, a).is_empty());
+    }
+}
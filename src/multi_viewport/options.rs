@@ -70,6 +70,9 @@ pub struct DockingMultiViewportOptions {
     pub focus_detached_on_custom_title_drag: bool,
 
     /// If true, show ImGui-style docking overlay targets even for drags that stay within the same viewport.
+    ///
+    /// When false, `egui_tiles` previews and applies those drops itself, but the
+    /// [`super::DockingPolicy`] and [`super::DockNodeFlags`] are still enforced.
     pub show_overlay_for_internal_drags: bool,
 
     /// If true, show ImGui-style *outer* docking targets (dockspace edge markers),
@@ -123,6 +126,10 @@ pub struct DockingMultiViewportOptions {
     /// `rg`/`tail -f` on the log from a terminal.
     pub debug_log_file_path: Option<PathBuf>,
 
+    /// Line format of the `debug_log_file_path` file: text like the debug window, or JSON lines
+    /// for scripts and CI (feature `debug-log-json`).
+    pub debug_log_file_format: DebugLogFileFormat,
+
     /// If true, truncate the log file once per process run before the first write.
     pub debug_log_file_clear_on_start: bool,
 
@@ -143,6 +150,11 @@ pub struct DockingMultiViewportOptions {
 
     /// If true, panic on integrity issues (debug-only).
     pub debug_integrity_panic: bool,
+
+    /// If true, run [`super::DockingMultiViewport::repair_integrity`] at the end of every frame
+    /// that changed the layout, so inconsistent trees are healed instead of only logged (or
+    /// panicked on).
+    pub integrity_auto_repair: bool,
 }
 
 impl Default for DockingMultiViewportOptions {
@@ -171,12 +183,14 @@ impl Default for DockingMultiViewportOptions {
             debug_show_window: false,
             debug_event_log: false,
             debug_log_file_path: None,
+            debug_log_file_format: DebugLogFileFormat::Text,
             debug_log_file_clear_on_start: true,
             debug_log_file_flush_each_line: true,
             debug_log_window_move_every_send: false,
             debug_event_log_capacity: 200,
             debug_integrity: false,
             debug_integrity_panic: false,
+            integrity_auto_repair: false,
         }
     }
 }
@@ -199,6 +213,118 @@ impl DockingMultiViewportOptions {
     }
 }
 
+/// Line format of [`DockingMultiViewportOptions::debug_log_file_path`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DebugLogFileFormat {
+    /// `[frame N] message`, the same text the debug window shows.
+    #[default]
+    Text,
+
+    /// One JSON object per line (see [`super::DebugLogEntry::to_json_line`]).
+    #[cfg(feature = "debug-log-json")]
+    JsonLines,
+}
+
+/// Per-window overrides for a single detached native viewport.
+///
+/// Every `None` field falls back to the matching global `detached_*` field of
+/// [`DockingMultiViewportOptions`], so the default value changes nothing.
+///
+/// Typical uses: a decorated OS window for a game preview while tool palettes stay borderless,
+/// or an always-on-top profiler window.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "persistence",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(default)
+)]
+pub struct DetachedViewportOptions {
+    /// Overrides [`DockingMultiViewportOptions::detached_viewport_decorations`].
+    pub decorations: Option<bool>,
+
+    /// Overrides [`DockingMultiViewportOptions::detached_csd_resize_edge_thickness`].
+    pub csd_resize_edge_thickness: Option<f32>,
+
+    /// Overrides [`DockingMultiViewportOptions::detached_csd_resize_corner_size`].
+    pub csd_resize_corner_size: Option<f32>,
+
+    /// Overrides [`DockingMultiViewportOptions::detached_csd_window_controls`].
+    pub csd_window_controls: Option<bool>,
+
+    /// Keep this window above other windows (`egui::WindowLevel::AlwaysOnTop`).
+    pub always_on_top: bool,
+}
+
+impl DetachedViewportOptions {
+    pub fn with_decorations(mut self, decorations: bool) -> Self {
+        self.decorations = Some(decorations);
+        self
+    }
+
+    pub fn with_csd_resize_edge_thickness(mut self, thickness: f32) -> Self {
+        self.csd_resize_edge_thickness = Some(thickness);
+        self
+    }
+
+    pub fn with_csd_resize_corner_size(mut self, size: f32) -> Self {
+        self.csd_resize_corner_size = Some(size);
+        self
+    }
+
+    pub fn with_csd_window_controls(mut self, enabled: bool) -> Self {
+        self.csd_window_controls = Some(enabled);
+        self
+    }
+
+    pub fn with_always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = always_on_top;
+        self
+    }
+
+    pub(crate) fn resolve(&self, global: &DockingMultiViewportOptions) -> ResolvedDetachedOptions {
+        ResolvedDetachedOptions {
+            decorations: self
+                .decorations
+                .unwrap_or(global.detached_viewport_decorations),
+            csd_resize_edge_thickness: self
+                .csd_resize_edge_thickness
+                .unwrap_or(global.detached_csd_resize_edge_thickness),
+            csd_resize_corner_size: self
+                .csd_resize_corner_size
+                .unwrap_or(global.detached_csd_resize_corner_size),
+            csd_window_controls: self
+                .csd_window_controls
+                .unwrap_or(global.detached_csd_window_controls),
+            always_on_top: self.always_on_top,
+        }
+    }
+}
+
+/// [`DetachedViewportOptions`] with the global fallbacks filled in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ResolvedDetachedOptions {
+    pub(crate) decorations: bool,
+    pub(crate) csd_resize_edge_thickness: f32,
+    pub(crate) csd_resize_corner_size: f32,
+    pub(crate) csd_window_controls: bool,
+    pub(crate) always_on_top: bool,
+}
+
+impl ResolvedDetachedOptions {
+    /// Apply the OS window settings to a viewport builder.
+    ///
+    /// egui diffs the builder of an immediate viewport every frame, so changing these at runtime
+    /// patches the live OS window. A builder-provided window level is kept unless `always_on_top`.
+    pub(crate) fn apply_to_builder(&self, builder: egui::ViewportBuilder) -> egui::ViewportBuilder {
+        let builder = builder.with_decorations(self.decorations);
+        if self.always_on_top {
+            builder.with_window_level(egui::WindowLevel::AlwaysOnTop)
+        } else {
+            builder
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,4 +348,28 @@ mod tests {
         assert!(!opt.window_move_docking_enabled_by_shift(false));
         assert!(opt.window_move_docking_enabled_by_shift(true));
     }
+
+    #[test]
+    fn detached_viewport_options_fall_back_to_globals() {
+        let global = DockingMultiViewportOptions {
+            detached_viewport_decorations: false,
+            detached_csd_resize_edge_thickness: 4.0,
+            ..Default::default()
+        };
+
+        let resolved = DetachedViewportOptions::default().resolve(&global);
+        assert!(!resolved.decorations);
+        assert_eq!(resolved.csd_resize_edge_thickness, 4.0);
+        assert_eq!(resolved.csd_resize_corner_size, global.detached_csd_resize_corner_size);
+        assert!(!resolved.always_on_top);
+
+        let resolved = DetachedViewportOptions::default()
+            .with_decorations(true)
+            .with_csd_resize_edge_thickness(9.0)
+            .with_always_on_top(true)
+            .resolve(&global);
+        assert!(resolved.decorations);
+        assert_eq!(resolved.csd_resize_edge_thickness, 9.0);
+        assert!(resolved.always_on_top);
+    }
 }
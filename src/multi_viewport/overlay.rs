@@ -1,6 +1,8 @@
 use egui::{Pos2, Rect, Vec2};
 use egui_tiles::{ContainerKind, InsertionPoint, Tile, TileId, Tree};
 
+use super::node_flags::{DockNodeFlags, NodeFlagsView};
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(super) enum OverlayTarget {
     Center,
@@ -12,7 +14,7 @@ pub(super) enum OverlayTarget {
 
 #[derive(Clone, Copy, Debug)]
 struct OverlayTargets {
-    center: Rect,
+    center: Option<Rect>,
     left: Option<Rect>,
     right: Option<Rect>,
     top: Option<Rect>,
@@ -22,7 +24,7 @@ struct OverlayTargets {
 impl OverlayTargets {
     fn iter(self) -> impl Iterator<Item = (OverlayTarget, Rect)> {
         [
-            Some((OverlayTarget::Center, self.center)),
+            self.center.map(|r| (OverlayTarget::Center, r)),
             self.left.map(|r| (OverlayTarget::Left, r)),
             self.right.map(|r| (OverlayTarget::Right, r)),
             self.top.map(|r| (OverlayTarget::Top, r)),
@@ -32,8 +34,18 @@ impl OverlayTargets {
         .flatten()
     }
 
+    fn is_empty(self) -> bool {
+        self.iter().next().is_none()
+    }
+
     fn hit_test_boxes(self, pointer: Pos2) -> Option<(OverlayTarget, Rect)> {
-        let hs_w = self.center.width() * 0.5;
+        let reference = self
+            .center
+            .or(self.left)
+            .or(self.right)
+            .or(self.top)
+            .or(self.bottom)?;
+        let hs_w = reference.width() * 0.5;
         let expand = if hs_w > 0.0 {
             (hs_w * 0.30).round()
         } else {
@@ -64,6 +76,15 @@ impl DockingOverlay {
     pub(super) fn hovered_target(self) -> Option<OverlayTarget> {
         self.hovered.map(|(t, _)| t)
     }
+
+    /// Where `target` is drawn, if this node offers it.
+    #[cfg(any(test, feature = "testing"))]
+    pub(super) fn target_rect(self, target: OverlayTarget) -> Option<Rect> {
+        self.targets
+            .iter()
+            .find(|&(t, _)| t == target)
+            .map(|(_, rect)| rect)
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -238,11 +259,7 @@ fn overlay_insertion_for_tree_explicit_considering_dragged<Pane>(
     let (tile_id, tile_rect) =
         best_tile_under_pointer_considering_dragged(tree, pointer, dragged_tile)?;
 
-    let kind = tree.tiles.get(tile_id).and_then(|t| t.kind());
-    let allow_lr = kind != Some(ContainerKind::Horizontal);
-    let allow_tb = kind != Some(ContainerKind::Vertical);
-
-    let targets = overlay_targets_in_rect(tile_rect, allow_lr, allow_tb);
+    let targets = overlay_targets_for_tile(tree, tile_id, tile_rect, None)?;
     let (target, _rect) = targets.hit_test_boxes(pointer)?;
 
     Some(match target {
@@ -283,14 +300,11 @@ pub(super) fn overlay_insertion_for_tree_explicit_with_outer_considering_dragged
 pub(super) fn overlay_for_tree_at_pointer_explicit<Pane>(
     tree: &Tree<Pane>,
     pointer: Pos2,
+    flags: Option<NodeFlagsView<'_>>,
 ) -> Option<DockingOverlay> {
     let (tile_id, tile_rect) = best_tile_under_pointer(tree, pointer)?;
 
-    let kind = tree.tiles.get(tile_id).and_then(|t| t.kind());
-    let allow_lr = kind != Some(ContainerKind::Horizontal);
-    let allow_tb = kind != Some(ContainerKind::Vertical);
-
-    let targets = overlay_targets_in_rect(tile_rect, allow_lr, allow_tb);
+    let targets = overlay_targets_for_tile(tree, tile_id, tile_rect, flags)?;
     let hovered = targets.hit_test_boxes(pointer);
 
     Some(DockingOverlay {
@@ -305,15 +319,12 @@ pub(super) fn overlay_for_tree_at_pointer_explicit_considering_dragged<Pane>(
     tree: &Tree<Pane>,
     pointer: Pos2,
     dragged_tile: Option<TileId>,
+    flags: Option<NodeFlagsView<'_>>,
 ) -> Option<DockingOverlay> {
     let (tile_id, tile_rect) =
         best_tile_under_pointer_considering_dragged(tree, pointer, dragged_tile)?;
 
-    let kind = tree.tiles.get(tile_id).and_then(|t| t.kind());
-    let allow_lr = kind != Some(ContainerKind::Horizontal);
-    let allow_tb = kind != Some(ContainerKind::Vertical);
-
-    let targets = overlay_targets_in_rect(tile_rect, allow_lr, allow_tb);
+    let targets = overlay_targets_for_tile(tree, tile_id, tile_rect, flags)?;
     let hovered = targets.hit_test_boxes(pointer);
 
     Some(DockingOverlay {
@@ -337,12 +348,54 @@ pub(super) fn insertion_from_hovered_target(
     }
 }
 
-fn overlay_targets_in_rect(tile_rect: Rect, allow_lr: bool, allow_tb: bool) -> OverlayTargets {
+/// The targets a dock node offers, given its container kind and [`DockNodeFlags`].
+///
+/// `None` if the node accepts nothing (e.g. [`DockNodeFlags::NO_DOCKING_OVER_ME`]).
+fn overlay_targets_for_tile<Pane>(
+    tree: &Tree<Pane>,
+    tile_id: TileId,
+    tile_rect: Rect,
+    flags: Option<NodeFlagsView<'_>>,
+) -> Option<OverlayTargets> {
+    let node_flags = flags
+        .map(|flags| flags.effective(tree, tile_id))
+        .unwrap_or_default();
+    if node_flags.contains(DockNodeFlags::NO_DOCKING_OVER_ME) {
+        return None;
+    }
+
+    let kind = tree.tiles.get(tile_id).and_then(|t| t.kind());
+    let allow_split = !node_flags.contains(DockNodeFlags::NO_SPLIT);
+    let allow_center = !node_flags.contains(DockNodeFlags::NO_TAB_INSERT);
+    let allow_lr = allow_split && kind != Some(ContainerKind::Horizontal);
+    let allow_tb = allow_split && kind != Some(ContainerKind::Vertical);
+
+    let targets = overlay_targets_in_rect(tile_rect, allow_center, allow_lr, allow_tb);
+    (!targets.is_empty()).then_some(targets)
+}
+
+/// Whether the outer (dockspace edge) targets may split the root of `tree`.
+pub(super) fn outer_targets_allowed<Pane>(tree: &Tree<Pane>, flags: Option<NodeFlagsView<'_>>) -> bool {
+    let (Some(root), Some(flags)) = (tree.root, flags) else {
+        return true;
+    };
+    !flags
+        .effective(tree, root)
+        .intersects(DockNodeFlags::NO_SPLIT | DockNodeFlags::NO_DOCKING_OVER_ME)
+}
+
+fn overlay_targets_in_rect(
+    tile_rect: Rect,
+    allow_center: bool,
+    allow_lr: bool,
+    allow_tb: bool,
+) -> OverlayTargets {
     let min_dim = tile_rect.width().min(tile_rect.height());
     let size = (min_dim * 0.16).clamp(24.0, 56.0);
     let gap = (size * 0.25).clamp(6.0, 18.0);
 
-    let center = Rect::from_center_size(tile_rect.center(), Vec2::splat(size)).intersect(tile_rect);
+    let center = allow_center
+        .then(|| Rect::from_center_size(tile_rect.center(), Vec2::splat(size)).intersect(tile_rect));
     let left = allow_lr
         .then(|| {
             Rect::from_center_size(
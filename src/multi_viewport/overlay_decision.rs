@@ -1,6 +1,8 @@
 use egui::{NumExt as _, Pos2, Rect};
 use egui_tiles::{Behavior, DockZone, InsertionPoint, TileId, Tree};
 
+use super::docking_policy::{insertion_preview_rect, DropVerdict, PolicyQuery};
+
 use super::overlay::{
     self, insertion_from_hovered_target, outer_overlay_for_dock_rect_explicit,
     overlay_for_tree_at_pointer_explicit, overlay_for_tree_at_pointer_explicit_considering_dragged,
@@ -189,8 +191,32 @@ pub(super) struct OverlayDecision {
     pub(super) fallback_zone: Option<DockZone>,
     pub(super) insertion_final: Option<InsertionPoint>,
     pub(super) disable_tiles_preview: bool,
+    /// Preview for an insertion rewritten by the [`super::DockingPolicy`] (replaces the overlay highlight).
+    pub(super) policy_preview: Option<DockZone>,
+    /// The [`super::DockingPolicy`] rejected the drop: nothing is previewed and the release
+    /// must not mutate any tree (including `egui_tiles`' own internal drop).
+    pub(super) policy_rejected: bool,
+}
+
+impl OverlayDecision {
+    pub(super) const NONE: Self = Self {
+        paint: None,
+        insertion_explicit: None,
+        fallback_zone: None,
+        insertion_final: None,
+        disable_tiles_preview: false,
+        policy_preview: None,
+        policy_rejected: false,
+    };
+
+    /// The [`super::DockingPolicy`] rejected or rewrote the drop, so `egui_tiles` must not apply
+    /// its own.
+    pub(super) fn overrides_tiles(&self) -> bool {
+        self.policy_rejected || self.policy_preview.is_some()
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(super) fn decide_overlay_for_tree<Pane>(
     tree: &Tree<Pane>,
     behavior: &dyn Behavior<Pane>,
@@ -199,15 +225,18 @@ pub(super) fn decide_overlay_for_tree<Pane>(
     pointer_local: Pos2,
     show_outer_overlay_targets: bool,
     drag_kind: DragKind,
+    policy: Option<&PolicyQuery<'_, Pane>>,
 ) -> OverlayDecision {
     let window_move_explicit_zone =
         window_move_explicit_target_zone_at_pointer(tree, behavior, style, pointer_local);
+    let flags = policy.map(|policy| policy.target_flags);
 
     // Prefer tab-bar docking over outer overlay mode so we can still dock as a tab when the
     // pointer is near the edge (tab bars often live at the top edge, which is inside the band).
     let outer_mode = show_outer_overlay_targets
         && pointer_in_outer_band(dock_rect, pointer_local)
-        && window_move_explicit_zone.is_none();
+        && window_move_explicit_zone.is_none()
+        && overlay::outer_targets_allowed(tree, flags);
 
     let (paint_candidate, insertion_explicit) = if outer_mode {
         let overlay = outer_overlay_for_dock_rect_explicit(dock_rect, pointer_local);
@@ -225,6 +254,7 @@ pub(super) fn decide_overlay_for_tree<Pane>(
                     tree,
                     pointer_local,
                     dragged_tile,
+                    flags,
                 );
                 let insertion = o
                     .and_then(|o| o.hovered_target().map(|t| (o.tile_id(), t)))
@@ -232,7 +262,7 @@ pub(super) fn decide_overlay_for_tree<Pane>(
                 (o, insertion)
             }
             _ => {
-                let o = overlay_for_tree_at_pointer_explicit(tree, pointer_local);
+                let o = overlay_for_tree_at_pointer_explicit(tree, pointer_local, flags);
                 let insertion = o
                     .and_then(|o| o.hovered_target().map(|t| (o.tile_id(), t)))
                     .map(|(tile_id, t)| insertion_from_hovered_target(tile_id, t));
@@ -289,11 +319,95 @@ pub(super) fn decide_overlay_for_tree<Pane>(
     let disable_tiles_preview = matches!(drag_kind, DragKind::Subtree { internal: true, .. })
         && insertion_explicit.is_some();
 
-    OverlayDecision {
+    let decision = OverlayDecision {
         paint,
         insertion_explicit,
         fallback_zone,
         insertion_final,
         disable_tiles_preview,
+        policy_preview: None,
+        policy_rejected: false,
+    };
+    match policy {
+        Some(policy) => {
+            apply_docking_policy(tree, behavior, style, pointer_local, drag_kind, decision, policy)
+        }
+        None => decision,
+    }
+}
+
+/// Policy check for an internal drag that `egui_tiles` previews and applies itself (overlay
+/// targets are off for internal drags): only tiles' `dock_zone_at` candidate is asked about.
+///
+/// Accepted drops come back as [`OverlayDecision::NONE`]; see [`OverlayDecision::overrides_tiles`].
+pub(super) fn decide_tiles_drop_for_tree<Pane>(
+    tree: &Tree<Pane>,
+    behavior: &dyn Behavior<Pane>,
+    style: &egui::Style,
+    pointer_local: Pos2,
+    dragged_tile: TileId,
+    policy: &PolicyQuery<'_, Pane>,
+) -> OverlayDecision {
+    apply_docking_policy(
+        tree,
+        behavior,
+        style,
+        pointer_local,
+        DragKind::Subtree {
+            dragged_tile: Some(dragged_tile),
+            internal: true,
+        },
+        OverlayDecision::NONE,
+        policy,
+    )
+}
+
+/// Let the app's [`super::DockingPolicy`] accept, reject or rewrite what the release would do.
+///
+/// For internal drags without an explicit overlay hit, the candidate is `egui_tiles`' own
+/// `dock_zone_at` result: a rejection or rewrite then takes the drop over from tiles.
+fn apply_docking_policy<Pane>(
+    tree: &Tree<Pane>,
+    behavior: &dyn Behavior<Pane>,
+    style: &egui::Style,
+    pointer_local: Pos2,
+    drag_kind: DragKind,
+    decision: OverlayDecision,
+    policy: &PolicyQuery<'_, Pane>,
+) -> OverlayDecision {
+    let internal = matches!(drag_kind, DragKind::Subtree { internal: true, .. });
+    let candidate = decision.insertion_final.or_else(|| {
+        internal
+            .then(|| tree.dock_zone_at(behavior, style, pointer_local))
+            .flatten()
+            .map(|zone| zone.insertion_point)
+    });
+    let Some(candidate) = candidate else {
+        return decision;
+    };
+
+    let rejected = OverlayDecision {
+        disable_tiles_preview: internal,
+        policy_rejected: true,
+        ..OverlayDecision::NONE
+    };
+    match policy.verdict(tree, candidate) {
+        DropVerdict::Accept => decision,
+        DropVerdict::Reject => rejected,
+        DropVerdict::Rewrite(insertion) => {
+            let Some(preview_rect) = insertion_preview_rect(tree, insertion) else {
+                return rejected;
+            };
+            OverlayDecision {
+                insertion_explicit: Some(insertion),
+                insertion_final: Some(insertion),
+                disable_tiles_preview: internal,
+                policy_preview: Some(DockZone {
+                    insertion_point: insertion,
+                    preview_rect,
+                }),
+                ..OverlayDecision::NONE
+            }
+        }
     }
 }
@@ -117,6 +117,7 @@ fn internal_drag_only_paints_on_explicit_hit() {
             dragged_tile: Some(dragged),
             internal: true,
         },
+        None,
     );
     assert!(decision.paint.is_none());
     assert!(decision.insertion_final.is_none());
@@ -134,6 +135,7 @@ fn internal_drag_only_paints_on_explicit_hit() {
             dragged_tile: Some(dragged),
             internal: true,
         },
+        None,
     );
     assert!(matches!(decision.paint, Some(OverlayPaint::Inner(_))));
     assert!(decision.insertion_final.is_some());
@@ -161,6 +163,7 @@ fn window_move_does_not_dock_from_content_area_without_explicit_target() {
         pointer_no_hit,
         true,
         window_move_strict(),
+        None,
     );
     assert!(decision.paint.is_some());
     assert!(decision.insertion_explicit.is_none());
@@ -176,6 +179,7 @@ fn window_move_does_not_dock_from_content_area_without_explicit_target() {
         pointer_hit,
         true,
         window_move_strict(),
+        None,
     );
     // If you hit the explicit center overlay target, docking is allowed.
     assert!(decision.insertion_final.is_some());
@@ -199,6 +203,7 @@ fn window_move_can_be_configured_to_tab_dock_from_content_area() {
         pointer_no_hit,
         true,
         window_move_relaxed(),
+        None,
     );
     assert!(decision.insertion_explicit.is_none());
     assert!(decision.fallback_zone.is_some());
@@ -224,6 +229,7 @@ fn window_move_docks_when_hovering_tab_bar() {
         pointer_tab_bar,
         true,
         window_move_strict(),
+        None,
     );
     // Strict window-move docking from the tab bar should always be allowed and deterministic.
     assert!(decision.fallback_zone.is_some());
@@ -248,6 +254,7 @@ fn window_move_docks_when_hovering_title_band_on_non_tabs_tile() {
         pointer_title_band,
         true,
         window_move_strict(),
+        None,
     );
     assert!(decision.fallback_zone.is_some());
     assert!(decision.insertion_final.is_some());
@@ -262,6 +269,7 @@ fn window_move_docks_when_hovering_title_band_on_non_tabs_tile() {
         pointer_content,
         true,
         window_move_strict(),
+        None,
     );
     assert!(decision.insertion_explicit.is_none());
     assert!(decision.fallback_zone.is_none());
@@ -291,6 +299,7 @@ fn window_move_in_outer_band_without_explicit_target_is_disallowed() {
         pointer_outer_band,
         true,
         window_move_strict(),
+        None,
     );
     assert!(matches!(decision.paint, Some(OverlayPaint::Outer(_))));
     assert!(decision.insertion_explicit.is_none());
@@ -317,6 +326,7 @@ fn external_subtree_falls_back_to_dock_zone_when_not_explicit() {
             dragged_tile: None,
             internal: false,
         },
+        None,
     );
 
     assert!(decision.paint.is_some());
@@ -347,6 +357,7 @@ fn outer_overlay_is_mutually_exclusive_and_respects_internal_policy() {
             dragged_tile: Some(dragged),
             internal: true,
         },
+        None,
     );
     assert!(internal.paint.is_none());
 
@@ -361,6 +372,163 @@ fn outer_overlay_is_mutually_exclusive_and_respects_internal_policy() {
             dragged_tile: None,
             internal: false,
         },
+        None,
     );
     assert!(matches!(external.paint, Some(OverlayPaint::Outer(_))));
 }
+
+#[test]
+fn docking_policy_rejects_or_rewrites_the_overlay_decision() {
+    use super::docking_policy::{DropContext, DropVerdict, PolicyQuery};
+    use super::node_flags::NodeFlagsView;
+    use super::pane_query::PaneHost;
+
+    let mut behavior = DummyBehavior::default();
+    let (mut tree, dragged, other) = tabs_tree_two_panes_active(1);
+    let (dock_rect, style) = layout_tree(&mut tree, &mut behavior);
+    let pointer_hit = tree.tiles.rect(other).expect("active pane must have rect").center();
+    let root = tree.root.expect("tree must have a root");
+    let no_flags = std::collections::BTreeMap::new();
+
+    let decide = |policy: &(dyn Fn(&DropContext<'_, ()>) -> DropVerdict)| {
+        let query = PolicyQuery {
+            policy: Some(&policy),
+            source: PaneHost::RootDock,
+            dragged_tile: Some(dragged),
+            dragged_panes: vec![&()],
+            is_window_move: false,
+            target: PaneHost::RootDock,
+            target_flags: NodeFlagsView::new(&no_flags, PaneHost::RootDock),
+            undocking_anchor: None,
+        };
+        decide_overlay_for_tree(
+            &tree,
+            &behavior,
+            &style,
+            dock_rect,
+            pointer_hit,
+            true,
+            DragKind::Subtree {
+                dragged_tile: Some(dragged),
+                internal: true,
+            },
+            Some(&query),
+        )
+    };
+
+    let accepted = decide(&|_| DropVerdict::Accept);
+    assert!(matches!(accepted.paint, Some(OverlayPaint::Inner(_))));
+    assert!(!accepted.policy_rejected);
+
+    let rejected = decide(&|_| DropVerdict::Reject);
+    assert!(rejected.paint.is_none());
+    assert!(rejected.insertion_final.is_none());
+    assert!(rejected.policy_rejected);
+    // Internal drags: tiles must not show its own preview either.
+    assert!(rejected.disable_tiles_preview);
+
+    let rewrite = egui_tiles::InsertionPoint::new(root, egui_tiles::ContainerInsertion::Vertical(0));
+    let rewritten = decide(&|_| DropVerdict::Rewrite(rewrite));
+    assert!(rewritten.paint.is_none());
+    assert_eq!(rewritten.insertion_final, Some(rewrite));
+    let preview = rewritten.policy_preview.expect("rewrite must be previewed");
+    assert_eq!(preview.insertion_point, rewrite);
+    let root_rect = tree.tiles.rect(root).expect("root must have rect");
+    assert!((preview.preview_rect.max.y - root_rect.center().y).abs() < 0.5);
+}
+
+#[test]
+fn node_flags_hide_the_targets_they_forbid() {
+    use super::docking_policy::PolicyQuery;
+    use super::node_flags::{DockNodeFlags, NodeFlagsView};
+    use super::pane_query::PaneHost;
+
+    let mut behavior = DummyBehavior::default();
+    let (mut tree, dragged, other) = tabs_tree_two_panes_active(1);
+    let (dock_rect, style) = layout_tree(&mut tree, &mut behavior);
+    let root = tree.root.expect("tree must have a root");
+    let other_rect = tree.tiles.rect(other).expect("active pane must have rect");
+
+    let decide = |flags: DockNodeFlags, pointer: Pos2| {
+        let node_flags = std::collections::BTreeMap::from([((PaneHost::RootDock, root), flags)]);
+        let query = PolicyQuery {
+            policy: None,
+            source: PaneHost::RootDock,
+            dragged_tile: Some(dragged),
+            dragged_panes: Vec::new(),
+            is_window_move: false,
+            target: PaneHost::RootDock,
+            target_flags: NodeFlagsView::new(&node_flags, PaneHost::RootDock),
+            undocking_anchor: None,
+        };
+        decide_overlay_for_tree(
+            &tree,
+            &behavior,
+            &style,
+            dock_rect,
+            pointer,
+            true,
+            DragKind::Subtree {
+                dragged_tile: Some(dragged),
+                internal: true,
+            },
+            Some(&query),
+        )
+    };
+
+    let center = other_rect.center();
+    assert!(decide(DockNodeFlags::NONE, center).insertion_final.is_some());
+    assert!(decide(DockNodeFlags::NO_SPLIT, center).insertion_final.is_some());
+
+    let no_tab_insert = decide(DockNodeFlags::NO_TAB_INSERT, center);
+    assert!(no_tab_insert.insertion_final.is_none());
+
+    let no_docking = decide(DockNodeFlags::NO_DOCKING_OVER_ME, center);
+    assert!(no_docking.paint.is_none());
+    assert!(no_docking.insertion_final.is_none());
+
+    // The outer band must not offer a root split for a `NO_SPLIT` root.
+    let pointer_outer_band = Pos2::new(dock_rect.left() + 2.0, dock_rect.center().y);
+    let outer = decide(DockNodeFlags::NO_SPLIT, pointer_outer_band);
+    assert!(!matches!(outer.paint, Some(OverlayPaint::Outer(_))));
+}
+
+#[test]
+fn node_flags_apply_to_drops_left_to_tiles() {
+    use super::docking_policy::PolicyQuery;
+    use super::node_flags::{DockNodeFlags, NodeFlagsView};
+    use super::overlay_decision::decide_tiles_drop_for_tree;
+    use super::pane_query::PaneHost;
+
+    let mut behavior = DummyBehavior::default();
+    let (mut tree, dragged, other) = tabs_tree_two_panes_active(1);
+    let (_dock_rect, style) = layout_tree(&mut tree, &mut behavior);
+    let root = tree.root.expect("tree must have a root");
+    let other_rect = tree.tiles.rect(other).expect("active pane must have rect");
+
+    let decide = |flags: DockNodeFlags, pointer: Pos2| {
+        let node_flags = std::collections::BTreeMap::from([((PaneHost::RootDock, root), flags)]);
+        let query = PolicyQuery {
+            policy: None,
+            source: PaneHost::RootDock,
+            dragged_tile: Some(dragged),
+            dragged_panes: Vec::new(),
+            is_window_move: false,
+            target: PaneHost::RootDock,
+            target_flags: NodeFlagsView::new(&node_flags, PaneHost::RootDock),
+            undocking_anchor: None,
+        };
+        decide_tiles_drop_for_tree(&tree, &behavior, &style, pointer, dragged, &query)
+    };
+
+    let center = other_rect.center();
+    let bottom_edge = Pos2::new(center.x, other_rect.bottom() - 4.0);
+    assert!(!decide(DockNodeFlags::NONE, bottom_edge).overrides_tiles());
+    assert!(!decide(DockNodeFlags::NO_SPLIT, center).overrides_tiles());
+
+    let no_split = decide(DockNodeFlags::NO_SPLIT, bottom_edge);
+    assert!(no_split.policy_rejected);
+    assert!(no_split.disable_tiles_preview);
+
+    assert!(decide(DockNodeFlags::NO_DOCKING_OVER_ME, center).policy_rejected);
+}
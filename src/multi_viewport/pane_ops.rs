@@ -0,0 +1,490 @@
+use egui::{Context, Rect, Vec2, ViewportBuilder, ViewportId};
+use egui_tiles::{Behavior, InsertionPoint, TileId, Tree};
+
+use super::DockingMultiViewport;
+use super::debug_log::DebugLogRecord;
+use super::docking_policy::NewWindowKind;
+use super::events::DockEvent;
+use super::geometry::infer_detached_geometry;
+use super::monitor_clamp::clamp_outer_pos_if_monitors_available;
+use super::pane_query::PaneHost;
+use super::title::title_for_detached_subtree;
+use super::types::{DetachedDock, FLOATING_MIN_SIZE, FloatingId};
+
+fn viewport_inner_rect_in_global(ctx: &Context, viewport_id: ViewportId) -> Option<Rect> {
+    ctx.input(|i| i.raw.viewports.get(&viewport_id)?.inner_rect)
+}
+
+impl<Pane> DockingMultiViewport<Pane> {
+    /// Programmatic tear-off: move the first pane matching `predicate` into a new native viewport.
+    ///
+    /// This is the code path behind "Open in new window" menu items; it produces the same result
+    /// as dragging the pane out of the dock and releasing it outside.
+    ///
+    /// `outer_rect` (global points) places the new window: `min` is the outer position and
+    /// `size()` the inner size. With `None`, the geometry is inferred from the pane's last rect.
+    ///
+    /// Returns the new [`ViewportId`], or `None` if no pane matches or the
+    /// [`super::DockingPolicy`] rejects the tear-off.
+    pub fn detach_pane_to_viewport(
+        &mut self,
+        ctx: &Context,
+        behavior: &mut dyn Behavior<Pane>,
+        mut predicate: impl FnMut(&Pane) -> bool,
+        outer_rect: Option<Rect>,
+    ) -> Option<ViewportId> {
+        let location = self.find_pane_dyn(&mut predicate)?;
+        self.detach_tile_to_viewport(ctx, behavior, location.host, location.tile_id, outer_rect)
+    }
+
+    /// Like [`Self::detach_pane_to_viewport`], but for an explicit tile (pane or container)
+    /// inside `host`, e.g. [`super::PaneLocation::parent_tabs`] to detach a whole tab group.
+    pub fn detach_tile_to_viewport(
+        &mut self,
+        ctx: &Context,
+        behavior: &mut dyn Behavior<Pane>,
+        host: PaneHost,
+        tile_id: TileId,
+        outer_rect: Option<Rect>,
+    ) -> Option<ViewportId> {
+        let tree = self.tree_for_pane_host(host)?;
+        if !self.docking_policy_allows_tear_off(host, tree, tile_id, NewWindowKind::Native, true) {
+            self.debug_log_record(DebugLogRecord::TearOff {
+                source_host: host,
+                tile_id,
+                floating: false,
+                api: true,
+                accepted: false,
+                new_host: None,
+            });
+            return None;
+        }
+        let pane_rect_last = tree.tiles.rect(tile_id);
+
+        let subtree =
+            self.take_subtree_from_host_for_drop(ctx, behavior, host.window_host(), tile_id)?;
+
+        let title = title_for_detached_subtree(&subtree, behavior);
+        let (pos, size) = match outer_rect {
+            Some(rect) => (rect.min, rect.size()),
+            None => infer_detached_geometry(
+                pane_rect_last,
+                None,
+                viewport_inner_rect_in_global(ctx, host.viewport()),
+                self.options.default_detached_inner_size,
+            ),
+        };
+        let pos = clamp_outer_pos_if_monitors_available(ctx, pos, size);
+
+        let (viewport_id, serial) = self.allocate_detached_viewport_id();
+        let builder = ViewportBuilder::default()
+            .with_title(title)
+            .with_position(pos)
+            .with_inner_size(size)
+            .with_decorations(self.options.detached_viewport_decorations);
+
+        let detached_tree_id =
+            egui::Id::new((self.tree.id(), "egui_docking_detached_tree", serial));
+        let detached_tree = Tree::new(detached_tree_id, subtree.root, subtree.tiles);
+
+        self.detached.insert(
+            viewport_id,
+            DetachedDock {
+                serial,
+                tree: detached_tree,
+                builder,
+                options: Default::default(),
+            },
+        );
+
+        self.push_dock_event(DockEvent::PaneDetached {
+            source: host,
+            viewport: viewport_id,
+        });
+        self.debug_log_record(DebugLogRecord::TearOff {
+            source_host: host,
+            tile_id,
+            floating: false,
+            api: true,
+            accepted: true,
+            new_host: Some(PaneHost::DetachedViewport {
+                viewport: viewport_id,
+            }),
+        });
+
+        ctx.request_repaint();
+        ctx.request_repaint_of(ViewportId::ROOT);
+        Some(viewport_id)
+    }
+
+    /// Programmatic float: move the first pane matching `predicate` into a new contained floating
+    /// window shown inside `viewport` (root or detached).
+    ///
+    /// `offset_in_dock` is relative to the top-left of that viewport's dock area. `size` is raised
+    /// to at least [`FLOATING_MIN_SIZE`], the smallest size floating windows are shown at. If
+    /// `viewport` no longer exists once the pane has been extracted (e.g. it was the last pane of
+    /// that detached viewport), the window is created in the root viewport instead.
+    ///
+    /// Returns the floating window id, or `None` if no pane matches or the
+    /// [`super::DockingPolicy`] rejects the tear-off.
+    pub fn float_pane(
+        &mut self,
+        ctx: &Context,
+        behavior: &mut dyn Behavior<Pane>,
+        mut predicate: impl FnMut(&Pane) -> bool,
+        viewport: ViewportId,
+        offset_in_dock: Vec2,
+        size: Vec2,
+    ) -> Option<FloatingId> {
+        let location = self.find_pane_dyn(&mut predicate)?;
+        self.float_tile(
+            ctx,
+            behavior,
+            location.host,
+            location.tile_id,
+            viewport,
+            offset_in_dock,
+            size,
+        )
+    }
+
+    /// Like [`Self::float_pane`], but for an explicit tile (pane or container) inside `host`,
+    /// e.g. [`super::PaneLocation::parent_tabs`] to float a whole tab group.
+    #[allow(clippy::too_many_arguments)]
+    pub fn float_tile(
+        &mut self,
+        ctx: &Context,
+        behavior: &mut dyn Behavior<Pane>,
+        host: PaneHost,
+        tile_id: TileId,
+        viewport: ViewportId,
+        offset_in_dock: Vec2,
+        size: Vec2,
+    ) -> Option<FloatingId> {
+        if viewport != ViewportId::ROOT && !self.detached.contains_key(&viewport) {
+            return None;
+        }
+        let tree = self.tree_for_pane_host(host)?;
+        let new_window = NewWindowKind::Floating { viewport };
+        if !self.docking_policy_allows_tear_off(host, tree, tile_id, new_window, true) {
+            self.debug_log_record(DebugLogRecord::TearOff {
+                source_host: host,
+                tile_id,
+                floating: true,
+                api: true,
+                accepted: false,
+                new_host: None,
+            });
+            return None;
+        }
+
+        let subtree =
+            self.take_subtree_from_host_for_drop(ctx, behavior, host.window_host(), tile_id)?;
+
+        let viewport = if viewport == ViewportId::ROOT || self.detached.contains_key(&viewport) {
+            viewport
+        } else {
+            ViewportId::ROOT
+        };
+        let size = size.max(FLOATING_MIN_SIZE);
+        let floating_id = self.insert_floating_window(viewport, subtree, offset_in_dock, size);
+
+        self.debug_log_record(DebugLogRecord::TearOff {
+            source_host: host,
+            tile_id,
+            floating: true,
+            api: true,
+            accepted: true,
+            new_host: Some(PaneHost::Floating {
+                viewport,
+                floating: floating_id,
+            }),
+        });
+
+        ctx.request_repaint();
+        ctx.request_repaint_of(viewport);
+        Some(floating_id)
+    }
+
+    /// Dock a contained floating window into `target` (the inverse of [`Self::float_pane`]).
+    ///
+    /// `insertion` refers to tiles of the target tree; `None` lets `egui_tiles` pick a default
+    /// spot. The floating window is removed on success. If `target` does not exist, the window
+    /// is left untouched.
+    ///
+    /// Returns `true` if the window was docked.
+    pub fn dock_floating_window(
+        &mut self,
+        floating: FloatingId,
+        target: PaneHost,
+        insertion: Option<InsertionPoint>,
+    ) -> bool {
+        let Some(viewport) = self.viewport_of_floating(floating) else {
+            return false;
+        };
+        if target == (PaneHost::Floating { viewport, floating }) {
+            return false;
+        }
+        if self.tree_for_pane_host(target).is_none() {
+            return false;
+        }
+        let Some(window) = self
+            .floating
+            .get(&viewport)
+            .and_then(|m| m.windows.get(&floating))
+        else {
+            return false;
+        };
+        let (offset_in_dock, size) = (window.offset_in_dock, window.size);
+
+        let Some(subtree) = self.take_whole_floating_tree(viewport, floating) else {
+            return false;
+        };
+
+        match self.insert_subtree_into_host(target.window_host(), subtree, insertion) {
+            Ok(()) => {
+                self.push_dock_event(DockEvent::PaneRedocked {
+                    source: PaneHost::Floating { viewport, floating },
+                    target,
+                });
+                if self.options.debug_event_log {
+                    self.debug_log_event(format!(
+                        "api_dock_floating viewport={viewport:?} floating={floating:?} -> target={target:?} insertion={insertion:?}"
+                    ));
+                }
+                true
+            }
+            Err(subtree) => {
+                // Should not happen (target checked above), but never lose panes.
+                self.insert_floating_window(viewport, subtree, offset_in_dock, size);
+                false
+            }
+        }
+    }
+
+    fn viewport_of_floating(&self, floating: FloatingId) -> Option<ViewportId> {
+        self.floating
+            .iter()
+            .find(|(_, manager)| manager.windows.contains_key(&floating))
+            .map(|(&viewport, _)| viewport)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multi_viewport::test_support::{DummyBehavior, docking_with_tabs};
+
+    #[test]
+    fn detach_pane_moves_it_into_a_new_viewport() {
+        let mut docking = docking_with_tabs(&[1, 2, 3]);
+        let ctx = egui::Context::default();
+        let mut behavior = DummyBehavior;
+
+        let rect = Rect::from_min_size(egui::pos2(100.0, 50.0), egui::vec2(400.0, 300.0));
+        let viewport = docking
+            .detach_pane_to_viewport(&ctx, &mut behavior, |&p| p == 2, Some(rect))
+            .unwrap();
+
+        assert_eq!(docking.detached_viewport_count(), 1);
+        let detached = docking.detached.get(&viewport).unwrap();
+        assert_eq!(detached.builder.title.as_deref(), Some("pane 2"));
+        assert_eq!(detached.builder.position, Some(rect.min));
+        assert_eq!(detached.builder.inner_size, Some(rect.size()));
+
+        let location = docking.find_pane(|&p| p == 2).unwrap();
+        assert_eq!(location.host, PaneHost::DetachedViewport { viewport });
+        assert_eq!(
+            docking.find_pane(|&p| p == 1).unwrap().host,
+            PaneHost::RootDock
+        );
+
+        assert!(docking
+            .detach_pane_to_viewport(&ctx, &mut behavior, |&p| p == 42, None)
+            .is_none());
+        assert_eq!(docking.detached_viewport_count(), 1);
+    }
+
+    #[test]
+    fn detach_last_pane_of_detached_viewport_replaces_it() {
+        let mut docking = docking_with_tabs(&[1, 2]);
+        let ctx = egui::Context::default();
+        let mut behavior = DummyBehavior;
+
+        let first = docking
+            .detach_pane_to_viewport(&ctx, &mut behavior, |&p| p == 2, None)
+            .unwrap();
+        let second = docking
+            .detach_pane_to_viewport(&ctx, &mut behavior, |&p| p == 2, None)
+            .unwrap();
+
+        assert_ne!(first, second);
+        assert!(!docking.detached.contains_key(&first));
+        assert_eq!(
+            docking.find_pane(|&p| p == 2).unwrap().host,
+            PaneHost::DetachedViewport { viewport: second }
+        );
+    }
+
+    #[test]
+    fn float_pane_and_dock_it_back() {
+        let mut docking = docking_with_tabs(&[1, 2, 3]);
+        let ctx = egui::Context::default();
+        let mut behavior = DummyBehavior;
+
+        let floating = docking
+            .float_pane(
+                &ctx,
+                &mut behavior,
+                |&p| p == 3,
+                ViewportId::ROOT,
+                egui::vec2(40.0, 30.0),
+                egui::vec2(320.0, 240.0),
+            )
+            .unwrap();
+
+        assert_eq!(docking.floating_window_count(), 1);
+        let manager = docking.floating.get(&ViewportId::ROOT).unwrap();
+        assert_eq!(manager.z_order, vec![floating]);
+        let window = manager.windows.get(&floating).unwrap();
+        assert_eq!(window.offset_in_dock, egui::vec2(40.0, 30.0));
+        assert_eq!(window.size, egui::vec2(320.0, 240.0));
+        assert_eq!(
+            docking.find_pane(|&p| p == 3).unwrap().host,
+            PaneHost::Floating {
+                viewport: ViewportId::ROOT,
+                floating
+            }
+        );
+
+        // Unknown target viewport is rejected without touching the pane.
+        assert!(docking
+            .float_pane(
+                &ctx,
+                &mut behavior,
+                |&p| p == 1,
+                ViewportId::from_hash_of("missing"),
+                egui::Vec2::ZERO,
+                egui::vec2(320.0, 240.0),
+            )
+            .is_none());
+        assert_eq!(docking.find_pane(|&p| p == 1).unwrap().host, PaneHost::RootDock);
+
+        assert!(docking.dock_floating_window(floating, PaneHost::RootDock, None));
+        assert_eq!(docking.floating_window_count(), 0);
+        assert_eq!(docking.find_pane(|&p| p == 3).unwrap().host, PaneHost::RootDock);
+        assert!(!docking.dock_floating_window(floating, PaneHost::RootDock, None));
+    }
+
+    #[test]
+    fn pane_ops_emit_dock_events() {
+        let mut docking = docking_with_tabs(&[1, 2, 3]);
+        let ctx = egui::Context::default();
+        let mut behavior = DummyBehavior;
+
+        let viewport = docking
+            .detach_pane_to_viewport(&ctx, &mut behavior, |&p| p == 2, None)
+            .unwrap();
+        let floating = docking
+            .float_pane(
+                &ctx,
+                &mut behavior,
+                |&p| p == 3,
+                ViewportId::ROOT,
+                egui::Vec2::ZERO,
+                egui::vec2(320.0, 240.0),
+            )
+            .unwrap();
+        assert!(docking.dock_floating_window(
+            floating,
+            PaneHost::DetachedViewport { viewport },
+            None
+        ));
+
+        let source = PaneHost::Floating {
+            viewport: ViewportId::ROOT,
+            floating,
+        };
+        assert_eq!(
+            docking.take_events(),
+            vec![
+                DockEvent::PaneDetached {
+                    source: PaneHost::RootDock,
+                    viewport
+                },
+                DockEvent::FloatingCreated {
+                    viewport: ViewportId::ROOT,
+                    floating
+                },
+                DockEvent::FloatingClosed {
+                    viewport: ViewportId::ROOT,
+                    floating
+                },
+                DockEvent::PaneRedocked {
+                    source,
+                    target: PaneHost::DetachedViewport { viewport }
+                },
+            ]
+        );
+        assert!(docking.take_events().is_empty());
+    }
+
+    #[test]
+    fn docking_policy_can_refuse_new_windows() {
+        use crate::multi_viewport::{
+            DockingPolicy, DropContext, DropVerdict, TearOffContext, TearOffVerdict,
+        };
+
+        /// Pane 2 may float inside the root viewport, nothing may open a native window.
+        struct NoNativeWindows;
+
+        impl DockingPolicy<usize> for NoNativeWindows {
+            fn check_drop(&self, _drop: &DropContext<'_, usize>) -> DropVerdict {
+                DropVerdict::Accept
+            }
+
+            fn check_tear_off(&self, tear_off: &TearOffContext<'_, usize>) -> TearOffVerdict {
+                assert!(tear_off.is_programmatic);
+                match tear_off.new_window {
+                    NewWindowKind::Floating { .. } if tear_off.panes == [&2] => {
+                        TearOffVerdict::Accept
+                    }
+                    _ => TearOffVerdict::Reject,
+                }
+            }
+        }
+
+        let mut docking = docking_with_tabs(&[1, 2]);
+        docking.set_docking_policy(NoNativeWindows);
+        let ctx = egui::Context::default();
+        let mut behavior = DummyBehavior;
+
+        assert!(docking
+            .detach_pane_to_viewport(&ctx, &mut behavior, |&p| p == 2, None)
+            .is_none());
+        assert!(docking
+            .float_pane(
+                &ctx,
+                &mut behavior,
+                |&p| p == 1,
+                ViewportId::ROOT,
+                egui::Vec2::ZERO,
+                egui::vec2(320.0, 240.0),
+            )
+            .is_none());
+        assert_eq!(docking.find_pane(|&p| p == 1).unwrap().host, PaneHost::RootDock);
+        assert_eq!(docking.find_pane(|&p| p == 2).unwrap().host, PaneHost::RootDock);
+        assert!(docking.take_events().is_empty());
+
+        assert!(docking
+            .float_pane(
+                &ctx,
+                &mut behavior,
+                |&p| p == 2,
+                ViewportId::ROOT,
+                egui::Vec2::ZERO,
+                egui::vec2(320.0, 240.0),
+            )
+            .is_some());
+    }
+}
@@ -0,0 +1,340 @@
+use egui::{Context, ViewportId};
+use egui_tiles::{Container, ContainerKind, Tile, TileId, Tree};
+
+use super::DockingMultiViewport;
+use super::host::WindowHost;
+use super::types::FloatingId;
+
+/// Which dock tree a pane lives in.
+///
+/// This is the public counterpart of the internal window-host model:
+/// - the root dock (hosted by [`ViewportId::ROOT`])
+/// - the dock tree of a detached native viewport (OS window)
+/// - a contained floating window inside some viewport
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PaneHost {
+    /// The root dock tree ([`DockingMultiViewport::tree`]).
+    RootDock,
+
+    /// The dock tree of a detached native viewport.
+    DetachedViewport { viewport: ViewportId },
+
+    /// A contained floating window, shown inside `viewport` (root or detached).
+    Floating {
+        viewport: ViewportId,
+        floating: FloatingId,
+    },
+}
+
+impl PaneHost {
+    /// The viewport that renders this host.
+    pub fn viewport(self) -> ViewportId {
+        match self {
+            Self::RootDock => ViewportId::ROOT,
+            Self::DetachedViewport { viewport } => viewport,
+            Self::Floating { viewport, .. } => viewport,
+        }
+    }
+
+    pub(super) fn window_host(self) -> WindowHost {
+        match self {
+            Self::RootDock => WindowHost::DockTree {
+                viewport: ViewportId::ROOT,
+            },
+            Self::DetachedViewport { viewport } => WindowHost::DockTree { viewport },
+            Self::Floating { viewport, floating } => WindowHost::Floating { viewport, floating },
+        }
+    }
+}
+
+impl WindowHost {
+    pub(super) fn pane_host(self) -> PaneHost {
+        match self {
+            Self::DockTree { viewport } | Self::NativeViewport { viewport } => {
+                if viewport == ViewportId::ROOT {
+                    PaneHost::RootDock
+                } else {
+                    PaneHost::DetachedViewport { viewport }
+                }
+            }
+            Self::Floating { viewport, floating } => PaneHost::Floating { viewport, floating },
+        }
+    }
+}
+
+/// The result of [`DockingMultiViewport::find_pane`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PaneLocation {
+    /// The tree that owns the pane.
+    pub host: PaneHost,
+
+    /// The pane tile inside the host tree.
+    pub tile_id: TileId,
+
+    /// The parent `Tabs` container, if the pane is a tab.
+    pub parent_tabs: Option<TileId>,
+}
+
+/// Find the first reachable pane matching `predicate`, in depth-first child order.
+///
+/// Unreachable tiles (e.g. leftovers waiting for `egui_tiles` GC) are ignored.
+fn find_pane_in_tree<Pane>(
+    tree: &Tree<Pane>,
+    predicate: &mut dyn FnMut(&Pane) -> bool,
+) -> Option<(TileId, Option<TileId>)> {
+    let root = tree.root?;
+
+    let mut stack: Vec<TileId> = vec![root];
+    while let Some(tile_id) = stack.pop() {
+        let Some(tile) = tree.tiles.get(tile_id) else {
+            continue;
+        };
+        match tile {
+            Tile::Pane(pane) => {
+                if predicate(pane) {
+                    let parent_tabs = tree.tiles.parent_of(tile_id).filter(|&parent| {
+                        tree.tiles.get(parent).and_then(|t| t.kind()) == Some(ContainerKind::Tabs)
+                    });
+                    return Some((tile_id, parent_tabs));
+                }
+            }
+            Tile::Container(container) => {
+                let children: Vec<TileId> = container.children().copied().collect();
+                stack.extend(children.into_iter().rev());
+            }
+        }
+    }
+
+    None
+}
+
+/// Activate `tile_id` in every `Tabs` ancestor, so the tile ends up visible.
+pub(super) fn activate_tile_in_tree<Pane>(tree: &mut Tree<Pane>, tile_id: TileId) {
+    let mut child = tile_id;
+    while let Some(parent) = tree.tiles.parent_of(child) {
+        if let Some(Tile::Container(Container::Tabs(tabs))) = tree.tiles.get_mut(parent) {
+            tabs.set_active(child);
+        }
+        child = parent;
+    }
+}
+
+impl<Pane> DockingMultiViewport<Pane> {
+    /// Find the first pane matching `predicate` across all hosts.
+    ///
+    /// Search order: root dock, detached viewports, then contained floating windows.
+    /// Returns `None` if no pane matches.
+    pub fn find_pane(&self, mut predicate: impl FnMut(&Pane) -> bool) -> Option<PaneLocation> {
+        self.find_pane_dyn(&mut predicate)
+    }
+
+    /// The dock tree behind a [`PaneHost`], if it still exists.
+    pub fn tree_for_pane_host(&self, host: PaneHost) -> Option<&Tree<Pane>> {
+        self.tree_for_host(host.window_host())
+    }
+
+    /// Make the first pane matching `predicate` the visible/active one, wherever it lives.
+    ///
+    /// - Every `Tabs` ancestor activates the branch containing the pane.
+    /// - A contained floating window is expanded and raised to the top of its z-order.
+    /// - If the host is rendered by a detached native viewport, that OS window is focused
+    ///   via [`egui::ViewportCommand::Focus`].
+    ///
+    /// Returns the pane location, or `None` if no pane matches.
+    pub fn focus_pane(
+        &mut self,
+        ctx: &Context,
+        mut predicate: impl FnMut(&Pane) -> bool,
+    ) -> Option<PaneLocation> {
+        let location = self.find_pane_dyn(&mut predicate)?;
+
+        if let Some(tree) = self.tree_mut_for_host(location.host.window_host()) {
+            activate_tile_in_tree(tree, location.tile_id);
+        }
+
+        if let PaneHost::Floating { viewport, floating } = location.host
+            && let Some(manager) = self.floating.get_mut(&viewport)
+        {
+            if let Some(window) = manager.windows.get_mut(&floating) {
+                window.collapsed = false;
+            }
+            manager.bring_to_front(floating);
+        }
+
+        let viewport = location.host.viewport();
+        if viewport != ViewportId::ROOT {
+            ctx.send_viewport_cmd_to(viewport, egui::ViewportCommand::Focus);
+            ctx.request_repaint_of(viewport);
+        }
+        ctx.request_repaint();
+
+        if self.options.debug_event_log {
+            self.debug_log_event(format!(
+                "focus_pane host={:?} tile={:?}",
+                location.host, location.tile_id
+            ));
+        }
+
+        Some(location)
+    }
+
+    pub(super) fn find_pane_dyn(
+        &self,
+        predicate: &mut dyn FnMut(&Pane) -> bool,
+    ) -> Option<PaneLocation> {
+        if let Some((tile_id, parent_tabs)) = find_pane_in_tree(&self.tree, predicate) {
+            return Some(PaneLocation {
+                host: PaneHost::RootDock,
+                tile_id,
+                parent_tabs,
+            });
+        }
+
+        for (&viewport, detached) in &self.detached {
+            if let Some((tile_id, parent_tabs)) = find_pane_in_tree(&detached.tree, predicate) {
+                return Some(PaneLocation {
+                    host: PaneHost::DetachedViewport { viewport },
+                    tile_id,
+                    parent_tabs,
+                });
+            }
+        }
+
+        for (&viewport, manager) in &self.floating {
+            for (&floating, window) in &manager.windows {
+                if let Some((tile_id, parent_tabs)) = find_pane_in_tree(&window.tree, predicate) {
+                    return Some(PaneLocation {
+                        host: PaneHost::Floating { viewport, floating },
+                        tile_id,
+                        parent_tabs,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::multi_viewport::test_support::{docking_with_tabs, tabs_tree};
+    use crate::multi_viewport::types::{DetachedDock, FloatingDockWindow, FloatingManager};
+
+    fn docking_with_all_hosts() -> (DockingMultiViewport<usize>, ViewportId) {
+        let mut docking = docking_with_tabs(&[1, 2]);
+
+        let viewport = ViewportId::from_hash_of("detached");
+        docking.detached.insert(
+            viewport,
+            DetachedDock {
+                serial: 1,
+                tree: tabs_tree("detached_tree", &[10, 11]),
+                builder: egui::ViewportBuilder::default(),
+                options: Default::default(),
+            },
+        );
+
+        docking.floating.insert(
+            viewport,
+            FloatingManager {
+                windows: BTreeMap::from([(
+                    7,
+                    FloatingDockWindow {
+                        tree: tabs_tree("floating_tree", &[100]),
+                        offset_in_dock: egui::Vec2::ZERO,
+                        size: egui::vec2(200.0, 100.0),
+                        collapsed: false,
+                        drag: None,
+                        resize: None,
+                    },
+                )]),
+                z_order: vec![7],
+            },
+        );
+
+        (docking, viewport)
+    }
+
+    #[test]
+    fn find_pane_reports_host_tile_and_parent_tabs() {
+        let (docking, viewport) = docking_with_all_hosts();
+
+        let root = docking.find_pane(|&p| p == 2).unwrap();
+        assert_eq!(root.host, PaneHost::RootDock);
+        assert_eq!(root.parent_tabs, docking.tree.root);
+        assert!(matches!(docking.tree.tiles.get(root.tile_id), Some(Tile::Pane(2))));
+
+        let detached = docking.find_pane(|&p| p == 11).unwrap();
+        assert_eq!(detached.host, PaneHost::DetachedViewport { viewport });
+
+        let floating = docking.find_pane(|&p| p == 100).unwrap();
+        assert_eq!(
+            floating.host,
+            PaneHost::Floating {
+                viewport,
+                floating: 7
+            }
+        );
+        let tree = docking.tree_for_pane_host(floating.host).unwrap();
+        assert!(matches!(tree.tiles.get(floating.tile_id), Some(Tile::Pane(100))));
+
+        assert!(docking.find_pane(|&p| p == 999).is_none());
+    }
+
+    #[test]
+    fn find_pane_without_tabs_parent() {
+        let mut tiles = egui_tiles::Tiles::default();
+        let a = tiles.insert_pane(1usize);
+        let b = tiles.insert_pane(2usize);
+        let root = tiles.insert_horizontal_tile(vec![a, b]);
+        let docking = DockingMultiViewport::new(Tree::new(egui::Id::new("root"), root, tiles));
+
+        let location = docking.find_pane(|&p| p == 2).unwrap();
+        assert_eq!(location.tile_id, b);
+        assert_eq!(location.parent_tabs, None);
+    }
+
+    #[test]
+    fn focus_pane_activates_tab_and_raises_floating_window() {
+        let (mut docking, viewport) = docking_with_all_hosts();
+        let manager = docking.floating.get_mut(&viewport).unwrap();
+        manager.windows.insert(
+            8,
+            FloatingDockWindow {
+                tree: tabs_tree("floating_tree_2", &[200, 201]),
+                offset_in_dock: egui::Vec2::ZERO,
+                size: egui::vec2(200.0, 100.0),
+                collapsed: true,
+                drag: None,
+                resize: None,
+            },
+        );
+        manager.z_order = vec![8, 7];
+
+        let ctx = egui::Context::default();
+        let location = docking.focus_pane(&ctx, |&p| p == 200).unwrap();
+
+        let manager = docking.floating.get(&viewport).unwrap();
+        assert_eq!(manager.z_order, vec![7, 8]);
+        let window = manager.windows.get(&8).unwrap();
+        assert!(!window.collapsed);
+        let Some(Tile::Container(Container::Tabs(tabs))) =
+            window.tree.tiles.get(location.parent_tabs.unwrap())
+        else {
+            panic!("expected a Tabs parent");
+        };
+        assert_eq!(tabs.active, Some(location.tile_id));
+
+        let location = docking.focus_pane(&ctx, |&p| p == 2).unwrap();
+        let Some(Tile::Container(Container::Tabs(tabs))) =
+            docking.tree.tiles.get(location.parent_tabs.unwrap())
+        else {
+            panic!("expected a Tabs parent");
+        };
+        assert_eq!(tabs.active, Some(location.tile_id));
+    }
+}
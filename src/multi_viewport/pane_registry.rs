@@ -1,3 +1,5 @@
+use std::hash::Hash;
+
 use serde::de::DeserializeOwned;
 
 /// A reusable mapping layer between `Pane` ⇄ `PaneId` for layout persistence.
@@ -6,7 +8,8 @@ use serde::de::DeserializeOwned;
 /// - Layout snapshots store only `PaneId`, never the `Pane` value itself (keep the RON small and portable).
 /// - The app decides how to restore a `Pane` from a `PaneId` (lazy loading, placeholder panes, migrations, etc).
 pub trait PaneRegistry<Pane> {
-    type PaneId: Clone + serde::Serialize + DeserializeOwned;
+    /// Compared and hashed to report pane ids that occur more than once in a loaded snapshot.
+    type PaneId: Clone + Eq + Hash + serde::Serialize + DeserializeOwned;
 
     fn pane_id(&mut self, pane: &Pane) -> Self::PaneId;
     fn pane_from_id(&mut self, id: Self::PaneId) -> Pane;
@@ -18,6 +21,29 @@ pub trait PaneRegistry<Pane> {
     fn try_pane_from_id(&mut self, id: Self::PaneId) -> Option<Pane> {
         Some(self.pane_from_id(id))
     }
+
+    /// Rewrite a loaded `PaneId` before its pane is restored (renamed or merged tools).
+    ///
+    /// Called for every pane of every loaded snapshot, so the mapping must also accept current ids.
+    /// Return `None` to drop the pane, e.g. the second of two tools merged into one.
+    /// The default implementation keeps every id.
+    fn migrate_pane_id(&mut self, id: Self::PaneId) -> Option<Self::PaneId> {
+        Some(id)
+    }
+}
+
+/// Opt-in extension of [`PaneRegistry`] that also persists per-pane state (an open file path, a
+/// camera position, ...) next to the `PaneId`.
+///
+/// The blob format is up to the app (e.g. RON or JSON text). Only the `*_with_state_registry`
+/// entry points use it; the `PaneRegistry` path stays id-only.
+pub trait PaneStateRegistry<Pane>: PaneRegistry<Pane> {
+    /// Serialize the state of `pane`, or `None` if there is nothing worth storing.
+    fn save_pane_state(&mut self, pane: &Pane) -> Option<String>;
+
+    /// Apply a blob from [`Self::save_pane_state`] to a pane just created by
+    /// [`PaneRegistry::try_pane_from_id`]. Invalid blobs should be ignored.
+    fn load_pane_state(&mut self, pane: &mut Pane, state: &str);
 }
 
 /// Convenience helper: build a [`PaneRegistry`] from two closures.
@@ -39,7 +65,7 @@ impl<PaneId, ToId, FromId> SimplePaneRegistry<PaneId, ToId, FromId> {
 
 impl<Pane, PaneId, ToId, FromId> PaneRegistry<Pane> for SimplePaneRegistry<PaneId, ToId, FromId>
 where
-    PaneId: Clone + serde::Serialize + DeserializeOwned,
+    PaneId: Clone + Eq + Hash + serde::Serialize + DeserializeOwned,
     ToId: FnMut(&Pane) -> PaneId,
     FromId: FnMut(PaneId) -> Pane,
 {
@@ -1,19 +1,47 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::hash::Hash;
 use std::path::Path;
 
-use egui::{Context, Id, Pos2, Vec2, ViewportBuilder, ViewportId};
-use egui_tiles::{Container, Grid, GridLayout, Linear, LinearDir, Tabs, Tile, Tree, Tiles};
+use egui::{Context, Rect, ViewportId};
 
-use super::monitor_clamp::clamp_outer_pos_best_effort;
-use super::PaneRegistry;
-
-pub const LAYOUT_SNAPSHOT_VERSION: u32 = 2;
+use super::backend_hints::backend_monitors_outer_rects_points;
+use super::load_report::LoadReport;
+use super::migration::{layout_snapshot_from_ron_str, migrate_layout_snapshot};
+use super::monitor_clamp::{
+    SavedMonitor, clamp_outer_pos_best_effort, remap_outer_pos_to_monitors,
+};
+use super::snapshot::{DroppedPane, LayoutSnapshot, detached_viewport_id_from_serial};
+use super::validation::validate_layout_snapshot;
+use super::{PaneRegistry, PaneStateRegistry};
 
 #[derive(Debug)]
 pub enum LayoutPersistenceError {
     UnsupportedVersion { found: u32, expected: u32 },
+    /// A child, root or `node_flags` index points past the end of its tree's nodes.
+    NodeIndexOutOfBounds { index: usize, len: usize },
+    /// A node is its own ancestor.
+    NodeCycle { index: usize },
+    /// A node is the child of more than one container.
+    DuplicateChildReference { index: usize },
+    /// The snapshot has more than [`super::LAYOUT_SNAPSHOT_MAX_NODES`] nodes.
+    TooManyNodes { count: usize, max: usize },
+    /// Containers are nested deeper than [`super::LAYOUT_SNAPSHOT_MAX_DEPTH`].
+    TreeTooDeep { max: usize },
+    /// A linear container doesn't have exactly one share per child, or a grid has more column or
+    /// row shares than it can have columns or rows.
+    ShareCountMismatch { index: usize },
+    /// A share or geometry value is NaN or infinite.
+    NonFiniteValue { field: &'static str },
+    /// A scale factor is zero or negative.
+    NonPositiveValue { field: &'static str },
     RonSerialize(ron::Error),
     RonDeserialize(ron::error::SpannedError),
+    #[cfg(feature = "persistence-json")]
+    Json(serde_json::Error),
+    #[cfg(feature = "persistence-msgpack")]
+    MsgPackEncode(rmp_serde::encode::Error),
+    #[cfg(feature = "persistence-msgpack")]
+    MsgPackDecode(rmp_serde::decode::Error),
     Io(std::io::Error),
 }
 
@@ -23,11 +51,37 @@ impl std::fmt::Display for LayoutPersistenceError {
             Self::UnsupportedVersion { found, expected } => {
                 write!(
                     f,
-                    "unsupported layout snapshot version: {found} (expected {expected})"
+                    "unsupported layout snapshot version: {found} (newest supported: {expected})"
                 )
             }
+            Self::NodeIndexOutOfBounds { index, len } => {
+                write!(f, "layout snapshot node index {index} out of bounds ({len} nodes)")
+            }
+            Self::NodeCycle { index } => write!(f, "layout snapshot node {index} is its own ancestor"),
+            Self::DuplicateChildReference { index } => {
+                write!(f, "layout snapshot node {index} has more than one parent")
+            }
+            Self::TooManyNodes { count, max } => {
+                write!(f, "layout snapshot has {count} nodes (max {max})")
+            }
+            Self::TreeTooDeep { max } => write!(f, "layout snapshot is nested deeper than {max}"),
+            Self::ShareCountMismatch { index } => {
+                write!(f, "layout snapshot node {index} has a share count unlike its child count")
+            }
+            Self::NonFiniteValue { field } => {
+                write!(f, "layout snapshot has a non-finite {field}")
+            }
+            Self::NonPositiveValue { field } => {
+                write!(f, "layout snapshot has a non-positive {field}")
+            }
             Self::RonSerialize(err) => write!(f, "ron serialize error: {err}"),
             Self::RonDeserialize(err) => write!(f, "ron deserialize error: {err}"),
+            #[cfg(feature = "persistence-json")]
+            Self::Json(err) => write!(f, "json error: {err}"),
+            #[cfg(feature = "persistence-msgpack")]
+            Self::MsgPackEncode(err) => write!(f, "msgpack encode error: {err}"),
+            #[cfg(feature = "persistence-msgpack")]
+            Self::MsgPackDecode(err) => write!(f, "msgpack decode error: {err}"),
             Self::Io(err) => write!(f, "io error: {err}"),
         }
     }
@@ -36,9 +90,23 @@ impl std::fmt::Display for LayoutPersistenceError {
 impl std::error::Error for LayoutPersistenceError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::UnsupportedVersion { .. } => None,
+            Self::UnsupportedVersion { .. }
+            | Self::NodeIndexOutOfBounds { .. }
+            | Self::NodeCycle { .. }
+            | Self::DuplicateChildReference { .. }
+            | Self::TooManyNodes { .. }
+            | Self::TreeTooDeep { .. }
+            | Self::ShareCountMismatch { .. }
+            | Self::NonFiniteValue { .. }
+            | Self::NonPositiveValue { .. } => None,
             Self::RonSerialize(err) => Some(err),
             Self::RonDeserialize(err) => Some(err),
+            #[cfg(feature = "persistence-json")]
+            Self::Json(err) => Some(err),
+            #[cfg(feature = "persistence-msgpack")]
+            Self::MsgPackEncode(err) => Some(err),
+            #[cfg(feature = "persistence-msgpack")]
+            Self::MsgPackDecode(err) => Some(err),
             Self::Io(err) => Some(err),
         }
     }
@@ -62,573 +130,50 @@ impl From<ron::error::SpannedError> for LayoutPersistenceError {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, serde::Deserialize, serde::Serialize)]
-pub struct ViewportSnapshot {
-    pub outer_pos: Option<Pos2>,
-    pub inner_size: Option<Vec2>,
-    pub fullscreen: bool,
-    pub maximized: bool,
-    pub pixels_per_point: Option<f32>,
-}
-
-#[derive(Clone, Copy, Debug, Default)]
-pub(super) struct ViewportRuntime {
-    pub outer_pos: Option<Pos2>,
-    pub inner_size: Option<Vec2>,
-    pub fullscreen: bool,
-    pub maximized: bool,
-    pub pixels_per_point: f32,
-}
-
-#[derive(Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
-pub enum HostSnapshot {
-    Root,
-    Detached { serial: u64 },
-}
-
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct FloatingWindowSnapshot<PaneId> {
-    pub id: u64,
-    pub tree: TreeSnapshot<PaneId>,
-    pub offset_in_dock: Vec2,
-    pub size: Vec2,
-    pub collapsed: bool,
-}
-
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct FloatingManagerSnapshot<PaneId> {
-    pub host: HostSnapshot,
-    pub windows: Vec<FloatingWindowSnapshot<PaneId>>,
-    pub z_order: Vec<u64>,
-}
-
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct DetachedSnapshot<PaneId> {
-    pub serial: u64,
-    pub viewport: ViewportSnapshot,
-    pub tree: TreeSnapshot<PaneId>,
-}
-
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct LayoutSnapshot<PaneId> {
-    pub version: u32,
-    pub root: TreeSnapshot<PaneId>,
-    pub detached: Vec<DetachedSnapshot<PaneId>>,
-    pub floating: Vec<FloatingManagerSnapshot<PaneId>>,
-    pub next_detached_serial: u64,
-    pub next_floating_id: u64,
-}
-
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct TreeSnapshot<PaneId> {
-    pub root: Option<usize>,
-    pub nodes: Vec<NodeSnapshot<PaneId>>,
-}
-
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub enum NodeSnapshot<PaneId> {
-    Pane {
-        pane: PaneId,
-        visible: bool,
-    },
-    Tabs {
-        children: Vec<usize>,
-        active: Option<usize>,
-        visible: bool,
-    },
-    Linear {
-        dir: LinearDir,
-        children: Vec<usize>,
-        shares: Vec<f32>,
-        visible: bool,
-    },
-    Grid {
-        layout: GridLayout,
-        children: Vec<usize>,
-        col_shares: Vec<f32>,
-        row_shares: Vec<f32>,
-        visible: bool,
-    },
-}
-
-fn detached_viewport_id_from_serial(serial: u64) -> ViewportId {
-    ViewportId::from_hash_of(("egui_docking_detached", serial))
-}
-
-fn snapshot_tree<Pane, PaneId>(
-    tree: &Tree<Pane>,
-    mut pane_to_id: impl FnMut(&Pane) -> PaneId,
-) -> TreeSnapshot<PaneId> {
-    let mut ids: HashMap<egui_tiles::TileId, usize> = HashMap::new();
-    let mut nodes: Vec<NodeSnapshot<PaneId>> = Vec::new();
-
-    fn snapshot_node<Pane, PaneId>(
-        tree: &Tree<Pane>,
-        tile_id: egui_tiles::TileId,
-        ids: &mut HashMap<egui_tiles::TileId, usize>,
-        nodes: &mut Vec<NodeSnapshot<PaneId>>,
-        pane_to_id: &mut dyn FnMut(&Pane) -> PaneId,
-    ) -> usize {
-        if let Some(&idx) = ids.get(&tile_id) {
-            return idx;
-        }
-
-        let visible = tree.tiles.is_visible(tile_id);
-        let tile = tree
-            .tiles
-            .get(tile_id)
-            .expect("tree root references missing tile");
-
-        // Important: reserve the index first (push placeholder), then fill it after recursing.
-        // This guarantees that child references are stable indices into `nodes`.
-        let idx = nodes.len();
-        ids.insert(tile_id, idx);
-        nodes.push(NodeSnapshot::Tabs {
-            children: Vec::new(),
-            active: None,
-            visible,
-        });
-
-        let node = match tile {
-            Tile::Pane(pane) => NodeSnapshot::Pane {
-                pane: pane_to_id(pane),
-                visible,
-            },
-            Tile::Container(container) => match container {
-                Container::Tabs(tabs) => {
-                    let children: Vec<usize> = tabs
-                        .children
-                        .iter()
-                        .copied()
-                        .map(|child| snapshot_node(tree, child, ids, nodes, pane_to_id))
-                        .collect();
-                    let active = tabs
-                        .active
-                        .and_then(|active_id| tabs.children.iter().position(|&c| c == active_id));
-                    NodeSnapshot::Tabs {
-                        children,
-                        active,
-                        visible,
-                    }
-                }
-                Container::Linear(linear) => {
-                    let children_tile_ids: Vec<_> = linear.children.clone();
-                    let children: Vec<usize> = children_tile_ids
-                        .iter()
-                        .copied()
-                        .map(|child| snapshot_node(tree, child, ids, nodes, pane_to_id))
-                        .collect();
-                    let shares: Vec<f32> = children_tile_ids
-                        .iter()
-                        .copied()
-                        .map(|child| linear.shares[child])
-                        .collect();
-                    NodeSnapshot::Linear {
-                        dir: linear.dir,
-                        children,
-                        shares,
-                        visible,
-                    }
-                }
-                Container::Grid(grid) => {
-                    let children_tile_ids: Vec<_> = grid.children().copied().collect();
-                    let children: Vec<usize> = children_tile_ids
-                        .iter()
-                        .copied()
-                        .map(|child| snapshot_node(tree, child, ids, nodes, pane_to_id))
-                        .collect();
-                    NodeSnapshot::Grid {
-                        layout: grid.layout,
-                        children,
-                        col_shares: grid.col_shares.clone(),
-                        row_shares: grid.row_shares.clone(),
-                        visible,
-                    }
-                }
-            },
-        };
-
-        nodes[idx] = node;
-        idx
+#[cfg(feature = "persistence-json")]
+impl From<serde_json::Error> for LayoutPersistenceError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
     }
-
-    let root = tree.root.map(|r| snapshot_node(tree, r, &mut ids, &mut nodes, &mut pane_to_id));
-    TreeSnapshot { root, nodes }
 }
 
-fn restore_tree<Pane, PaneId>(
-    tree_id: Id,
-    snapshot: TreeSnapshot<PaneId>,
-    mut pane_from_id: impl FnMut(PaneId) -> Pane,
-) -> Tree<Pane>
-where
-    PaneId: Clone,
-{
-    let mut tiles: Tiles<Pane> = Tiles::default();
-    let mut built: Vec<Option<egui_tiles::TileId>> = vec![None; snapshot.nodes.len()];
-
-    fn build_node<Pane, PaneId>(
-        snapshot: &TreeSnapshot<PaneId>,
-        idx: usize,
-        tiles: &mut Tiles<Pane>,
-        built: &mut [Option<egui_tiles::TileId>],
-        pane_from_id: &mut dyn FnMut(PaneId) -> Pane,
-    ) -> egui_tiles::TileId
-    where
-        PaneId: Clone,
-    {
-        if let Some(id) = built[idx] {
-            return id;
-        }
-
-        let (tile_id, visible) = match &snapshot.nodes[idx] {
-            NodeSnapshot::Pane { pane, visible } => (tiles.insert_pane(pane_from_id(pane.clone())), *visible),
-            NodeSnapshot::Tabs {
-                children,
-                active,
-                visible,
-            } => {
-                let child_ids: Vec<_> = children
-                    .iter()
-                    .copied()
-                    .map(|c| build_node(snapshot, c, tiles, built, pane_from_id))
-                    .collect();
-                let mut tabs = Tabs::new(child_ids.clone());
-                if let Some(active_index) = *active {
-                    if let Some(&active_child) = child_ids.get(active_index) {
-                        tabs.active = Some(active_child);
-                    }
-                }
-                (tiles.insert_container(tabs), *visible)
-            }
-            NodeSnapshot::Linear {
-                dir,
-                children,
-                shares,
-                visible,
-            } => {
-                let child_ids: Vec<_> = children
-                    .iter()
-                    .copied()
-                    .map(|c| build_node(snapshot, c, tiles, built, pane_from_id))
-                    .collect();
-                let mut linear = Linear::new(*dir, child_ids.clone());
-                for (child_id, share) in child_ids.iter().copied().zip(shares.iter().copied()) {
-                    if share.is_finite() && share != 1.0 {
-                        linear.shares.set_share(child_id, share);
-                    }
-                }
-                (tiles.insert_container(linear), *visible)
-            }
-            NodeSnapshot::Grid {
-                layout,
-                children,
-                col_shares,
-                row_shares,
-                visible,
-            } => {
-                let child_ids: Vec<_> = children
-                    .iter()
-                    .copied()
-                    .map(|c| build_node(snapshot, c, tiles, built, pane_from_id))
-                    .collect();
-                let mut grid = Grid::new(child_ids);
-                grid.layout = *layout;
-                grid.col_shares = col_shares.clone();
-                grid.row_shares = row_shares.clone();
-                (tiles.insert_container(grid), *visible)
-            }
-        };
-
-        tiles.set_visible(tile_id, visible);
-        built[idx] = Some(tile_id);
-        tile_id
-    }
-
-    let root = snapshot
-        .root
-        .map(|idx| build_node(&snapshot, idx, &mut tiles, &mut built, &mut pane_from_id));
-
-    match root {
-        Some(root) => Tree::new(tree_id, root, tiles),
-        None => Tree::empty(tree_id),
+#[cfg(feature = "persistence-msgpack")]
+impl From<rmp_serde::encode::Error> for LayoutPersistenceError {
+    fn from(err: rmp_serde::encode::Error) -> Self {
+        Self::MsgPackEncode(err)
     }
 }
 
-fn restore_tree_try<Pane, PaneId>(
-    tree_id: Id,
-    snapshot: TreeSnapshot<PaneId>,
-    mut pane_from_id: impl FnMut(PaneId) -> Option<Pane>,
-) -> Tree<Pane>
-where
-    PaneId: Clone,
-{
-    let mut tiles: Tiles<Pane> = Tiles::default();
-    let mut built: Vec<Option<egui_tiles::TileId>> = vec![None; snapshot.nodes.len()];
-    let mut missing: Vec<bool> = vec![false; snapshot.nodes.len()];
-
-    fn build_node_try<Pane, PaneId>(
-        snapshot: &TreeSnapshot<PaneId>,
-        idx: usize,
-        tiles: &mut Tiles<Pane>,
-        built: &mut [Option<egui_tiles::TileId>],
-        missing: &mut [bool],
-        pane_from_id: &mut dyn FnMut(PaneId) -> Option<Pane>,
-    ) -> Option<egui_tiles::TileId>
-    where
-        PaneId: Clone,
-    {
-        if missing[idx] {
-            return None;
-        }
-        if let Some(id) = built[idx] {
-            return Some(id);
-        }
-
-        let (tile_id, visible) = match &snapshot.nodes[idx] {
-            NodeSnapshot::Pane { pane, visible } => {
-                let Some(pane) = pane_from_id(pane.clone()) else {
-                    missing[idx] = true;
-                    return None;
-                };
-                (tiles.insert_pane(pane), *visible)
-            }
-            NodeSnapshot::Tabs {
-                children,
-                active,
-                visible,
-            } => {
-                let mut child_ids: Vec<egui_tiles::TileId> = Vec::with_capacity(children.len());
-                for c in children.iter().copied() {
-                    if let Some(child_id) =
-                        build_node_try(snapshot, c, tiles, built, missing, pane_from_id)
-                    {
-                        child_ids.push(child_id);
-                    }
-                }
-                if child_ids.is_empty() {
-                    missing[idx] = true;
-                    return None;
-                }
-                let mut tabs = Tabs::new(child_ids.clone());
-                if let Some(active_index) = *active {
-                    if let Some(&active_child) = child_ids.get(active_index) {
-                        tabs.active = Some(active_child);
-                    }
-                }
-                (tiles.insert_container(tabs), *visible)
-            }
-            NodeSnapshot::Linear {
-                dir,
-                children,
-                shares,
-                visible,
-            } => {
-                let mut child_ids: Vec<egui_tiles::TileId> = Vec::with_capacity(children.len());
-                let mut child_shares: Vec<f32> = Vec::with_capacity(children.len());
-                for (c, share) in children.iter().copied().zip(shares.iter().copied()) {
-                    if let Some(child_id) =
-                        build_node_try(snapshot, c, tiles, built, missing, pane_from_id)
-                    {
-                        child_ids.push(child_id);
-                        child_shares.push(share);
-                    }
-                }
-                if child_ids.is_empty() {
-                    missing[idx] = true;
-                    return None;
-                }
-
-                let mut linear = Linear::new(*dir, child_ids.clone());
-                for (child_id, share) in child_ids.iter().copied().zip(child_shares.iter().copied())
-                {
-                    if share.is_finite() && share != 1.0 {
-                        linear.shares.set_share(child_id, share);
-                    }
-                }
-                (tiles.insert_container(linear), *visible)
-            }
-            NodeSnapshot::Grid {
-                layout,
-                children,
-                col_shares,
-                row_shares,
-                visible,
-            } => {
-                let mut child_ids: Vec<egui_tiles::TileId> = Vec::with_capacity(children.len());
-                for c in children.iter().copied() {
-                    if let Some(child_id) =
-                        build_node_try(snapshot, c, tiles, built, missing, pane_from_id)
-                    {
-                        child_ids.push(child_id);
-                    }
-                }
-                if child_ids.is_empty() {
-                    missing[idx] = true;
-                    return None;
-                }
-
-                let mut grid = Grid::new(child_ids);
-                grid.layout = *layout;
-                grid.col_shares = col_shares.clone();
-                grid.row_shares = row_shares.clone();
-                (tiles.insert_container(grid), *visible)
-            }
-        };
-
-        tiles.set_visible(tile_id, visible);
-        built[idx] = Some(tile_id);
-        Some(tile_id)
-    }
-
-    let root = snapshot
-        .root
-        .and_then(|idx| build_node_try(&snapshot, idx, &mut tiles, &mut built, &mut missing, &mut pane_from_id));
-
-    match root {
-        Some(root) => Tree::new(tree_id, root, tiles),
-        None => Tree::empty(tree_id),
+#[cfg(feature = "persistence-msgpack")]
+impl From<rmp_serde::decode::Error> for LayoutPersistenceError {
+    fn from(err: rmp_serde::decode::Error) -> Self {
+        Self::MsgPackDecode(err)
     }
 }
 
-fn pretty_ron_config() -> ron::ser::PrettyConfig {
+pub(super) fn pretty_ron_config() -> ron::ser::PrettyConfig {
     ron::ser::PrettyConfig::new()
         .depth_limit(128)
         .separate_tuple_members(true)
         .enumerate_arrays(true)
 }
 
-impl<Pane> super::DockingMultiViewport<Pane> {
-    pub(super) fn capture_viewport_runtime(&mut self, ctx: &Context) {
-        let viewport_id = ctx.viewport_id();
-        let runtime = ctx.input(|i| {
-            let outer_pos = i.viewport().outer_rect.map(|r| r.min);
-            let inner_size = i.viewport().inner_rect.map(|r| r.size());
-            ViewportRuntime {
-                outer_pos,
-                inner_size,
-                fullscreen: i.viewport().fullscreen.unwrap_or(false),
-                maximized: i.viewport().maximized.unwrap_or(false),
-                pixels_per_point: ctx.pixels_per_point(),
-            }
-        });
-        self.last_viewport_runtime.insert(viewport_id, runtime);
-    }
-
-    fn viewport_snapshot_for_detached(
-        &self,
-        viewport_id: ViewportId,
-        detached: &super::types::DetachedDock<Pane>,
-    ) -> ViewportSnapshot {
-        let runtime = self.last_viewport_runtime.get(&viewport_id).copied();
-        ViewportSnapshot {
-            outer_pos: runtime
-                .and_then(|r| r.outer_pos)
-                .or(detached.builder.position),
-            inner_size: runtime
-                .and_then(|r| r.inner_size)
-                .or(detached.builder.inner_size),
-            fullscreen: runtime.map(|r| r.fullscreen).unwrap_or(false),
-            maximized: runtime.map(|r| r.maximized).unwrap_or(false),
-            pixels_per_point: runtime.map(|r| r.pixels_per_point),
-        }
-    }
-
-    fn viewport_builder_from_snapshot(
-        &self,
-        snapshot: ViewportSnapshot,
-        title_hint: &str,
-    ) -> ViewportBuilder {
-        let mut builder = ViewportBuilder::default()
-            .with_title(title_hint)
-            .with_decorations(self.options.detached_viewport_decorations)
-            .with_fullscreen(snapshot.fullscreen)
-            .with_maximized(snapshot.maximized);
-        if let Some(pos) = snapshot.outer_pos {
-            builder = builder.with_position(pos);
-        }
-        if let Some(size) = snapshot.inner_size {
-            builder = builder.with_inner_size(size);
-        }
-        builder
-    }
-
-    fn clear_interaction_state_for_load(&mut self) {
-        self.pending_drop = None;
-        self.pending_internal_drop = None;
-        self.pending_local_drop = None;
-        self.ghost = None;
-        self.drag_state = super::drag_state::DragState::default();
-
-        self.last_root_dock_rect = None;
-        self.last_dock_rects.clear();
-        self.viewport_outer_from_inner_offset.clear();
-        self.last_floating_rects.clear();
-        self.last_floating_content_rects.clear();
-        self.detached_rendered_frame.clear();
-        self.last_viewport_runtime.clear();
-    }
-
-    fn snapshot_layout_impl<PaneId>(
-        &self,
-        mut pane_to_id: impl FnMut(&Pane) -> PaneId,
-    ) -> LayoutSnapshot<PaneId> {
-        let root = snapshot_tree(&self.tree, &mut pane_to_id);
-
-        let detached: Vec<_> = self
-            .detached
-            .iter()
-            .map(|(viewport_id, detached)| DetachedSnapshot {
-                serial: detached.serial,
-                viewport: self.viewport_snapshot_for_detached(*viewport_id, detached),
-                tree: snapshot_tree(&detached.tree, &mut pane_to_id),
-            })
-            .collect();
-
-        let floating: Vec<_> = self
-            .floating
-            .iter()
-            .map(|(viewport_id, manager)| {
-                let host = if *viewport_id == ViewportId::ROOT {
-                    HostSnapshot::Root
-                } else if let Some(detached) = self.detached.get(viewport_id) {
-                    HostSnapshot::Detached {
-                        serial: detached.serial,
-                    }
-                } else {
-                    HostSnapshot::Root
-                };
-
-                let windows = manager
-                    .windows
-                    .iter()
-                    .map(|(&id, w)| FloatingWindowSnapshot {
-                        id,
-                        tree: snapshot_tree(&w.tree, &mut pane_to_id),
-                        offset_in_dock: w.offset_in_dock,
-                        size: w.size,
-                        collapsed: w.collapsed,
-                    })
-                    .collect();
-
-                FloatingManagerSnapshot {
-                    host,
-                    windows,
-                    z_order: manager.z_order.clone(),
-                }
-            })
-            .collect();
-
-        LayoutSnapshot {
-            version: LAYOUT_SNAPSHOT_VERSION,
-            root,
-            detached,
-            floating,
-            next_detached_serial: self.next_viewport_serial,
-            next_floating_id: self.next_floating_serial,
-        }
-    }
+/// Saved monitor of each detached viewport, read before the snapshot is consumed by the restore.
+fn saved_monitors<PaneId>(snapshot: &LayoutSnapshot<PaneId>) -> BTreeMap<ViewportId, SavedMonitor> {
+    snapshot
+        .detached
+        .iter()
+        .filter_map(|detached| {
+            let saved = SavedMonitor {
+                monitor: detached.viewport.monitor?,
+                pixels_per_point: detached.viewport.pixels_per_point,
+            };
+            Some((detached_viewport_id_from_serial(detached.serial), saved))
+        })
+        .collect()
+}
 
+impl<Pane> super::DockingMultiViewport<Pane> {
     pub fn snapshot_layout<PaneId>(
         &self,
         pane_to_id: impl FnMut(&Pane) -> PaneId,
@@ -695,214 +240,50 @@ impl<Pane> super::DockingMultiViewport<Pane> {
         &mut self,
         snapshot: LayoutSnapshot<PaneId>,
         mut pane_from_id: impl FnMut(PaneId) -> Pane,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
     where
-        PaneId: Clone,
+        PaneId: Clone + Eq + Hash,
     {
-        if snapshot.version != LAYOUT_SNAPSHOT_VERSION {
-            return Err(LayoutPersistenceError::UnsupportedVersion {
-                found: snapshot.version,
-                expected: LAYOUT_SNAPSHOT_VERSION,
-            });
-        }
-
-        self.clear_interaction_state_for_load();
-
-        let bridge_id = self.tree.id();
-
-        self.tree = restore_tree(bridge_id, snapshot.root, &mut pane_from_id);
-
-        self.detached.clear();
-        self.floating.clear();
-
-        let mut max_detached_serial = 0u64;
-        for detached in snapshot.detached {
-            max_detached_serial = max_detached_serial.max(detached.serial);
-
-            let viewport_id = detached_viewport_id_from_serial(detached.serial);
-            let detached_tree_id =
-                Id::new((bridge_id, "egui_docking_detached_tree", detached.serial));
-            let tree = restore_tree(detached_tree_id, detached.tree, &mut pane_from_id);
-
-            let builder = self.viewport_builder_from_snapshot(detached.viewport, "detached");
-
-            self.detached.insert(
-                viewport_id,
-                super::types::DetachedDock {
-                    serial: detached.serial,
-                    tree,
-                    builder,
-                },
-            );
-        }
-
-        let mut max_floating_id = 0u64;
-        for manager in snapshot.floating {
-            let viewport_id = match manager.host {
-                HostSnapshot::Root => ViewportId::ROOT,
-                HostSnapshot::Detached { serial } => detached_viewport_id_from_serial(serial),
-            };
-
-            let mut restored = super::types::FloatingManager::default();
-            for w in manager.windows {
-                max_floating_id = max_floating_id.max(w.id);
-
-                let floating_tree_id = Id::new((bridge_id, "egui_docking_floating_tree", w.id));
-                let tree = restore_tree(floating_tree_id, w.tree, &mut pane_from_id);
-
-                restored.windows.insert(
-                    w.id,
-                    super::types::FloatingDockWindow {
-                        tree,
-                        offset_in_dock: w.offset_in_dock,
-                        size: w.size,
-                        collapsed: w.collapsed,
-                        drag: None,
-                        resize: None,
-                    },
-                );
-            }
-            restored.z_order = manager.z_order;
-            restored
-                .z_order
-                .retain(|id| restored.windows.contains_key(id));
-            for id in restored.windows.keys().copied().collect::<Vec<_>>() {
-                if !restored.z_order.contains(&id) {
-                    restored.z_order.push(id);
-                }
-            }
-
-            self.floating.insert(viewport_id, restored);
-        }
-
-        self.next_viewport_serial = snapshot
-            .next_detached_serial
-            .max(max_detached_serial.saturating_add(1))
-            .max(1);
-        self.next_floating_serial = snapshot
-            .next_floating_id
-            .max(max_floating_id.saturating_add(1))
-            .max(1);
-
-        Ok(())
+        let snapshot = migrate_layout_snapshot(snapshot)?;
+        validate_layout_snapshot(&snapshot)?;
+        let duplicate_panes = snapshot.duplicate_pane_ids();
+        let mut report = self.restore_layout_snapshot(snapshot, |id| Some(pane_from_id(id)));
+        report.duplicate_panes = duplicate_panes;
+        self.mark_layout_changed();
+        Ok(report)
     }
 
+    /// Load `snapshot`, restoring panes through `registry`.
+    ///
+    /// The report lists pane ids as saved; ids [`PaneRegistry::migrate_pane_id`] dropped are in
+    /// [`LoadReport::migrated_away_panes`], not in [`LoadReport::missing_panes`].
     pub fn load_layout_snapshot_with_registry<R>(
         &mut self,
         snapshot: LayoutSnapshot<R::PaneId>,
         registry: &mut R,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
     where
         R: PaneRegistry<Pane>,
     {
-        if snapshot.version != LAYOUT_SNAPSHOT_VERSION {
-            return Err(LayoutPersistenceError::UnsupportedVersion {
-                found: snapshot.version,
-                expected: LAYOUT_SNAPSHOT_VERSION,
-            });
-        }
-
-        self.clear_interaction_state_for_load();
-
-        let bridge_id = self.tree.id();
-
-        self.tree = restore_tree_try(bridge_id, snapshot.root, |id| registry.try_pane_from_id(id));
-
-        self.detached.clear();
-        self.floating.clear();
-
-        let mut max_detached_serial = 0u64;
-        for detached in snapshot.detached {
-            max_detached_serial = max_detached_serial.max(detached.serial);
-
-            let viewport_id = detached_viewport_id_from_serial(detached.serial);
-            let detached_tree_id =
-                Id::new((bridge_id, "egui_docking_detached_tree", detached.serial));
-            let tree = restore_tree_try(detached_tree_id, detached.tree, |id| {
-                registry.try_pane_from_id(id)
-            });
-            if tree.root.is_none() {
-                continue;
-            }
-
-            let builder = self.viewport_builder_from_snapshot(detached.viewport, "detached");
-
-            self.detached.insert(
-                viewport_id,
-                super::types::DetachedDock {
-                    serial: detached.serial,
-                    tree,
-                    builder,
-                },
-            );
-        }
-
-        let mut max_floating_id = 0u64;
-        for manager in snapshot.floating {
-            let viewport_id = match manager.host {
-                HostSnapshot::Root => ViewportId::ROOT,
-                HostSnapshot::Detached { serial } => detached_viewport_id_from_serial(serial),
-            };
-
-            let mut restored = super::types::FloatingManager::default();
-            for w in manager.windows {
-                max_floating_id = max_floating_id.max(w.id);
-
-                let floating_tree_id = Id::new((bridge_id, "egui_docking_floating_tree", w.id));
-                let tree = restore_tree_try(floating_tree_id, w.tree, |id| {
-                    registry.try_pane_from_id(id)
-                });
-
-                if tree.root.is_none() {
-                    continue;
-                }
-
-                restored.windows.insert(
-                    w.id,
-                    super::types::FloatingDockWindow {
-                        tree,
-                        offset_in_dock: w.offset_in_dock,
-                        size: w.size,
-                        collapsed: w.collapsed,
-                        drag: None,
-                        resize: None,
-                    },
-                );
-            }
-            restored.z_order = manager.z_order;
-            restored
-                .z_order
-                .retain(|id| restored.windows.contains_key(id));
-            for id in restored.windows.keys().copied().collect::<Vec<_>>() {
-                if !restored.z_order.contains(&id) {
-                    restored.z_order.push(id);
-                }
-            }
-
-            if !restored.windows.is_empty() {
-                self.floating.insert(viewport_id, restored);
-            }
-        }
-
-        self.next_viewport_serial = snapshot
-            .next_detached_serial
-            .max(max_detached_serial.saturating_add(1))
-            .max(1);
-        self.next_floating_serial = snapshot
-            .next_floating_id
-            .max(max_floating_id.saturating_add(1))
-            .max(1);
-
-        Ok(())
+        let snapshot = migrate_layout_snapshot(snapshot)?;
+        validate_layout_snapshot(&snapshot)?;
+        let duplicate_panes = snapshot.duplicate_pane_ids();
+        let mut report = self.restore_layout_snapshot_with_states(snapshot, |id, _state| {
+            let id = registry.migrate_pane_id(id).ok_or(DroppedPane::Migrated)?;
+            registry.try_pane_from_id(id).ok_or(DroppedPane::Missing)
+        });
+        report.duplicate_panes = duplicate_panes;
+        self.mark_layout_changed();
+        Ok(report)
     }
 
     pub fn load_layout_snapshot<PaneId>(
         &mut self,
         snapshot: LayoutSnapshot<PaneId>,
         pane_from_id: impl FnMut(PaneId) -> Pane,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
     where
-        PaneId: Clone,
+        PaneId: Clone + Eq + Hash,
     {
         self.load_layout_snapshot_impl(snapshot, pane_from_id)
     }
@@ -916,82 +297,162 @@ impl<Pane> super::DockingMultiViewport<Pane> {
         ctx: &Context,
         snapshot: LayoutSnapshot<R::PaneId>,
         registry: &mut R,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
     where
         R: PaneRegistry<Pane>,
     {
         egui::DragAndDrop::clear_payload(ctx);
-        self.load_layout_snapshot_with_registry(snapshot, registry)?;
+        let monitors = saved_monitors(&snapshot);
+        let mut report = self.load_layout_snapshot_with_registry(snapshot, registry)?;
+        self.apply_loaded_viewport_geometry(ctx, &monitors, &mut report);
+        Ok(report)
+    }
 
-        // Apply geometry to already-existing native viewports immediately.
-        // For viewports that are created on the next frame, `ViewportBuilder` will take effect.
-        let mut clamp_builder_pos: Vec<(ViewportId, Pos2)> = Vec::new();
-        for (&viewport_id, detached) in &self.detached {
-            if detached.builder.fullscreen == Some(true) || detached.builder.maximized == Some(true)
-            {
-                continue;
-            }
+    pub fn load_layout_snapshot_in_ctx<PaneId>(
+        &mut self,
+        ctx: &Context,
+        snapshot: LayoutSnapshot<PaneId>,
+        pane_from_id: impl FnMut(PaneId) -> Pane,
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
+    where
+        PaneId: Clone + Eq + Hash,
+    {
+        egui::DragAndDrop::clear_payload(ctx);
+        let monitors = saved_monitors(&snapshot);
+        let mut report = self.load_layout_snapshot_impl(snapshot, pane_from_id)?;
+        self.apply_loaded_viewport_geometry(ctx, &monitors, &mut report);
+        Ok(report)
+    }
 
-            if let Some(pos) = detached.builder.position {
-                let pos = if let Some(size) = detached.builder.inner_size {
-                    clamp_outer_pos_best_effort(ctx, pos, size)
-                } else {
-                    pos
-                };
-                ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::OuterPosition(pos));
-                clamp_builder_pos.push((viewport_id, pos));
-            }
-            if let Some(size) = detached.builder.inner_size {
-                ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::InnerSize(size));
-            }
-            if let Some(fullscreen) = detached.builder.fullscreen {
-                ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::Fullscreen(fullscreen));
-            }
-            if let Some(maximized) = detached.builder.maximized {
-                ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::Maximized(maximized));
-            }
-        }
+    /// Like [`Self::snapshot_layout_with_registry`], also storing each pane's state blob.
+    pub fn snapshot_layout_with_state_registry<R>(
+        &self,
+        registry: &mut R,
+    ) -> LayoutSnapshot<R::PaneId>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        self.snapshot_layout_with_states_impl(|pane| {
+            (registry.pane_id(pane), registry.save_pane_state(pane))
+        })
+    }
 
-        // Keep the builder in sync so the next `show_viewport_immediate` also uses the clamped position.
-        for (viewport_id, pos) in clamp_builder_pos {
-            if let Some(detached) = self.detached.get_mut(&viewport_id) {
-                detached.builder = detached.builder.clone().with_position(pos);
-            }
-        }
+    pub fn snapshot_layout_to_ron_string_with_state_registry<R>(
+        &self,
+        registry: &mut R,
+    ) -> Result<String, LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let snapshot = self.snapshot_layout_with_state_registry(registry);
+        Ok(ron::ser::to_string_pretty(&snapshot, pretty_ron_config())?)
+    }
 
-        ctx.request_repaint();
+    pub fn save_layout_to_ron_file_with_state_registry<R>(
+        &self,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<(), LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let ron = self.snapshot_layout_to_ron_string_with_state_registry(registry)?;
+        std::fs::write(path, ron)?;
         Ok(())
     }
 
-    pub fn load_layout_snapshot_in_ctx<PaneId>(
+    /// Like [`Self::load_layout_snapshot_with_registry`], handing stored state blobs to
+    /// [`PaneStateRegistry::load_pane_state`].
+    pub fn load_layout_snapshot_with_state_registry<R>(
+        &mut self,
+        snapshot: LayoutSnapshot<R::PaneId>,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let snapshot = migrate_layout_snapshot(snapshot)?;
+        validate_layout_snapshot(&snapshot)?;
+        let duplicate_panes = snapshot.duplicate_pane_ids();
+        let mut report = self.restore_layout_snapshot_with_states(snapshot, |id, state| {
+            let id = registry.migrate_pane_id(id).ok_or(DroppedPane::Migrated)?;
+            let mut pane = registry.try_pane_from_id(id).ok_or(DroppedPane::Missing)?;
+            if let Some(state) = state {
+                registry.load_pane_state(&mut pane, &state);
+            }
+            Ok(pane)
+        });
+        report.duplicate_panes = duplicate_panes;
+        self.mark_layout_changed();
+        Ok(report)
+    }
+
+    pub fn load_layout_snapshot_in_ctx_with_state_registry<R>(
         &mut self,
         ctx: &Context,
-        snapshot: LayoutSnapshot<PaneId>,
-        pane_from_id: impl FnMut(PaneId) -> Pane,
-    ) -> Result<(), LayoutPersistenceError>
+        snapshot: LayoutSnapshot<R::PaneId>,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
     where
-        PaneId: Clone,
+        R: PaneStateRegistry<Pane>,
     {
         egui::DragAndDrop::clear_payload(ctx);
-        self.load_layout_snapshot_impl(snapshot, pane_from_id)?;
+        let monitors = saved_monitors(&snapshot);
+        let mut report = self.load_layout_snapshot_with_state_registry(snapshot, registry)?;
+        self.apply_loaded_viewport_geometry(ctx, &monitors, &mut report);
+        Ok(report)
+    }
 
-        // Apply geometry to already-existing native viewports immediately.
-        // For viewports that are created on the next frame, `ViewportBuilder` will take effect.
-        let mut clamp_builder_pos: Vec<(ViewportId, Pos2)> = Vec::new();
-        for (&viewport_id, detached) in &self.detached {
+    pub fn load_layout_from_ron_str_with_state_registry<R>(
+        &mut self,
+        ron_str: &str,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_ron_str(ron_str)?;
+        self.load_layout_snapshot_with_state_registry(snapshot, registry)
+    }
+
+    pub fn load_layout_from_ron_file_in_ctx_with_state_registry<R>(
+        &mut self,
+        ctx: &Context,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let ron_str = std::fs::read_to_string(path)?;
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_ron_str(&ron_str)?;
+        self.load_layout_snapshot_in_ctx_with_state_registry(ctx, snapshot, registry)
+    }
+
+    /// Push the restored detached window geometry to already-existing native viewports.
+    ///
+    /// Windows saved on a monitor that is no longer where it was are first moved to the same spot
+    /// on their monitor (or on the primary one), see [`MonitorSnapshot`]. Before the first frame
+    /// the monitors (and the scale factor) are not known yet: those windows are placed by
+    /// [`Self::apply_pending_viewport_placement`] instead, and are not listed in the report.
+    ///
+    /// [`MonitorSnapshot`]: super::snapshot::MonitorSnapshot
+    fn apply_loaded_viewport_geometry<PaneId>(
+        &mut self,
+        ctx: &Context,
+        saved_monitors: &BTreeMap<ViewportId, SavedMonitor>,
+        report: &mut LoadReport<PaneId>,
+    ) {
+        let monitors = backend_monitors_outer_rects_points(ctx);
+        let before_first_frame = self.debug_frame == 0;
+        let viewport_ids: Vec<ViewportId> = self.detached.keys().copied().collect();
+        for viewport_id in viewport_ids {
+            let Some(detached) = self.detached.get(&viewport_id) else {
+                continue;
+            };
             if detached.builder.fullscreen == Some(true) || detached.builder.maximized == Some(true)
             {
-                continue;
-            }
-
-            if let Some(pos) = detached.builder.position {
-                let pos = if let Some(size) = detached.builder.inner_size {
-                    clamp_outer_pos_best_effort(ctx, pos, size)
-                } else {
-                    pos
-                };
-                ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::OuterPosition(pos));
-                clamp_builder_pos.push((viewport_id, pos));
+                continue;
             }
             if let Some(size) = detached.builder.inner_size {
                 ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::InnerSize(size));
@@ -1003,32 +464,107 @@ impl<Pane> super::DockingMultiViewport<Pane> {
                 );
             }
             if let Some(maximized) = detached.builder.maximized {
-                ctx.send_viewport_cmd_to(
-                    viewport_id,
-                    egui::ViewportCommand::Maximized(maximized),
-                );
+                ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::Maximized(maximized));
             }
-        }
 
-        for (viewport_id, pos) in clamp_builder_pos {
-            if let Some(detached) = self.detached.get_mut(&viewport_id) {
-                detached.builder = detached.builder.clone().with_position(pos);
+            let saved_monitor = saved_monitors.get(&viewport_id).copied();
+            let waiting_for_monitors = monitors.is_none() && before_first_frame;
+            if waiting_for_monitors || self.pending_dpi_rescale.contains_key(&viewport_id) {
+                self.pending_viewport_placement
+                    .insert(viewport_id, saved_monitor);
+                continue;
             }
+            let monitors = monitors.as_deref().unwrap_or_default();
+            self.place_loaded_viewport(ctx, viewport_id, saved_monitor, monitors, report);
         }
 
         ctx.request_repaint();
-        Ok(())
+    }
+
+    /// Finish [`Self::apply_loaded_viewport_geometry`] for windows restored before the first
+    /// frame, on the first frame that knows the monitors (and after their DPI conversion).
+    pub(super) fn apply_pending_viewport_placement(&mut self, ctx: &Context) {
+        if self.pending_viewport_placement.is_empty() {
+            return;
+        }
+        let monitors = match backend_monitors_outer_rects_points(ctx) {
+            Some(monitors) => monitors,
+            // Backends that never report monitors: fall back to the single-monitor clamp.
+            None if self.debug_frame > 1 => Vec::new(),
+            None => return,
+        };
+
+        let ready: Vec<ViewportId> = self
+            .pending_viewport_placement
+            .keys()
+            .copied()
+            .filter(|viewport_id| !self.pending_dpi_rescale.contains_key(viewport_id))
+            .collect();
+        for viewport_id in ready {
+            let Some(saved_monitor) = self.pending_viewport_placement.remove(&viewport_id) else {
+                continue;
+            };
+            let mut report = LoadReport::<()>::default();
+            self.place_loaded_viewport(ctx, viewport_id, saved_monitor, &monitors, &mut report);
+            if self.options.debug_event_log && !report.is_clean() {
+                self.debug_log_event(format!(
+                    "load_placement viewport={viewport_id:?} remapped={:?} clamped={:?}",
+                    report.remapped_viewports, report.clamped_viewports
+                ));
+            }
+        }
+    }
+
+    /// Move a restored detached window off a monitor that is gone and onto the desktop, and push
+    /// its position to the native viewport.
+    fn place_loaded_viewport<PaneId>(
+        &mut self,
+        ctx: &Context,
+        viewport_id: ViewportId,
+        saved_monitor: Option<SavedMonitor>,
+        monitors: &[Rect],
+        report: &mut LoadReport<PaneId>,
+    ) {
+        let Some(detached) = self.detached.get_mut(&viewport_id) else {
+            return;
+        };
+        if detached.builder.fullscreen == Some(true) || detached.builder.maximized == Some(true) {
+            return;
+        }
+        let Some(mut pos) = detached.builder.position else {
+            return;
+        };
+
+        if let Some(saved) = saved_monitor
+            && let Some(remapped) =
+                remap_outer_pos_to_monitors(&saved, monitors, ctx.pixels_per_point())
+        {
+            report.remapped_viewports.push((viewport_id, pos, remapped));
+            pos = remapped;
+        }
+        let clamped = if let Some(size) = detached.builder.inner_size {
+            clamp_outer_pos_best_effort(ctx, pos, size)
+        } else {
+            pos
+        };
+        if clamped != pos {
+            report.clamped_viewports.push((viewport_id, pos, clamped));
+        }
+
+        // Keep the builder in sync so the next `show_viewport_immediate` also uses the final position.
+        detached.builder = detached.builder.clone().with_position(clamped);
+        ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::OuterPosition(clamped));
     }
 
     pub fn load_layout_from_ron_str<PaneId>(
         &mut self,
         ron_str: &str,
         pane_from_id: impl FnMut(PaneId) -> Pane,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
     where
-        PaneId: Clone + for<'de> serde::Deserialize<'de>,
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
     {
-        let snapshot: LayoutSnapshot<PaneId> = ron::from_str(ron_str)?;
+        let snapshot: LayoutSnapshot<PaneId> = layout_snapshot_from_ron_str(ron_str)?;
         self.load_layout_snapshot(snapshot, pane_from_id)
     }
 
@@ -1036,11 +572,11 @@ impl<Pane> super::DockingMultiViewport<Pane> {
         &mut self,
         ron_str: &str,
         registry: &mut R,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
     where
         R: PaneRegistry<Pane>,
     {
-        let snapshot: LayoutSnapshot<R::PaneId> = ron::from_str(ron_str)?;
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_ron_str(ron_str)?;
         self.load_layout_snapshot_with_registry(snapshot, registry)
     }
 
@@ -1049,11 +585,11 @@ impl<Pane> super::DockingMultiViewport<Pane> {
         ctx: &Context,
         ron_str: &str,
         pane_from_id: impl FnMut(PaneId) -> Pane,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
     where
-        PaneId: Clone + for<'de> serde::Deserialize<'de>,
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
     {
-        let snapshot: LayoutSnapshot<PaneId> = ron::from_str(ron_str)?;
+        let snapshot: LayoutSnapshot<PaneId> = layout_snapshot_from_ron_str(ron_str)?;
         self.load_layout_snapshot_in_ctx(ctx, snapshot, pane_from_id)
     }
 
@@ -1062,11 +598,11 @@ impl<Pane> super::DockingMultiViewport<Pane> {
         ctx: &Context,
         ron_str: &str,
         registry: &mut R,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
     where
         R: PaneRegistry<Pane>,
     {
-        let snapshot: LayoutSnapshot<R::PaneId> = ron::from_str(ron_str)?;
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_ron_str(ron_str)?;
         self.load_layout_snapshot_in_ctx_with_registry(ctx, snapshot, registry)
     }
 
@@ -1074,9 +610,9 @@ impl<Pane> super::DockingMultiViewport<Pane> {
         &mut self,
         path: impl AsRef<Path>,
         pane_from_id: impl FnMut(PaneId) -> Pane,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
     where
-        PaneId: Clone + for<'de> serde::Deserialize<'de>,
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
     {
         let ron_str = std::fs::read_to_string(path)?;
         self.load_layout_from_ron_str(&ron_str, pane_from_id)
@@ -1086,7 +622,7 @@ impl<Pane> super::DockingMultiViewport<Pane> {
         &mut self,
         path: impl AsRef<Path>,
         registry: &mut R,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
     where
         R: PaneRegistry<Pane>,
     {
@@ -1099,9 +635,9 @@ impl<Pane> super::DockingMultiViewport<Pane> {
         ctx: &Context,
         path: impl AsRef<Path>,
         pane_from_id: impl FnMut(PaneId) -> Pane,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
     where
-        PaneId: Clone + for<'de> serde::Deserialize<'de>,
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
     {
         let ron_str = std::fs::read_to_string(path)?;
         self.load_layout_from_ron_str_in_ctx(ctx, &ron_str, pane_from_id)
@@ -1112,7 +648,7 @@ impl<Pane> super::DockingMultiViewport<Pane> {
         ctx: &Context,
         path: impl AsRef<Path>,
         registry: &mut R,
-    ) -> Result<(), LayoutPersistenceError>
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
     where
         R: PaneRegistry<Pane>,
     {
@@ -1126,8 +662,8 @@ impl<Pane> super::DockingMultiViewport<Pane> {
     /// `fallback`, but return the error so callers can surface it in the UI.
     ///
     /// Returns:
-    /// - `Ok(true)` if the layout was loaded from disk
-    /// - `Ok(false)` if the file was missing and `fallback` was applied
+    /// - `Ok(Some(report))` if the layout was loaded from disk
+    /// - `Ok(None)` if the file was missing and `fallback` was applied
     /// - `Err(e)` if loading failed and `fallback` was applied
     pub fn load_layout_from_ron_file_in_ctx_with_registry_or_apply_workspace<R>(
         &mut self,
@@ -1135,15 +671,15 @@ impl<Pane> super::DockingMultiViewport<Pane> {
         path: impl AsRef<Path>,
         registry: &mut R,
         fallback: impl FnOnce() -> crate::workspace::WorkspaceLayout<Pane>,
-    ) -> Result<bool, LayoutPersistenceError>
+    ) -> Result<Option<LoadReport<R::PaneId>>, LayoutPersistenceError>
     where
         R: PaneRegistry<Pane>,
     {
         match self.load_layout_from_ron_file_in_ctx_with_registry(ctx, path.as_ref(), registry) {
-            Ok(()) => Ok(true),
+            Ok(report) => Ok(Some(report)),
             Err(LayoutPersistenceError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {
                 self.set_workspace_layout_in_ctx(ctx, fallback());
-                Ok(false)
+                Ok(None)
             }
             Err(err) => {
                 self.set_workspace_layout_in_ctx(ctx, fallback());
@@ -1157,7 +693,11 @@ impl<Pane> super::DockingMultiViewport<Pane> {
 mod tests {
     use std::collections::BTreeMap;
 
+    use egui::{Id, Pos2, Vec2, ViewportBuilder};
+    use egui_tiles::{Container, Tile, Tree};
+
     use super::*;
+    use crate::multi_viewport::snapshot::MonitorSnapshot;
     use crate::multi_viewport::types::{DetachedDock, FloatingDockWindow, FloatingManager};
     use crate::multi_viewport::PaneRegistry;
 
@@ -1195,6 +735,7 @@ mod tests {
                     .with_title("detached")
                     .with_position(Pos2::new(123.0, 456.0))
                     .with_inner_size(Vec2::new(640.0, 480.0)),
+                options: Default::default(),
             },
         );
 
@@ -1224,9 +765,10 @@ mod tests {
             .unwrap();
 
         let mut restored = crate::multi_viewport::DockingMultiViewport::new(Tree::empty("restored"));
-        restored
+        let report = restored
             .load_layout_from_ron_str::<usize>(&ron, |id| Pane { id })
             .unwrap();
+        assert!(report.is_clean());
 
         // Detached restored:
         assert_eq!(restored.detached.len(), 1);
@@ -1243,6 +785,124 @@ mod tests {
         assert!(w.collapsed);
     }
 
+    #[test]
+    fn persistence_roundtrip_restores_node_flags() {
+        use crate::multi_viewport::{DockNodeFlags, PaneHost};
+
+        let root_tree = new_tree_tabs(Id::new("root"), &[1, 2]);
+        let root = root_tree.root.unwrap();
+        let mut docking = crate::multi_viewport::DockingMultiViewport::new(root_tree);
+        let flags = DockNodeFlags::NO_SPLIT | DockNodeFlags::NO_UNDOCKING;
+        assert!(docking.set_node_flags(PaneHost::RootDock, root, flags));
+
+        let ron = docking
+            .snapshot_layout_to_ron_string::<usize>(|pane| pane.id)
+            .unwrap();
+
+        let mut restored = crate::multi_viewport::DockingMultiViewport::new(Tree::empty("restored"));
+        restored
+            .load_layout_from_ron_str::<usize>(&ron, |id| Pane { id })
+            .unwrap();
+
+        let restored_root = restored.tree.root.unwrap();
+        assert_eq!(restored.node_flags(PaneHost::RootDock, restored_root), flags);
+        let pane = restored.tree.tiles.tile_ids().find(|&id| id != restored_root).unwrap();
+        assert_eq!(restored.node_flags(PaneHost::RootDock, pane), DockNodeFlags::NONE);
+    }
+
+    #[test]
+    fn persistence_roundtrip_restores_detached_viewport_options() {
+        use crate::multi_viewport::DetachedViewportOptions;
+
+        let mut docking =
+            crate::multi_viewport::DockingMultiViewport::new(new_tree_tabs(Id::new("root"), &[1]));
+        let options = DetachedViewportOptions::default()
+            .with_decorations(true)
+            .with_csd_resize_edge_thickness(3.0)
+            .with_always_on_top(true);
+        let plain = docking
+            .add_detached_viewport(new_tree_tabs(Id::new("plain"), &[2]), ViewportBuilder::default())
+            .unwrap();
+        let profiler = docking
+            .add_detached_viewport_with_options(
+                new_tree_tabs(Id::new("profiler"), &[3]),
+                ViewportBuilder::default(),
+                options,
+            )
+            .unwrap();
+
+        let ron = docking
+            .snapshot_layout_to_ron_string::<usize>(|pane| pane.id)
+            .unwrap();
+
+        let mut restored = crate::multi_viewport::DockingMultiViewport::new(Tree::empty("restored"));
+        restored
+            .load_layout_from_ron_str::<usize>(&ron, |id| Pane { id })
+            .unwrap();
+
+        assert_eq!(
+            restored.detached_viewport_options(plain),
+            Some(DetachedViewportOptions::default())
+        );
+        assert_eq!(restored.detached_viewport_options(profiler), Some(options));
+        let builder = &restored.detached.get(&profiler).unwrap().builder;
+        assert_eq!(builder.decorations, Some(true));
+        assert_eq!(builder.window_level, Some(egui::WindowLevel::AlwaysOnTop));
+    }
+
+    #[test]
+    fn central_node_survives_losing_all_its_panes_on_load() {
+        use crate::multi_viewport::{DockNodeFlags, PaneHost};
+
+        struct ToolsOnlyRegistry;
+
+        impl PaneRegistry<Pane> for ToolsOnlyRegistry {
+            type PaneId = usize;
+
+            fn pane_id(&mut self, pane: &Pane) -> Self::PaneId {
+                pane.id
+            }
+
+            fn pane_from_id(&mut self, id: Self::PaneId) -> Pane {
+                Pane { id }
+            }
+
+            fn try_pane_from_id(&mut self, id: Self::PaneId) -> Option<Pane> {
+                (id == 1).then_some(Pane { id })
+            }
+        }
+
+        let mut tiles = egui_tiles::Tiles::default();
+        let doc = tiles.insert_pane(Pane { id: 2 });
+        let central = tiles.insert_tab_tile(vec![doc]);
+        let tool = tiles.insert_pane(Pane { id: 1 });
+        let tool_tabs = tiles.insert_tab_tile(vec![tool]);
+        let root = tiles.insert_horizontal_tile(vec![tool_tabs, central]);
+        let mut docking =
+            crate::multi_viewport::DockingMultiViewport::new(Tree::new(Id::new("root"), root, tiles));
+        assert!(docking.set_central_node(central));
+
+        let ron = docking
+            .snapshot_layout_to_ron_string::<usize>(|pane| pane.id)
+            .unwrap();
+
+        // The registry no longer knows the document (id 2).
+        let mut registry = ToolsOnlyRegistry;
+        let mut restored = crate::multi_viewport::DockingMultiViewport::new(Tree::empty("restored"));
+        restored
+            .load_layout_from_ron_str_with_registry(&ron, &mut registry)
+            .unwrap();
+
+        let central = restored.central_node().expect("central node must be restored");
+        assert!(matches!(
+            restored.tree.tiles.get(central),
+            Some(Tile::Container(Container::Tabs(tabs))) if tabs.children.is_empty()
+        ));
+        assert!(restored
+            .node_flags(PaneHost::RootDock, central)
+            .contains(DockNodeFlags::CENTRAL_NODE));
+    }
+
     #[test]
     fn missing_panes_are_dropped_on_load() {
         let root_tree = new_tree_tabs(Id::new("root"), &[1, 2, 3]);
@@ -1298,4 +958,370 @@ mod tests {
         // Root still exists.
         assert!(restored.tree.tiles.get(root).is_some());
     }
+
+    #[test]
+    fn crafted_snapshots_are_rejected_before_restore() {
+        use crate::multi_viewport::snapshot::NodeSnapshot;
+
+        let docking =
+            crate::multi_viewport::DockingMultiViewport::new(new_tree_tabs(Id::new("root"), &[1, 2]));
+        let mut snapshot = docking.snapshot_layout::<usize>(|pane| pane.id);
+        let root = snapshot.root.root.unwrap();
+        if let NodeSnapshot::Tabs { children, .. } = &mut snapshot.root.nodes[root] {
+            children.push(root);
+        }
+        let ron = ron::to_string(&snapshot).unwrap();
+
+        let mut restored =
+            crate::multi_viewport::DockingMultiViewport::new(new_tree_tabs(Id::new("restored"), &[7]));
+        assert!(matches!(
+            restored.load_layout_from_ron_str::<usize>(&ron, |id| Pane { id }),
+            Err(LayoutPersistenceError::NodeCycle { .. })
+        ));
+        // The current layout is left alone.
+        assert_eq!(restored.tree.tiles.iter().count(), 2);
+    }
+
+    #[test]
+    fn load_report_lists_dropped_and_repaired_parts() {
+        let mut root_tree = new_tree_tabs(Id::new("root"), &[1, 2, 3]);
+        let root = root_tree.root.unwrap();
+        let pane_3 = root_tree
+            .tiles
+            .iter()
+            .find_map(|(&id, tile)| matches!(tile, Tile::Pane(Pane { id: 3 })).then_some(id))
+            .unwrap();
+        if let Some(Tile::Container(Container::Tabs(tabs))) = root_tree.tiles.get_mut(root) {
+            tabs.active = Some(pane_3);
+        }
+        let mut docking = crate::multi_viewport::DockingMultiViewport::new(root_tree);
+        docking
+            .add_detached_viewport(new_tree_tabs(Id::new("detached"), &[2]), ViewportBuilder::default())
+            .unwrap();
+
+        let window = |panes: &[usize]| FloatingDockWindow {
+            tree: new_tree_tabs(Id::new(("floating", panes[0])), panes),
+            offset_in_dock: Vec2::ZERO,
+            size: Vec2::new(200.0, 100.0),
+            collapsed: false,
+            drag: None,
+            resize: None,
+        };
+        docking.floating.insert(
+            ViewportId::ROOT,
+            FloatingManager {
+                windows: BTreeMap::from([(7, window(&[4])), (8, window(&[5]))]),
+                z_order: vec![99, 7],
+            },
+        );
+
+        let ron = docking
+            .snapshot_layout_to_ron_string::<usize>(|pane| pane.id)
+            .unwrap();
+
+        struct DroppingRegistry;
+
+        impl PaneRegistry<Pane> for DroppingRegistry {
+            type PaneId = usize;
+
+            fn pane_id(&mut self, pane: &Pane) -> Self::PaneId {
+                pane.id
+            }
+
+            fn pane_from_id(&mut self, id: Self::PaneId) -> Pane {
+                Pane { id }
+            }
+
+            fn try_pane_from_id(&mut self, id: Self::PaneId) -> Option<Pane> {
+                (id != 3 && id != 5).then_some(Pane { id })
+            }
+        }
+
+        let mut restored = crate::multi_viewport::DockingMultiViewport::new(Tree::empty("restored"));
+        let report = restored
+            .load_layout_from_ron_str_with_registry(&ron, &mut DroppingRegistry)
+            .unwrap();
+
+        assert_eq!(report.missing_panes, vec![3, 5]);
+        assert_eq!(report.duplicate_panes, vec![2]);
+        assert!(report.pruned_detached.is_empty());
+        assert_eq!(report.pruned_floating, vec![8]);
+        assert_eq!(report.pruned_containers, 1);
+        assert_eq!(report.repaired_tabs_active, 1);
+        assert_eq!(report.repaired_z_order, 1);
+        assert!(!report.is_clean());
+
+        let root_manager = restored.floating.get(&ViewportId::ROOT).unwrap();
+        assert_eq!(root_manager.z_order, vec![7]);
+    }
+
+    #[test]
+    fn detached_windows_follow_their_monitor() {
+        use egui::{Rect, pos2, vec2};
+
+        use crate::multi_viewport::backend_hints::set_backend_monitors_outer_rects_points;
+
+        let mut docking =
+            crate::multi_viewport::DockingMultiViewport::new(new_tree_tabs(Id::new("root"), &[1]));
+        docking
+            .add_detached_viewport(
+                new_tree_tabs(Id::new("detached"), &[2]),
+                ViewportBuilder::default()
+                    .with_position(pos2(2240.0, 256.0))
+                    .with_inner_size(vec2(400.0, 300.0)),
+            )
+            .unwrap();
+        let mut snapshot = docking.snapshot_layout(|pane| pane.id);
+        snapshot.detached[0].viewport.monitor = Some(MonitorSnapshot {
+            index: 1,
+            rect: Rect::from_min_size(pos2(1920.0, 0.0), vec2(1280.0, 1024.0)),
+            relative_pos: vec2(0.25, 0.25),
+        });
+
+        // The second monitor was unplugged: the window lands on the primary one.
+        let ctx = Context::default();
+        set_backend_monitors_outer_rects_points(
+            &ctx,
+            vec![Rect::from_min_size(Pos2::ZERO, vec2(1920.0, 1080.0))],
+        );
+        let mut restored = crate::multi_viewport::DockingMultiViewport::new(Tree::empty("restored"));
+        let report = restored
+            .load_layout_snapshot_in_ctx(&ctx, snapshot, |id| Pane { id })
+            .unwrap();
+
+        let (&viewport_id, detached) = restored.detached.iter().next().unwrap();
+        assert_eq!(
+            report.remapped_viewports,
+            vec![(viewport_id, pos2(2240.0, 256.0), pos2(480.0, 270.0))]
+        );
+        assert!(report.clamped_viewports.is_empty());
+        assert_eq!(detached.builder.position, Some(pos2(480.0, 270.0)));
+    }
+
+    #[test]
+    fn detached_windows_loaded_before_the_first_frame_are_placed_once_monitors_are_known() {
+        use egui::{Rect, pos2, vec2};
+
+        use crate::multi_viewport::backend_hints::set_backend_monitors_outer_rects_points;
+
+        let mut docking =
+            crate::multi_viewport::DockingMultiViewport::new(new_tree_tabs(Id::new("root"), &[1]));
+        docking
+            .add_detached_viewport(
+                new_tree_tabs(Id::new("detached"), &[2]),
+                ViewportBuilder::default()
+                    .with_position(pos2(2240.0, 256.0))
+                    .with_inner_size(vec2(400.0, 300.0)),
+            )
+            .unwrap();
+        let mut snapshot = docking.snapshot_layout(|pane| pane.id);
+        snapshot.detached[0].viewport.monitor = Some(MonitorSnapshot {
+            index: 1,
+            rect: Rect::from_min_size(pos2(1920.0, 0.0), vec2(1280.0, 1024.0)),
+            relative_pos: vec2(0.25, 0.25),
+        });
+
+        // Loaded from `CreationContext`: no frame has reported the monitors yet.
+        let ctx = Context::default();
+        let mut restored = crate::multi_viewport::DockingMultiViewport::new(Tree::empty("restored"));
+        let report = restored
+            .load_layout_snapshot_in_ctx(&ctx, snapshot, |id| Pane { id })
+            .unwrap();
+        assert!(report.remapped_viewports.is_empty());
+        let viewport_id = *restored.detached.keys().next().unwrap();
+        assert_eq!(
+            restored.detached[&viewport_id].builder.position,
+            Some(pos2(2240.0, 256.0))
+        );
+
+        // The first frame knows them: the second monitor is gone.
+        set_backend_monitors_outer_rects_points(
+            &ctx,
+            vec![Rect::from_min_size(Pos2::ZERO, vec2(1920.0, 1080.0))],
+        );
+        restored.apply_pending_viewport_placement(&ctx);
+        assert!(restored.pending_viewport_placement.is_empty());
+        assert_eq!(
+            restored.detached[&viewport_id].builder.position,
+            Some(pos2(480.0, 270.0))
+        );
+    }
+
+    #[test]
+    fn windows_loaded_before_the_first_frame_are_rescaled_before_they_are_clamped() {
+        use egui::{Rect, pos2, vec2};
+
+        use crate::multi_viewport::backend_hints::set_backend_monitors_outer_rects_points;
+        use crate::multi_viewport::snapshot::ViewportRuntime;
+
+        let mut docking =
+            crate::multi_viewport::DockingMultiViewport::new(new_tree_tabs(Id::new("root"), &[1]));
+        docking
+            .add_detached_viewport(
+                new_tree_tabs(Id::new("detached"), &[2]),
+                ViewportBuilder::default(),
+            )
+            .unwrap();
+        let mut snapshot = docking.snapshot_layout(|pane| pane.id);
+        // Saved on a 2x display, in points at that scale.
+        let viewport = &mut snapshot.detached[0].viewport;
+        viewport.outer_pos = Some(pos2(700.0, 300.0));
+        viewport.inner_size = Some(vec2(400.0, 300.0));
+        viewport.pixels_per_point = Some(2.0);
+        viewport.monitor = None;
+
+        let ctx = Context::default();
+        let mut restored = crate::multi_viewport::DockingMultiViewport::new(Tree::empty("restored"));
+        restored
+            .load_layout_snapshot_in_ctx(&ctx, snapshot, |id| Pane { id })
+            .unwrap();
+        let viewport_id = *restored.detached.keys().next().unwrap();
+
+        // First frame: a 1x display.
+        restored.last_viewport_runtime.insert(
+            ViewportId::ROOT,
+            ViewportRuntime {
+                pixels_per_point: 1.0,
+                ..Default::default()
+            },
+        );
+        set_backend_monitors_outer_rects_points(
+            &ctx,
+            vec![Rect::from_min_size(Pos2::ZERO, vec2(1920.0, 1080.0))],
+        );
+        restored.apply_pending_dpi_rescale(&ctx);
+        restored.apply_pending_viewport_placement(&ctx);
+
+        let builder = &restored.detached[&viewport_id].builder;
+        assert_eq!(builder.inner_size, Some(vec2(800.0, 600.0)));
+        // Clamped with the converted size; the saved 400x300 would have fit at (1400, 600).
+        assert_eq!(builder.position, Some(pos2(1120.0, 480.0)));
+    }
+
+    #[test]
+    fn older_snapshot_versions_load_with_migrated_pane_ids() {
+        use crate::multi_viewport::LAYOUT_SNAPSHOT_VERSION;
+
+        let root_tree = new_tree_tabs(Id::new("root"), &[1, 2, 3]);
+        let docking = crate::multi_viewport::DockingMultiViewport::new(root_tree);
+        let mut snapshot = docking.snapshot_layout(|pane| pane.id);
+        snapshot.version = 2;
+        let ron = ron::ser::to_string_pretty(&snapshot, pretty_ron_config()).unwrap();
+
+        // Tool 2 was renamed to 20; tool 3 was merged into tool 1.
+        struct RenamingRegistry;
+
+        impl PaneRegistry<Pane> for RenamingRegistry {
+            type PaneId = usize;
+
+            fn pane_id(&mut self, pane: &Pane) -> Self::PaneId {
+                pane.id
+            }
+
+            fn pane_from_id(&mut self, id: Self::PaneId) -> Pane {
+                Pane { id }
+            }
+
+            fn migrate_pane_id(&mut self, id: Self::PaneId) -> Option<Self::PaneId> {
+                match id {
+                    2 => Some(20),
+                    3 => None,
+                    id => Some(id),
+                }
+            }
+        }
+
+        let mut restored = crate::multi_viewport::DockingMultiViewport::new(Tree::empty("restored"));
+        let report = restored
+            .load_layout_from_ron_str_with_registry(&ron, &mut RenamingRegistry)
+            .unwrap();
+        assert_eq!(report.migrated_away_panes, vec![3]);
+        assert!(report.missing_panes.is_empty());
+
+        let mut panes: Vec<_> = restored
+            .tree
+            .tiles
+            .iter()
+            .filter_map(|(_, t)| match t {
+                Tile::Pane(p) => Some(p.id),
+                Tile::Container(_) => None,
+            })
+            .collect();
+        panes.sort_unstable();
+        assert_eq!(panes, vec![1, 20]);
+        assert_eq!(
+            restored.snapshot_layout(|pane| pane.id).version,
+            LAYOUT_SNAPSHOT_VERSION
+        );
+    }
+
+    #[test]
+    fn state_registry_roundtrips_pane_state() {
+        use crate::multi_viewport::PaneStateRegistry;
+
+        #[derive(Debug, PartialEq)]
+        struct Editor {
+            id: usize,
+            path: Option<String>,
+        }
+
+        struct EditorRegistry;
+
+        impl PaneRegistry<Editor> for EditorRegistry {
+            type PaneId = usize;
+
+            fn pane_id(&mut self, pane: &Editor) -> Self::PaneId {
+                pane.id
+            }
+
+            fn pane_from_id(&mut self, id: Self::PaneId) -> Editor {
+                Editor { id, path: None }
+            }
+        }
+
+        impl PaneStateRegistry<Editor> for EditorRegistry {
+            fn save_pane_state(&mut self, pane: &Editor) -> Option<String> {
+                pane.path.clone()
+            }
+
+            fn load_pane_state(&mut self, pane: &mut Editor, state: &str) {
+                pane.path = Some(state.to_owned());
+            }
+        }
+
+        let mut tiles = egui_tiles::Tiles::default();
+        let a = tiles.insert_pane(Editor {
+            id: 1,
+            path: Some("src/main.rs".to_owned()),
+        });
+        let b = tiles.insert_pane(Editor { id: 2, path: None });
+        let root = tiles.insert_tab_tile(vec![a, b]);
+        let docking =
+            crate::multi_viewport::DockingMultiViewport::new(Tree::new(Id::new("root"), root, tiles));
+
+        let id_only = docking
+            .snapshot_layout_to_ron_string_with_registry(&mut EditorRegistry)
+            .unwrap();
+        assert!(!id_only.contains("state"));
+
+        let ron = docking
+            .snapshot_layout_to_ron_string_with_state_registry(&mut EditorRegistry)
+            .unwrap();
+        let mut restored = crate::multi_viewport::DockingMultiViewport::new(Tree::empty("restored"));
+        restored
+            .load_layout_from_ron_str_with_state_registry(&ron, &mut EditorRegistry)
+            .unwrap();
+
+        let mut panes: Vec<_> = restored
+            .tree
+            .tiles
+            .iter()
+            .filter_map(|(_, t)| match t {
+                Tile::Pane(p) => Some((p.id, p.path.clone())),
+                Tile::Container(_) => None,
+            })
+            .collect();
+        panes.sort_unstable();
+        assert_eq!(panes, vec![(1, Some("src/main.rs".to_owned())), (2, None)]);
+    }
 }
@@ -0,0 +1,291 @@
+use std::hash::Hash;
+use std::path::Path;
+
+use egui::Context;
+
+use super::load_report::LoadReport;
+use super::migration::{LayoutVersionHeader, check_layout_snapshot_version};
+use super::persistence::LayoutPersistenceError;
+use super::snapshot::LayoutSnapshot;
+use super::{PaneRegistry, PaneStateRegistry};
+
+impl<Pane> super::DockingMultiViewport<Pane> {
+    pub fn snapshot_layout_to_json_string_with_registry<R>(
+        &self,
+        registry: &mut R,
+    ) -> Result<String, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let snapshot = self.snapshot_layout_with_registry(registry);
+        Ok(serde_json::to_string_pretty(&snapshot)?)
+    }
+
+    pub fn snapshot_layout_to_json_string<PaneId>(
+        &self,
+        pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> Result<String, LayoutPersistenceError>
+    where
+        PaneId: serde::Serialize,
+    {
+        let snapshot = self.snapshot_layout_impl(pane_to_id);
+        Ok(serde_json::to_string_pretty(&snapshot)?)
+    }
+
+    pub fn save_layout_to_json_file_with_registry<R>(
+        &self,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<(), LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let json = self.snapshot_layout_to_json_string_with_registry(registry)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    pub fn save_layout_to_json_file<PaneId>(
+        &self,
+        path: impl AsRef<Path>,
+        pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> Result<(), LayoutPersistenceError>
+    where
+        PaneId: serde::Serialize,
+    {
+        let json = self.snapshot_layout_to_json_string(pane_to_id)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    pub fn load_layout_from_json_str<PaneId>(
+        &mut self,
+        json: &str,
+        pane_from_id: impl FnMut(PaneId) -> Pane,
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
+    where
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
+    {
+        let snapshot: LayoutSnapshot<PaneId> = layout_snapshot_from_json_str(json)?;
+        self.load_layout_snapshot(snapshot, pane_from_id)
+    }
+
+    pub fn load_layout_from_json_str_with_registry<R>(
+        &mut self,
+        json: &str,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_json_str(json)?;
+        self.load_layout_snapshot_with_registry(snapshot, registry)
+    }
+
+    pub fn load_layout_from_json_str_in_ctx<PaneId>(
+        &mut self,
+        ctx: &Context,
+        json: &str,
+        pane_from_id: impl FnMut(PaneId) -> Pane,
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
+    where
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
+    {
+        let snapshot: LayoutSnapshot<PaneId> = layout_snapshot_from_json_str(json)?;
+        self.load_layout_snapshot_in_ctx(ctx, snapshot, pane_from_id)
+    }
+
+    pub fn load_layout_from_json_str_in_ctx_with_registry<R>(
+        &mut self,
+        ctx: &Context,
+        json: &str,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_json_str(json)?;
+        self.load_layout_snapshot_in_ctx_with_registry(ctx, snapshot, registry)
+    }
+
+    pub fn load_layout_from_json_file<PaneId>(
+        &mut self,
+        path: impl AsRef<Path>,
+        pane_from_id: impl FnMut(PaneId) -> Pane,
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
+    where
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
+    {
+        let json = std::fs::read_to_string(path)?;
+        self.load_layout_from_json_str(&json, pane_from_id)
+    }
+
+    pub fn load_layout_from_json_file_with_registry<R>(
+        &mut self,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let json = std::fs::read_to_string(path)?;
+        self.load_layout_from_json_str_with_registry(&json, registry)
+    }
+
+    pub fn load_layout_from_json_file_in_ctx<PaneId>(
+        &mut self,
+        ctx: &Context,
+        path: impl AsRef<Path>,
+        pane_from_id: impl FnMut(PaneId) -> Pane,
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
+    where
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
+    {
+        let json = std::fs::read_to_string(path)?;
+        self.load_layout_from_json_str_in_ctx(ctx, &json, pane_from_id)
+    }
+
+    pub fn load_layout_from_json_file_in_ctx_with_registry<R>(
+        &mut self,
+        ctx: &Context,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let json = std::fs::read_to_string(path)?;
+        self.load_layout_from_json_str_in_ctx_with_registry(ctx, &json, registry)
+    }
+
+    pub fn snapshot_layout_to_json_string_with_state_registry<R>(
+        &self,
+        registry: &mut R,
+    ) -> Result<String, LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let snapshot = self.snapshot_layout_with_state_registry(registry);
+        Ok(serde_json::to_string_pretty(&snapshot)?)
+    }
+
+    pub fn save_layout_to_json_file_with_state_registry<R>(
+        &self,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<(), LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let json = self.snapshot_layout_to_json_string_with_state_registry(registry)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    pub fn load_layout_from_json_str_with_state_registry<R>(
+        &mut self,
+        json: &str,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_json_str(json)?;
+        self.load_layout_snapshot_with_state_registry(snapshot, registry)
+    }
+
+    pub fn load_layout_from_json_file_in_ctx_with_state_registry<R>(
+        &mut self,
+        ctx: &Context,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let json = std::fs::read_to_string(path)?;
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_json_str(&json)?;
+        self.load_layout_snapshot_in_ctx_with_state_registry(ctx, snapshot, registry)
+    }
+}
+
+/// Like `layout_snapshot_from_ron_str`: the version is checked before the whole snapshot is read.
+fn layout_snapshot_from_json_str<PaneId>(
+    json: &str,
+) -> Result<LayoutSnapshot<PaneId>, LayoutPersistenceError>
+where
+    PaneId: for<'de> serde::Deserialize<'de>,
+{
+    let header: LayoutVersionHeader = serde_json::from_str(json)?;
+    check_layout_snapshot_version(header.version)?;
+    Ok(serde_json::from_str(json)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Pos2, Vec2, ViewportBuilder};
+    use egui_tiles::{Tile, Tree};
+
+    use crate::multi_viewport::test_support::{
+        StateRegistry, docking_with_tabs, root_panes, tabs_tree,
+    };
+    use crate::multi_viewport::{DockNodeFlags, DockingMultiViewport, PaneHost};
+
+    #[test]
+    fn json_roundtrip_restores_layout() {
+        let root_tree = tabs_tree("root", &[1, 2]);
+        let root = root_tree.root.unwrap();
+        let mut docking = DockingMultiViewport::new(root_tree);
+        assert!(docking.set_node_flags(PaneHost::RootDock, root, DockNodeFlags::NO_SPLIT));
+        docking
+            .add_detached_viewport(
+                tabs_tree("detached", &[3]),
+                ViewportBuilder::default()
+                    .with_position(Pos2::new(10.0, 20.0))
+                    .with_inner_size(Vec2::new(300.0, 200.0)),
+            )
+            .unwrap();
+
+        let json = docking.snapshot_layout_to_json_string(|&pane| pane).unwrap();
+
+        let mut restored = DockingMultiViewport::new(Tree::empty("restored"));
+        restored.load_layout_from_json_str(&json, |id: usize| id).unwrap();
+
+        assert_eq!(restored.detached_viewport_count(), 1);
+        let restored_root = restored.tree.root.unwrap();
+        assert_eq!(
+            restored.node_flags(PaneHost::RootDock, restored_root),
+            DockNodeFlags::NO_SPLIT
+        );
+        let panes = restored
+            .tree
+            .tiles
+            .iter()
+            .filter(|(_, tile)| matches!(tile, Tile::Pane(_)))
+            .count();
+        assert_eq!(panes, 2);
+    }
+
+    #[test]
+    fn json_roundtrip_applies_pane_state() {
+        let docking = docking_with_tabs(&[1, 2]);
+        let json = docking
+            .snapshot_layout_to_json_string_with_state_registry(&mut StateRegistry)
+            .unwrap();
+
+        let mut restored = DockingMultiViewport::new(Tree::empty("restored"));
+        restored
+            .load_layout_from_json_str_with_state_registry(&json, &mut StateRegistry)
+            .unwrap();
+        assert_eq!(root_panes(&restored), vec![1, 2]);
+    }
+
+    #[test]
+    fn invalid_json_is_reported() {
+        let mut docking = docking_with_tabs(&[1]);
+        assert!(matches!(
+            docking.load_layout_from_json_str("{", |id: usize| id),
+            Err(crate::LayoutPersistenceError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,292 @@
+use std::hash::Hash;
+use std::path::Path;
+
+use egui::Context;
+
+use super::load_report::LoadReport;
+use super::migration::{LayoutVersionHeader, check_layout_snapshot_version};
+use super::persistence::LayoutPersistenceError;
+use super::snapshot::LayoutSnapshot;
+use super::{PaneRegistry, PaneStateRegistry};
+
+impl<Pane> super::DockingMultiViewport<Pane> {
+    pub fn snapshot_layout_to_msgpack_bytes_with_registry<R>(
+        &self,
+        registry: &mut R,
+    ) -> Result<Vec<u8>, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let snapshot = self.snapshot_layout_with_registry(registry);
+        Ok(rmp_serde::to_vec_named(&snapshot)?)
+    }
+
+    /// Encode the layout as MessagePack (structs as maps, so field additions stay decodable).
+    pub fn snapshot_layout_to_msgpack_bytes<PaneId>(
+        &self,
+        pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> Result<Vec<u8>, LayoutPersistenceError>
+    where
+        PaneId: serde::Serialize,
+    {
+        let snapshot = self.snapshot_layout_impl(pane_to_id);
+        Ok(rmp_serde::to_vec_named(&snapshot)?)
+    }
+
+    pub fn save_layout_to_msgpack_file_with_registry<R>(
+        &self,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<(), LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let bytes = self.snapshot_layout_to_msgpack_bytes_with_registry(registry)?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    pub fn save_layout_to_msgpack_file<PaneId>(
+        &self,
+        path: impl AsRef<Path>,
+        pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> Result<(), LayoutPersistenceError>
+    where
+        PaneId: serde::Serialize,
+    {
+        let bytes = self.snapshot_layout_to_msgpack_bytes(pane_to_id)?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    pub fn load_layout_from_msgpack_bytes<PaneId>(
+        &mut self,
+        bytes: &[u8],
+        pane_from_id: impl FnMut(PaneId) -> Pane,
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
+    where
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
+    {
+        let snapshot: LayoutSnapshot<PaneId> = layout_snapshot_from_msgpack_bytes(bytes)?;
+        self.load_layout_snapshot(snapshot, pane_from_id)
+    }
+
+    pub fn load_layout_from_msgpack_bytes_with_registry<R>(
+        &mut self,
+        bytes: &[u8],
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_msgpack_bytes(bytes)?;
+        self.load_layout_snapshot_with_registry(snapshot, registry)
+    }
+
+    pub fn load_layout_from_msgpack_bytes_in_ctx<PaneId>(
+        &mut self,
+        ctx: &Context,
+        bytes: &[u8],
+        pane_from_id: impl FnMut(PaneId) -> Pane,
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
+    where
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
+    {
+        let snapshot: LayoutSnapshot<PaneId> = layout_snapshot_from_msgpack_bytes(bytes)?;
+        self.load_layout_snapshot_in_ctx(ctx, snapshot, pane_from_id)
+    }
+
+    pub fn load_layout_from_msgpack_bytes_in_ctx_with_registry<R>(
+        &mut self,
+        ctx: &Context,
+        bytes: &[u8],
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_msgpack_bytes(bytes)?;
+        self.load_layout_snapshot_in_ctx_with_registry(ctx, snapshot, registry)
+    }
+
+    pub fn load_layout_from_msgpack_file<PaneId>(
+        &mut self,
+        path: impl AsRef<Path>,
+        pane_from_id: impl FnMut(PaneId) -> Pane,
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
+    where
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
+    {
+        let bytes = std::fs::read(path)?;
+        self.load_layout_from_msgpack_bytes(&bytes, pane_from_id)
+    }
+
+    pub fn load_layout_from_msgpack_file_with_registry<R>(
+        &mut self,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let bytes = std::fs::read(path)?;
+        self.load_layout_from_msgpack_bytes_with_registry(&bytes, registry)
+    }
+
+    pub fn load_layout_from_msgpack_file_in_ctx<PaneId>(
+        &mut self,
+        ctx: &Context,
+        path: impl AsRef<Path>,
+        pane_from_id: impl FnMut(PaneId) -> Pane,
+    ) -> Result<LoadReport<PaneId>, LayoutPersistenceError>
+    where
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
+    {
+        let bytes = std::fs::read(path)?;
+        self.load_layout_from_msgpack_bytes_in_ctx(ctx, &bytes, pane_from_id)
+    }
+
+    pub fn load_layout_from_msgpack_file_in_ctx_with_registry<R>(
+        &mut self,
+        ctx: &Context,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneRegistry<Pane>,
+    {
+        let bytes = std::fs::read(path)?;
+        self.load_layout_from_msgpack_bytes_in_ctx_with_registry(ctx, &bytes, registry)
+    }
+
+    pub fn snapshot_layout_to_msgpack_bytes_with_state_registry<R>(
+        &self,
+        registry: &mut R,
+    ) -> Result<Vec<u8>, LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let snapshot = self.snapshot_layout_with_state_registry(registry);
+        Ok(rmp_serde::to_vec_named(&snapshot)?)
+    }
+
+    pub fn save_layout_to_msgpack_file_with_state_registry<R>(
+        &self,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<(), LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let bytes = self.snapshot_layout_to_msgpack_bytes_with_state_registry(registry)?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    pub fn load_layout_from_msgpack_bytes_with_state_registry<R>(
+        &mut self,
+        bytes: &[u8],
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_msgpack_bytes(bytes)?;
+        self.load_layout_snapshot_with_state_registry(snapshot, registry)
+    }
+
+    pub fn load_layout_from_msgpack_file_in_ctx_with_state_registry<R>(
+        &mut self,
+        ctx: &Context,
+        path: impl AsRef<Path>,
+        registry: &mut R,
+    ) -> Result<LoadReport<R::PaneId>, LayoutPersistenceError>
+    where
+        R: PaneStateRegistry<Pane>,
+    {
+        let bytes = std::fs::read(path)?;
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_msgpack_bytes(&bytes)?;
+        self.load_layout_snapshot_in_ctx_with_state_registry(ctx, snapshot, registry)
+    }
+}
+
+/// Like `layout_snapshot_from_ron_str`: the version is checked before the whole snapshot is read.
+fn layout_snapshot_from_msgpack_bytes<PaneId>(
+    bytes: &[u8],
+) -> Result<LayoutSnapshot<PaneId>, LayoutPersistenceError>
+where
+    PaneId: for<'de> serde::Deserialize<'de>,
+{
+    let header: LayoutVersionHeader = rmp_serde::from_slice(bytes)?;
+    check_layout_snapshot_version(header.version)?;
+    Ok(rmp_serde::from_slice(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Vec2, ViewportBuilder};
+    use egui_tiles::{Tile, Tree};
+
+    use crate::multi_viewport::test_support::{
+        StateRegistry, docking_with_tabs, root_panes, tabs_tree,
+    };
+    use crate::multi_viewport::{DockingMultiViewport, LAYOUT_SNAPSHOT_VERSION};
+
+    #[test]
+    fn msgpack_roundtrip_restores_layout() {
+        let mut docking = docking_with_tabs(&[1, 2]);
+        docking
+            .add_detached_viewport(
+                tabs_tree("detached", &[3]),
+                ViewportBuilder::default().with_inner_size(Vec2::new(200.0, 100.0)),
+            )
+            .unwrap();
+
+        let bytes = docking.snapshot_layout_to_msgpack_bytes(|&pane| pane).unwrap();
+        let ron = docking.snapshot_layout_to_ron_string(|&pane| pane).unwrap();
+        assert!(bytes.len() < ron.len());
+
+        let mut restored = DockingMultiViewport::new(Tree::empty("restored"));
+        restored
+            .load_layout_from_msgpack_bytes(&bytes, |id: usize| id)
+            .unwrap();
+        assert_eq!(restored.detached_viewport_count(), 1);
+        let panes = restored
+            .tree
+            .tiles
+            .iter()
+            .filter(|(_, tile)| matches!(tile, Tile::Pane(_)))
+            .count();
+        assert_eq!(panes, 2);
+        assert_eq!(
+            restored.snapshot_layout(|&pane| pane).version,
+            LAYOUT_SNAPSHOT_VERSION
+        );
+    }
+
+    #[test]
+    fn msgpack_roundtrip_applies_pane_state() {
+        let docking = docking_with_tabs(&[1, 2]);
+        let bytes = docking
+            .snapshot_layout_to_msgpack_bytes_with_state_registry(&mut StateRegistry)
+            .unwrap();
+
+        let mut restored = DockingMultiViewport::new(Tree::empty("restored"));
+        restored
+            .load_layout_from_msgpack_bytes_with_state_registry(&bytes, &mut StateRegistry)
+            .unwrap();
+        assert_eq!(root_panes(&restored), vec![1, 2]);
+    }
+
+    #[test]
+    fn truncated_msgpack_is_reported() {
+        let docking = docking_with_tabs(&[1, 2]);
+        let bytes = docking.snapshot_layout_to_msgpack_bytes(|&pane| pane).unwrap();
+
+        let mut restored = DockingMultiViewport::new(Tree::empty("restored"));
+        assert!(matches!(
+            restored.load_layout_from_msgpack_bytes(&bytes[..bytes.len() / 2], |id: usize| id),
+            Err(crate::LayoutPersistenceError::MsgPackDecode(_))
+        ));
+    }
+}
@@ -0,0 +1,650 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use egui::{Context, ViewportId};
+use egui_tiles::{Tile, TileId, Tree};
+
+use super::debug_log::DebugLogRecord;
+use super::snapshot::LayoutSnapshot;
+use super::DockingMultiViewport;
+use crate::workspace::WorkspaceLayout;
+
+/// A named set of workspace layouts (Blender workspaces / Eclipse perspectives).
+///
+/// Each preset keeps the layout it was created with plus the user's modifications of it.
+/// Switch between presets with [`DockingMultiViewport::switch_workspace_preset`], which reuses
+/// the live `Pane` values by `PaneId` instead of re-creating them.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub struct WorkspacePresets<PaneId> {
+    presets: Vec<WorkspacePreset<PaneId>>,
+    active: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+struct WorkspacePreset<PaneId> {
+    name: String,
+
+    /// The layout the preset was inserted with; see [`WorkspacePresets::reset`].
+    default: LayoutSnapshot<PaneId>,
+
+    /// The layout including the user's modifications.
+    layout: LayoutSnapshot<PaneId>,
+}
+
+impl<PaneId> Default for WorkspacePresets<PaneId> {
+    fn default() -> Self {
+        Self {
+            presets: Vec::new(),
+            active: None,
+        }
+    }
+}
+
+impl<PaneId> WorkspacePresets<PaneId> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a preset (or replace the preset called `name`, keeping its position).
+    ///
+    /// `layout` becomes both the default and the current layout of the preset.
+    pub fn insert(&mut self, name: impl Into<String>, layout: LayoutSnapshot<PaneId>)
+    where
+        PaneId: Clone,
+    {
+        let name = name.into();
+        let preset = WorkspacePreset {
+            name,
+            default: layout.clone(),
+            layout,
+        };
+        match self.index_of(&preset.name) {
+            Some(index) => self.presets[index] = preset,
+            None => self.presets.push(preset),
+        }
+    }
+
+    /// Like [`Self::insert`], from a scripted [`WorkspaceLayout`].
+    ///
+    /// The panes of `layout` are only used to compute their `PaneId`s and are dropped.
+    pub fn insert_workspace_layout<Pane>(
+        &mut self,
+        name: impl Into<String>,
+        layout: WorkspaceLayout<Pane>,
+        pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) where
+        PaneId: Clone,
+    {
+        let mut docking = DockingMultiViewport::new(Tree::empty("egui_docking_workspace_preset"));
+        docking.apply_workspace_layout(layout);
+        self.insert(name, docking.snapshot_layout_impl(pane_to_id));
+    }
+
+    /// Remove the preset called `name`. Returns `false` if there is no such preset.
+    ///
+    /// Removing the active preset leaves no preset active; the current layout is kept.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let Some(index) = self.index_of(name) else {
+            return false;
+        };
+        self.presets.remove(index);
+        if self.active.as_deref() == Some(name) {
+            self.active = None;
+        }
+        true
+    }
+
+    /// Discard the user's modifications of the preset called `name`.
+    ///
+    /// The live layout of the active preset is saved over it by the next switch; use
+    /// [`DockingMultiViewport::reset_workspace_preset`] to reset and re-apply it instead.
+    pub fn reset(&mut self, name: &str) -> bool
+    where
+        PaneId: Clone,
+    {
+        let Some(index) = self.index_of(name) else {
+            return false;
+        };
+        let preset = &mut self.presets[index];
+        preset.layout = preset.default.clone();
+        true
+    }
+
+    /// Preset names, in insertion order.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.presets.iter().map(|p| p.name.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.index_of(name).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.presets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+
+    /// The preset last switched to (if it still exists).
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// The current layout (with user modifications) of the preset called `name`.
+    pub fn layout(&self, name: &str) -> Option<&LayoutSnapshot<PaneId>> {
+        self.index_of(name).map(|index| &self.presets[index].layout)
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.presets.iter().position(|p| p.name == name)
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl<PaneId> WorkspacePresets<PaneId> {
+    pub fn to_ron_string(&self) -> Result<String, super::LayoutPersistenceError>
+    where
+        PaneId: serde::Serialize,
+    {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            super::persistence::pretty_ron_config(),
+        )?)
+    }
+
+    /// Parse a preset set written by [`Self::to_ron_string`].
+    ///
+    /// Stored layouts are upgraded with [`super::migrate_layout_snapshot`] and checked with
+    /// [`super::validate_layout_snapshot`]. Their versions are checked before anything else is
+    /// read, so presets saved by a newer version fail with `UnsupportedVersion`.
+    pub fn from_ron_str(ron_str: &str) -> Result<Self, super::LayoutPersistenceError>
+    where
+        PaneId: for<'de> serde::Deserialize<'de>,
+    {
+        Self::from_ron_str_impl(ron_str, Some)
+    }
+
+    /// Like [`Self::from_ron_str`], also rewriting every stored pane id with
+    /// [`super::PaneRegistry::migrate_pane_id`]. Panes it drops are removed from the presets.
+    pub fn from_ron_str_with_registry<Pane, R>(
+        ron_str: &str,
+        registry: &mut R,
+    ) -> Result<Self, super::LayoutPersistenceError>
+    where
+        R: super::PaneRegistry<Pane, PaneId = PaneId>,
+        PaneId: for<'de> serde::Deserialize<'de>,
+    {
+        Self::from_ron_str_impl(ron_str, |id| registry.migrate_pane_id(id))
+    }
+
+    fn from_ron_str_impl(
+        ron_str: &str,
+        mut migrate_pane_id: impl FnMut(PaneId) -> Option<PaneId>,
+    ) -> Result<Self, super::LayoutPersistenceError>
+    where
+        PaneId: for<'de> serde::Deserialize<'de>,
+    {
+        let header: PresetsVersionHeader = ron::from_str(ron_str)?;
+        for preset in &header.presets {
+            super::migration::check_layout_snapshot_version(preset.default.version)?;
+            super::migration::check_layout_snapshot_version(preset.layout.version)?;
+        }
+        let Self { presets, active } = ron::from_str(ron_str)?;
+        let mut migrate = |layout| {
+            let layout = super::migrate_layout_snapshot(layout)?;
+            super::validate_layout_snapshot(&layout)?;
+            Ok::<_, super::LayoutPersistenceError>(layout.filter_map_pane_ids(&mut migrate_pane_id))
+        };
+        let presets = presets
+            .into_iter()
+            .map(|preset| {
+                Ok(WorkspacePreset {
+                    name: preset.name,
+                    default: migrate(preset.default)?,
+                    layout: migrate(preset.layout)?,
+                })
+            })
+            .collect::<Result<_, super::LayoutPersistenceError>>()?;
+        Ok(Self { presets, active })
+    }
+
+    pub fn save_to_ron_file(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), super::LayoutPersistenceError>
+    where
+        PaneId: serde::Serialize,
+    {
+        std::fs::write(path, self.to_ron_string()?)?;
+        Ok(())
+    }
+
+    pub fn load_from_ron_file(
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, super::LayoutPersistenceError>
+    where
+        PaneId: for<'de> serde::Deserialize<'de>,
+    {
+        let ron = std::fs::read_to_string(path)?;
+        Self::from_ron_str(&ron)
+    }
+
+    pub fn load_from_ron_file_with_registry<Pane, R>(
+        path: impl AsRef<std::path::Path>,
+        registry: &mut R,
+    ) -> Result<Self, super::LayoutPersistenceError>
+    where
+        R: super::PaneRegistry<Pane, PaneId = PaneId>,
+        PaneId: for<'de> serde::Deserialize<'de>,
+    {
+        let ron = std::fs::read_to_string(path)?;
+        Self::from_ron_str_with_registry(&ron, registry)
+    }
+}
+
+/// The layout versions of stored presets, checked before the layouts are read (see
+/// `LayoutVersionHeader`).
+#[cfg(feature = "persistence")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "WorkspacePresets")]
+struct PresetsVersionHeader {
+    presets: Vec<PresetVersionHeader>,
+}
+
+#[cfg(feature = "persistence")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "WorkspacePreset")]
+struct PresetVersionHeader {
+    default: super::migration::LayoutVersionHeader,
+    layout: super::migration::LayoutVersionHeader,
+}
+
+fn drain_panes<Pane>(tree: &mut Tree<Pane>, out: &mut Vec<Pane>) {
+    let tile_ids: Vec<TileId> = tree.tiles.tile_ids().collect();
+    for tile_id in tile_ids {
+        if let Some(Tile::Pane(pane)) = tree.tiles.remove(tile_id) {
+            out.push(pane);
+        }
+    }
+    tree.root = None;
+}
+
+impl<Pane> DockingMultiViewport<Pane> {
+    /// Switch to the preset called `name`, reusing the live panes.
+    ///
+    /// The current layout is first stored as the user's modification of the active preset (so
+    /// switching to the active preset keeps the live layout). Every pane is then keyed by
+    /// `pane_to_id` and moved into the new layout; `pane_from_id` is only called for panes that
+    /// don't exist yet (return `None` to drop them). Panes the new layout doesn't use are parked
+    /// (see [`Self::parked_panes`]) until a later switch needs them.
+    ///
+    /// The undo history (if enabled) is cleared.
+    ///
+    /// Returns `false` (and changes nothing) if there is no preset called `name`.
+    pub fn switch_workspace_preset<PaneId>(
+        &mut self,
+        ctx: &Context,
+        presets: &mut WorkspacePresets<PaneId>,
+        name: &str,
+        pane_to_id: impl FnMut(&Pane) -> PaneId,
+        pane_from_id: impl FnMut(PaneId) -> Option<Pane>,
+    ) -> bool
+    where
+        PaneId: Clone + Eq + Hash,
+    {
+        let Some(pool) = self.begin_workspace_switch(presets, name, false, pane_to_id) else {
+            return false;
+        };
+        self.apply_workspace_preset(ctx, presets, name, pool, pane_from_id);
+        true
+    }
+
+    /// Like [`Self::switch_workspace_preset`], using a [`super::PaneRegistry`] for pane ids.
+    #[cfg(feature = "persistence")]
+    pub fn switch_workspace_preset_with_registry<R>(
+        &mut self,
+        ctx: &Context,
+        presets: &mut WorkspacePresets<R::PaneId>,
+        name: &str,
+        registry: &mut R,
+    ) -> bool
+    where
+        R: super::PaneRegistry<Pane>,
+    {
+        let Some(pool) =
+            self.begin_workspace_switch(presets, name, false, |pane| registry.pane_id(pane))
+        else {
+            return false;
+        };
+        self.apply_workspace_preset(ctx, presets, name, pool, |id| registry.try_pane_from_id(id));
+        true
+    }
+
+    /// Discard the user's modifications of the preset called `name` (see
+    /// [`WorkspacePresets::reset`]) and switch to its default layout, even if it is active.
+    ///
+    /// Otherwise like [`Self::switch_workspace_preset`]: the active preset's layout is saved
+    /// first, and live panes are reused.
+    pub fn reset_workspace_preset<PaneId>(
+        &mut self,
+        ctx: &Context,
+        presets: &mut WorkspacePresets<PaneId>,
+        name: &str,
+        pane_to_id: impl FnMut(&Pane) -> PaneId,
+        pane_from_id: impl FnMut(PaneId) -> Option<Pane>,
+    ) -> bool
+    where
+        PaneId: Clone + Eq + Hash,
+    {
+        let Some(pool) = self.begin_workspace_switch(presets, name, true, pane_to_id) else {
+            return false;
+        };
+        self.apply_workspace_preset(ctx, presets, name, pool, pane_from_id);
+        true
+    }
+
+    /// Like [`Self::reset_workspace_preset`], using a [`super::PaneRegistry`] for pane ids.
+    #[cfg(feature = "persistence")]
+    pub fn reset_workspace_preset_with_registry<R>(
+        &mut self,
+        ctx: &Context,
+        presets: &mut WorkspacePresets<R::PaneId>,
+        name: &str,
+        registry: &mut R,
+    ) -> bool
+    where
+        R: super::PaneRegistry<Pane>,
+    {
+        let Some(pool) =
+            self.begin_workspace_switch(presets, name, true, |pane| registry.pane_id(pane))
+        else {
+            return false;
+        };
+        self.apply_workspace_preset(ctx, presets, name, pool, |id| registry.try_pane_from_id(id));
+        true
+    }
+
+    /// Store the current layout as the user's modification of the active preset
+    /// (e.g. before saving `presets`).
+    ///
+    /// Returns `false` if no preset is active.
+    pub fn save_active_workspace_preset<PaneId>(
+        &self,
+        presets: &mut WorkspacePresets<PaneId>,
+        pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> bool {
+        let Some(index) = presets.active.as_deref().and_then(|name| presets.index_of(name)) else {
+            return false;
+        };
+        presets.presets[index].layout = self.snapshot_layout_impl(pane_to_id);
+        true
+    }
+
+    /// Panes not used by the active workspace preset.
+    pub fn parked_panes(&self) -> impl Iterator<Item = &Pane> + '_ {
+        self.parked_panes.iter()
+    }
+
+    /// Take ownership of the parked panes (e.g. to drop panes the app no longer needs).
+    pub fn take_parked_panes(&mut self) -> Vec<Pane> {
+        std::mem::take(&mut self.parked_panes)
+    }
+
+    /// Save the active preset, optionally reset the preset called `name`, then pool the live
+    /// panes for [`Self::apply_workspace_preset`]. `None` if there is no preset called `name`.
+    fn begin_workspace_switch<PaneId>(
+        &mut self,
+        presets: &mut WorkspacePresets<PaneId>,
+        name: &str,
+        reset: bool,
+        mut pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> Option<HashMap<PaneId, Vec<Pane>>>
+    where
+        PaneId: Clone + Eq + Hash,
+    {
+        if !presets.contains(name) {
+            return None;
+        }
+        self.save_active_workspace_preset(presets, &mut pane_to_id);
+        if reset {
+            presets.reset(name);
+        }
+        Some(self.pool_panes(pane_to_id))
+    }
+
+    /// Move every pane (docked, detached, floating and parked) into a pool keyed by `PaneId`.
+    pub(super) fn pool_panes<PaneId>(
+        &mut self,
+        mut pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> HashMap<PaneId, Vec<Pane>>
+    where
+        PaneId: Eq + Hash,
+    {
+        let mut panes = std::mem::take(&mut self.parked_panes);
+        drain_panes(&mut self.tree, &mut panes);
+        for detached in self.detached.values_mut() {
+            drain_panes(&mut detached.tree, &mut panes);
+        }
+        for manager in self.floating.values_mut() {
+            for window in manager.windows.values_mut() {
+                drain_panes(&mut window.tree, &mut panes);
+            }
+        }
+
+        let mut pool: HashMap<PaneId, Vec<Pane>> = HashMap::new();
+        for pane in panes {
+            pool.entry(pane_to_id(&pane)).or_default().push(pane);
+        }
+        pool
+    }
+
+    fn apply_workspace_preset<PaneId>(
+        &mut self,
+        ctx: &Context,
+        presets: &mut WorkspacePresets<PaneId>,
+        name: &str,
+        mut pool: HashMap<PaneId, Vec<Pane>>,
+        mut pane_from_id: impl FnMut(PaneId) -> Option<Pane>,
+    ) where
+        PaneId: Clone + Eq + Hash,
+    {
+        let Some(layout) = presets.layout(name).cloned() else {
+            return;
+        };
+
+        egui::DragAndDrop::clear_payload(ctx);
+        ctx.stop_dragging();
+
+        self.restore_layout_snapshot(layout, |id| {
+            pool.get_mut(&id)
+                .and_then(Vec::pop)
+                .or_else(|| pane_from_id(id))
+        });
+        self.parked_panes.extend(pool.into_values().flatten());
+        presets.active = Some(name.to_owned());
+
+        self.debug_log_record(DebugLogRecord::PresetSwitch {
+            name: name.to_owned(),
+            parked: self.parked_panes.len(),
+        });
+
+        self.mark_layout_changed();
+        // A switch isn't an undoable step, and older steps belong to the previous preset.
+        self.clear_layout_history();
+
+        ctx.request_repaint();
+        ctx.request_repaint_of(ViewportId::ROOT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Vec2;
+
+    use super::*;
+    use crate::multi_viewport::test_support::{DummyBehavior, root_panes, tabs_tree};
+
+    fn workspace(panes: &[usize]) -> WorkspaceLayout<usize> {
+        WorkspaceLayout::new(tabs_tree("preset", panes))
+    }
+
+    fn presets() -> WorkspacePresets<usize> {
+        let mut presets = WorkspacePresets::new();
+        presets.insert_workspace_layout("edit", workspace(&[1, 2]), |&pane| pane);
+        presets.insert_workspace_layout("anim", workspace(&[2, 3]), |&pane| pane);
+        presets
+    }
+
+    /// Switch to `name`, returning how many panes had to be created.
+    fn switch(
+        docking: &mut DockingMultiViewport<usize>,
+        presets: &mut WorkspacePresets<usize>,
+        name: &str,
+    ) -> usize {
+        let ctx = Context::default();
+        let mut created = 0;
+        assert!(docking.switch_workspace_preset(&ctx, presets, name, |&pane| pane, |id| {
+            created += 1;
+            Some(id)
+        }));
+        created
+    }
+
+    #[test]
+    fn switching_reuses_and_parks_panes() {
+        let mut presets = presets();
+        let mut docking = DockingMultiViewport::new(Tree::empty("root"));
+
+        assert_eq!(switch(&mut docking, &mut presets, "edit"), 2);
+        assert_eq!(presets.active(), Some("edit"));
+
+        assert_eq!(switch(&mut docking, &mut presets, "anim"), 1);
+        assert_eq!(root_panes(&docking), vec![2, 3]);
+        assert_eq!(docking.parked_panes().copied().collect::<Vec<_>>(), vec![1]);
+
+        assert_eq!(switch(&mut docking, &mut presets, "edit"), 0);
+        assert_eq!(root_panes(&docking), vec![1, 2]);
+        assert_eq!(docking.parked_panes().copied().collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn switching_remembers_user_modifications() {
+        let mut presets = presets();
+        let mut docking = DockingMultiViewport::new(Tree::empty("root"));
+        switch(&mut docking, &mut presets, "edit");
+
+        docking
+            .float_pane(
+                &Context::default(),
+                &mut DummyBehavior,
+                |&pane| pane == 2,
+                ViewportId::ROOT,
+                Vec2::ZERO,
+                Vec2::new(320.0, 240.0),
+            )
+            .unwrap();
+
+        switch(&mut docking, &mut presets, "anim");
+        assert_eq!(docking.floating_window_count(), 0);
+
+        assert_eq!(switch(&mut docking, &mut presets, "edit"), 0);
+        assert_eq!(root_panes(&docking), vec![1]);
+        assert_eq!(docking.floating_window_count(), 1);
+
+        // Switching to the active preset keeps the live layout, even after a reset.
+        assert!(presets.reset("edit"));
+        switch(&mut docking, &mut presets, "edit");
+        assert_eq!(root_panes(&docking), vec![1]);
+        assert_eq!(docking.floating_window_count(), 1);
+
+        let ctx = Context::default();
+        assert!(docking.reset_workspace_preset(&ctx, &mut presets, "edit", |&p| p, Some));
+        assert_eq!(root_panes(&docking), vec![1, 2]);
+        assert_eq!(docking.floating_window_count(), 0);
+        assert!(!docking.reset_workspace_preset(&ctx, &mut presets, "missing", |&p| p, Some));
+    }
+
+    #[test]
+    fn unknown_preset_changes_nothing() {
+        let mut presets = presets();
+        let mut docking = DockingMultiViewport::new(Tree::empty("root"));
+        switch(&mut docking, &mut presets, "edit");
+
+        let ctx = Context::default();
+        assert!(!docking.switch_workspace_preset(&ctx, &mut presets, "missing", |&p| p, Some));
+        assert_eq!(root_panes(&docking), vec![1, 2]);
+        assert_eq!(presets.active(), Some("edit"));
+
+        assert!(presets.remove("edit"));
+        assert_eq!(presets.active(), None);
+        assert_eq!(presets.names().collect::<Vec<_>>(), vec!["anim"]);
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn presets_roundtrip_through_ron() {
+        let mut presets = presets();
+        let mut docking = DockingMultiViewport::new(Tree::empty("root"));
+        switch(&mut docking, &mut presets, "anim");
+
+        let ron = presets.to_ron_string().unwrap();
+        let mut restored = WorkspacePresets::<usize>::from_ron_str(&ron).unwrap();
+        assert_eq!(restored.names().collect::<Vec<_>>(), vec!["edit", "anim"]);
+        assert_eq!(restored.active(), Some("anim"));
+
+        let mut docking = DockingMultiViewport::new(Tree::empty("root"));
+        assert_eq!(switch(&mut docking, &mut restored, "anim"), 2);
+        assert_eq!(root_panes(&docking), vec![2, 3]);
+
+        let version = crate::LAYOUT_SNAPSHOT_VERSION;
+        let newer = ron.replace(
+            &format!("version: {version}"),
+            &format!("version: {}", version + 1),
+        );
+        assert!(matches!(
+            WorkspacePresets::<usize>::from_ron_str(&newer),
+            Err(crate::LayoutPersistenceError::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn registry_migrates_pane_ids_of_loaded_presets() {
+        /// Pane 1 was renamed to 4 and pane 2 was removed.
+        struct Renames;
+
+        impl crate::PaneRegistry<usize> for Renames {
+            type PaneId = usize;
+
+            fn pane_id(&mut self, pane: &usize) -> usize {
+                *pane
+            }
+
+            fn pane_from_id(&mut self, id: usize) -> usize {
+                id
+            }
+
+            fn migrate_pane_id(&mut self, id: usize) -> Option<usize> {
+                match id {
+                    1 => Some(4),
+                    2 => None,
+                    id => Some(id),
+                }
+            }
+        }
+
+        let ron = presets().to_ron_string().unwrap();
+        let mut restored =
+            WorkspacePresets::from_ron_str_with_registry(&ron, &mut Renames).unwrap();
+
+        let mut docking = DockingMultiViewport::new(Tree::empty("root"));
+        switch(&mut docking, &mut restored, "edit");
+        assert_eq!(root_panes(&docking), vec![4]);
+        switch(&mut docking, &mut restored, "anim");
+        assert_eq!(root_panes(&docking), vec![3]);
+    }
+}
@@ -0,0 +1,406 @@
+use std::path::Path;
+
+use egui::{Context, Modifiers, Pos2, Rect, ViewportId};
+
+use super::backend_hints::{backend_mouse_hovered_viewport_id, backend_pointer_global_points};
+use super::persistence::{LayoutPersistenceError, pretty_ron_config};
+use super::snapshot::LayoutSnapshot;
+use super::{DockingMultiViewport, PaneRegistry};
+
+/// Version of the [`DragSessionRecording`] file format.
+pub const DRAG_SESSION_RECORDING_VERSION: u32 = 1;
+
+/// Docking-relevant input of one frame, in global points.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedFrame {
+    /// Backend pointer hint, or the pointer of the viewport that has it.
+    pub pointer: Option<Pos2>,
+
+    /// Backend hovered-viewport hint.
+    pub hovered_viewport: Option<ViewportId>,
+
+    pub modifiers: Modifiers,
+
+    /// Primary button held in any viewport.
+    pub primary_down: bool,
+
+    /// Inner rect of every open viewport.
+    pub viewports: Vec<(ViewportId, Rect)>,
+}
+
+impl RecordedFrame {
+    pub fn viewport_rect(&self, viewport_id: ViewportId) -> Option<Rect> {
+        self.viewports
+            .iter()
+            .find(|(id, _)| *id == viewport_id)
+            .map(|&(_, rect)| rect)
+    }
+}
+
+/// A layout plus the input that was applied to it, frame by frame.
+///
+/// Produced by [`DragSessionRecorder`]; attach the RON file to a bug report and replay it with
+/// [`Self::replay`] (`testing` feature) to reproduce the session headlessly.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct DragSessionRecording<PaneId> {
+    pub version: u32,
+    pub initial_layout: LayoutSnapshot<PaneId>,
+    pub frames: Vec<RecordedFrame>,
+}
+
+/// Error reading, writing or replaying a [`DragSessionRecording`].
+#[derive(Debug)]
+pub enum DragSessionRecordingError {
+    /// The recording was written by a newer (or older) format.
+    UnsupportedVersion {
+        found: u32,
+        expected: u32,
+    },
+    /// The initial layout couldn't be loaded for replay.
+    Layout(LayoutPersistenceError),
+    RonSerialize(ron::Error),
+    RonDeserialize(ron::error::SpannedError),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for DragSessionRecordingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedVersion { found, expected } => {
+                write!(
+                    f,
+                    "unsupported drag session recording version: {found} (expected: {expected})"
+                )
+            }
+            Self::Layout(err) => write!(f, "recorded layout error: {err}"),
+            Self::RonSerialize(err) => write!(f, "ron serialize error: {err}"),
+            Self::RonDeserialize(err) => write!(f, "ron deserialize error: {err}"),
+            Self::Io(err) => write!(f, "io error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for DragSessionRecordingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::UnsupportedVersion { .. } => None,
+            Self::Layout(err) => Some(err),
+            Self::RonSerialize(err) => Some(err),
+            Self::RonDeserialize(err) => Some(err),
+            Self::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<LayoutPersistenceError> for DragSessionRecordingError {
+    fn from(err: LayoutPersistenceError) -> Self {
+        Self::Layout(err)
+    }
+}
+
+impl From<std::io::Error> for DragSessionRecordingError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::Error> for DragSessionRecordingError {
+    fn from(err: ron::Error) -> Self {
+        Self::RonSerialize(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for DragSessionRecordingError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::RonDeserialize(err)
+    }
+}
+
+impl<PaneId: serde::Serialize> DragSessionRecording<PaneId> {
+    pub fn to_ron_string(&self) -> Result<String, DragSessionRecordingError> {
+        Ok(ron::ser::to_string_pretty(self, pretty_ron_config())?)
+    }
+
+    pub fn save_to_ron_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), DragSessionRecordingError> {
+        std::fs::write(path, self.to_ron_string()?)?;
+        Ok(())
+    }
+}
+
+impl<PaneId> DragSessionRecording<PaneId>
+where
+    PaneId: for<'de> serde::Deserialize<'de>,
+{
+    pub fn from_ron_str(ron_str: &str) -> Result<Self, DragSessionRecordingError> {
+        let recording: Self = ron::from_str(ron_str)?;
+        if recording.version != DRAG_SESSION_RECORDING_VERSION {
+            return Err(DragSessionRecordingError::UnsupportedVersion {
+                found: recording.version,
+                expected: DRAG_SESSION_RECORDING_VERSION,
+            });
+        }
+        Ok(recording)
+    }
+
+    pub fn load_from_ron_file(path: impl AsRef<Path>) -> Result<Self, DragSessionRecordingError> {
+        Self::from_ron_str(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Records a [`DragSessionRecording`] while the app runs.
+///
+/// Start it from the current layout, then call [`Self::record_frame`] every frame after
+/// [`DockingMultiViewport::ui`]. Every frame is kept (replay timing depends on the frame count),
+/// so only record while reproducing an issue.
+#[derive(Clone, Debug)]
+pub struct DragSessionRecorder<PaneId> {
+    recording: DragSessionRecording<PaneId>,
+}
+
+impl<PaneId> DragSessionRecorder<PaneId> {
+    pub fn start<Pane>(
+        docking: &DockingMultiViewport<Pane>,
+        pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> Self {
+        Self::from_layout(docking.snapshot_layout(pane_to_id))
+    }
+
+    pub fn start_with_registry<Pane, R>(
+        docking: &DockingMultiViewport<Pane>,
+        registry: &mut R,
+    ) -> Self
+    where
+        R: PaneRegistry<Pane, PaneId = PaneId>,
+    {
+        Self::from_layout(docking.snapshot_layout_with_registry(registry))
+    }
+
+    fn from_layout(initial_layout: LayoutSnapshot<PaneId>) -> Self {
+        Self {
+            recording: DragSessionRecording {
+                version: DRAG_SESSION_RECORDING_VERSION,
+                initial_layout,
+                frames: Vec::new(),
+            },
+        }
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.recording.frames.len()
+    }
+
+    /// Capture this frame's input. Call once per frame, from the root viewport's pass.
+    pub fn record_frame(&mut self, ctx: &Context) {
+        self.recording.frames.push(capture_frame(ctx));
+    }
+
+    pub fn finish(self) -> DragSessionRecording<PaneId> {
+        self.recording
+    }
+}
+
+fn capture_frame(ctx: &Context) -> RecordedFrame {
+    let viewports = ctx.input_for(ViewportId::ROOT, |i| i.raw.viewports.clone());
+
+    let mut frame = RecordedFrame {
+        pointer: backend_pointer_global_points(ctx),
+        hovered_viewport: backend_mouse_hovered_viewport_id(ctx),
+        modifiers: ctx.input_for(ViewportId::ROOT, |i| i.modifiers),
+        primary_down: false,
+        viewports: Vec::new(),
+    };
+
+    for (&viewport_id, info) in &viewports {
+        let (primary_down, local_pointer, modifiers) = ctx.input_for(viewport_id, |i| {
+            (
+                i.pointer.primary_down(),
+                i.pointer.latest_pos(),
+                i.modifiers,
+            )
+        });
+        frame.primary_down |= primary_down;
+        if info.focused == Some(true) {
+            frame.modifiers = modifiers;
+        }
+        if let Some(rect) = info.inner_rect {
+            frame.viewports.push((viewport_id, rect));
+            if frame.pointer.is_none() {
+                // No backend hint: take the pointer of the viewport that has it.
+                frame.pointer = local_pointer.map(|pos| rect.min + pos.to_vec2());
+            }
+        }
+    }
+    frame
+}
+
+#[cfg(any(test, feature = "testing"))]
+mod replay {
+    use egui::{Pos2, Rect, Vec2, ViewportId};
+    use egui_tiles::Behavior;
+
+    use super::{DragSessionRecording, DragSessionRecordingError, RecordedFrame};
+    use crate::multi_viewport::simulation::DockingSimulation;
+    use crate::multi_viewport::{DockingMultiViewport, PaneRegistry};
+
+    /// Root window size if the recording has no frames.
+    const FALLBACK_ROOT_SIZE: Vec2 = Vec2::new(800.0, 600.0);
+
+    impl<PaneId: Clone + serde::Serialize> DragSessionRecording<PaneId> {
+        /// Load the initial layout into `docking` and play every frame back through a
+        /// [`DockingSimulation`], which is returned for further inspection.
+        pub fn replay<Pane>(
+            &self,
+            docking: &mut DockingMultiViewport<Pane>,
+            behavior: &mut dyn Behavior<Pane>,
+            pane_from_id: impl FnMut(PaneId) -> Pane,
+        ) -> Result<DockingSimulation, DragSessionRecordingError> {
+            let mut sim = DockingSimulation::new(self.root_rect());
+            docking.load_layout_snapshot_in_ctx(
+                sim.ctx(),
+                self.initial_layout.clone(),
+                pane_from_id,
+            )?;
+            self.play_frames(&mut sim, docking, behavior);
+            Ok(sim)
+        }
+
+        pub fn replay_with_registry<Pane, R>(
+            &self,
+            docking: &mut DockingMultiViewport<Pane>,
+            behavior: &mut dyn Behavior<Pane>,
+            registry: &mut R,
+        ) -> Result<DockingSimulation, DragSessionRecordingError>
+        where
+            R: PaneRegistry<Pane, PaneId = PaneId>,
+        {
+            let mut sim = DockingSimulation::new(self.root_rect());
+            docking.load_layout_snapshot_in_ctx_with_registry(
+                sim.ctx(),
+                self.initial_layout.clone(),
+                registry,
+            )?;
+            self.play_frames(&mut sim, docking, behavior);
+            Ok(sim)
+        }
+
+        fn root_rect(&self) -> Rect {
+            self.frames
+                .first()
+                .and_then(|frame| frame.viewport_rect(ViewportId::ROOT))
+                .unwrap_or(Rect::from_min_size(Pos2::ZERO, FALLBACK_ROOT_SIZE))
+        }
+
+        fn play_frames<Pane>(
+            &self,
+            sim: &mut DockingSimulation,
+            docking: &mut DockingMultiViewport<Pane>,
+            behavior: &mut dyn Behavior<Pane>,
+        ) {
+            let mut primary_down = false;
+            for frame in &self.frames {
+                apply_frame(sim, frame, &mut primary_down);
+                sim.step(docking, behavior);
+            }
+        }
+    }
+
+    fn apply_frame(sim: &mut DockingSimulation, frame: &RecordedFrame, primary_down: &mut bool) {
+        // The recorded rects win over wherever the simulation put the windows.
+        for &(viewport_id, rect) in &frame.viewports {
+            sim.set_window_rect(viewport_id, rect);
+        }
+        // Overlapping windows: whichever the backend reported as hovered was on top.
+        if let Some(hovered) = frame.hovered_viewport {
+            sim.raise_window(hovered);
+        }
+        sim.set_modifiers(frame.modifiers);
+        if let Some(pointer) = frame.pointer {
+            sim.pointer_move(pointer);
+        }
+        if frame.primary_down != *primary_down {
+            *primary_down = frame.primary_down;
+            if frame.primary_down {
+                sim.pointer_press();
+            } else {
+                sim.pointer_release();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui_tiles::Tree;
+
+    use super::*;
+    use crate::multi_viewport::PaneHost;
+    use crate::multi_viewport::simulation::{DockingSimulation, SimulatedDropTarget};
+    use crate::multi_viewport::test_support::{
+        DummyBehavior, docking_with_tabs, pane_host, simulation_with_detached,
+    };
+
+    #[test]
+    fn replayed_session_reproduces_the_recorded_drop() {
+        let mut behavior = DummyBehavior;
+        let (mut sim, mut live, detached) = simulation_with_detached();
+        sim.run_frames(3, &mut live, &mut behavior);
+        let tab = sim.tab_center(&live, |&p| p == 1).unwrap();
+        let left = sim
+            .drop_target_pos(&live, detached, SimulatedDropTarget::Left)
+            .unwrap();
+
+        // Record the same gesture `DockingSimulation::drag` performs.
+        let mut recorder = DragSessionRecorder::start(&live, |&pane| pane);
+        let mut step = |sim: &mut DockingSimulation, live: &mut DockingMultiViewport<usize>| {
+            sim.step(live, &mut behavior);
+            recorder.record_frame(sim.ctx());
+        };
+        sim.pointer_move(tab);
+        step(&mut sim, &mut live);
+        sim.pointer_press();
+        step(&mut sim, &mut live);
+        for i in 1..=10 {
+            sim.pointer_move(tab.lerp(left, i as f32 / 10.0));
+            step(&mut sim, &mut live);
+        }
+        sim.pointer_release();
+        for _ in 0..3 {
+            step(&mut sim, &mut live);
+        }
+        let detached_host = Some(PaneHost::DetachedViewport { viewport: detached });
+        assert_eq!(pane_host(&live, 1), detached_host);
+
+        let recording = recorder.finish();
+        assert!(recording.frames.iter().any(|frame| frame.primary_down));
+        let ron = recording.to_ron_string().unwrap();
+        let recording = DragSessionRecording::<usize>::from_ron_str(&ron).unwrap();
+
+        drop(sim);
+        let mut replayed = DockingMultiViewport::new(Tree::empty("replayed"));
+        recording
+            .replay(&mut replayed, &mut DummyBehavior, |id| id)
+            .unwrap();
+        assert_eq!(pane_host(&replayed, 1), detached_host);
+        assert_eq!(pane_host(&replayed, 2), Some(PaneHost::RootDock));
+    }
+
+    #[test]
+    fn unknown_recording_version_is_rejected() {
+        let recording =
+            DragSessionRecorder::start(&docking_with_tabs(&[1, 2]), |&pane| pane).finish();
+        let ron = recording
+            .to_ron_string()
+            .unwrap()
+            .replacen("version: 1", "version: 99", 1);
+        assert!(matches!(
+            DragSessionRecording::<usize>::from_ron_str(&ron),
+            Err(DragSessionRecordingError::UnsupportedVersion {
+                found: 99,
+                expected: DRAG_SESSION_RECORDING_VERSION,
+            })
+        ));
+    }
+}
@@ -2,6 +2,7 @@ use egui::{Context, Rect, ViewportId};
 use egui_tiles::{Behavior, Tree};
 
 use super::DockingMultiViewport;
+use super::host::WindowHost;
 
 impl<Pane> DockingMultiViewport<Pane> {
     /// Phase A (per-viewport, early): handle release-driven actions that depend on the dock tree
@@ -22,7 +23,9 @@ impl<Pane> DockingMultiViewport<Pane> {
                 ctx,
                 behavior,
                 dock_rect,
-                ViewportId::ROOT,
+                WindowHost::DockTree {
+                    viewport: ViewportId::ROOT,
+                },
                 &self.tree,
             )
         } else {
@@ -52,12 +55,15 @@ impl<Pane> DockingMultiViewport<Pane> {
         took_over_internal_drop
     }
 
-    pub(super) fn process_release_before_detached_tree_ui(
+    /// Like [`Self::process_release_before_root_tree_ui`], for a tree held outside of `self`:
+    /// a detached viewport's dock tree or a contained floating window (`dock_rect` is then its
+    /// content rect).
+    pub(super) fn process_release_before_tree_ui(
         &mut self,
         ctx: &Context,
         behavior: &mut dyn Behavior<Pane>,
         dock_rect: Rect,
-        viewport_id: ViewportId,
+        host: WindowHost,
         tree: &Tree<Pane>,
         internal_overlay_kind: &'static str,
     ) -> bool {
@@ -65,13 +71,7 @@ impl<Pane> DockingMultiViewport<Pane> {
         self.queue_pending_drop_on_release(ctx);
 
         let internal_drop = if self.pending_drop.is_none() && self.pending_internal_drop.is_none() {
-            self.pending_internal_overlay_drop_on_release(
-                ctx,
-                behavior,
-                dock_rect,
-                viewport_id,
-                tree,
-            )
+            self.pending_internal_overlay_drop_on_release(ctx, behavior, dock_rect, host, tree)
         } else {
             None
         };
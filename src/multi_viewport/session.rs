@@ -1,3 +1,5 @@
+use super::debug_log::DebugLogRecord;
+
 #[derive(Debug, Default)]
 pub(super) struct DragSession {
     next_id: u64,
@@ -18,7 +20,11 @@ impl DragSession {
         self.observed_active_this_frame = false;
     }
 
-    pub(super) fn observe_active(&mut self, frame: u64, source: &'static str) -> Option<String> {
+    pub(super) fn observe_active(
+        &mut self,
+        frame: u64,
+        source: &'static str,
+    ) -> Option<DebugLogRecord> {
         self.observed_active_this_frame = true;
 
         match &mut self.active {
@@ -35,7 +41,10 @@ impl DragSession {
                     release_action_frame: None,
                     last_source: Some(source),
                 });
-                Some(format!("session START id={id} source={source}"))
+                Some(DebugLogRecord::SessionStart {
+                    session: id,
+                    source,
+                })
             }
         }
     }
@@ -44,44 +53,53 @@ impl DragSession {
         &mut self,
         frame: u64,
         kind: &'static str,
-    ) -> (bool, Option<String>) {
+    ) -> (bool, Option<DebugLogRecord>) {
         let Some(active) = &mut self.active else {
             // Still allow release actions if a drag source didn't get observed this frame
             // (e.g. a payload-only drag that is cleared early).
             return (
                 true,
-                Some(format!("session RELEASE kind={kind} (no active session)")),
+                Some(DebugLogRecord::SessionRelease {
+                    session: None,
+                    kind,
+                    source: None,
+                    ignored: false,
+                }),
             );
         };
 
         if active.release_action_frame == Some(frame) {
             return (
                 false,
-                Some(format!(
-                    "session RELEASE ignored id={} kind={kind}",
-                    active.id
-                )),
+                Some(DebugLogRecord::SessionRelease {
+                    session: Some(active.id),
+                    kind,
+                    source: active.last_source,
+                    ignored: true,
+                }),
             );
         }
 
         active.release_action_frame = Some(frame);
         (
             true,
-            Some(format!(
-                "session RELEASE id={} kind={kind} source={}",
-                active.id,
-                active.last_source.unwrap_or("unknown")
-            )),
+            Some(DebugLogRecord::SessionRelease {
+                session: Some(active.id),
+                kind,
+                source: active.last_source,
+                ignored: false,
+            }),
         )
     }
 
-    pub(super) fn end_frame(&mut self, frame: u64) -> Option<String> {
+    pub(super) fn end_frame(&mut self, frame: u64) -> Option<DebugLogRecord> {
         if self.active.is_some() && !self.observed_active_this_frame {
             let ended = self.active.take().unwrap();
-            return Some(format!(
-                "session END id={} started_frame={} end_frame={}",
-                ended.id, ended.started_frame, frame
-            ));
+            return Some(DebugLogRecord::SessionEnd {
+                session: ended.id,
+                started_frame: ended.started_frame,
+                end_frame: frame,
+            });
         }
         None
     }
@@ -0,0 +1,587 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use egui::mutex::Mutex;
+use egui::{
+    Context, Event, FullOutput, Id, ImmediateViewport, Modifiers, PointerButton, Pos2, RawInput,
+    Rect, Vec2, ViewportBuilder, ViewportCommand, ViewportEvent, ViewportId, ViewportIdMap,
+    ViewportInfo,
+};
+use egui_tiles::{Behavior, Tree};
+
+use super::DockingMultiViewport;
+use super::backend_hints::{
+    BACKEND_MOUSE_HOVERED_VIEWPORT_ID_KEY, BACKEND_POINTER_GLOBAL_POINTS_KEY,
+    clear_backend_monitors_outer_rects_points, set_backend_monitors_outer_rects_points,
+};
+use super::overlay::{OverlayTarget, overlay_for_tree_at_pointer_explicit};
+use super::pane_query::PaneHost;
+
+const FRAME_DT: f32 = 1.0 / 60.0;
+
+/// Pointer moves per [`DockingSimulation::drag`].
+const DRAG_STEPS: usize = 12;
+
+/// Size of windows opened without `ViewportBuilder::inner_size`.
+const DEFAULT_WINDOW_SIZE: Vec2 = Vec2::new(480.0, 360.0);
+
+/// Tab button rects shown in the current step, by tab id, in viewport-local points.
+type TabRects = Arc<Mutex<HashMap<Id, (ViewportId, Rect)>>>;
+
+fn tab_rects_id() -> Id {
+    Id::new("egui_docking_simulation_tab_rects")
+}
+
+/// Test hook called by the behavior wrapper for every tab button shown, so
+/// [`DockingSimulation::tab_center`] can find it. Does nothing outside a simulation.
+pub(super) fn record_tab_rect(ctx: &Context, tab_id: Id, rect: Rect) {
+    if let Some(tab_rects) = ctx.data(|d| d.get_temp::<TabRects>(tab_rects_id())) {
+        tab_rects.lock().insert(tab_id, (ctx.viewport_id(), rect));
+    }
+}
+
+/// One of the dock target buttons shown over a dock node while dragging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimulatedDropTarget {
+    Center,
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl From<SimulatedDropTarget> for OverlayTarget {
+    fn from(target: SimulatedDropTarget) -> Self {
+        match target {
+            SimulatedDropTarget::Center => Self::Center,
+            SimulatedDropTarget::Left => Self::Left,
+            SimulatedDropTarget::Right => Self::Right,
+            SimulatedDropTarget::Top => Self::Top,
+            SimulatedDropTarget::Bottom => Self::Bottom,
+        }
+    }
+}
+
+/// Runs [`DockingMultiViewport::ui`] frame by frame against simulated native windows, without a
+/// GPU or windowing system.
+///
+/// Every viewport is a window with an OS rect in global points (no decorations: the outer rect is
+/// the inner rect). Each [`Self::step`]:
+/// - routes the queued pointer input like a windowing system would: the window under the pointer
+///   gets it, and the window the button went down in keeps it until release,
+/// - publishes the backend hints (global pointer, hovered viewport, monitors),
+/// - runs the root pass, plus a nested pass for every immediate viewport it shows,
+/// - applies the emitted viewport commands (move, resize, focus) and opens/closes windows.
+///
+/// The immediate viewport renderer is per thread, so keep one simulation per test.
+pub struct DockingSimulation {
+    ctx: Context,
+    desktop: Rc<RefCell<Desktop>>,
+    tab_rects: TabRects,
+}
+
+struct Desktop {
+    /// Inner rects in global points.
+    windows: BTreeMap<ViewportId, Rect>,
+    /// Back to front.
+    z_order: Vec<ViewportId>,
+    focused: Option<ViewportId>,
+    pointer: Option<Pos2>,
+    /// Window that keeps the pointer while the button is held (implicit OS capture).
+    captured: Option<ViewportId>,
+    hovered: Option<ViewportId>,
+    /// Window being moved by the OS (`ViewportCommand::StartDrag`) and the grab offset in it.
+    os_move: Option<(ViewportId, Vec2)>,
+    modifiers: Modifiers,
+    events: BTreeMap<ViewportId, Vec<Event>>,
+    close_requests: Vec<ViewportId>,
+    monitors: Vec<Rect>,
+    backend_hints: bool,
+    time: f64,
+}
+
+impl DockingSimulation {
+    /// A desktop with only the root window, at `root_inner_rect`.
+    pub fn new(root_inner_rect: Rect) -> Self {
+        let ctx = Context::default();
+        ctx.set_embed_viewports(false);
+
+        let desktop = Rc::new(RefCell::new(Desktop {
+            windows: BTreeMap::from([(ViewportId::ROOT, root_inner_rect)]),
+            z_order: vec![ViewportId::ROOT],
+            focused: Some(ViewportId::ROOT),
+            pointer: None,
+            captured: None,
+            hovered: None,
+            os_move: None,
+            modifiers: Modifiers::NONE,
+            events: BTreeMap::new(),
+            close_requests: Vec::new(),
+            monitors: Vec::new(),
+            backend_hints: true,
+            time: 0.0,
+        }));
+
+        let weak = Rc::downgrade(&desktop);
+        Context::set_immediate_viewport_renderer(
+            move |ctx: &Context, viewport: ImmediateViewport<'_>| {
+                render_immediate_viewport(ctx, &weak, viewport);
+            },
+        );
+
+        let tab_rects = TabRects::default();
+        ctx.data_mut(|d| d.insert_temp(tab_rects_id(), tab_rects.clone()));
+
+        Self {
+            ctx,
+            desktop,
+            tab_rects,
+        }
+    }
+
+    /// Report these monitor rects through `BACKEND_MONITORS_OUTER_RECTS_POINTS_KEY`.
+    pub fn with_monitors(self, monitors: Vec<Rect>) -> Self {
+        self.desktop.borrow_mut().monitors = monitors;
+        self
+    }
+
+    /// Don't publish the backend hints, like a backend without the `egui-winit` fork.
+    pub fn without_backend_hints(self) -> Self {
+        self.desktop.borrow_mut().backend_hints = false;
+        self
+    }
+
+    pub fn ctx(&self) -> &Context {
+        &self.ctx
+    }
+
+    /// Open windows, back to front.
+    pub fn windows(&self) -> Vec<ViewportId> {
+        self.desktop.borrow().z_order.clone()
+    }
+
+    /// The inner rect of a window in global points.
+    pub fn window_rect(&self, viewport_id: ViewportId) -> Option<Rect> {
+        self.desktop.borrow().windows.get(&viewport_id).copied()
+    }
+
+    /// Move/resize a window as the user (or OS) would.
+    pub fn set_window_rect(&mut self, viewport_id: ViewportId, rect: Rect) {
+        if let Some(window) = self.desktop.borrow_mut().windows.get_mut(&viewport_id) {
+            *window = rect;
+        }
+    }
+
+    /// Put a window on top of the others without focusing it.
+    pub fn raise_window(&mut self, viewport_id: ViewportId) {
+        self.desktop.borrow_mut().bring_to_front(viewport_id);
+    }
+
+    /// Click the window's close button on the next frame.
+    pub fn request_close(&mut self, viewport_id: ViewportId) {
+        self.desktop.borrow_mut().close_requests.push(viewport_id);
+    }
+
+    pub fn pointer_move(&mut self, pos: Pos2) {
+        self.desktop.borrow_mut().pointer_move(pos);
+    }
+
+    pub fn pointer_press(&mut self) {
+        self.desktop.borrow_mut().pointer_button(true);
+    }
+
+    pub fn pointer_release(&mut self) {
+        self.desktop.borrow_mut().pointer_button(false);
+    }
+
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.desktop.borrow_mut().modifiers = modifiers;
+    }
+
+    /// Run one frame with the input queued since the last one.
+    pub fn step<Pane>(
+        &mut self,
+        docking: &mut DockingMultiViewport<Pane>,
+        behavior: &mut dyn Behavior<Pane>,
+    ) {
+        self.publish_backend_hints();
+        // `tab_center` reports where the tabs were shown by this step.
+        self.tab_rects.lock().clear();
+        run_pass(&self.ctx, &self.desktop, ViewportId::ROOT, |ctx| {
+            docking.ui(ctx, behavior);
+        });
+        self.desktop.borrow_mut().time += f64::from(FRAME_DT);
+    }
+
+    pub fn run_frames<Pane>(
+        &mut self,
+        frames: usize,
+        docking: &mut DockingMultiViewport<Pane>,
+        behavior: &mut dyn Behavior<Pane>,
+    ) {
+        for _ in 0..frames {
+            self.step(docking, behavior);
+        }
+    }
+
+    /// Press at `from`, move to `to` over several frames and release there.
+    ///
+    /// A few more frames run after the release: drops are queued on release and applied at the end
+    /// of a frame, and windows they open are shown on the next one.
+    pub fn drag<Pane>(
+        &mut self,
+        docking: &mut DockingMultiViewport<Pane>,
+        behavior: &mut dyn Behavior<Pane>,
+        from: Pos2,
+        to: Pos2,
+    ) {
+        self.pointer_move(from);
+        self.step(docking, behavior);
+        self.pointer_press();
+        self.step(docking, behavior);
+        for i in 1..=DRAG_STEPS {
+            self.pointer_move(from.lerp(to, i as f32 / DRAG_STEPS as f32));
+            self.step(docking, behavior);
+        }
+        self.pointer_release();
+        self.run_frames(3, docking, behavior);
+    }
+
+    /// Global position of the tab button of the first pane matching `predicate`, as shown in the
+    /// last frame.
+    pub fn tab_center<Pane>(
+        &self,
+        docking: &DockingMultiViewport<Pane>,
+        predicate: impl FnMut(&Pane) -> bool,
+    ) -> Option<Pos2> {
+        let location = docking.find_pane(predicate)?;
+        let tree = docking.tree_for_pane_host(location.host)?;
+        let tab_id = location.tile_id.egui_id(tree.id());
+        let (viewport_id, rect) = *self.tab_rects.lock().get(&tab_id)?;
+        self.to_global(viewport_id, rect.center())
+    }
+
+    /// Global position of `target` over the dock node at the center of a viewport's dock tree,
+    /// as laid out in the last frame.
+    pub fn drop_target_pos<Pane>(
+        &self,
+        docking: &DockingMultiViewport<Pane>,
+        viewport_id: ViewportId,
+        target: SimulatedDropTarget,
+    ) -> Option<Pos2> {
+        let host = if viewport_id == ViewportId::ROOT {
+            PaneHost::RootDock
+        } else {
+            PaneHost::DetachedViewport {
+                viewport: viewport_id,
+            }
+        };
+        self.drop_target_pos_in_host(docking, host, target)
+    }
+
+    /// Like [`Self::drop_target_pos`], for the dock tree of any host (including contained
+    /// floating windows).
+    pub fn drop_target_pos_in_host<Pane>(
+        &self,
+        docking: &DockingMultiViewport<Pane>,
+        host: PaneHost,
+        target: SimulatedDropTarget,
+    ) -> Option<Pos2> {
+        let tree: &Tree<Pane> = docking.tree_for_pane_host(host)?;
+        let root_rect = tree.tiles.rect(tree.root?)?;
+        let overlay = overlay_for_tree_at_pointer_explicit(tree, root_rect.center(), None)?;
+        let rect = overlay.target_rect(target.into())?;
+        self.to_global(host.viewport(), rect.center())
+    }
+
+    fn to_global(&self, viewport_id: ViewportId, local: Pos2) -> Option<Pos2> {
+        Some(self.window_rect(viewport_id)?.min + local.to_vec2())
+    }
+
+    fn publish_backend_hints(&self) {
+        let desktop = self.desktop.borrow();
+        if !desktop.backend_hints {
+            return;
+        }
+        let hovered = desktop.pointer.and_then(|pos| desktop.window_at(pos));
+        self.ctx.data_mut(|d| {
+            d.insert_temp::<Option<Pos2>>(
+                Id::new(BACKEND_POINTER_GLOBAL_POINTS_KEY),
+                desktop.pointer,
+            );
+            d.insert_temp::<Option<ViewportId>>(
+                Id::new(BACKEND_MOUSE_HOVERED_VIEWPORT_ID_KEY),
+                hovered,
+            );
+        });
+        if desktop.monitors.is_empty() {
+            clear_backend_monitors_outer_rects_points(&self.ctx);
+        } else {
+            set_backend_monitors_outer_rects_points(&self.ctx, desktop.monitors.clone());
+        }
+    }
+}
+
+/// The backend side of `Context::show_viewport_immediate`: run the viewport's own pass.
+fn render_immediate_viewport(
+    ctx: &Context,
+    desktop: &Weak<RefCell<Desktop>>,
+    viewport: ImmediateViewport<'_>,
+) {
+    let viewport_id = viewport.ids.this;
+    let mut viewport_ui_cb = viewport.viewport_ui_cb;
+    let Some(desktop) = desktop.upgrade() else {
+        // The simulation is gone; still run the UI so egui's contract holds.
+        viewport_ui_cb(ctx);
+        return;
+    };
+    desktop
+        .borrow_mut()
+        .open_window(viewport_id, &viewport.builder);
+    run_pass(ctx, &desktop, viewport_id, |ctx| viewport_ui_cb(ctx));
+}
+
+fn run_pass(
+    ctx: &Context,
+    desktop: &Rc<RefCell<Desktop>>,
+    viewport_id: ViewportId,
+    ui: impl FnOnce(&Context),
+) {
+    let input = desktop.borrow_mut().raw_input(viewport_id);
+    ctx.begin_pass(input);
+    ui(ctx);
+    let output = ctx.end_pass();
+    desktop
+        .borrow_mut()
+        .apply_output(output, viewport_id == ViewportId::ROOT);
+}
+
+impl Desktop {
+    fn window_at(&self, pos: Pos2) -> Option<ViewportId> {
+        self.z_order
+            .iter()
+            .rev()
+            .copied()
+            .find(|id| self.windows.get(id).is_some_and(|rect| rect.contains(pos)))
+    }
+
+    fn local(&self, viewport_id: ViewportId, pos: Pos2) -> Pos2 {
+        let min = self
+            .windows
+            .get(&viewport_id)
+            .map_or(Pos2::ZERO, |rect| rect.min);
+        (pos - min).to_pos2()
+    }
+
+    fn push_event(&mut self, viewport_id: ViewportId, event: Event) {
+        self.events.entry(viewport_id).or_default().push(event);
+    }
+
+    fn bring_to_front(&mut self, viewport_id: ViewportId) -> bool {
+        if !self.windows.contains_key(&viewport_id) {
+            return false;
+        }
+        self.z_order.retain(|&id| id != viewport_id);
+        self.z_order.push(viewport_id);
+        true
+    }
+
+    fn raise(&mut self, viewport_id: ViewportId) {
+        if self.bring_to_front(viewport_id) {
+            self.focused = Some(viewport_id);
+        }
+    }
+
+    fn pointer_move(&mut self, pos: Pos2) {
+        let delta = self.pointer.map_or(Vec2::ZERO, |prev| pos - prev);
+        self.pointer = Some(pos);
+
+        // Raw device motion goes to the focused window, even while the OS moves a window.
+        if let Some(focused) = self.focused.filter(|_| delta != Vec2::ZERO) {
+            self.push_event(focused, Event::MouseMoved(delta));
+        }
+
+        if let Some((moving, grab)) = self.os_move {
+            // The OS move loop swallows cursor events; the window follows the pointer.
+            if let Some(rect) = self.windows.get_mut(&moving) {
+                *rect = Rect::from_min_size(pos - grab, rect.size());
+            }
+            return;
+        }
+
+        let target = self.captured.or_else(|| self.window_at(pos));
+        if self.captured.is_none() && self.hovered != target {
+            if let Some(left) = self.hovered {
+                self.push_event(left, Event::PointerGone);
+            }
+            self.hovered = target;
+        }
+        if let Some(target) = target {
+            let local = self.local(target, pos);
+            self.push_event(target, Event::PointerMoved(local));
+        }
+    }
+
+    fn pointer_button(&mut self, pressed: bool) {
+        let Some(pos) = self.pointer else {
+            return;
+        };
+        let target = if pressed {
+            let target = self.window_at(pos);
+            if let Some(target) = target {
+                self.raise(target);
+            }
+            self.captured = target;
+            target
+        } else {
+            self.os_move = None;
+            self.captured.take().or_else(|| self.window_at(pos))
+        };
+
+        if let Some(target) = target {
+            let event = Event::PointerButton {
+                pos: self.local(target, pos),
+                button: PointerButton::Primary,
+                pressed,
+                modifiers: self.modifiers,
+            };
+            self.push_event(target, event);
+        }
+
+        if !pressed {
+            // Without the capture, the pointer belongs to whatever window is under it.
+            let under = self.window_at(pos);
+            if under != target {
+                if let Some(target) = target {
+                    self.push_event(target, Event::PointerGone);
+                }
+                if let Some(under) = under {
+                    let local = self.local(under, pos);
+                    self.push_event(under, Event::PointerMoved(local));
+                }
+            }
+            self.hovered = under;
+        }
+    }
+
+    fn open_window(&mut self, viewport_id: ViewportId, builder: &ViewportBuilder) {
+        if self.windows.contains_key(&viewport_id) {
+            return;
+        }
+        let pos = builder.position.unwrap_or(Pos2::ZERO);
+        let size = builder.inner_size.unwrap_or(DEFAULT_WINDOW_SIZE);
+        self.windows
+            .insert(viewport_id, Rect::from_min_size(pos, size));
+        self.raise(viewport_id);
+    }
+
+    fn close_window(&mut self, viewport_id: ViewportId) {
+        self.windows.remove(&viewport_id);
+        self.z_order.retain(|&id| id != viewport_id);
+        self.events.remove(&viewport_id);
+        for slot in [&mut self.focused, &mut self.captured, &mut self.hovered] {
+            if *slot == Some(viewport_id) {
+                *slot = None;
+            }
+        }
+        if self.os_move.is_some_and(|(id, _)| id == viewport_id) {
+            self.os_move = None;
+        }
+        if self.focused.is_none() {
+            self.focused = self.z_order.last().copied();
+        }
+    }
+
+    fn raw_input(&mut self, viewport_id: ViewportId) -> RawInput {
+        let viewports: ViewportIdMap<ViewportInfo> = self
+            .windows
+            .iter()
+            .map(|(&id, &rect)| {
+                let events = if self.close_requests.contains(&id) {
+                    vec![ViewportEvent::Close]
+                } else {
+                    Vec::new()
+                };
+                let info = ViewportInfo {
+                    events,
+                    native_pixels_per_point: Some(1.0),
+                    inner_rect: Some(rect),
+                    outer_rect: Some(rect),
+                    focused: Some(self.focused == Some(id)),
+                    ..Default::default()
+                };
+                (id, info)
+            })
+            .collect();
+        self.close_requests.retain(|&id| id != viewport_id);
+
+        let size = self
+            .windows
+            .get(&viewport_id)
+            .map_or(DEFAULT_WINDOW_SIZE, |rect| rect.size());
+        RawInput {
+            viewport_id,
+            viewports,
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, size)),
+            time: Some(self.time),
+            predicted_dt: FRAME_DT,
+            modifiers: self.modifiers,
+            events: self.events.remove(&viewport_id).unwrap_or_default(),
+            focused: self.focused == Some(viewport_id),
+            ..Default::default()
+        }
+    }
+
+    fn apply_output(&mut self, output: FullOutput, is_root: bool) {
+        let shown: Vec<ViewportId> = output.viewport_output.keys().copied().collect();
+        for (viewport_id, viewport) in output.viewport_output {
+            if viewport_id != ViewportId::ROOT {
+                self.open_window(viewport_id, &viewport.builder);
+            }
+            for command in viewport.commands {
+                self.apply_command(viewport_id, command);
+            }
+        }
+
+        // The root pass reports every viewport that is still alive.
+        if is_root {
+            let closed: Vec<ViewportId> = self
+                .windows
+                .keys()
+                .copied()
+                .filter(|id| *id != ViewportId::ROOT && !shown.contains(id))
+                .collect();
+            for viewport_id in closed {
+                self.close_window(viewport_id);
+            }
+        }
+    }
+
+    fn apply_command(&mut self, viewport_id: ViewportId, command: ViewportCommand) {
+        match command {
+            ViewportCommand::OuterPosition(pos) => {
+                if let Some(rect) = self.windows.get_mut(&viewport_id) {
+                    *rect = Rect::from_min_size(pos, rect.size());
+                }
+            }
+            ViewportCommand::InnerSize(size) => {
+                if let Some(rect) = self.windows.get_mut(&viewport_id) {
+                    *rect = Rect::from_min_size(rect.min, size);
+                }
+            }
+            ViewportCommand::Focus => self.raise(viewport_id),
+            ViewportCommand::StartDrag => {
+                if let (Some(pointer), Some(rect)) = (self.pointer, self.windows.get(&viewport_id))
+                {
+                    self.os_move = Some((viewport_id, pointer - rect.min));
+                }
+            }
+            ViewportCommand::Close if viewport_id != ViewportId::ROOT => {
+                self.close_window(viewport_id);
+            }
+            _ => {}
+        }
+    }
+}
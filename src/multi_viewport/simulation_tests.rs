@@ -0,0 +1,278 @@
+use egui::{Pos2, Rect};
+
+use super::DockingMultiViewport;
+use super::pane_query::PaneHost;
+use super::simulation::{DockingSimulation, SimulatedDropTarget};
+use super::test_support::{DummyBehavior, pane_host, simulation_with_detached};
+
+fn pane_rect(docking: &DockingMultiViewport<usize>, pane: usize) -> Rect {
+    let location = docking.find_pane(|&p| p == pane).unwrap();
+    let tree = docking.tree_for_pane_host(location.host).unwrap();
+    tree.tiles.rect(location.tile_id).unwrap()
+}
+
+#[test]
+fn drag_tab_from_root_onto_detached_left_target() {
+    let (mut sim, mut docking, detached) = simulation_with_detached();
+    let mut behavior = DummyBehavior;
+    sim.run_frames(3, &mut docking, &mut behavior);
+    assert_eq!(sim.windows().len(), 2);
+
+    let tab = sim
+        .tab_center(&docking, |&p| p == 1)
+        .expect("tab 1 is shown");
+    let left = sim
+        .drop_target_pos(&docking, detached, SimulatedDropTarget::Left)
+        .expect("detached dock offers a left target");
+    sim.drag(&mut docking, &mut behavior, tab, left);
+
+    assert_eq!(
+        pane_host(&docking, 1),
+        Some(PaneHost::DetachedViewport { viewport: detached })
+    );
+    assert_eq!(pane_host(&docking, 2), Some(PaneHost::RootDock));
+    assert!(pane_rect(&docking, 1).center().x < pane_rect(&docking, 3).center().x);
+    assert_eq!(sim.windows().len(), 2);
+}
+
+#[test]
+fn drag_tab_out_of_every_window_tears_it_off() {
+    let (mut sim, mut docking, detached) = simulation_with_detached();
+    let mut behavior = DummyBehavior;
+    sim.run_frames(3, &mut docking, &mut behavior);
+
+    let tab = sim
+        .tab_center(&docking, |&p| p == 2)
+        .expect("tab 2 is shown");
+    let release = Pos2::new(1200.0, 700.0);
+    sim.drag(&mut docking, &mut behavior, tab, release);
+
+    let Some(PaneHost::DetachedViewport { viewport }) = pane_host(&docking, 2) else {
+        panic!("pane 2 must be torn off into a native window");
+    };
+    assert_ne!(viewport, detached);
+    assert_eq!(pane_host(&docking, 1), Some(PaneHost::RootDock));
+    assert_eq!(sim.windows().len(), 3);
+    let window = sim.window_rect(viewport).expect("torn-off window is open");
+    assert!(window.expand(1.0).contains(release), "{window:?}");
+}
+
+#[test]
+fn docking_policy_can_refuse_a_tear_off() {
+    use super::{DockingPolicy, DropContext, DropVerdict, TearOffContext, TearOffVerdict};
+
+    struct NoTearOff;
+
+    impl DockingPolicy<usize> for NoTearOff {
+        fn check_drop(&self, _drop: &DropContext<'_, usize>) -> DropVerdict {
+            DropVerdict::Accept
+        }
+
+        fn check_tear_off(&self, _tear_off: &TearOffContext<'_, usize>) -> TearOffVerdict {
+            TearOffVerdict::Reject
+        }
+    }
+
+    let (mut sim, mut docking, detached) = simulation_with_detached();
+    docking.set_docking_policy(NoTearOff);
+    let mut behavior = DummyBehavior;
+    sim.run_frames(3, &mut docking, &mut behavior);
+
+    let tab = sim
+        .tab_center(&docking, |&p| p == 2)
+        .expect("tab 2 is shown");
+    sim.drag(&mut docking, &mut behavior, tab, Pos2::new(1200.0, 700.0));
+
+    assert_eq!(pane_host(&docking, 2), Some(PaneHost::RootDock));
+    assert_eq!(
+        pane_host(&docking, 3),
+        Some(PaneHost::DetachedViewport { viewport: detached })
+    );
+    assert_eq!(sim.windows().len(), 2);
+}
+
+#[test]
+fn docking_policy_covers_drags_inside_a_floating_window() {
+    use egui::{Vec2, ViewportId};
+    use egui_tiles::{Tiles, Tree};
+
+    use super::{DropContext, DropVerdict};
+
+    let mut sim = DockingSimulation::new(Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0)));
+    let mut tiles: Tiles<usize> = Tiles::default();
+    let pane_1 = tiles.insert_pane(1);
+    let left = tiles.insert_tab_tile(vec![pane_1]);
+    let panes = vec![tiles.insert_pane(2), tiles.insert_pane(3)];
+    let right = tiles.insert_tab_tile(panes);
+    let root = tiles.insert_horizontal_tile(vec![left, right]);
+    let mut docking = DockingMultiViewport::new(Tree::new(egui::Id::new("root"), root, tiles));
+    docking.options.ghost_tear_off = false;
+    docking.set_docking_policy(|drop: &DropContext<'_, usize>| {
+        if drop.is_tab_insertion() {
+            DropVerdict::Accept
+        } else {
+            DropVerdict::Reject
+        }
+    });
+
+    let mut behavior = DummyBehavior;
+    let floating = docking
+        .float_tile(
+            sim.ctx(),
+            &mut behavior,
+            PaneHost::RootDock,
+            right,
+            ViewportId::ROOT,
+            Vec2::new(100.0, 100.0),
+            Vec2::new(400.0, 300.0),
+        )
+        .expect("the policy allows new windows");
+    let host = PaneHost::Floating {
+        viewport: ViewportId::ROOT,
+        floating,
+    };
+    sim.run_frames(3, &mut docking, &mut behavior);
+
+    let tab = sim
+        .tab_center(&docking, |&p| p == 2)
+        .expect("tab 2 is shown");
+    let target = sim
+        .drop_target_pos_in_host(&docking, host, SimulatedDropTarget::Left)
+        .expect("the floating tree is laid out");
+    sim.drag(&mut docking, &mut behavior, tab, target);
+
+    assert_eq!(pane_host(&docking, 2), Some(host));
+    assert_eq!(pane_host(&docking, 3), Some(host));
+    let tree = docking.tree_for_pane_host(host).unwrap();
+    assert!(matches!(
+        tree.root.and_then(|root| tree.tiles.get(root)),
+        Some(egui_tiles::Tile::Container(container))
+            if container.kind() == egui_tiles::ContainerKind::Tabs
+    ));
+}
+
+#[test]
+fn docking_policy_covers_internal_drags_without_the_overlay() {
+    use egui::Vec2;
+    use egui_tiles::{TileId, Tiles, Tree};
+
+    use super::{DropContext, DropVerdict};
+
+    fn parent_of_pane(docking: &DockingMultiViewport<usize>, pane: usize) -> Option<TileId> {
+        let location = docking.find_pane(|&p| p == pane)?;
+        docking.tree.tiles.parent_of(location.tile_id)
+    }
+
+    let mut sim = DockingSimulation::new(Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0)));
+    let mut tiles: Tiles<usize> = Tiles::default();
+    let pane_1 = tiles.insert_pane(1);
+    let left = tiles.insert_tab_tile(vec![pane_1]);
+    let panes = vec![tiles.insert_pane(2), tiles.insert_pane(3)];
+    let right = tiles.insert_tab_tile(panes);
+    let root = tiles.insert_horizontal_tile(vec![left, right]);
+    let mut docking = DockingMultiViewport::new(Tree::new(egui::Id::new("root"), root, tiles));
+    docking.options.ghost_tear_off = false;
+    docking.options.show_overlay_for_internal_drags = false;
+    docking.set_docking_policy(|drop: &DropContext<'_, usize>| {
+        if drop.is_tab_insertion() {
+            DropVerdict::Accept
+        } else {
+            DropVerdict::Reject
+        }
+    });
+    let mut behavior = DummyBehavior;
+    sim.run_frames(3, &mut docking, &mut behavior);
+
+    // `egui_tiles` would split pane 1 here; the policy refuses splits.
+    let tab = sim
+        .tab_center(&docking, |&p| p == 2)
+        .expect("tab 2 is shown");
+    let pane_1_rect = pane_rect(&docking, 1);
+    let bottom_edge = Pos2::new(pane_1_rect.center().x, pane_1_rect.bottom() - 8.0);
+    sim.drag(&mut docking, &mut behavior, tab, bottom_edge);
+
+    assert_eq!(parent_of_pane(&docking, 2), parent_of_pane(&docking, 3));
+    assert_ne!(parent_of_pane(&docking, 1), parent_of_pane(&docking, 2));
+
+    // Accepted drops are still applied by `egui_tiles` itself.
+    let tab = sim
+        .tab_center(&docking, |&p| p == 2)
+        .expect("tab 2 is shown");
+    let pane_1_center = pane_rect(&docking, 1).center();
+    sim.drag(&mut docking, &mut behavior, tab, pane_1_center);
+
+    assert_eq!(parent_of_pane(&docking, 1), parent_of_pane(&docking, 2));
+    assert_ne!(parent_of_pane(&docking, 2), parent_of_pane(&docking, 3));
+}
+
+#[test]
+fn no_resize_nodes_keep_their_splitters_still() {
+    use egui::{Vec2, vec2};
+    use egui_tiles::{Behavior, Container, EditAction, Tile, Tiles, Tree, UiResponse};
+
+    use super::DockNodeFlags;
+
+    /// Counts the splitter drags `egui_tiles` applied.
+    #[derive(Default)]
+    struct CountResizes(usize);
+
+    impl Behavior<usize> for CountResizes {
+        fn pane_ui(
+            &mut self,
+            _ui: &mut egui::Ui,
+            _tile_id: egui_tiles::TileId,
+            _pane: &mut usize,
+        ) -> UiResponse {
+            Default::default()
+        }
+
+        fn tab_title_for_pane(&mut self, pane: &usize) -> egui::WidgetText {
+            format!("pane {pane}").into()
+        }
+
+        fn on_edit(&mut self, edit_action: EditAction) {
+            if matches!(edit_action, EditAction::TileResized) {
+                self.0 += 1;
+            }
+        }
+    }
+
+    let mut sim = DockingSimulation::new(Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0)));
+    let mut tiles: Tiles<usize> = Tiles::default();
+    let pane_1 = tiles.insert_pane(1);
+    let left = tiles.insert_tab_tile(vec![pane_1]);
+    let pane_2 = tiles.insert_pane(2);
+    let right = tiles.insert_tab_tile(vec![pane_2]);
+    let root = tiles.insert_horizontal_tile(vec![left, right]);
+    let mut docking = DockingMultiViewport::new(Tree::new(egui::Id::new("root"), root, tiles));
+    let mut behavior = CountResizes::default();
+    sim.run_frames(3, &mut docking, &mut behavior);
+
+    let splitter = |docking: &DockingMultiViewport<usize>| {
+        let left = docking.tree.tiles.rect(left).unwrap();
+        let right = docking.tree.tiles.rect(right).unwrap();
+        Pos2::new((left.right() + right.left()) / 2.0, left.center().y)
+    };
+    let shares = |docking: &DockingMultiViewport<usize>| {
+        let Some(Tile::Container(Container::Linear(linear))) = docking.tree.tiles.get(root) else {
+            panic!("root must stay a Linear container");
+        };
+        (linear.shares[left], linear.shares[right])
+    };
+
+    // Unlocked, the same drag resizes (and autosave sees the change).
+    let generation = docking.layout_generation();
+    let from = splitter(&docking);
+    sim.drag(&mut docking, &mut behavior, from, from + vec2(100.0, 0.0));
+    assert!(behavior.0 > 0);
+    assert_ne!(docking.layout_generation(), generation);
+
+    assert!(docking.set_node_flags(PaneHost::RootDock, left, DockNodeFlags::NO_RESIZE));
+    sim.run_frames(2, &mut docking, &mut behavior);
+    behavior.0 = 0;
+    let before = shares(&docking);
+    let from = splitter(&docking);
+    sim.drag(&mut docking, &mut behavior, from, from - vec2(200.0, 0.0));
+    assert_eq!(behavior.0, 0, "egui_tiles must not see the drag");
+    assert_eq!(shares(&docking), before);
+}
@@ -0,0 +1,1344 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+use egui::{Context, Id, Pos2, Rect, Vec2, ViewportBuilder, ViewportId};
+use egui_tiles::{Container, Grid, GridLayout, Linear, LinearDir, Tabs, Tile, TileId, Tree, Tiles};
+
+use super::backend_hints::backend_monitors_outer_rects_points;
+use super::load_report::LoadReport;
+use super::monitor_clamp::monitor_snapshot_for_window;
+use super::node_flags::DockNodeFlags;
+use super::options::DetachedViewportOptions;
+use super::pane_query::PaneHost;
+
+/// Current snapshot format version.
+///
+/// - 3: `TreeSnapshot::node_flags` and `DetachedSnapshot::options`.
+/// - 4: `NodeSnapshot::Pane::state`.
+/// - 5: `ViewportSnapshot::monitor`.
+pub const LAYOUT_SNAPSHOT_VERSION: u32 = 5;
+
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub struct ViewportSnapshot {
+    pub outer_pos: Option<Pos2>,
+    pub inner_size: Option<Vec2>,
+    pub fullscreen: bool,
+    pub maximized: bool,
+    pub pixels_per_point: Option<f32>,
+    /// The monitor the window was mostly on (absent if the backend did not report monitors).
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub monitor: Option<MonitorSnapshot>,
+}
+
+impl ViewportSnapshot {
+    /// This geometry as it should be applied at `pixels_per_point`, so a window saved at another
+    /// scale factor keeps its physical size and position.
+    ///
+    /// Snapshots without a (valid) saved scale factor are returned unchanged.
+    pub fn rescaled_to_pixels_per_point(mut self, pixels_per_point: f32) -> Self {
+        let Some(scale) = dpi_scale(self.pixels_per_point, pixels_per_point) else {
+            return self;
+        };
+        self.outer_pos = self.outer_pos.map(|pos| (pos.to_vec2() * scale).to_pos2());
+        self.inner_size = self.inner_size.map(|size| size * scale);
+        self.pixels_per_point = Some(pixels_per_point);
+        self
+    }
+}
+
+/// Factor converting points saved at `saved` pixels-per-point to points at `current`, if they
+/// differ.
+fn dpi_scale(saved: Option<f32>, current: f32) -> Option<f32> {
+    let saved = saved?;
+    let valid = |ppp: f32| ppp.is_finite() && ppp > 0.0;
+    (valid(saved) && valid(current) && saved != current).then(|| saved / current)
+}
+
+/// Where a window sat on its monitor, so it can be put back after the display setup changed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub struct MonitorSnapshot {
+    /// Index into the backend's monitor list (`BACKEND_MONITORS_OUTER_RECTS_POINTS_KEY`).
+    pub index: usize,
+    /// The monitor's outer rect in points.
+    pub rect: Rect,
+    /// Window `outer_pos` relative to `rect.min`, as a fraction of the monitor size.
+    pub relative_pos: Vec2,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(super) struct ViewportRuntime {
+    pub outer_pos: Option<Pos2>,
+    pub inner_size: Option<Vec2>,
+    pub fullscreen: bool,
+    pub maximized: bool,
+    pub pixels_per_point: f32,
+    pub monitor: Option<MonitorSnapshot>,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub enum HostSnapshot {
+    Root,
+    Detached { serial: u64 },
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub struct FloatingWindowSnapshot<PaneId> {
+    pub id: u64,
+    pub tree: TreeSnapshot<PaneId>,
+    pub offset_in_dock: Vec2,
+    pub size: Vec2,
+    pub collapsed: bool,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub struct FloatingManagerSnapshot<PaneId> {
+    pub host: HostSnapshot,
+    pub windows: Vec<FloatingWindowSnapshot<PaneId>>,
+    pub z_order: Vec<u64>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub struct DetachedSnapshot<PaneId> {
+    pub serial: u64,
+    pub viewport: ViewportSnapshot,
+    pub tree: TreeSnapshot<PaneId>,
+    /// Per-window option overrides (absent in snapshots that predate them).
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default, skip_serializing_if = "is_default_detached_options")
+    )]
+    pub options: DetachedViewportOptions,
+}
+
+#[cfg(feature = "persistence")]
+fn is_default_detached_options(options: &DetachedViewportOptions) -> bool {
+    *options == DetachedViewportOptions::default()
+}
+
+/// Every dock host (root, detached viewports, floating windows) with panes replaced by `PaneId`.
+///
+/// Used by layout persistence, workspace presets and the undo/redo history.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub struct LayoutSnapshot<PaneId> {
+    pub version: u32,
+    pub root: TreeSnapshot<PaneId>,
+    pub detached: Vec<DetachedSnapshot<PaneId>>,
+    pub floating: Vec<FloatingManagerSnapshot<PaneId>>,
+    pub next_detached_serial: u64,
+    pub next_floating_id: u64,
+}
+
+impl<PaneId> LayoutSnapshot<PaneId> {
+    /// Replace every `PaneId` in every host (e.g. to migrate renamed panes); panes mapped to
+    /// `None` are removed from their parents.
+    pub fn filter_map_pane_ids<T>(
+        self,
+        mut f: impl FnMut(PaneId) -> Option<T>,
+    ) -> LayoutSnapshot<T> {
+        LayoutSnapshot {
+            version: self.version,
+            root: self.root.filter_map_pane_ids(&mut f),
+            detached: self
+                .detached
+                .into_iter()
+                .map(|d| DetachedSnapshot {
+                    serial: d.serial,
+                    viewport: d.viewport,
+                    tree: d.tree.filter_map_pane_ids(&mut f),
+                    options: d.options,
+                })
+                .collect(),
+            floating: self
+                .floating
+                .into_iter()
+                .map(|m| FloatingManagerSnapshot {
+                    host: m.host,
+                    windows: m
+                        .windows
+                        .into_iter()
+                        .map(|w| FloatingWindowSnapshot {
+                            id: w.id,
+                            tree: w.tree.filter_map_pane_ids(&mut f),
+                            offset_in_dock: w.offset_in_dock,
+                            size: w.size,
+                            collapsed: w.collapsed,
+                        })
+                        .collect(),
+                    z_order: m.z_order,
+                })
+                .collect(),
+            next_detached_serial: self.next_detached_serial,
+            next_floating_id: self.next_floating_id,
+        }
+    }
+
+    /// Hash of everything that makes up the layout: tree structure, pane ids, shares, window
+    /// geometry and options. Id counters and the format version are left out.
+    ///
+    /// Equal layouts hash equally, so comparing hashes across frames detects changes.
+    pub fn layout_hash(&self) -> u64
+    where
+        PaneId: Hash,
+    {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.root.hash_into(&mut hasher);
+        for detached in &self.detached {
+            detached.serial.hash(&mut hasher);
+            let viewport = &detached.viewport;
+            viewport.outer_pos.map(|p| [p.x, p.y]).hash_f32s(&mut hasher);
+            viewport.inner_size.map(|s| [s.x, s.y]).hash_f32s(&mut hasher);
+            viewport.pixels_per_point.map(|ppp| [ppp]).hash_f32s(&mut hasher);
+            (viewport.fullscreen, viewport.maximized).hash(&mut hasher);
+            viewport.monitor.map(|m| m.index).hash(&mut hasher);
+            let options = &detached.options;
+            (options.decorations, options.csd_window_controls, options.always_on_top)
+                .hash(&mut hasher);
+            options.csd_resize_edge_thickness.map(|t| [t]).hash_f32s(&mut hasher);
+            options.csd_resize_corner_size.map(|s| [s]).hash_f32s(&mut hasher);
+            detached.tree.hash_into(&mut hasher);
+        }
+        for manager in &self.floating {
+            let host = match manager.host {
+                HostSnapshot::Root => None,
+                HostSnapshot::Detached { serial } => Some(serial),
+            };
+            (host, &manager.z_order).hash(&mut hasher);
+            for window in &manager.windows {
+                (window.id, window.collapsed).hash(&mut hasher);
+                let (offset, size) = (window.offset_in_dock, window.size);
+                Some([offset.x, offset.y, size.x, size.y]).hash_f32s(&mut hasher);
+                window.tree.hash_into(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Pane ids that occur more than once across all hosts, once per extra occurrence.
+    pub fn duplicate_pane_ids(&self) -> Vec<PaneId>
+    where
+        PaneId: Clone + Eq + Hash,
+    {
+        let trees = std::iter::once(&self.root)
+            .chain(self.detached.iter().map(|d| &d.tree))
+            .chain(self.floating.iter().flat_map(|m| m.windows.iter().map(|w| &w.tree)));
+        let mut seen = std::collections::HashSet::new();
+        let mut duplicates = Vec::new();
+        for node in trees.flat_map(|tree| tree.nodes.iter()) {
+            if let NodeSnapshot::Pane { pane, .. } = node
+                && !seen.insert(pane)
+            {
+                duplicates.push(pane.clone());
+            }
+        }
+        duplicates
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub struct TreeSnapshot<PaneId> {
+    pub root: Option<usize>,
+    pub nodes: Vec<NodeSnapshot<PaneId>>,
+    /// [`DockNodeFlags`] keyed by index into `nodes`.
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub node_flags: BTreeMap<usize, DockNodeFlags>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
+pub enum NodeSnapshot<PaneId> {
+    Pane {
+        pane: PaneId,
+        visible: bool,
+        /// Pane state blob from a `PaneStateRegistry` (absent on the id-only path).
+        #[cfg_attr(
+            feature = "persistence",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        state: Option<String>,
+    },
+    Tabs {
+        children: Vec<usize>,
+        active: Option<usize>,
+        visible: bool,
+    },
+    Linear {
+        dir: LinearDir,
+        children: Vec<usize>,
+        shares: Vec<f32>,
+        visible: bool,
+    },
+    Grid {
+        layout: GridLayout,
+        children: Vec<usize>,
+        col_shares: Vec<f32>,
+        row_shares: Vec<f32>,
+        visible: bool,
+    },
+}
+
+impl<PaneId> TreeSnapshot<PaneId> {
+    fn hash_into(&self, hasher: &mut impl Hasher)
+    where
+        PaneId: Hash,
+    {
+        (self.root, self.nodes.len()).hash(hasher);
+        for node in &self.nodes {
+            match node {
+                NodeSnapshot::Pane {
+                    pane,
+                    visible,
+                    state,
+                } => (0u8, pane, visible, state).hash(hasher),
+                NodeSnapshot::Tabs {
+                    children,
+                    active,
+                    visible,
+                } => (1u8, children, active, visible).hash(hasher),
+                NodeSnapshot::Linear {
+                    dir,
+                    children,
+                    shares,
+                    visible,
+                } => {
+                    let horizontal = matches!(dir, LinearDir::Horizontal);
+                    (2u8, horizontal, children, visible).hash(hasher);
+                    Some(shares.as_slice()).hash_f32s(hasher);
+                }
+                NodeSnapshot::Grid {
+                    layout,
+                    children,
+                    col_shares,
+                    row_shares,
+                    visible,
+                } => {
+                    let columns = match layout {
+                        GridLayout::Columns(columns) => Some(*columns),
+                        GridLayout::Auto => None,
+                    };
+                    (3u8, columns, children, visible).hash(hasher);
+                    Some(col_shares.as_slice()).hash_f32s(hasher);
+                    Some(row_shares.as_slice()).hash_f32s(hasher);
+                }
+            }
+        }
+        for (index, flags) in &self.node_flags {
+            (index, flags).hash(hasher);
+        }
+    }
+
+    /// See [`LayoutSnapshot::filter_map_pane_ids`]. Containers left empty are kept; the restore
+    /// prunes them.
+    pub fn filter_map_pane_ids<T>(self, mut f: impl FnMut(PaneId) -> Option<T>) -> TreeSnapshot<T> {
+        // New index of every old node (`None` for removed panes).
+        let mut new_index = Vec::with_capacity(self.nodes.len());
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        for node in self.nodes {
+            let node = match node {
+                NodeSnapshot::Pane {
+                    pane,
+                    visible,
+                    state,
+                } => f(pane).map(|pane| NodeSnapshot::Pane {
+                    pane,
+                    visible,
+                    state,
+                }),
+                NodeSnapshot::Tabs {
+                    children,
+                    active,
+                    visible,
+                } => Some(NodeSnapshot::Tabs {
+                    children,
+                    active,
+                    visible,
+                }),
+                NodeSnapshot::Linear {
+                    dir,
+                    children,
+                    shares,
+                    visible,
+                } => Some(NodeSnapshot::Linear {
+                    dir,
+                    children,
+                    shares,
+                    visible,
+                }),
+                NodeSnapshot::Grid {
+                    layout,
+                    children,
+                    col_shares,
+                    row_shares,
+                    visible,
+                } => Some(NodeSnapshot::Grid {
+                    layout,
+                    children,
+                    col_shares,
+                    row_shares,
+                    visible,
+                }),
+            };
+            new_index.push(node.is_some().then_some(nodes.len()));
+            nodes.extend(node);
+        }
+
+        fn remap_children(
+            new_index: &[Option<usize>],
+            children: &mut Vec<usize>,
+            shares: Option<&mut Vec<f32>>,
+        ) {
+            let mut kept_shares = Vec::new();
+            for (i, child) in std::mem::take(children).into_iter().enumerate() {
+                let Some(child) = new_index.get(child).copied().flatten() else {
+                    continue;
+                };
+                children.push(child);
+                if let Some(&share) = shares.as_ref().and_then(|shares| shares.get(i)) {
+                    kept_shares.push(share);
+                }
+            }
+            if let Some(shares) = shares {
+                *shares = kept_shares;
+            }
+        }
+
+        let remap = |index: usize| new_index.get(index).copied().flatten();
+        for node in &mut nodes {
+            match node {
+                NodeSnapshot::Pane { .. } => {}
+                NodeSnapshot::Tabs {
+                    children, active, ..
+                } => {
+                    let active_child = active.and_then(|i| children.get(i).copied());
+                    remap_children(&new_index, children, None);
+                    *active = active_child
+                        .and_then(remap)
+                        .and_then(|child| children.iter().position(|&c| c == child));
+                }
+                NodeSnapshot::Linear {
+                    children, shares, ..
+                } => remap_children(&new_index, children, Some(shares)),
+                NodeSnapshot::Grid { children, .. } => remap_children(&new_index, children, None),
+            }
+        }
+
+        TreeSnapshot {
+            root: self.root.and_then(remap),
+            nodes,
+            node_flags: self
+                .node_flags
+                .into_iter()
+                .filter_map(|(index, flags)| Some((remap(index)?, flags)))
+                .collect(),
+        }
+    }
+}
+
+pub(super) fn detached_viewport_id_from_serial(serial: u64) -> ViewportId {
+    ViewportId::from_hash_of(("egui_docking_detached", serial))
+}
+
+pub(super) fn snapshot_tree<Pane, PaneId>(
+    tree: &Tree<Pane>,
+    node_flags: &BTreeMap<TileId, DockNodeFlags>,
+    mut pane_to_node: impl FnMut(&Pane) -> (PaneId, Option<String>),
+) -> TreeSnapshot<PaneId> {
+    let mut ids: HashMap<egui_tiles::TileId, usize> = HashMap::new();
+    let mut nodes: Vec<NodeSnapshot<PaneId>> = Vec::new();
+
+    fn snapshot_node<Pane, PaneId>(
+        tree: &Tree<Pane>,
+        tile_id: egui_tiles::TileId,
+        ids: &mut HashMap<egui_tiles::TileId, usize>,
+        nodes: &mut Vec<NodeSnapshot<PaneId>>,
+        pane_to_node: &mut dyn FnMut(&Pane) -> (PaneId, Option<String>),
+    ) -> usize {
+        if let Some(&idx) = ids.get(&tile_id) {
+            return idx;
+        }
+
+        let visible = tree.tiles.is_visible(tile_id);
+        let tile = tree
+            .tiles
+            .get(tile_id)
+            .expect("tree root references missing tile");
+
+        // Important: reserve the index first (push placeholder), then fill it after recursing.
+        // This guarantees that child references are stable indices into `nodes`.
+        let idx = nodes.len();
+        ids.insert(tile_id, idx);
+        nodes.push(NodeSnapshot::Tabs {
+            children: Vec::new(),
+            active: None,
+            visible,
+        });
+
+        let node = match tile {
+            Tile::Pane(pane) => {
+                let (pane, state) = pane_to_node(pane);
+                NodeSnapshot::Pane {
+                    pane,
+                    visible,
+                    state,
+                }
+            }
+            Tile::Container(container) => match container {
+                Container::Tabs(tabs) => {
+                    let children: Vec<usize> = tabs
+                        .children
+                        .iter()
+                        .copied()
+                        .map(|child| snapshot_node(tree, child, ids, nodes, pane_to_node))
+                        .collect();
+                    let active = tabs
+                        .active
+                        .and_then(|active_id| tabs.children.iter().position(|&c| c == active_id));
+                    NodeSnapshot::Tabs {
+                        children,
+                        active,
+                        visible,
+                    }
+                }
+                Container::Linear(linear) => {
+                    let children_tile_ids: Vec<_> = linear.children.clone();
+                    let children: Vec<usize> = children_tile_ids
+                        .iter()
+                        .copied()
+                        .map(|child| snapshot_node(tree, child, ids, nodes, pane_to_node))
+                        .collect();
+                    let shares: Vec<f32> = children_tile_ids
+                        .iter()
+                        .copied()
+                        .map(|child| linear.shares[child])
+                        .collect();
+                    NodeSnapshot::Linear {
+                        dir: linear.dir,
+                        children,
+                        shares,
+                        visible,
+                    }
+                }
+                Container::Grid(grid) => {
+                    let children_tile_ids: Vec<_> = grid.children().copied().collect();
+                    let children: Vec<usize> = children_tile_ids
+                        .iter()
+                        .copied()
+                        .map(|child| snapshot_node(tree, child, ids, nodes, pane_to_node))
+                        .collect();
+                    NodeSnapshot::Grid {
+                        layout: grid.layout,
+                        children,
+                        col_shares: grid.col_shares.clone(),
+                        row_shares: grid.row_shares.clone(),
+                        visible,
+                    }
+                }
+            },
+        };
+
+        nodes[idx] = node;
+        idx
+    }
+
+    let root = tree
+        .root
+        .map(|r| snapshot_node(tree, r, &mut ids, &mut nodes, &mut pane_to_node));
+    let node_flags = node_flags
+        .iter()
+        .filter_map(|(tile_id, flags)| Some((*ids.get(tile_id)?, *flags)))
+        .collect();
+    TreeSnapshot {
+        root,
+        nodes,
+        node_flags,
+    }
+}
+
+/// Map the node-indexed flags of a snapshot onto the tiles built for it.
+pub(super) fn restored_node_flags(
+    node_flags: &BTreeMap<usize, DockNodeFlags>,
+    built: &[Option<TileId>],
+) -> Vec<(TileId, DockNodeFlags)> {
+    node_flags
+        .iter()
+        .filter_map(|(&idx, &flags)| Some((built.get(idx).copied().flatten()?, flags)))
+        .collect()
+}
+
+/// `f32` isn't `Hash`; hash the bit patterns instead.
+trait HashF32s {
+    fn hash_f32s(&self, hasher: &mut impl Hasher);
+}
+
+impl<T: AsRef<[f32]>> HashF32s for Option<T> {
+    fn hash_f32s(&self, hasher: &mut impl Hasher) {
+        match self {
+            Some(values) => {
+                let values = values.as_ref();
+                values.len().hash(hasher);
+                for value in values {
+                    value.to_bits().hash(hasher);
+                }
+            }
+            None => usize::MAX.hash(hasher),
+        }
+    }
+}
+
+/// Why a saved pane was left out of a restored layout.
+pub(super) enum DroppedPane {
+    /// The app could not provide the pane.
+    Missing,
+    /// `PaneRegistry::migrate_pane_id` retired its id.
+    Migrated,
+}
+
+/// Rebuild a tree from `snapshot`, dropping panes `pane_from_node` can't provide.
+///
+/// Containers left without children are dropped too, except the central node.
+/// Everything dropped or repaired is recorded in `report`.
+pub(super) fn restore_tree<Pane, PaneId>(
+    tree_id: Id,
+    snapshot: TreeSnapshot<PaneId>,
+    pane_from_node: impl FnMut(PaneId, Option<String>) -> Result<Pane, DroppedPane>,
+    report: &mut LoadReport<PaneId>,
+) -> (Tree<Pane>, Vec<(TileId, DockNodeFlags)>)
+where
+    PaneId: Clone,
+{
+    let central = snapshot
+        .node_flags
+        .iter()
+        .find(|(_, flags)| flags.contains(DockNodeFlags::CENTRAL_NODE))
+        .map(|(&idx, _)| idx);
+    let mut restore = TreeRestore {
+        snapshot: &snapshot,
+        tiles: Tiles::default(),
+        built: vec![None; snapshot.nodes.len()],
+        missing: vec![false; snapshot.nodes.len()],
+        central,
+        pane_from_node,
+        report,
+    };
+    let root = snapshot.root.and_then(|idx| restore.build_node(idx));
+    let TreeRestore { tiles, built, .. } = restore;
+
+    let node_flags = restored_node_flags(&snapshot.node_flags, &built);
+    let tree = match root {
+        Some(root) => Tree::new(tree_id, root, tiles),
+        None => Tree::empty(tree_id),
+    };
+    (tree, node_flags)
+}
+
+struct TreeRestore<'a, Pane, PaneId, F> {
+    snapshot: &'a TreeSnapshot<PaneId>,
+    tiles: Tiles<Pane>,
+    built: Vec<Option<TileId>>,
+    missing: Vec<bool>,
+    central: Option<usize>,
+    pane_from_node: F,
+    report: &'a mut LoadReport<PaneId>,
+}
+
+/// Shares must be positive and finite; anything else falls back to the default share.
+fn sanitized_share(share: f32, repaired: &mut usize) -> f32 {
+    if share.is_finite() && share > 0.0 {
+        share
+    } else {
+        *repaired += 1;
+        1.0
+    }
+}
+
+impl<Pane, PaneId, F> TreeRestore<'_, Pane, PaneId, F>
+where
+    PaneId: Clone,
+    F: FnMut(PaneId, Option<String>) -> Result<Pane, DroppedPane>,
+{
+    fn build_node(&mut self, idx: usize) -> Option<TileId> {
+        if self.missing[idx] {
+            return None;
+        }
+        if let Some(id) = self.built[idx] {
+            return Some(id);
+        }
+
+        let snapshot = self.snapshot;
+        let (tile_id, visible) = match &snapshot.nodes[idx] {
+            NodeSnapshot::Pane {
+                pane,
+                visible,
+                state,
+            } => {
+                let restored = match (self.pane_from_node)(pane.clone(), state.clone()) {
+                    Ok(restored) => restored,
+                    Err(dropped) => {
+                        match dropped {
+                            DroppedPane::Missing => self.report.missing_panes.push(pane.clone()),
+                            DroppedPane::Migrated => {
+                                self.report.migrated_away_panes.push(pane.clone());
+                            }
+                        }
+                        self.missing[idx] = true;
+                        return None;
+                    }
+                };
+                (self.tiles.insert_pane(restored), *visible)
+            }
+            NodeSnapshot::Tabs {
+                children,
+                active,
+                visible,
+            } => {
+                let mut child_ids: Vec<TileId> = Vec::with_capacity(children.len());
+                let mut active_child = None;
+                for (i, c) in children.iter().copied().enumerate() {
+                    if let Some(child_id) = self.build_node(c) {
+                        child_ids.push(child_id);
+                        if *active == Some(i) {
+                            active_child = Some(child_id);
+                        }
+                    }
+                }
+                // The central node is kept even when none of its panes could be restored.
+                if child_ids.is_empty() && self.central != Some(idx) {
+                    self.report.pruned_containers += 1;
+                    self.missing[idx] = true;
+                    return None;
+                }
+                let mut tabs = Tabs::new(child_ids);
+                match active_child {
+                    Some(active_child) => tabs.active = Some(active_child),
+                    None if active.is_some() => self.report.repaired_tabs_active += 1,
+                    None => {}
+                }
+                (self.tiles.insert_container(tabs), *visible)
+            }
+            NodeSnapshot::Linear {
+                dir,
+                children,
+                shares,
+                visible,
+            } => {
+                let mut child_ids: Vec<TileId> = Vec::with_capacity(children.len());
+                let mut child_shares: Vec<f32> = Vec::with_capacity(children.len());
+                for (c, share) in children.iter().copied().zip(shares.iter().copied()) {
+                    if let Some(child_id) = self.build_node(c) {
+                        child_ids.push(child_id);
+                        child_shares.push(share);
+                    }
+                }
+                if child_ids.is_empty() {
+                    self.report.pruned_containers += 1;
+                    self.missing[idx] = true;
+                    return None;
+                }
+
+                let mut linear = Linear::new(*dir, child_ids.clone());
+                for (child_id, share) in child_ids.iter().copied().zip(child_shares.iter().copied())
+                {
+                    let share = sanitized_share(share, &mut self.report.repaired_shares);
+                    if share != 1.0 {
+                        linear.shares.set_share(child_id, share);
+                    }
+                }
+                (self.tiles.insert_container(linear), *visible)
+            }
+            NodeSnapshot::Grid {
+                layout,
+                children,
+                col_shares,
+                row_shares,
+                visible,
+            } => {
+                let mut child_ids: Vec<TileId> = Vec::with_capacity(children.len());
+                for c in children.iter().copied() {
+                    if let Some(child_id) = self.build_node(c) {
+                        child_ids.push(child_id);
+                    }
+                }
+                if child_ids.is_empty() {
+                    self.report.pruned_containers += 1;
+                    self.missing[idx] = true;
+                    return None;
+                }
+
+                let repaired = &mut self.report.repaired_shares;
+                let mut grid = Grid::new(child_ids);
+                grid.layout = *layout;
+                grid.col_shares = col_shares
+                    .iter()
+                    .map(|&share| sanitized_share(share, repaired))
+                    .collect();
+                grid.row_shares = row_shares
+                    .iter()
+                    .map(|&share| sanitized_share(share, repaired))
+                    .collect();
+                (self.tiles.insert_container(grid), *visible)
+            }
+        };
+
+        self.tiles.set_visible(tile_id, visible);
+        self.built[idx] = Some(tile_id);
+        Some(tile_id)
+    }
+}
+
+impl<Pane> super::DockingMultiViewport<Pane> {
+    pub(super) fn capture_viewport_runtime(&mut self, ctx: &Context) {
+        let viewport_id = ctx.viewport_id();
+        let mut runtime = ctx.input(|i| {
+            let outer_pos = i.viewport().outer_rect.map(|r| r.min);
+            let inner_size = i.viewport().inner_rect.map(|r| r.size());
+            ViewportRuntime {
+                outer_pos,
+                inner_size,
+                fullscreen: i.viewport().fullscreen.unwrap_or(false),
+                maximized: i.viewport().maximized.unwrap_or(false),
+                pixels_per_point: ctx.pixels_per_point(),
+                monitor: None,
+            }
+        });
+        if let Some(outer_rect) = ctx.input(|i| i.viewport().outer_rect)
+            && let Some(monitors) = backend_monitors_outer_rects_points(ctx)
+        {
+            runtime.monitor = monitor_snapshot_for_window(outer_rect, &monitors);
+        }
+        let previous = self.last_viewport_runtime.insert(viewport_id, runtime);
+        // Detached window geometry is part of the saved layout.
+        if previous != Some(runtime) && self.detached.contains_key(&viewport_id) {
+            self.bump_layout_generation();
+        }
+    }
+
+    fn viewport_snapshot_for_detached(
+        &self,
+        viewport_id: ViewportId,
+        detached: &super::types::DetachedDock<Pane>,
+    ) -> ViewportSnapshot {
+        let runtime = self.last_viewport_runtime.get(&viewport_id).copied();
+        ViewportSnapshot {
+            outer_pos: runtime
+                .and_then(|r| r.outer_pos)
+                .or(detached.builder.position),
+            inner_size: runtime
+                .and_then(|r| r.inner_size)
+                .or(detached.builder.inner_size),
+            fullscreen: runtime.map(|r| r.fullscreen).unwrap_or(false),
+            maximized: runtime.map(|r| r.maximized).unwrap_or(false),
+            pixels_per_point: runtime.map(|r| r.pixels_per_point),
+            monitor: runtime.and_then(|r| r.monitor),
+        }
+    }
+
+    pub(super) fn viewport_builder_from_snapshot(
+        &self,
+        snapshot: ViewportSnapshot,
+        options: DetachedViewportOptions,
+        title_hint: &str,
+    ) -> ViewportBuilder {
+        let mut builder = options.resolve(&self.options).apply_to_builder(
+            ViewportBuilder::default()
+                .with_title(title_hint)
+                .with_fullscreen(snapshot.fullscreen)
+                .with_maximized(snapshot.maximized),
+        );
+        if let Some(pos) = snapshot.outer_pos {
+            builder = builder.with_position(pos);
+        }
+        if let Some(size) = snapshot.inner_size {
+            builder = builder.with_inner_size(size);
+        }
+        builder
+    }
+
+    pub(super) fn clear_interaction_state_for_load(&mut self) {
+        self.pending_drop = None;
+        self.pending_internal_drop = None;
+        self.pending_local_drop = None;
+        self.ghost = None;
+        self.drag_state = super::drag_state::DragState::default();
+
+        self.last_root_dock_rect = None;
+        self.last_dock_rects.clear();
+        self.viewport_outer_from_inner_offset.clear();
+        self.last_floating_rects.clear();
+        self.last_floating_content_rects.clear();
+        self.detached_rendered_frame.clear();
+        self.last_viewport_runtime.clear();
+        self.pending_dpi_rescale.clear();
+        #[cfg(feature = "persistence")]
+        self.pending_viewport_placement.clear();
+        self.last_active_tabs.clear();
+    }
+
+    /// Finish the DPI conversion of detached viewports restored before the first frame, once the
+    /// root viewport's scale factor is known.
+    ///
+    /// Position and size are converted together, as [`ViewportSnapshot::rescaled_to_pixels_per_point`]
+    /// does for a known scale; placing the window on the current monitors waits for this.
+    pub(super) fn apply_pending_dpi_rescale(&mut self, ctx: &Context) {
+        if self.pending_dpi_rescale.is_empty() {
+            return;
+        }
+        let Some(current) = self
+            .last_viewport_runtime
+            .get(&ViewportId::ROOT)
+            .map(|runtime| runtime.pixels_per_point)
+        else {
+            return;
+        };
+        for (viewport_id, saved) in std::mem::take(&mut self.pending_dpi_rescale) {
+            let Some(detached) = self.detached.get_mut(&viewport_id) else {
+                continue;
+            };
+            let Some(scale) = dpi_scale(Some(saved), current) else {
+                continue;
+            };
+            let mut builder = detached.builder.clone();
+            if let Some(pos) = builder.position {
+                let pos = (pos.to_vec2() * scale).to_pos2();
+                builder = builder.with_position(pos);
+                ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::OuterPosition(pos));
+            }
+            if let Some(size) = builder.inner_size {
+                let size = size * scale;
+                builder = builder.with_inner_size(size);
+                ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::InnerSize(size));
+            }
+            detached.builder = builder;
+        }
+    }
+
+    pub(super) fn snapshot_layout_impl<PaneId>(
+        &self,
+        mut pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> LayoutSnapshot<PaneId> {
+        self.snapshot_layout_with_states_impl(|pane| (pane_to_id(pane), None))
+    }
+
+    /// Like [`Self::snapshot_layout_impl`], also storing a state blob per pane.
+    pub(super) fn snapshot_layout_with_states_impl<PaneId>(
+        &self,
+        mut pane_to_node: impl FnMut(&Pane) -> (PaneId, Option<String>),
+    ) -> LayoutSnapshot<PaneId> {
+        let root = snapshot_tree(
+            &self.tree,
+            &self.node_flags_in_host(PaneHost::RootDock),
+            &mut pane_to_node,
+        );
+
+        let detached: Vec<_> = self
+            .detached
+            .iter()
+            .map(|(viewport_id, detached)| DetachedSnapshot {
+                serial: detached.serial,
+                viewport: self.viewport_snapshot_for_detached(*viewport_id, detached),
+                tree: snapshot_tree(
+                    &detached.tree,
+                    &self.node_flags_in_host(PaneHost::DetachedViewport {
+                        viewport: *viewport_id,
+                    }),
+                    &mut pane_to_node,
+                ),
+                options: detached.options,
+            })
+            .collect();
+
+        let floating: Vec<_> = self
+            .floating
+            .iter()
+            .map(|(viewport_id, manager)| {
+                let host = if *viewport_id == ViewportId::ROOT {
+                    HostSnapshot::Root
+                } else if let Some(detached) = self.detached.get(viewport_id) {
+                    HostSnapshot::Detached {
+                        serial: detached.serial,
+                    }
+                } else {
+                    HostSnapshot::Root
+                };
+
+                let windows = manager
+                    .windows
+                    .iter()
+                    .map(|(&id, w)| FloatingWindowSnapshot {
+                        id,
+                        tree: snapshot_tree(
+                            &w.tree,
+                            &self.node_flags_in_host(PaneHost::Floating {
+                                viewport: *viewport_id,
+                                floating: id,
+                            }),
+                            &mut pane_to_node,
+                        ),
+                        offset_in_dock: w.offset_in_dock,
+                        size: w.size,
+                        collapsed: w.collapsed,
+                    })
+                    .collect();
+
+                FloatingManagerSnapshot {
+                    host,
+                    windows,
+                    z_order: manager.z_order.clone(),
+                }
+            })
+            .collect();
+
+        LayoutSnapshot {
+            version: LAYOUT_SNAPSHOT_VERSION,
+            root,
+            detached,
+            floating,
+            next_detached_serial: self.next_viewport_serial,
+            next_floating_id: self.next_floating_serial,
+        }
+    }
+
+    /// Replace every host with the contents of `snapshot` (no version check).
+    ///
+    /// Panes for which `pane_from_id` returns `None` are dropped; detached viewports and floating
+    /// windows left without panes are not restored.
+    /// Everything dropped or repaired is returned in the [`LoadReport`] (duplicate pane ids and
+    /// geometry clamping are filled in by the loaders).
+    pub(super) fn restore_layout_snapshot<PaneId>(
+        &mut self,
+        snapshot: LayoutSnapshot<PaneId>,
+        mut pane_from_id: impl FnMut(PaneId) -> Option<Pane>,
+    ) -> LoadReport<PaneId>
+    where
+        PaneId: Clone,
+    {
+        self.restore_layout_snapshot_with_states(snapshot, |id, _state| {
+            pane_from_id(id).ok_or(DroppedPane::Missing)
+        })
+    }
+
+    /// Like [`Self::restore_layout_snapshot`], also handing each pane's state blob to
+    /// `pane_from_node`, which tells why a pane was dropped.
+    pub(super) fn restore_layout_snapshot_with_states<PaneId>(
+        &mut self,
+        snapshot: LayoutSnapshot<PaneId>,
+        mut pane_from_node: impl FnMut(PaneId, Option<String>) -> Result<Pane, DroppedPane>,
+    ) -> LoadReport<PaneId>
+    where
+        PaneId: Clone,
+    {
+        // Scale factors of the viewports shown so far (none before the first frame).
+        let current_pixels_per_point: BTreeMap<ViewportId, f32> = self
+            .last_viewport_runtime
+            .iter()
+            .map(|(&viewport_id, runtime)| (viewport_id, runtime.pixels_per_point))
+            .collect();
+        self.clear_interaction_state_for_load();
+
+        let bridge_id = self.tree.id();
+        let mut report = LoadReport::default();
+
+        let (tree, root_flags) =
+            restore_tree(bridge_id, snapshot.root, &mut pane_from_node, &mut report);
+        self.tree = tree;
+        let mut restored_flags = vec![(PaneHost::RootDock, root_flags)];
+
+        self.detached.clear();
+        self.floating.clear();
+
+        let mut max_detached_serial = 0u64;
+        for detached in snapshot.detached {
+            max_detached_serial = max_detached_serial.max(detached.serial);
+
+            let viewport_id = detached_viewport_id_from_serial(detached.serial);
+            let detached_tree_id =
+                Id::new((bridge_id, "egui_docking_detached_tree", detached.serial));
+            let (tree, flags) =
+                restore_tree(detached_tree_id, detached.tree, &mut pane_from_node, &mut report);
+            if tree.root.is_none() {
+                report.pruned_detached.push(detached.serial);
+                continue;
+            }
+            restored_flags.push((PaneHost::DetachedViewport { viewport: viewport_id }, flags));
+
+            let current = current_pixels_per_point
+                .get(&viewport_id)
+                .or_else(|| current_pixels_per_point.get(&ViewportId::ROOT))
+                .copied();
+            let viewport = match current {
+                Some(pixels_per_point) => detached
+                    .viewport
+                    .rescaled_to_pixels_per_point(pixels_per_point),
+                None => {
+                    if let Some(saved) = detached.viewport.pixels_per_point {
+                        self.pending_dpi_rescale.insert(viewport_id, saved);
+                    }
+                    detached.viewport
+                }
+            };
+            let builder =
+                self.viewport_builder_from_snapshot(viewport, detached.options, "detached");
+
+            self.detached.insert(
+                viewport_id,
+                super::types::DetachedDock {
+                    serial: detached.serial,
+                    tree,
+                    builder,
+                    options: detached.options,
+                },
+            );
+        }
+
+        let mut max_floating_id = 0u64;
+        for manager in snapshot.floating {
+            let viewport_id = match manager.host {
+                HostSnapshot::Root => ViewportId::ROOT,
+                HostSnapshot::Detached { serial } => detached_viewport_id_from_serial(serial),
+            };
+
+            let saved_ids: Vec<u64> = manager.windows.iter().map(|w| w.id).collect();
+            let mut restored = super::types::FloatingManager::default();
+            for w in manager.windows {
+                max_floating_id = max_floating_id.max(w.id);
+
+                let floating_tree_id = Id::new((bridge_id, "egui_docking_floating_tree", w.id));
+                let (tree, flags) =
+                    restore_tree(floating_tree_id, w.tree, &mut pane_from_node, &mut report);
+                if tree.root.is_none() {
+                    report.pruned_floating.push(w.id);
+                    continue;
+                }
+                restored_flags.push((
+                    PaneHost::Floating {
+                        viewport: viewport_id,
+                        floating: w.id,
+                    },
+                    flags,
+                ));
+
+                restored.windows.insert(
+                    w.id,
+                    super::types::FloatingDockWindow {
+                        tree,
+                        offset_in_dock: w.offset_in_dock,
+                        size: w.size,
+                        collapsed: w.collapsed,
+                        drag: None,
+                        resize: None,
+                    },
+                );
+            }
+            restored.z_order = manager.z_order;
+            // Entries for windows pruned above are dropped silently; they're already reported.
+            restored.z_order.retain(|id| {
+                let keep = restored.windows.contains_key(id);
+                if !keep && !saved_ids.contains(id) {
+                    report.repaired_z_order += 1;
+                }
+                keep
+            });
+            for id in restored.windows.keys().copied().collect::<Vec<_>>() {
+                if !restored.z_order.contains(&id) {
+                    restored.z_order.push(id);
+                    report.repaired_z_order += 1;
+                }
+            }
+
+            if !restored.windows.is_empty() {
+                self.floating.insert(viewport_id, restored);
+            }
+        }
+
+        self.next_viewport_serial = snapshot
+            .next_detached_serial
+            .max(max_detached_serial.saturating_add(1))
+            .max(1);
+        self.next_floating_serial = snapshot
+            .next_floating_id
+            .max(max_floating_id.saturating_add(1))
+            .max(1);
+
+        self.node_flags.clear();
+        for (host, flags) in restored_flags {
+            self.apply_node_flags(host, flags);
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{pos2, vec2};
+
+    use super::*;
+    use crate::multi_viewport::DockingMultiViewport;
+
+    fn detached_snapshot(viewport: ViewportSnapshot) -> LayoutSnapshot<usize> {
+        let tree = |pane| TreeSnapshot {
+            root: Some(0),
+            nodes: vec![NodeSnapshot::Pane {
+                pane,
+                visible: true,
+                state: None,
+            }],
+            node_flags: BTreeMap::new(),
+        };
+        LayoutSnapshot {
+            version: LAYOUT_SNAPSHOT_VERSION,
+            root: tree(1),
+            detached: vec![DetachedSnapshot {
+                serial: 1,
+                viewport,
+                tree: tree(2),
+                options: DetachedViewportOptions::default(),
+            }],
+            floating: Vec::new(),
+            next_detached_serial: 2,
+            next_floating_id: 1,
+        }
+    }
+
+    fn saved_at_2x() -> ViewportSnapshot {
+        ViewportSnapshot {
+            outer_pos: Some(pos2(100.0, 50.0)),
+            inner_size: Some(vec2(400.0, 300.0)),
+            pixels_per_point: Some(2.0),
+            ..Default::default()
+        }
+    }
+
+    fn runtime(pixels_per_point: f32) -> ViewportRuntime {
+        ViewportRuntime {
+            pixels_per_point,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn viewport_geometry_keeps_its_physical_size() {
+        let rescaled = saved_at_2x().rescaled_to_pixels_per_point(1.0);
+        assert_eq!(rescaled.outer_pos, Some(pos2(200.0, 100.0)));
+        assert_eq!(rescaled.inner_size, Some(vec2(800.0, 600.0)));
+        assert_eq!(rescaled.pixels_per_point, Some(1.0));
+
+        let same = saved_at_2x().rescaled_to_pixels_per_point(2.0);
+        assert_eq!(same.inner_size, Some(vec2(400.0, 300.0)));
+
+        let unknown = ViewportSnapshot {
+            pixels_per_point: None,
+            ..saved_at_2x()
+        };
+        assert_eq!(
+            unknown.rescaled_to_pixels_per_point(1.0).inner_size,
+            Some(vec2(400.0, 300.0))
+        );
+        let invalid = ViewportSnapshot {
+            pixels_per_point: Some(0.0),
+            ..saved_at_2x()
+        };
+        assert_eq!(
+            invalid.rescaled_to_pixels_per_point(1.0).inner_size,
+            Some(vec2(400.0, 300.0))
+        );
+    }
+
+    #[test]
+    fn restore_converts_detached_geometry_to_the_current_scale() {
+        let mut docking = DockingMultiViewport::new(Tree::<usize>::empty("docking"));
+        docking
+            .last_viewport_runtime
+            .insert(ViewportId::ROOT, runtime(1.0));
+
+        let report = docking.restore_layout_snapshot(detached_snapshot(saved_at_2x()), Some);
+        assert!(report.is_clean());
+        let detached = docking.detached.values().next().unwrap();
+        assert_eq!(detached.builder.position, Some(pos2(200.0, 100.0)));
+        assert_eq!(detached.builder.inner_size, Some(vec2(800.0, 600.0)));
+        assert!(docking.pending_dpi_rescale.is_empty());
+    }
+
+    #[test]
+    fn restore_before_the_first_frame_rescales_once_the_scale_is_known() {
+        let ctx = Context::default();
+        let mut docking = DockingMultiViewport::new(Tree::<usize>::empty("docking"));
+
+        docking.restore_layout_snapshot(detached_snapshot(saved_at_2x()), Some);
+        let viewport_id = *docking.detached.keys().next().unwrap();
+        assert_eq!(
+            docking.detached[&viewport_id].builder.inner_size,
+            Some(vec2(400.0, 300.0))
+        );
+
+        // Nothing to convert against yet.
+        docking.apply_pending_dpi_rescale(&ctx);
+        assert!(!docking.pending_dpi_rescale.is_empty());
+
+        docking
+            .last_viewport_runtime
+            .insert(ViewportId::ROOT, runtime(1.0));
+        docking.apply_pending_dpi_rescale(&ctx);
+        assert!(docking.pending_dpi_rescale.is_empty());
+        let detached = &docking.detached[&viewport_id];
+        assert_eq!(detached.builder.inner_size, Some(vec2(800.0, 600.0)));
+        assert_eq!(detached.builder.position, Some(pos2(200.0, 100.0)));
+    }
+
+    #[test]
+    fn removed_panes_are_dropped_from_their_parents() {
+        let pane = |pane| NodeSnapshot::Pane {
+            pane,
+            visible: true,
+            state: None,
+        };
+        let tree = TreeSnapshot {
+            root: Some(0),
+            nodes: vec![
+                NodeSnapshot::Linear {
+                    dir: LinearDir::Horizontal,
+                    children: vec![1, 2],
+                    shares: vec![0.3, 0.7],
+                    visible: true,
+                },
+                pane(1usize),
+                NodeSnapshot::Tabs {
+                    children: vec![3, 4],
+                    active: Some(1),
+                    visible: true,
+                },
+                pane(2),
+                pane(3),
+            ],
+            node_flags: BTreeMap::from([(2, DockNodeFlags::NO_SPLIT)]),
+        };
+
+        let tree = tree.filter_map_pane_ids(|pane| (pane == 3).then_some(pane * 10));
+        assert_eq!(tree.root, Some(0));
+        assert_eq!(tree.nodes.len(), 3);
+        assert!(matches!(
+            &tree.nodes[0],
+            NodeSnapshot::Linear { children, shares, .. } if *children == [1] && *shares == [0.7]
+        ));
+        assert!(matches!(
+            &tree.nodes[1],
+            NodeSnapshot::Tabs { children, active: Some(0), .. } if *children == [2]
+        ));
+        assert!(matches!(tree.nodes[2], NodeSnapshot::Pane { pane: 30, .. }));
+        assert_eq!(
+            tree.node_flags,
+            BTreeMap::from([(1, DockNodeFlags::NO_SPLIT)])
+        );
+    }
+}
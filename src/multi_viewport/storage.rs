@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use egui::Context;
+
+use super::load_report::LoadReport;
+use super::migration::layout_snapshot_from_ron_str;
+use super::persistence::LayoutPersistenceError;
+use super::snapshot::LayoutSnapshot;
+use super::{PaneRegistry, PaneStateRegistry};
+
+/// Key the layout is stored under unless the app picks its own.
+pub const DEFAULT_LAYOUT_STORAGE_KEY: &str = "egui_docking_layout";
+
+/// A string key/value store for layouts, shaped like `eframe::Storage`.
+///
+/// With the `eframe-storage` feature, `dyn eframe::Storage` implements it, so the storage handed
+/// to `eframe::App::save` and `eframe::CreationContext::storage` can be passed directly.
+pub trait LayoutStorage {
+    fn get_string(&self, key: &str) -> Option<String>;
+    fn set_string(&mut self, key: &str, value: String);
+}
+
+impl LayoutStorage for HashMap<String, String> {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        self.insert(key.to_owned(), value);
+    }
+}
+
+#[cfg(feature = "eframe-storage")]
+impl LayoutStorage for dyn eframe::Storage + '_ {
+    fn get_string(&self, key: &str) -> Option<String> {
+        eframe::Storage::get_string(self, key)
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        eframe::Storage::set_string(self, key, value);
+    }
+}
+
+impl<Pane> super::DockingMultiViewport<Pane> {
+    /// Store the layout as RON under `key` (typically from `eframe::App::save`).
+    pub fn save_layout_to_storage_with_registry<S, R>(
+        &self,
+        storage: &mut S,
+        key: &str,
+        registry: &mut R,
+    ) -> Result<(), LayoutPersistenceError>
+    where
+        S: LayoutStorage + ?Sized,
+        R: PaneRegistry<Pane>,
+    {
+        let ron = self.snapshot_layout_to_ron_string_with_registry(registry)?;
+        storage.set_string(key, ron);
+        Ok(())
+    }
+
+    /// Like [`Self::save_layout_to_storage_with_registry`], also storing each pane's state blob.
+    pub fn save_layout_to_storage_with_state_registry<S, R>(
+        &self,
+        storage: &mut S,
+        key: &str,
+        registry: &mut R,
+    ) -> Result<(), LayoutPersistenceError>
+    where
+        S: LayoutStorage + ?Sized,
+        R: PaneStateRegistry<Pane>,
+    {
+        let ron = self.snapshot_layout_to_ron_string_with_state_registry(registry)?;
+        storage.set_string(key, ron);
+        Ok(())
+    }
+
+    pub fn save_layout_to_storage<S, PaneId>(
+        &self,
+        storage: &mut S,
+        key: &str,
+        pane_to_id: impl FnMut(&Pane) -> PaneId,
+    ) -> Result<(), LayoutPersistenceError>
+    where
+        S: LayoutStorage + ?Sized,
+        PaneId: serde::Serialize,
+    {
+        let ron = self.snapshot_layout_to_ron_string(pane_to_id)?;
+        storage.set_string(key, ron);
+        Ok(())
+    }
+
+    /// Load the layout stored under `key`. Returns `Ok(None)` if nothing is stored there.
+    pub fn load_layout_from_storage_in_ctx_with_registry<S, R>(
+        &mut self,
+        ctx: &Context,
+        storage: &S,
+        key: &str,
+        registry: &mut R,
+    ) -> Result<Option<LoadReport<R::PaneId>>, LayoutPersistenceError>
+    where
+        S: LayoutStorage + ?Sized,
+        R: PaneRegistry<Pane>,
+    {
+        let Some(ron_str) = storage.get_string(key) else {
+            return Ok(None);
+        };
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_ron_str(&ron_str)?;
+        self.load_layout_snapshot_in_ctx_with_registry(ctx, snapshot, registry)
+            .map(Some)
+    }
+
+    /// Like [`Self::load_layout_from_storage_in_ctx_with_registry`], handing stored state blobs
+    /// to [`PaneStateRegistry::load_pane_state`].
+    pub fn load_layout_from_storage_in_ctx_with_state_registry<S, R>(
+        &mut self,
+        ctx: &Context,
+        storage: &S,
+        key: &str,
+        registry: &mut R,
+    ) -> Result<Option<LoadReport<R::PaneId>>, LayoutPersistenceError>
+    where
+        S: LayoutStorage + ?Sized,
+        R: PaneStateRegistry<Pane>,
+    {
+        let Some(ron_str) = storage.get_string(key) else {
+            return Ok(None);
+        };
+        let snapshot: LayoutSnapshot<R::PaneId> = layout_snapshot_from_ron_str(&ron_str)?;
+        self.load_layout_snapshot_in_ctx_with_state_registry(ctx, snapshot, registry)
+            .map(Some)
+    }
+
+    /// Load the layout stored under `key`. Returns `Ok(None)` if nothing is stored there.
+    pub fn load_layout_from_storage_in_ctx<S, PaneId>(
+        &mut self,
+        ctx: &Context,
+        storage: &S,
+        key: &str,
+        pane_from_id: impl FnMut(PaneId) -> Pane,
+    ) -> Result<Option<LoadReport<PaneId>>, LayoutPersistenceError>
+    where
+        S: LayoutStorage + ?Sized,
+        PaneId: Clone + Eq + Hash + for<'de> serde::Deserialize<'de>,
+    {
+        let Some(ron_str) = storage.get_string(key) else {
+            return Ok(None);
+        };
+        let snapshot: LayoutSnapshot<PaneId> = layout_snapshot_from_ron_str(&ron_str)?;
+        self.load_layout_snapshot_in_ctx(ctx, snapshot, pane_from_id)
+            .map(Some)
+    }
+
+    /// Load the layout stored under `key`. If there is no storage or nothing under `key`, apply
+    /// `fallback` instead.
+    ///
+    /// Mirrors [`Self::load_layout_from_ron_file_in_ctx_with_registry_or_apply_workspace`]:
+    /// - `Ok(Some(report))` if the stored layout was loaded
+    /// - `Ok(None)` if nothing was stored and `fallback` was applied
+    /// - `Err(e)` if the stored layout was invalid and `fallback` was applied
+    pub fn load_layout_from_storage_in_ctx_with_registry_or_apply_workspace<S, R>(
+        &mut self,
+        ctx: &Context,
+        storage: Option<&S>,
+        key: &str,
+        registry: &mut R,
+        fallback: impl FnOnce() -> crate::workspace::WorkspaceLayout<Pane>,
+    ) -> Result<Option<LoadReport<R::PaneId>>, LayoutPersistenceError>
+    where
+        S: LayoutStorage + ?Sized,
+        R: PaneRegistry<Pane>,
+    {
+        let loaded = match storage {
+            Some(storage) => {
+                self.load_layout_from_storage_in_ctx_with_registry(ctx, storage, key, registry)
+            }
+            None => Ok(None),
+        };
+        match loaded {
+            Ok(Some(report)) => Ok(Some(report)),
+            Ok(None) => {
+                self.set_workspace_layout_in_ctx(ctx, fallback());
+                Ok(None)
+            }
+            Err(err) => {
+                self.set_workspace_layout_in_ctx(ctx, fallback());
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui_tiles::Tree;
+
+    use super::*;
+    use crate::SimplePaneRegistry;
+    use crate::multi_viewport::DockingMultiViewport;
+    use crate::multi_viewport::test_support::{StateRegistry, docking_with_tabs, root_panes};
+    use crate::workspace::WorkspaceLayout;
+
+    #[test]
+    fn storage_roundtrip_uses_the_given_key() {
+        let ctx = Context::default();
+        let docking = docking_with_tabs(&[1, 2]);
+        let mut storage: HashMap<String, String> = HashMap::new();
+        docking
+            .save_layout_to_storage(&mut storage, "my_layout", |&pane| pane)
+            .unwrap();
+        assert!(storage.contains_key("my_layout"));
+
+        let mut restored = DockingMultiViewport::new(Tree::empty("restored"));
+        let report = restored
+            .load_layout_from_storage_in_ctx(&ctx, &storage, "my_layout", |id: usize| id)
+            .unwrap();
+        assert!(report.is_some_and(|report| report.is_clean()));
+        assert_eq!(root_panes(&restored), vec![1, 2]);
+
+        assert!(
+            restored
+                .load_layout_from_storage_in_ctx(
+                    &ctx,
+                    &storage,
+                    DEFAULT_LAYOUT_STORAGE_KEY,
+                    |id: usize| id
+                )
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn storage_roundtrip_applies_pane_state() {
+        let ctx = Context::default();
+        let docking = docking_with_tabs(&[1, 2]);
+        let mut storage: HashMap<String, String> = HashMap::new();
+        docking
+            .save_layout_to_storage_with_state_registry(
+                &mut storage,
+                DEFAULT_LAYOUT_STORAGE_KEY,
+                &mut StateRegistry,
+            )
+            .unwrap();
+
+        let mut restored = DockingMultiViewport::new(Tree::empty("restored"));
+        let report = restored
+            .load_layout_from_storage_in_ctx_with_state_registry(
+                &ctx,
+                &storage,
+                DEFAULT_LAYOUT_STORAGE_KEY,
+                &mut StateRegistry,
+            )
+            .unwrap();
+        assert!(report.is_some_and(|report| report.is_clean()));
+        assert_eq!(root_panes(&restored), vec![1, 2]);
+    }
+
+    #[test]
+    fn missing_or_invalid_storage_applies_fallback() {
+        let ctx = Context::default();
+        let mut registry = SimplePaneRegistry::new(|&pane: &usize| pane, |id: usize| id);
+        let fallback = || WorkspaceLayout::new(docking_with_tabs(&[9]).tree);
+
+        let mut docking = DockingMultiViewport::new(Tree::empty("docking"));
+        let loaded = docking.load_layout_from_storage_in_ctx_with_registry_or_apply_workspace(
+            &ctx,
+            None::<&HashMap<String, String>>,
+            DEFAULT_LAYOUT_STORAGE_KEY,
+            &mut registry,
+            fallback,
+        );
+        assert!(matches!(loaded, Ok(None)));
+        assert_eq!(root_panes(&docking), vec![9]);
+
+        let storage = HashMap::from([(DEFAULT_LAYOUT_STORAGE_KEY.to_owned(), "(".to_owned())]);
+        let loaded = docking.load_layout_from_storage_in_ctx_with_registry_or_apply_workspace(
+            &ctx,
+            Some(&storage),
+            DEFAULT_LAYOUT_STORAGE_KEY,
+            &mut registry,
+            fallback,
+        );
+        assert!(matches!(
+            loaded,
+            Err(LayoutPersistenceError::RonDeserialize(_))
+        ));
+        assert_eq!(root_panes(&docking), vec![9]);
+    }
+}
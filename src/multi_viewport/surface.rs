@@ -2,12 +2,15 @@ use egui::{Context, Pos2, Rect, ViewportId};
 use egui_tiles::{Behavior, InsertionPoint, Tree};
 
 use super::DockingMultiViewport;
+use super::docking_policy::{DropRejected, PolicyQuery};
 use super::geometry::{
     pointer_pos_in_target_viewport_space, viewport_under_pointer_global,
     viewport_under_pointer_global_excluding,
 };
+use super::host::WindowHost;
 use super::overlay::overlay_insertion_for_tree_explicit_with_outer_considering_dragged;
 use super::overlay_decision::{decide_overlay_for_tree, DragKind};
+use super::pane_query::PaneHost;
 use super::types::FloatingId;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -28,6 +31,15 @@ impl DockSurface {
             DockSurface::Floating { viewport, .. } => viewport,
         }
     }
+
+    pub(super) fn pane_host(self) -> PaneHost {
+        match self {
+            DockSurface::DockTree { viewport } => WindowHost::DockTree { viewport }.pane_host(),
+            DockSurface::Floating { viewport, floating } => {
+                PaneHost::Floating { viewport, floating }
+            }
+        }
+    }
 }
 
 impl<Pane> DockingMultiViewport<Pane> {
@@ -116,6 +128,8 @@ impl<Pane> DockingMultiViewport<Pane> {
         Some((surface, pointer_local))
     }
 
+    /// `Err(DropRejected)` means the [`super::DockingPolicy`] vetoed the drop; `Ok(None)` means
+    /// there is no target under the pointer.
     pub(super) fn insertion_at_pointer_local(
         &self,
         behavior: &dyn Behavior<Pane>,
@@ -123,9 +137,14 @@ impl<Pane> DockingMultiViewport<Pane> {
         surface: DockSurface,
         pointer_local: Pos2,
         dragged_tile: Option<egui_tiles::TileId>,
-    ) -> Option<InsertionPoint> {
-        let dock_rect = self.dock_rect_for_surface(surface)?;
-        let tree = self.tree_for_surface(surface)?;
+        policy: Option<&PolicyQuery<'_, Pane>>,
+    ) -> Result<Option<InsertionPoint>, DropRejected> {
+        let Some(dock_rect) = self.dock_rect_for_surface(surface) else {
+            return Ok(None);
+        };
+        let Some(tree) = self.tree_for_surface(surface) else {
+            return Ok(None);
+        };
         let decision = decide_overlay_for_tree(
             tree,
             behavior,
@@ -137,13 +156,17 @@ impl<Pane> DockingMultiViewport<Pane> {
                 dragged_tile: None,
                 internal: false,
             },
+            policy,
         );
+        if decision.policy_rejected {
+            return Err(DropRejected);
+        }
         let insertion = decision.insertion_final;
         let Some(dragged_tile) = dragged_tile else {
-            return insertion;
+            return Ok(insertion);
         };
         let Some(ins) = insertion else {
-            return None;
+            return Ok(None);
         };
 
         // If we are dropping a subtree back into the same tree (e.g. floating → dock),
@@ -151,9 +174,9 @@ impl<Pane> DockingMultiViewport<Pane> {
         if ins.parent_id == dragged_tile
             || super::overlay::tile_contains_descendant(tree, dragged_tile, ins.parent_id)
         {
-            return None;
+            return Ok(None);
         }
-        Some(ins)
+        Ok(Some(ins))
     }
 
     pub(super) fn explicit_insertion_at_pointer_local(
@@ -171,15 +194,21 @@ impl<Pane> DockingMultiViewport<Pane> {
         )
     }
 
+    /// Like [`Self::insertion_at_pointer_local`], for window-move drags (explicit targets only).
     pub(super) fn window_move_insertion_at_pointer_local(
         &self,
         behavior: &dyn Behavior<Pane>,
         style: &egui::Style,
         surface: DockSurface,
         pointer_local: Pos2,
-    ) -> Option<InsertionPoint> {
-        let dock_rect = self.dock_rect_for_surface(surface)?;
-        let tree = self.tree_for_surface(surface)?;
+        policy: Option<&PolicyQuery<'_, Pane>>,
+    ) -> Result<Option<InsertionPoint>, DropRejected> {
+        let Some(dock_rect) = self.dock_rect_for_surface(surface) else {
+            return Ok(None);
+        };
+        let Some(tree) = self.tree_for_surface(surface) else {
+            return Ok(None);
+        };
         let decision = decide_overlay_for_tree(
             tree,
             behavior,
@@ -192,8 +221,12 @@ impl<Pane> DockingMultiViewport<Pane> {
                     .options
                     .window_move_tab_dock_requires_explicit_target,
             },
+            policy,
         );
-        decision.insertion_final
+        if decision.policy_rejected {
+            return Err(DropRejected);
+        }
+        Ok(decision.insertion_final)
     }
 }
 
@@ -265,9 +298,15 @@ mod tests {
         };
         let style = egui::Style::default();
 
-        let insertion_without_filter =
-            docking.insertion_at_pointer_local(&behavior, &style, surface, pointer_over_self, None);
-        assert!(insertion_without_filter.is_some());
+        let insertion_without_filter = docking.insertion_at_pointer_local(
+            &behavior,
+            &style,
+            surface,
+            pointer_over_self,
+            None,
+            None,
+        );
+        assert!(matches!(insertion_without_filter, Ok(Some(_))));
 
         let insertion_with_filter = docking.insertion_at_pointer_local(
             &behavior,
@@ -275,7 +314,8 @@ mod tests {
             surface,
             pointer_over_self,
             Some(root),
+            None,
         );
-        assert!(insertion_with_filter.is_none());
+        assert!(matches!(insertion_with_filter, Ok(None)));
     }
 }
@@ -0,0 +1,104 @@
+use egui::{Pos2, Rect, Vec2, ViewportBuilder, ViewportId};
+use egui_tiles::{Behavior, Tile, TileId, Tiles, Tree, UiResponse};
+
+use super::DockingMultiViewport;
+use super::pane_query::PaneHost;
+use super::simulation::DockingSimulation;
+
+/// Shows nothing; tabs are titled `pane {n}`.
+#[derive(Default)]
+pub(super) struct DummyBehavior;
+
+impl Behavior<usize> for DummyBehavior {
+    fn pane_ui(&mut self, _ui: &mut egui::Ui, _tile_id: TileId, _pane: &mut usize) -> UiResponse {
+        Default::default()
+    }
+
+    fn tab_title_for_pane(&mut self, pane: &usize) -> egui::WidgetText {
+        format!("pane {pane}").into()
+    }
+}
+
+/// A tree made of a single tabs container holding `panes`.
+pub(super) fn tabs_tree(id: &str, panes: &[usize]) -> Tree<usize> {
+    let mut tiles: Tiles<usize> = Tiles::default();
+    let children = panes.iter().map(|&pane| tiles.insert_pane(pane)).collect();
+    let root = tiles.insert_tab_tile(children);
+    Tree::new(egui::Id::new(id), root, tiles)
+}
+
+/// Docking whose root tree is `tabs_tree("root", panes)`.
+pub(super) fn docking_with_tabs(panes: &[usize]) -> DockingMultiViewport<usize> {
+    DockingMultiViewport::new(tabs_tree("root", panes))
+}
+
+/// Panes of the root dock tree, sorted.
+pub(super) fn root_panes(docking: &DockingMultiViewport<usize>) -> Vec<usize> {
+    let mut panes: Vec<usize> = docking
+        .tree
+        .tiles
+        .iter()
+        .filter_map(|(_, tile)| match tile {
+            Tile::Pane(pane) => Some(*pane),
+            Tile::Container(_) => None,
+        })
+        .collect();
+    panes.sort_unstable();
+    panes
+}
+
+/// [`super::PaneStateRegistry`] whose state blob is the pane itself. Panes created without a
+/// state are `id + 1000`, so tests can tell whether the state was applied.
+#[cfg(feature = "persistence")]
+pub(super) struct StateRegistry;
+
+#[cfg(feature = "persistence")]
+impl super::PaneRegistry<usize> for StateRegistry {
+    type PaneId = usize;
+
+    fn pane_id(&mut self, pane: &usize) -> usize {
+        *pane
+    }
+
+    fn pane_from_id(&mut self, id: usize) -> usize {
+        id + 1000
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl super::PaneStateRegistry<usize> for StateRegistry {
+    fn save_pane_state(&mut self, pane: &usize) -> Option<String> {
+        Some(pane.to_string())
+    }
+
+    fn load_pane_state(&mut self, pane: &mut usize, state: &str) {
+        if let Ok(saved) = state.parse() {
+            *pane = saved;
+        }
+    }
+}
+
+pub(super) fn pane_host(docking: &DockingMultiViewport<usize>, pane: usize) -> Option<PaneHost> {
+    docking
+        .find_pane(|&p| p == pane)
+        .map(|location| location.host)
+}
+
+/// Simulated root window (800x600) with tabs `[1, 2]` and a detached window with tab `[3]` to
+/// its right. Ghost tear-off is off so drags move panes directly.
+pub(super) fn simulation_with_detached()
+-> (DockingSimulation, DockingMultiViewport<usize>, ViewportId) {
+    let sim = DockingSimulation::new(Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0)));
+
+    let mut docking = docking_with_tabs(&[1, 2]);
+    docking.options.ghost_tear_off = false;
+    let detached = docking
+        .add_detached_viewport(
+            tabs_tree("detached", &[3]),
+            ViewportBuilder::default()
+                .with_position(Pos2::new(1000.0, 100.0))
+                .with_inner_size(Vec2::new(400.0, 300.0)),
+        )
+        .expect("tree has a root");
+    (sim, docking, detached)
+}
@@ -0,0 +1,344 @@
+use tracing::Span;
+
+use super::DockingMultiViewport;
+use super::debug_log::{DebugLogRecord, WindowMoveCommand};
+use super::events::DockEvent;
+
+/// `tracing` state kept by [`DockingMultiViewport`] when the `tracing` feature is enabled.
+#[derive(Debug, Default)]
+pub(super) struct TraceState {
+    /// Span of the active drag session, entered while `ui` runs.
+    session_span: Option<Span>,
+}
+
+impl<Pane> DockingMultiViewport<Pane> {
+    /// Enter the active drag session span (if any) until the guard is dropped.
+    pub(super) fn trace_enter_session(&self) -> Option<tracing::span::EnteredSpan> {
+        self.trace.session_span.clone().map(Span::entered)
+    }
+
+    pub(super) fn trace_record(&mut self, record: &DebugLogRecord) {
+        match record {
+            DebugLogRecord::SessionStart { session, source } => {
+                let span = tracing::info_span!(
+                    target: "egui_docking",
+                    "drag_session",
+                    session = *session,
+                    source = *source
+                );
+                tracing::info!(target: "egui_docking", parent: &span, "drag session started");
+                self.trace.session_span = Some(span);
+            }
+            DebugLogRecord::SessionRelease {
+                session,
+                kind,
+                source,
+                ignored,
+            } => tracing::debug!(
+                target: "egui_docking",
+                session = ?session,
+                release_kind = *kind,
+                source = ?source,
+                ignored = *ignored,
+                "drag session release"
+            ),
+            DebugLogRecord::SessionEnd {
+                session,
+                started_frame,
+                end_frame,
+            } => {
+                let span = self.trace.session_span.take().unwrap_or_else(Span::none);
+                tracing::info!(
+                    target: "egui_docking",
+                    parent: &span,
+                    session = *session,
+                    started_frame = *started_frame,
+                    end_frame = *end_frame,
+                    "drag session ended"
+                );
+            }
+            DebugLogRecord::DropDecision {
+                stage,
+                window_move,
+                rejected: true,
+                source_host,
+                target_host,
+                tile_id,
+                ..
+            } => tracing::debug!(
+                target: "egui_docking",
+                stage = *stage,
+                window_move = *window_move,
+                source_host = ?source_host,
+                target_host = ?target_host,
+                tile_id = ?tile_id,
+                "drop rejected by docking policy"
+            ),
+            DebugLogRecord::DropDecision {
+                stage,
+                window_move,
+                rejected: false,
+                source_host,
+                target_host,
+                tile_id,
+                target_surface,
+                insertion,
+                pointer_global,
+                pointer_local,
+            } => tracing::info!(
+                target: "egui_docking",
+                stage = *stage,
+                window_move = *window_move,
+                source_host = ?source_host,
+                target_host = ?target_host,
+                tile_id = ?tile_id,
+                target_surface = ?target_surface,
+                insertion = ?insertion,
+                pointer_global = ?pointer_global,
+                pointer_local = ?pointer_local,
+                "drop decision"
+            ),
+            DebugLogRecord::IntegrityFailure {
+                viewport,
+                tree,
+                issues,
+            } => tracing::warn!(
+                target: "egui_docking",
+                viewport = ?viewport,
+                tree = ?tree,
+                issue_count = issues.len(),
+                issues = ?issues,
+                "dock tree integrity failure"
+            ),
+            DebugLogRecord::WindowMove {
+                viewport,
+                command: WindowMoveCommand::StartDrag,
+            } => tracing::debug!(
+                target: "egui_docking",
+                viewport = ?viewport,
+                "native window move started"
+            ),
+            DebugLogRecord::WindowMove {
+                viewport,
+                command:
+                    WindowMoveCommand::OuterPosition {
+                        step,
+                        pointer_global,
+                        desired_outer,
+                        ..
+                    },
+            } => tracing::trace!(
+                target: "egui_docking",
+                viewport = ?viewport,
+                step = *step,
+                pointer_global = ?pointer_global,
+                desired_outer = ?desired_outer,
+                "ghost window moved"
+            ),
+            DebugLogRecord::TearOff {
+                source_host,
+                tile_id,
+                floating,
+                api,
+                accepted: false,
+                ..
+            } => tracing::debug!(
+                target: "egui_docking",
+                source_host = ?source_host,
+                tile_id = ?tile_id,
+                floating = *floating,
+                api = *api,
+                "tear-off rejected by docking policy"
+            ),
+            DebugLogRecord::TearOff {
+                source_host,
+                tile_id,
+                floating,
+                api,
+                accepted: true,
+                new_host,
+            } => tracing::debug!(
+                target: "egui_docking",
+                source_host = ?source_host,
+                tile_id = ?tile_id,
+                floating = *floating,
+                api = *api,
+                new_host = ?new_host,
+                "tear-off"
+            ),
+            DebugLogRecord::DockEvent(event) => self.trace_dock_event(event),
+            DebugLogRecord::PresetSwitch { name, parked } => tracing::info!(
+                target: "egui_docking",
+                name = %name,
+                parked = *parked,
+                "workspace preset switched"
+            ),
+            DebugLogRecord::GhostUpgrade {
+                viewport,
+                floating,
+                native_viewport,
+            } => tracing::info!(
+                target: "egui_docking",
+                viewport = ?viewport,
+                floating = *floating,
+                native_viewport = ?native_viewport,
+                "ghost upgraded to native window"
+            ),
+            DebugLogRecord::BackendHints {
+                hovered_viewport,
+                pointer_global,
+                monitors,
+            } => tracing::debug!(
+                target: "egui_docking",
+                hovered_viewport = ?hovered_viewport,
+                pointer_global = ?pointer_global,
+                monitor_count = monitors.as_ref().map(Vec::len),
+                monitors = ?monitors,
+                "backend hints changed"
+            ),
+            DebugLogRecord::Message(text) => {
+                tracing::trace!(target: "egui_docking", text = %text, "debug log");
+            }
+        }
+    }
+
+    fn trace_dock_event(&self, event: &DockEvent) {
+        match *event {
+            DockEvent::PaneDetached { source, viewport } => tracing::info!(
+                target: "egui_docking",
+                source = ?source,
+                viewport = ?viewport,
+                "pane torn off"
+            ),
+            DockEvent::TabActivated { .. } => {
+                tracing::trace!(target: "egui_docking", event = ?event, "dock event");
+            }
+            _ => tracing::debug!(target: "egui_docking", event = ?event, "dock event"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    use egui_tiles::{Container, Tile, TileId};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    use crate::multi_viewport::test_support::docking_with_tabs;
+
+    #[derive(Debug)]
+    struct CapturedEvent {
+        level: Level,
+        message: String,
+    }
+
+    /// Records the `egui_docking` events at `max_level` or more severe.
+    struct CaptureSubscriber {
+        max_level: Level,
+        events: Arc<Mutex<Vec<CapturedEvent>>>,
+    }
+
+    struct MessageVisitor(String);
+
+    impl Visit for MessageVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "message" {
+                self.0 = format!("{value:?}");
+            }
+        }
+    }
+
+    impl Subscriber for CaptureSubscriber {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.target() == "egui_docking" && *metadata.level() <= self.max_level
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut visitor = MessageVisitor(String::new());
+            event.record(&mut visitor);
+            self.events.lock().unwrap().push(CapturedEvent {
+                level: *event.metadata().level(),
+                message: visitor.0,
+            });
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    fn capture<R>(max_level: Level, f: impl FnOnce() -> R) -> (R, Vec<CapturedEvent>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = CaptureSubscriber {
+            max_level,
+            events: events.clone(),
+        };
+        let result = tracing::subscriber::with_default(subscriber, f);
+        let events = std::mem::take(&mut *events.lock().unwrap());
+        (result, events)
+    }
+
+    #[test]
+    fn integrity_checks_run_when_warnings_are_traced() {
+        let mut docking = docking_with_tabs(&[1, 2]);
+        assert!(!docking.options.debug_integrity);
+        let (enabled, _) = capture(Level::ERROR, || docking.integrity_checks_enabled());
+        assert!(!enabled);
+
+        let root = docking.tree.root.unwrap();
+        let Some(Tile::Container(Container::Tabs(tabs))) = docking.tree.tiles.get_mut(root) else {
+            panic!("root is a tabs container");
+        };
+        tabs.children.push(TileId::from_u64(9_999));
+
+        let (enabled, events) = capture(Level::WARN, || {
+            let enabled = docking.integrity_checks_enabled();
+            docking.debug_check_integrity_all();
+            enabled
+        });
+        assert!(enabled);
+        assert!(
+            events
+                .iter()
+                .any(|e| e.level == Level::WARN && e.message == "dock tree integrity failure"),
+            "{events:?}"
+        );
+        // Nothing is kept in the event log without `debug_integrity`.
+        assert_eq!(docking.debug_log_entries().count(), 0);
+    }
+
+    #[test]
+    fn backend_hint_changes_are_traced_once() {
+        let mut docking = docking_with_tabs(&[1]);
+        let ctx = egui::Context::default();
+
+        let ((), events) = capture(Level::DEBUG, || {
+            docking.debug_log_backend_hints_if_changed(&ctx);
+            docking.debug_log_backend_hints_if_changed(&ctx);
+        });
+        let hints: Vec<_> = events
+            .iter()
+            .filter(|e| e.message == "backend hints changed")
+            .collect();
+        assert_eq!(hints.len(), 1, "{events:?}");
+        assert_eq!(hints[0].level, Level::DEBUG);
+
+        // Below `DEBUG`, the hints aren't even queried.
+        let ((), events) = capture(Level::INFO, || {
+            docking.debug_log_backend_hints_if_changed(&egui::Context::default());
+        });
+        assert!(events.is_empty(), "{events:?}");
+    }
+}
@@ -3,6 +3,7 @@ use std::collections::BTreeMap;
 use egui::{Modifiers, Pos2, Vec2, ViewportBuilder, ViewportId};
 use egui_tiles::{InsertionPoint, TileId, Tree};
 
+use super::options::DetachedViewportOptions;
 use super::surface::DockSurface;
 use super::host::WindowHost;
 
@@ -60,11 +61,32 @@ pub(super) struct ResolvedDrop {
 #[derive(Clone, Copy, Debug)]
 pub(super) struct PendingInternalDrop {
     pub(super) viewport: ViewportId,
+    /// Set for a drag inside a contained floating window of `viewport`.
+    pub(super) floating: Option<FloatingId>,
     pub(super) tile_id: TileId,
-    pub(super) insertion: InsertionPoint,
+    /// `None` if the docking policy rejected the drop (swallow the release, apply nothing).
+    pub(super) insertion: Option<InsertionPoint>,
 }
 
-pub(super) type FloatingId = u64;
+impl PendingInternalDrop {
+    pub(super) fn host(&self) -> WindowHost {
+        match self.floating {
+            Some(floating) => WindowHost::Floating {
+                viewport: self.viewport,
+                floating,
+            },
+            None => WindowHost::DockTree {
+                viewport: self.viewport,
+            },
+        }
+    }
+}
+
+/// Id of a contained floating window, unique within a [`super::DockingMultiViewport`].
+pub type FloatingId = u64;
+
+/// Floating windows are never shown (or resized) smaller than this, in points.
+pub const FLOATING_MIN_SIZE: Vec2 = Vec2::new(220.0, 120.0);
 
 #[derive(Clone, Copy, Debug)]
 pub(super) struct PendingLocalDrop {
@@ -98,6 +120,7 @@ pub(super) struct DetachedDock<Pane> {
     pub(super) serial: u64,
     pub(super) tree: Tree<Pane>,
     pub(super) builder: ViewportBuilder,
+    pub(super) options: DetachedViewportOptions,
 }
 
 #[derive(Clone, Copy, Debug)]
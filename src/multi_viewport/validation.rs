@@ -0,0 +1,377 @@
+use egui_tiles::GridLayout;
+
+use super::options::DetachedViewportOptions;
+use super::persistence::LayoutPersistenceError;
+use super::snapshot::{LayoutSnapshot, NodeSnapshot, TreeSnapshot, ViewportSnapshot};
+
+/// Most tree nodes a loaded snapshot may contain, summed over every dock host.
+pub const LAYOUT_SNAPSHOT_MAX_NODES: usize = 10_000;
+
+/// Deepest container nesting a loaded snapshot may contain (the root node is depth 1).
+pub const LAYOUT_SNAPSHOT_MAX_DEPTH: usize = 64;
+
+/// Check that `snapshot` describes well-formed trees before anything is built from it.
+///
+/// Loaders call this on every snapshot read from disk, so a hand-edited or crafted file fails with
+/// a [`LayoutPersistenceError`] instead of panicking, recursing forever or producing tiles with
+/// several parents. Checked:
+/// - every child / root / `node_flags` index is in bounds,
+/// - no node is its own ancestor and no node has two parents,
+/// - at most [`LAYOUT_SNAPSHOT_MAX_NODES`] nodes and [`LAYOUT_SNAPSHOT_MAX_DEPTH`] levels,
+/// - linear containers have one share per child, grids no more column / row shares than they can
+///   have columns / rows,
+/// - all shares and geometry are finite, and saved scale factors are positive.
+pub fn validate_layout_snapshot<PaneId>(
+    snapshot: &LayoutSnapshot<PaneId>,
+) -> Result<(), LayoutPersistenceError> {
+    let trees = std::iter::once(&snapshot.root)
+        .chain(snapshot.detached.iter().map(|d| &d.tree))
+        .chain(
+            snapshot
+                .floating
+                .iter()
+                .flat_map(|m| m.windows.iter().map(|w| &w.tree)),
+        );
+    let count = trees.clone().map(|tree| tree.nodes.len()).sum::<usize>();
+    if count > LAYOUT_SNAPSHOT_MAX_NODES {
+        return Err(LayoutPersistenceError::TooManyNodes {
+            count,
+            max: LAYOUT_SNAPSHOT_MAX_NODES,
+        });
+    }
+    for tree in trees {
+        validate_tree(tree)?;
+    }
+
+    for detached in &snapshot.detached {
+        validate_viewport(&detached.viewport)?;
+        validate_options(&detached.options)?;
+    }
+    for window in snapshot.floating.iter().flat_map(|m| &m.windows) {
+        finite(
+            "floating offset_in_dock",
+            &[window.offset_in_dock.x, window.offset_in_dock.y],
+        )?;
+        finite("floating size", &[window.size.x, window.size.y])?;
+    }
+    Ok(())
+}
+
+fn validate_tree<PaneId>(tree: &TreeSnapshot<PaneId>) -> Result<(), LayoutPersistenceError> {
+    let len = tree.nodes.len();
+    let in_bounds = |index: usize| {
+        if index < len {
+            Ok(())
+        } else {
+            Err(LayoutPersistenceError::NodeIndexOutOfBounds { index, len })
+        }
+    };
+
+    for &index in tree.node_flags.keys() {
+        in_bounds(index)?;
+    }
+    for (index, node) in tree.nodes.iter().enumerate() {
+        for &child in children(node) {
+            in_bounds(child)?;
+        }
+        match node {
+            NodeSnapshot::Pane { .. } | NodeSnapshot::Tabs { .. } => {}
+            NodeSnapshot::Linear {
+                children, shares, ..
+            } => {
+                if shares.len() != children.len() {
+                    return Err(LayoutPersistenceError::ShareCountMismatch { index });
+                }
+                finite("linear share", shares)?;
+            }
+            NodeSnapshot::Grid {
+                layout,
+                children,
+                col_shares,
+                row_shares,
+                ..
+            } => {
+                // egui_tiles keeps one share per laid-out column and row (none before the first
+                // layout), so only an upper bound holds.
+                let max_cols = match layout {
+                    GridLayout::Columns(columns) => *columns,
+                    GridLayout::Auto => children.len(),
+                };
+                if col_shares.len() > max_cols.max(1) || row_shares.len() > children.len().max(1) {
+                    return Err(LayoutPersistenceError::ShareCountMismatch { index });
+                }
+                finite("grid column share", col_shares)?;
+                finite("grid row share", row_shares)?;
+            }
+        }
+    }
+
+    if let Some(root) = tree.root {
+        in_bounds(root)?;
+        let mut visits = vec![Visit::Unseen; len];
+        visit(tree, root, 1, &mut visits)?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Visit {
+    Unseen,
+    InProgress,
+    Done,
+}
+
+/// Depth-first walk from the root. Recursion is bounded by [`LAYOUT_SNAPSHOT_MAX_DEPTH`].
+fn visit<PaneId>(
+    tree: &TreeSnapshot<PaneId>,
+    index: usize,
+    depth: usize,
+    visits: &mut [Visit],
+) -> Result<(), LayoutPersistenceError> {
+    match visits[index] {
+        Visit::InProgress => return Err(LayoutPersistenceError::NodeCycle { index }),
+        Visit::Done => return Err(LayoutPersistenceError::DuplicateChildReference { index }),
+        Visit::Unseen => {}
+    }
+    if depth > LAYOUT_SNAPSHOT_MAX_DEPTH {
+        return Err(LayoutPersistenceError::TreeTooDeep {
+            max: LAYOUT_SNAPSHOT_MAX_DEPTH,
+        });
+    }
+
+    visits[index] = Visit::InProgress;
+    for &child in children(&tree.nodes[index]) {
+        visit(tree, child, depth + 1, visits)?;
+    }
+    visits[index] = Visit::Done;
+    Ok(())
+}
+
+fn children<PaneId>(node: &NodeSnapshot<PaneId>) -> &[usize] {
+    match node {
+        NodeSnapshot::Pane { .. } => &[],
+        NodeSnapshot::Tabs { children, .. }
+        | NodeSnapshot::Linear { children, .. }
+        | NodeSnapshot::Grid { children, .. } => children,
+    }
+}
+
+fn validate_viewport(viewport: &ViewportSnapshot) -> Result<(), LayoutPersistenceError> {
+    if let Some(pos) = viewport.outer_pos {
+        finite("viewport outer_pos", &[pos.x, pos.y])?;
+    }
+    if let Some(size) = viewport.inner_size {
+        finite("viewport inner_size", &[size.x, size.y])?;
+    }
+    if let Some(pixels_per_point) = viewport.pixels_per_point {
+        finite("viewport pixels_per_point", &[pixels_per_point])?;
+        if pixels_per_point <= 0.0 {
+            return Err(LayoutPersistenceError::NonPositiveValue {
+                field: "viewport pixels_per_point",
+            });
+        }
+    }
+    if let Some(monitor) = &viewport.monitor {
+        let rect = monitor.rect;
+        finite(
+            "viewport monitor rect",
+            &[rect.min.x, rect.min.y, rect.max.x, rect.max.y],
+        )?;
+        let relative = monitor.relative_pos;
+        finite("viewport monitor relative_pos", &[relative.x, relative.y])?;
+    }
+    Ok(())
+}
+
+fn validate_options(options: &DetachedViewportOptions) -> Result<(), LayoutPersistenceError> {
+    if let Some(thickness) = options.csd_resize_edge_thickness {
+        finite("csd_resize_edge_thickness", &[thickness])?;
+    }
+    if let Some(size) = options.csd_resize_corner_size {
+        finite("csd_resize_corner_size", &[size])?;
+    }
+    Ok(())
+}
+
+fn finite(field: &'static str, values: &[f32]) -> Result<(), LayoutPersistenceError> {
+    if values.iter().all(|v| v.is_finite()) {
+        Ok(())
+    } else {
+        Err(LayoutPersistenceError::NonFiniteValue { field })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use egui_tiles::LinearDir;
+
+    use super::*;
+    use crate::multi_viewport::snapshot::LAYOUT_SNAPSHOT_VERSION;
+
+    fn pane(pane: usize) -> NodeSnapshot<usize> {
+        NodeSnapshot::Pane {
+            pane,
+            visible: true,
+            state: None,
+        }
+    }
+
+    fn tabs(children: Vec<usize>) -> NodeSnapshot<usize> {
+        NodeSnapshot::Tabs {
+            children,
+            active: None,
+            visible: true,
+        }
+    }
+
+    fn snapshot(nodes: Vec<NodeSnapshot<usize>>) -> LayoutSnapshot<usize> {
+        LayoutSnapshot {
+            version: LAYOUT_SNAPSHOT_VERSION,
+            root: TreeSnapshot {
+                root: Some(0),
+                nodes,
+                node_flags: BTreeMap::new(),
+            },
+            detached: Vec::new(),
+            floating: Vec::new(),
+            next_detached_serial: 1,
+            next_floating_id: 1,
+        }
+    }
+
+    #[test]
+    fn well_formed_snapshot_passes() {
+        let linear = NodeSnapshot::Linear {
+            dir: LinearDir::Horizontal,
+            children: vec![1, 2],
+            shares: vec![1.0, 2.0],
+            visible: true,
+        };
+        let snapshot = snapshot(vec![linear, tabs(vec![3]), pane(2), pane(1)]);
+        assert!(validate_layout_snapshot(&snapshot).is_ok());
+    }
+
+    #[test]
+    fn malformed_trees_are_rejected() {
+        assert!(matches!(
+            validate_layout_snapshot(&snapshot(vec![tabs(vec![5])])),
+            Err(LayoutPersistenceError::NodeIndexOutOfBounds { index: 5, len: 1 })
+        ));
+        assert!(matches!(
+            validate_layout_snapshot(&snapshot(vec![tabs(vec![1]), tabs(vec![0])])),
+            Err(LayoutPersistenceError::NodeCycle { index: 0 })
+        ));
+        assert!(matches!(
+            validate_layout_snapshot(&snapshot(vec![tabs(vec![1, 1]), pane(1)])),
+            Err(LayoutPersistenceError::DuplicateChildReference { index: 1 })
+        ));
+
+        let mut chain: Vec<_> = (1..=LAYOUT_SNAPSHOT_MAX_DEPTH)
+            .map(|i| tabs(vec![i]))
+            .collect();
+        chain.push(pane(1));
+        assert!(matches!(
+            validate_layout_snapshot(&snapshot(chain)),
+            Err(LayoutPersistenceError::TreeTooDeep { .. })
+        ));
+
+        let many = (0..=LAYOUT_SNAPSHOT_MAX_NODES).map(pane).collect();
+        assert!(matches!(
+            validate_layout_snapshot(&snapshot(many)),
+            Err(LayoutPersistenceError::TooManyNodes { .. })
+        ));
+    }
+
+    #[test]
+    fn non_finite_values_are_rejected() {
+        let linear = NodeSnapshot::Linear {
+            dir: LinearDir::Vertical,
+            children: vec![1],
+            shares: vec![f32::NAN],
+            visible: true,
+        };
+        assert!(matches!(
+            validate_layout_snapshot(&snapshot(vec![linear, pane(1)])),
+            Err(LayoutPersistenceError::NonFiniteValue {
+                field: "linear share"
+            })
+        ));
+
+        let mut with_detached = snapshot(vec![pane(1)]);
+        with_detached
+            .detached
+            .push(crate::multi_viewport::snapshot::DetachedSnapshot {
+                serial: 1,
+                viewport: ViewportSnapshot {
+                    outer_pos: Some(egui::Pos2::new(f32::INFINITY, 0.0)),
+                    ..Default::default()
+                },
+                tree: TreeSnapshot {
+                    root: Some(0),
+                    nodes: vec![pane(2)],
+                    node_flags: BTreeMap::new(),
+                },
+                options: Default::default(),
+            });
+        assert!(matches!(
+            validate_layout_snapshot(&with_detached),
+            Err(LayoutPersistenceError::NonFiniteValue {
+                field: "viewport outer_pos"
+            })
+        ));
+    }
+
+    #[test]
+    fn grid_share_counts_are_bounded_by_the_child_count() {
+        let grid = |layout, col_shares: Vec<f32>, row_shares: Vec<f32>| NodeSnapshot::Grid {
+            layout,
+            children: vec![1, 2],
+            col_shares,
+            row_shares,
+            visible: true,
+        };
+        let check = |grid| validate_layout_snapshot(&snapshot(vec![grid, pane(1), pane(2)]));
+
+        assert!(check(grid(GridLayout::Auto, vec![1.0, 1.0], vec![1.0])).is_ok());
+        assert!(check(grid(GridLayout::Columns(3), vec![1.0; 3], vec![1.0])).is_ok());
+        assert!(check(grid(GridLayout::Auto, Vec::new(), Vec::new())).is_ok());
+        assert!(matches!(
+            check(grid(GridLayout::Auto, vec![1.0; 3], vec![1.0])),
+            Err(LayoutPersistenceError::ShareCountMismatch { index: 0 })
+        ));
+        assert!(matches!(
+            check(grid(GridLayout::Columns(1), vec![1.0], vec![1.0; 3])),
+            Err(LayoutPersistenceError::ShareCountMismatch { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn non_positive_scale_factors_are_rejected() {
+        for pixels_per_point in [0.0, -2.0] {
+            let mut with_detached = snapshot(vec![pane(1)]);
+            with_detached
+                .detached
+                .push(crate::multi_viewport::snapshot::DetachedSnapshot {
+                    serial: 1,
+                    viewport: ViewportSnapshot {
+                        pixels_per_point: Some(pixels_per_point),
+                        ..Default::default()
+                    },
+                    tree: TreeSnapshot {
+                        root: Some(0),
+                        nodes: vec![pane(2)],
+                        node_flags: BTreeMap::new(),
+                    },
+                    options: Default::default(),
+                });
+            assert!(matches!(
+                validate_layout_snapshot(&with_detached),
+                Err(LayoutPersistenceError::NonPositiveValue {
+                    field: "viewport pixels_per_point"
+                })
+            ));
+        }
+    }
+}
@@ -156,6 +156,7 @@ fn world_model_cross_host_moves_keep_integrity() {
                 serial: 1,
                 tree: new_tree_tabs(egui::Id::new(("detached_tree", seed)), 3),
                 builder: egui::ViewportBuilder::default(),
+                options: Default::default(),
             },
         );
 
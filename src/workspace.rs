@@ -1,5 +1,7 @@
 use egui::ViewportBuilder;
-use egui_tiles::Tree;
+use egui_tiles::{TileId, Tree};
+
+use crate::multi_viewport::{DetachedViewportOptions, DockNodeFlags};
 
 /// A scripted "workspace preset": one root dock tree plus zero or more detached native viewport trees.
 ///
@@ -9,9 +11,18 @@ use egui_tiles::Tree;
 ///
 /// It is designed for game-engine/editor use cases where you want to define a deterministic default
 /// layout in code (Unity-style) and then allow the user to customize it at runtime.
+///
+/// Build it with [`Self::new`] and the `with_*` methods; new fields may be added, so it can't be
+/// built with a struct literal outside this crate.
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct WorkspaceLayout<Pane> {
     pub root: Tree<Pane>,
+
+    /// [`DockNodeFlags`] for tiles of [`Self::root`], e.g. from
+    /// [`crate::DockBuilder::finish_with_node_flags`].
+    pub root_node_flags: Vec<(TileId, DockNodeFlags)>,
+
     pub detached: Vec<DetachedViewportLayout<Pane>>,
 }
 
@@ -19,21 +30,63 @@ impl<Pane> WorkspaceLayout<Pane> {
     pub fn new(root: Tree<Pane>) -> Self {
         Self {
             root,
+            root_node_flags: Vec::new(),
             detached: Vec::new(),
         }
     }
+
+    /// Set [`Self::root_node_flags`].
+    #[must_use]
+    pub fn with_root_node_flags(
+        mut self,
+        flags: impl IntoIterator<Item = (TileId, DockNodeFlags)>,
+    ) -> Self {
+        self.root_node_flags = flags.into_iter().collect();
+        self
+    }
+
+    /// Make the root `Tabs` container `tile_id` the central node
+    /// (see [`crate::DockingMultiViewport::set_central_node`]).
+    #[must_use]
+    pub fn with_central_node(mut self, tile_id: TileId) -> Self {
+        match self.root_node_flags.iter_mut().find(|(id, _)| *id == tile_id) {
+            Some((_, flags)) => flags.insert(DockNodeFlags::CENTRAL_NODE),
+            None => self
+                .root_node_flags
+                .push((tile_id, DockNodeFlags::CENTRAL_NODE)),
+        }
+        self
+    }
 }
 
 /// A detached native viewport (OS window) hosting a dock tree.
+///
+/// Build it with [`Self::new`] and [`Self::with_options`]; new fields may be added, so it can't be
+/// built with a struct literal outside this crate.
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct DetachedViewportLayout<Pane> {
     pub builder: ViewportBuilder,
     pub tree: Tree<Pane>,
+
+    /// Per-window overrides of the global decoration/CSD options.
+    pub options: DetachedViewportOptions,
 }
 
 impl<Pane> DetachedViewportLayout<Pane> {
     pub fn new(builder: ViewportBuilder, tree: Tree<Pane>) -> Self {
-        Self { builder, tree }
+        Self {
+            builder,
+            tree,
+            options: DetachedViewportOptions::default(),
+        }
+    }
+
+    /// Set [`Self::options`].
+    #[must_use]
+    pub fn with_options(mut self, options: DetachedViewportOptions) -> Self {
+        self.options = options;
+        self
     }
 }
 